@@ -0,0 +1,231 @@
+//! Gate and circuit benchmarks, run with `cargo bench`.
+//!
+//! Environment variables:
+//! * `PULPFHE_BENCH_RUNS` - timed runs per measurement (default 10)
+//! * `PULPFHE_BENCH_WIDTHS` - comma separated bit widths (default `8,16,32`)
+//! * `PULPFHE_BENCH_TEMPLATES` - comma separated template lengths of the matching
+//!   benchmarks (default `256,512,1024,2048`)
+//! * `PULPFHE_BENCH_OUT` - output path without extension
+//!   (default `store/gate_vs_bootstrapping_benchmark`)
+//!
+//! A positional argument, e.g. `cargo bench -- mul`, only runs benchmarks whose name
+//! contains it. Results are written as `<out>.csv` and `<out>.json`.
+
+use pulpfhe::benchmark::{Benchmark, Phase};
+use pulpfhe::constant_fold_module::GateKind;
+use pulpfhe::key_manager::KeyManager;
+use pulpfhe::processor_boolean::ProcessorBoolean;
+use pulpfhe::processor_circuits::ProcessorCircuits;
+use pulpfhe::processor_gates::ProcessorGate;
+use rand::Rng;
+use std::env;
+use std::path::PathBuf;
+use tfhe::boolean::prelude::*;
+
+type Circuit<'a> = (&'static str, Box<dyn FnMut() + 'a>);
+
+const GATES: [GateKind; 6] = [
+    GateKind::And,
+    GateKind::Or,
+    GateKind::Xor,
+    GateKind::Nand,
+    GateKind::Nor,
+    GateKind::Xnor,
+];
+
+fn encrypt(value: i64, size: usize, ck: &ClientKey) -> Vec<Ciphertext> {
+    (0..size)
+        .map(|i| ck.encrypt(((value >> i) & 1) != 0))
+        .collect()
+}
+
+fn random(size: usize, ck: &ClientKey) -> Vec<Ciphertext> {
+    let mut rng = rand::thread_rng();
+    (0..size).map(|_| ck.encrypt(rng.r#gen())).collect()
+}
+
+fn blank(size: usize) -> Vec<Ciphertext> {
+    vec![Ciphertext::Trivial(false); size]
+}
+
+fn main() {
+    let runs: usize = env::var("PULPFHE_BENCH_RUNS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(10);
+    let widths: Vec<usize> = env::var("PULPFHE_BENCH_WIDTHS")
+        .unwrap_or_else(|_| "8,16,32".to_string())
+        .split(',')
+        .filter_map(|w| w.trim().parse().ok())
+        .collect();
+    let templates: Vec<usize> = env::var("PULPFHE_BENCH_TEMPLATES")
+        .unwrap_or_else(|_| "256,512,1024,2048".to_string())
+        .split(',')
+        .filter_map(|w| w.trim().parse().ok())
+        .collect();
+    let out = env::var("PULPFHE_BENCH_OUT")
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| {
+            PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("store/gate_vs_bootstrapping_benchmark")
+        });
+    // cargo passes `--bench`; anything else positional is a name filter
+    let filter: Option<String> = env::args().skip(1).find(|a| !a.starts_with("--"));
+    let selected = |name: &str| filter.as_ref().is_none_or(|f| name.contains(f.as_str()));
+
+    let (ck, sk) = KeyManager::cached_keys();
+    let server = ProcessorBoolean::new();
+    let mut bench = Benchmark::new(runs);
+
+    for &width in widths.iter() {
+        let a = random(width, ck);
+        let b = random(width, ck);
+        let mut result = blank(width);
+
+        for gate in GATES {
+            if selected(&format!("{:?}", gate).to_lowercase()) {
+                bench.gate(sk, gate, &a, &b, &mut result);
+            }
+        }
+        if selected("not") {
+            bench.measure("not", width, Phase::Gate, || {
+                server.e_not(sk, &a, &mut result).unwrap()
+            });
+        }
+        if selected("mux") {
+            let selector = ck.encrypt(true);
+            bench.measure("mux", width, Phase::Gate, || {
+                server.e_mux(sk, &selector, &a, &b, &mut result).unwrap()
+            });
+        }
+
+        // Operands small enough for the arithmetic circuits not to overflow
+        let x = encrypt(7, width, ck);
+        let y = encrypt(-3, width, ck);
+        let values: Vec<Vec<Ciphertext>> = [4, 1, 3, 2]
+            .iter()
+            .map(|&v| encrypt(v, width, ck))
+            .collect();
+        let vector: Vec<&[Ciphertext]> = values.iter().map(|v| v.as_slice()).collect();
+
+        let mut circuits: Vec<Circuit> = Vec::new();
+        circuits.push(("shl", Box::new(|| server.e_shl(&x, 1, &mut blank(width)).unwrap())));
+        circuits.push(("shr", Box::new(|| server.e_shr(&x, 1, &mut blank(width)).unwrap())));
+        circuits.push(("rotl", Box::new(|| server.e_rotl(&x, 1, &mut blank(width)).unwrap())));
+        circuits.push(("rotr", Box::new(|| server.e_rotr(&x, 1, &mut blank(width)).unwrap())));
+        circuits.push((
+            "comparator",
+            Box::new(|| {
+                server.comparator(sk, &x, &y, 1).unwrap();
+            }),
+        ));
+        circuits.push((
+            "scalar_cmp",
+            Box::new(|| {
+                server.scalar_cmp(sk, &x, 5, 1).unwrap();
+            }),
+        ));
+        circuits.push((
+            "scalar_add",
+            Box::new(|| server.scalar_add(sk, &x, 5, &mut blank(width)).unwrap()),
+        ));
+        circuits.push((
+            "scalar_mul",
+            Box::new(|| server.scalar_mul(sk, &x, 5, &mut blank(width)).unwrap()),
+        ));
+        circuits.push((
+            "scalar_div",
+            Box::new(|| server.scalar_div(sk, &x, 5, &mut blank(width)).unwrap()),
+        ));
+        circuits.push((
+            "subtracter",
+            Box::new(|| server.subtracter(sk, &x, &y, &mut blank(width)).unwrap()),
+        ));
+        circuits.push((
+            "adder",
+            Box::new(|| server.adder(sk, &x, &y, &mut blank(width)).unwrap()),
+        ));
+        circuits.push((
+            "sign_adder",
+            Box::new(|| server.sign_adder(sk, &x, &y, &mut blank(width)).unwrap()),
+        ));
+        circuits.push((
+            "multiplier",
+            Box::new(|| server.multiplier(sk, &x, &y, &mut blank(width)).unwrap()),
+        ));
+        circuits.push((
+            "divider",
+            Box::new(|| server.divider(sk, &x, &y, &mut blank(width)).unwrap()),
+        ));
+        circuits.push((
+            "modulo",
+            Box::new(|| server.modulo(sk, &x, &y, &mut blank(width)).unwrap()),
+        ));
+        circuits.push((
+            "max",
+            Box::new(|| server.max(sk, &vector, &mut blank(width)).unwrap()),
+        ));
+        circuits.push((
+            "min",
+            Box::new(|| server.min(sk, &vector, &mut blank(width)).unwrap()),
+        ));
+        circuits.push(("relu", Box::new(|| server.relu(sk, &y, &mut blank(width)).unwrap())));
+        circuits.push((
+            "mean",
+            Box::new(|| server.mean(sk, &vector, vector.len(), &mut blank(width)).unwrap()),
+        ));
+        circuits.push(("sqrt", Box::new(|| server.sqrt(sk, &x, &mut blank(width)).unwrap())));
+        circuits.push((
+            "variance",
+            Box::new(|| server.variance(sk, &vector, vector.len(), &mut blank(width)).unwrap()),
+        ));
+        circuits.push((
+            "standard_deviation",
+            Box::new(|| server.standard_deviation(sk, &vector, vector.len(), &mut blank(width)).unwrap()),
+        ));
+        circuits.push((
+            "histogram",
+            Box::new(|| server.histogram(sk, &vector, &[2, 4], &mut Vec::new()).unwrap()),
+        ));
+        circuits.push((
+            "dot_product",
+            Box::new(|| server.dot_product(sk, &vector, &vector, &mut Vec::new()).unwrap()),
+        ));
+
+        for (name, circuit) in circuits.iter_mut() {
+            if selected(name) {
+                bench.measure(name, width, Phase::Circuit, circuit);
+            }
+        }
+    }
+
+    // Biometric matching over long bit strings, e.g. iris codes
+    for &bits in templates.iter() {
+        let template = random(bits, ck);
+        let probe = random(bits, ck);
+        let count_width: usize = (usize::BITS - bits.leading_zeros()) as usize;
+
+        if selected("hamming_distance") {
+            bench.measure("hamming_distance", bits, Phase::Circuit, || {
+                server
+                    .hamming_distance(sk, &template, &probe, &mut blank(count_width))
+                    .unwrap()
+            });
+        }
+        if selected("match_below_threshold") {
+            bench.measure("match_below_threshold", bits, Phase::Circuit, || {
+                server
+                    .match_below_threshold(sk, &template, &probe, bits / 3)
+                    .unwrap();
+            });
+        }
+    }
+
+    bench
+        .write(&out)
+        .expect("Failed to write the benchmark results.");
+    println!(
+        "Results written to {}.csv and {}.json",
+        out.display(),
+        out.display()
+    );
+}
@@ -0,0 +1,247 @@
+//! # Description
+//! Timing harness for gates and circuits. Every measurement is repeated `runs` times (after
+//! one warm-up run) and summarised as mean, standard deviation, min, median and max in
+//! microseconds. Gate benchmarks time the gate evaluation and the `pitch_trim_bit`
+//! bootstrap separately, as two records.
+//!
+//! Results can be written as CSV and JSON; `benches/processor.rs` drives the harness over
+//! every gate and circuit through `cargo bench`.
+
+use crate::constant_fold_module::GateKind;
+use crate::pitch_trim_module::PitchTrimModule;
+use serde::Serialize;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::time::Instant;
+use tfhe::boolean::prelude::*;
+
+/// What a benchmark record measures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Phase {
+    /// Gate evaluation without bootstrapping
+    Gate,
+    /// `pitch_trim_bit` on the gate outputs
+    Bootstrap,
+    /// A complete circuit call
+    Circuit,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Phase::Gate => write!(f, "gate"),
+            Phase::Bootstrap => write!(f, "bootstrap"),
+            Phase::Circuit => write!(f, "circuit"),
+        }
+    }
+}
+
+/// Summary statistics of repeated timings, in microseconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize)]
+pub struct Summary {
+    pub runs: usize,
+    pub mean_us: f64,
+    pub std_dev_us: f64,
+    pub min_us: f64,
+    pub median_us: f64,
+    pub max_us: f64,
+}
+
+impl Summary {
+    pub fn from_samples(samples: &[f64]) -> Self {
+        if samples.is_empty() {
+            return Summary::default();
+        }
+        let runs = samples.len();
+        let mean = samples.iter().sum::<f64>() / runs as f64;
+        // Sample standard deviation; zero for a single run
+        let std_dev = if runs > 1 {
+            let var =
+                samples.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / (runs - 1) as f64;
+            var.sqrt()
+        } else {
+            0.0
+        };
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let median = if runs % 2 == 1 {
+            sorted[runs / 2]
+        } else {
+            (sorted[runs / 2 - 1] + sorted[runs / 2]) / 2.0
+        };
+        Summary {
+            runs,
+            mean_us: mean,
+            std_dev_us: std_dev,
+            min_us: sorted[0],
+            median_us: median,
+            max_us: sorted[runs - 1],
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct BenchRecord {
+    pub name: String,
+    pub width: usize,
+    pub phase: Phase,
+    #[serde(flatten)]
+    pub summary: Summary,
+}
+
+pub struct Benchmark {
+    runs: usize,
+    records: Vec<BenchRecord>,
+}
+
+impl Benchmark {
+    pub fn new(runs: usize) -> Self {
+        assert!(runs > 0, "A benchmark needs at least one run.");
+        Benchmark {
+            runs,
+            records: Vec::new(),
+        }
+    }
+
+    pub fn records(&self) -> &[BenchRecord] {
+        &self.records
+    }
+
+    fn push(&mut self, name: &str, width: usize, phase: Phase, samples: &[f64]) -> &BenchRecord {
+        let record = BenchRecord {
+            name: name.to_string(),
+            width,
+            phase,
+            summary: Summary::from_samples(samples),
+        };
+        println!(
+            "{:<24} {:>3} bits {:<9} mean {:>12.1} us  sd {:>10.1} us  ({} runs)",
+            record.name,
+            record.width,
+            record.phase,
+            record.summary.mean_us,
+            record.summary.std_dev_us,
+            record.summary.runs
+        );
+        self.records.push(record);
+        self.records.last().unwrap()
+    }
+
+    /// Times `f` over the configured number of runs, after one untimed warm-up run.
+    pub fn measure<F: FnMut()>(
+        &mut self,
+        name: &str,
+        width: usize,
+        phase: Phase,
+        mut f: F,
+    ) -> &BenchRecord {
+        f();
+        let mut samples: Vec<f64> = Vec::with_capacity(self.runs);
+        for _ in 0..self.runs {
+            let start = Instant::now();
+            f();
+            samples.push(start.elapsed().as_secs_f64() * 1e6);
+        }
+        self.push(name, width, phase, &samples)
+    }
+
+    /// Times a gate over all bits of `a` and `b` and, separately, the bootstrap of its
+    /// outputs. `result` receives the bootstrapped outputs of the last run.
+    pub fn gate(
+        &mut self,
+        sk: &ServerKey,
+        gate: GateKind,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) {
+        let name = format!("{:?}", gate).to_lowercase();
+        let mut gate_samples: Vec<f64> = Vec::with_capacity(self.runs);
+        let mut bootstrap_samples: Vec<f64> = Vec::with_capacity(self.runs);
+        for _ in 0..self.runs {
+            let start = Instant::now();
+            for i in 0..a.len() {
+                result[i] = Self::eval_gate(sk, gate, &a[i], &b[i]);
+            }
+            gate_samples.push(start.elapsed().as_secs_f64() * 1e6);
+
+            let start = Instant::now();
+            for bit in result.iter_mut().take(a.len()) {
+                *bit = PitchTrimModule::pitch_trim_bit(sk, bit);
+            }
+            bootstrap_samples.push(start.elapsed().as_secs_f64() * 1e6);
+        }
+        self.push(&name, a.len(), Phase::Gate, &gate_samples);
+        self.push(&name, a.len(), Phase::Bootstrap, &bootstrap_samples);
+    }
+
+    /// Times a gate over all bits of `a` and `b` without bootstrapping its outputs.
+    pub fn gate_no_bootstrap(
+        &mut self,
+        sk: &ServerKey,
+        gate: GateKind,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) {
+        let name = format!("{:?}", gate).to_lowercase();
+        let mut samples: Vec<f64> = Vec::with_capacity(self.runs);
+        for _ in 0..self.runs {
+            let start = Instant::now();
+            for i in 0..a.len() {
+                result[i] = Self::eval_gate(sk, gate, &a[i], &b[i]);
+            }
+            samples.push(start.elapsed().as_secs_f64() * 1e6);
+        }
+        self.push(&name, a.len(), Phase::Gate, &samples);
+    }
+
+    fn eval_gate(sk: &ServerKey, gate: GateKind, a: &Ciphertext, b: &Ciphertext) -> Ciphertext {
+        match gate {
+            GateKind::And => sk.and(a, b),
+            GateKind::Or => sk.or(a, b),
+            GateKind::Xor => sk.xor(a, b),
+            GateKind::Nand => sk.nand(a, b),
+            GateKind::Nor => sk.nor(a, b),
+            GateKind::Xnor => sk.xnor(a, b),
+        }
+    }
+
+    pub fn to_csv(&self) -> String {
+        let mut csv =
+            String::from("name,width,phase,runs,mean_us,std_dev_us,min_us,median_us,max_us\n");
+        for r in self.records.iter() {
+            let s = &r.summary;
+            csv.push_str(&format!(
+                "{},{},{},{},{:.3},{:.3},{:.3},{:.3},{:.3}\n",
+                r.name,
+                r.width,
+                r.phase,
+                s.runs,
+                s.mean_us,
+                s.std_dev_us,
+                s.min_us,
+                s.median_us,
+                s.max_us
+            ));
+        }
+        csv
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.records).expect("Benchmark serialisation failed.")
+    }
+
+    /// Writes the records to `<stem>.csv` and `<stem>.json`.
+    pub fn write(&self, stem: &Path) -> io::Result<()> {
+        if let Some(dir) = stem.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(stem.with_extension("csv"), self.to_csv())?;
+        fs::write(stem.with_extension("json"), self.to_json())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,37 @@
+//! # Description
+//! Reference evaluation server: listens on a local address and serves `protocol` sessions,
+//! one thread per client.
+
+use clap::Parser;
+use pulpfhe::server::Server;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(
+    name = "pulpfhe-server",
+    about = "Evaluate PulpFHE requests from remote clients"
+)]
+struct Args {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:7878")]
+    addr: String,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let server = match Server::bind(&args.addr) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("error: cannot listen on {}: {e}", args.addr);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Ok(addr) = server.local_addr() {
+        println!("listening on {addr}");
+    }
+    if let Err(e) = server.serve() {
+        eprintln!("error: {e}");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
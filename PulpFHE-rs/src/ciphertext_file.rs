@@ -0,0 +1,377 @@
+//! # Description
+//! File and stream format for encrypted integers and arrays of integers.
+//!
+//! A file is a fixed header followed by the bincode encoding of all bits as one
+//! `Vec<Ciphertext>`, element after element, each element LSB first (the bit order used by
+//! every circuit in `ProcessorCircuits`):
+//!
+//! | bytes | field                                          |
+//! |-------|------------------------------------------------|
+//! | 8     | magic `PULPCT\0\0`                             |
+//! | 2     | format version, little endian                  |
+//! | 2     | width in bits, little endian                   |
+//! | 1     | flags: bit 0 = signed, other bits reserved (0) |
+//! | 4     | element count, little endian                   |
+//! | 8     | parameter fingerprint, `ParameterSet::fingerprint` |
+//! | 8     | key id (`KeyInfo::key_id`)                     |
+//!
+//! `CiphertextFile::read` checks the header and that the body holds exactly
+//! `width * count` bits. Whether the integers belong to a given key, width or signedness is
+//! checked with `EncryptedIntegers::verify_key` and `verify_shape`.
+
+use crate::key_manager::KeyInfo;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use tfhe::boolean::prelude::{Ciphertext, ClientKey};
+
+#[cfg(test)]
+mod test_ciphertext_file;
+
+pub const CIPHERTEXT_MAGIC: [u8; 8] = *b"PULPCT\0\0";
+/// Bumped whenever the header or the bit encoding changes
+pub const CIPHERTEXT_FORMAT_VERSION: u16 = 1;
+const HEADER_LEN: usize = 33;
+const FLAG_SIGNED: u8 = 1;
+
+#[derive(Debug)]
+pub enum CiphertextFileError {
+    Io(io::Error),
+    /// The stream does not start with `CIPHERTEXT_MAGIC`
+    BadMagic,
+    UnsupportedVersion {
+        found: u16,
+        expected: u16,
+    },
+    /// Reserved flag bits are set
+    UnsupportedFlags(u8),
+    /// The body does not hold `width * count` bits
+    Truncated {
+        expected_bits: usize,
+        found_bits: usize,
+    },
+    WidthMismatch {
+        expected: usize,
+        found: usize,
+    },
+    SignednessMismatch {
+        expected: bool,
+        found: bool,
+    },
+    CountMismatch {
+        expected: usize,
+        found: usize,
+    },
+    /// Encrypted under another parameter set
+    ParameterMismatch {
+        expected: u64,
+        found: u64,
+    },
+    /// Encrypted under another key pair
+    KeyMismatch {
+        expected: u64,
+        found: u64,
+    },
+    /// The width is 0 or does not fit the 2-byte header field
+    UnsupportedWidth(usize),
+    /// The element count does not fit the 4-byte header field
+    TooManyIntegers(usize),
+    /// An integer does not have the declared width
+    MalformedInteger {
+        index: usize,
+        expected: usize,
+        found: usize,
+    },
+    Encoding(String),
+}
+
+impl fmt::Display for CiphertextFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CiphertextFileError::Io(e) => write!(f, "ciphertext I/O error: {e}"),
+            CiphertextFileError::BadMagic => write!(f, "not a PulpFHE ciphertext file"),
+            CiphertextFileError::UnsupportedVersion { found, expected } => write!(
+                f,
+                "ciphertext format version {found} is not supported (expected {expected})"
+            ),
+            CiphertextFileError::UnsupportedFlags(flags) => {
+                write!(f, "unsupported ciphertext flags {flags:#04x}")
+            }
+            CiphertextFileError::Truncated {
+                expected_bits,
+                found_bits,
+            } => write!(
+                f,
+                "expected {expected_bits} encrypted bits, found {found_bits}"
+            ),
+            CiphertextFileError::WidthMismatch { expected, found } => {
+                write!(f, "expected {expected}-bit integers, found {found}-bit")
+            }
+            CiphertextFileError::SignednessMismatch { expected, found } => write!(
+                f,
+                "expected {} integers, found {}",
+                signedness(*expected),
+                signedness(*found)
+            ),
+            CiphertextFileError::CountMismatch { expected, found } => {
+                write!(f, "expected {expected} integers, found {found}")
+            }
+            CiphertextFileError::ParameterMismatch { expected, found } => write!(
+                f,
+                "parameter fingerprint {found:#018x} does not match {expected:#018x}"
+            ),
+            CiphertextFileError::KeyMismatch { expected, found } => {
+                write!(f, "key id {found:#018x} does not match {expected:#018x}")
+            }
+            CiphertextFileError::UnsupportedWidth(width) => {
+                write!(
+                    f,
+                    "{width}-bit integers are not supported (1..={})",
+                    u16::MAX
+                )
+            }
+            CiphertextFileError::TooManyIntegers(count) => {
+                write!(f, "{count} integers do not fit one ciphertext file")
+            }
+            CiphertextFileError::MalformedInteger {
+                index,
+                expected,
+                found,
+            } => write!(f, "integer {index} has {found} bits, expected {expected}"),
+            CiphertextFileError::Encoding(e) => write!(f, "ciphertext encoding error: {e}"),
+        }
+    }
+}
+
+fn signedness(signed: bool) -> &'static str {
+    if signed { "signed" } else { "unsigned" }
+}
+
+impl std::error::Error for CiphertextFileError {}
+
+impl From<io::Error> for CiphertextFileError {
+    fn from(e: io::Error) -> Self {
+        CiphertextFileError::Io(e)
+    }
+}
+
+impl From<bincode::Error> for CiphertextFileError {
+    fn from(e: bincode::Error) -> Self {
+        match *e {
+            bincode::ErrorKind::Io(e) => CiphertextFileError::Io(e),
+            e => CiphertextFileError::Encoding(e.to_string()),
+        }
+    }
+}
+
+/// One or more encrypted integers of the same width, with the key they belong to.
+#[derive(Clone, Debug)]
+pub struct EncryptedIntegers {
+    pub width: usize,
+    pub signed: bool,
+    pub params_fingerprint: u64,
+    pub key_id: u64,
+    /// One `width`-bit vector per integer, LSB first
+    pub values: Vec<Vec<Ciphertext>>,
+}
+
+impl EncryptedIntegers {
+    /// Fails unless `width` fits the file header and every integer is `width` bits wide.
+    pub fn new(
+        width: usize,
+        signed: bool,
+        key: &KeyInfo,
+        values: Vec<Vec<Ciphertext>>,
+    ) -> Result<Self, CiphertextFileError> {
+        let integers = EncryptedIntegers {
+            width,
+            signed,
+            params_fingerprint: key.params.fingerprint(),
+            key_id: key.key_id,
+            values,
+        };
+        integers.check()?;
+        Ok(integers)
+    }
+
+    /// Encrypts the low `width` bits of every value in two's complement.
+    pub fn encrypt(
+        client_key: &ClientKey,
+        key: &KeyInfo,
+        width: usize,
+        signed: bool,
+        values: &[i64],
+    ) -> Result<Self, CiphertextFileError> {
+        let encrypted = values
+            .iter()
+            .map(|&v| {
+                (0..width)
+                    .map(|i| client_key.encrypt(i < 64 && (v >> i) & 1 != 0))
+                    .collect()
+            })
+            .collect();
+        Self::new(width, signed, key, encrypted)
+    }
+
+    /// Decrypts every value, sign-extending signed integers.
+    pub fn decrypt(&self, client_key: &ClientKey) -> Vec<i64> {
+        self.values
+            .iter()
+            .map(|bits| {
+                let mut value: i64 = 0;
+                for (i, bit) in bits.iter().enumerate().take(64) {
+                    if client_key.decrypt(bit) {
+                        value |= 1 << i;
+                    }
+                }
+                if self.signed && self.width < 64 && (value >> (self.width - 1)) & 1 == 1 {
+                    value |= -1i64 << self.width;
+                }
+                value
+            })
+            .collect()
+    }
+
+    /// Checks that the width, count and bit vectors can be written to a file.
+    fn check(&self) -> Result<(), CiphertextFileError> {
+        if self.width == 0 || self.width > u16::MAX as usize {
+            return Err(CiphertextFileError::UnsupportedWidth(self.width));
+        }
+        if self.values.len() > u32::MAX as usize {
+            return Err(CiphertextFileError::TooManyIntegers(self.values.len()));
+        }
+        for (index, value) in self.values.iter().enumerate() {
+            if value.len() != self.width {
+                return Err(CiphertextFileError::MalformedInteger {
+                    index,
+                    expected: self.width,
+                    found: value.len(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the integers were encrypted under `key`.
+    pub fn verify_key(&self, key: &KeyInfo) -> Result<(), CiphertextFileError> {
+        let fingerprint = key.params.fingerprint();
+        if self.params_fingerprint != fingerprint {
+            return Err(CiphertextFileError::ParameterMismatch {
+                expected: fingerprint,
+                found: self.params_fingerprint,
+            });
+        }
+        if self.key_id != key.key_id {
+            return Err(CiphertextFileError::KeyMismatch {
+                expected: key.key_id,
+                found: self.key_id,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks the width and signedness of the integers.
+    pub fn verify_shape(&self, width: usize, signed: bool) -> Result<(), CiphertextFileError> {
+        if self.width != width {
+            return Err(CiphertextFileError::WidthMismatch {
+                expected: width,
+                found: self.width,
+            });
+        }
+        if self.signed != signed {
+            return Err(CiphertextFileError::SignednessMismatch {
+                expected: signed,
+                found: self.signed,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks the number of integers.
+    pub fn verify_count(&self, count: usize) -> Result<(), CiphertextFileError> {
+        if self.values.len() != count {
+            return Err(CiphertextFileError::CountMismatch {
+                expected: count,
+                found: self.values.len(),
+            });
+        }
+        Ok(())
+    }
+}
+
+pub struct CiphertextFile;
+
+impl CiphertextFile {
+    pub fn write<W: Write>(
+        mut writer: W,
+        integers: &EncryptedIntegers,
+    ) -> Result<(), CiphertextFileError> {
+        // The fields are public, so the shape is checked again before anything is written
+        integers.check()?;
+        let mut header = [0u8; HEADER_LEN];
+        header[..8].copy_from_slice(&CIPHERTEXT_MAGIC);
+        header[8..10].copy_from_slice(&CIPHERTEXT_FORMAT_VERSION.to_le_bytes());
+        header[10..12].copy_from_slice(&(integers.width as u16).to_le_bytes());
+        header[12] = if integers.signed { FLAG_SIGNED } else { 0 };
+        header[13..17].copy_from_slice(&(integers.values.len() as u32).to_le_bytes());
+        header[17..25].copy_from_slice(&integers.params_fingerprint.to_le_bytes());
+        header[25..33].copy_from_slice(&integers.key_id.to_le_bytes());
+        writer.write_all(&header)?;
+
+        let bits: Vec<&Ciphertext> = integers.values.iter().flatten().collect();
+        bincode::serialize_into(&mut writer, &bits)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    pub fn read<R: Read>(mut reader: R) -> Result<EncryptedIntegers, CiphertextFileError> {
+        let mut header = [0u8; HEADER_LEN];
+        reader.read_exact(&mut header).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => CiphertextFileError::BadMagic,
+            _ => CiphertextFileError::Io(e),
+        })?;
+        if header[..8] != CIPHERTEXT_MAGIC {
+            return Err(CiphertextFileError::BadMagic);
+        }
+        let version = u16::from_le_bytes([header[8], header[9]]);
+        if version != CIPHERTEXT_FORMAT_VERSION {
+            return Err(CiphertextFileError::UnsupportedVersion {
+                found: version,
+                expected: CIPHERTEXT_FORMAT_VERSION,
+            });
+        }
+        let width = u16::from_le_bytes([header[10], header[11]]) as usize;
+        let flags = header[12];
+        if flags & !FLAG_SIGNED != 0 {
+            return Err(CiphertextFileError::UnsupportedFlags(flags));
+        }
+        let count = u32::from_le_bytes(header[13..17].try_into().unwrap()) as usize;
+        let params_fingerprint = u64::from_le_bytes(header[17..25].try_into().unwrap());
+        let key_id = u64::from_le_bytes(header[25..33].try_into().unwrap());
+
+        let bits: Vec<Ciphertext> = bincode::deserialize_from(reader)?;
+        if width == 0 || bits.len() != width * count {
+            return Err(CiphertextFileError::Truncated {
+                expected_bits: width * count,
+                found_bits: bits.len(),
+            });
+        }
+        let values = bits.chunks(width).map(|c| c.to_vec()).collect();
+        Ok(EncryptedIntegers {
+            width,
+            signed: flags & FLAG_SIGNED != 0,
+            params_fingerprint,
+            key_id,
+            values,
+        })
+    }
+
+    pub fn save(path: &Path, integers: &EncryptedIntegers) -> Result<(), CiphertextFileError> {
+        Self::write(BufWriter::new(File::create(path)?), integers)
+    }
+
+    pub fn load(path: &Path) -> Result<EncryptedIntegers, CiphertextFileError> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+}
@@ -0,0 +1,169 @@
+#![allow(warnings)]
+use super::*; // Imports items from the parent module
+use crate::key_manager::{KeyManager, ParameterSet};
+use crate::processor_boolean::ProcessorBoolean;
+use crate::processor_circuits::ProcessorCircuits;
+use rand::Rng;
+use serial_test::serial;
+
+fn to_bytes(integers: &EncryptedIntegers) -> Vec<u8> {
+    let mut bytes: Vec<u8> = Vec::new();
+    CiphertextFile::write(&mut bytes, integers).unwrap();
+    bytes
+}
+
+#[test]
+#[serial]
+fn test_ciphertext_roundtrip() {
+    let fn_name = "ciphertext_roundtrip";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let info = KeyManager::cached_key_info();
+    let mut rng = rand::thread_rng();
+
+    let a: Vec<i64> = (0..3).map(|_| rng.gen_range(-128..128)).collect();
+    let ct_a = EncryptedIntegers::encrypt(client_key, &info, 8, true, &a).unwrap();
+    let loaded = CiphertextFile::read(to_bytes(&ct_a).as_slice()).unwrap();
+    assert_eq!(loaded.width, 8);
+    assert!(loaded.signed);
+    assert_eq!(loaded.values.len(), 3);
+    assert!(loaded.verify_key(&info).is_ok());
+    assert!(loaded.verify_shape(8, true).is_ok());
+    assert!(loaded.verify_count(3).is_ok());
+    assert_eq!(loaded.decrypt(client_key), a);
+
+    // Loaded bits feed straight into the circuits
+    let server = ProcessorBoolean;
+    let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 8];
+    server.adder(
+        &server_key,
+        &loaded.values[0],
+        &loaded.values[1],
+        &mut ct_result,
+    ).unwrap();
+    let sum = EncryptedIntegers::new(8, true, &info, vec![ct_result]).unwrap();
+    assert_eq!(sum.decrypt(client_key), vec![(a[0] + a[1]) as i8 as i64]);
+
+    // Unsigned values are not sign-extended
+    let b = EncryptedIntegers::encrypt(client_key, &info, 16, false, &[40000]).unwrap();
+    assert_eq!(
+        CiphertextFile::read(to_bytes(&b).as_slice())
+            .unwrap()
+            .decrypt(client_key),
+        vec![40000]
+    );
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_ciphertext_rejects_mismatches() {
+    let fn_name = "ciphertext_rejects_mismatches";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, _) = KeyManager::cached_keys();
+    let info = KeyManager::cached_key_info();
+    let ct = EncryptedIntegers::encrypt(client_key, &info, 8, true, &[5, -5]).unwrap();
+    let bytes = to_bytes(&ct);
+
+    let mut bad_magic = bytes.clone();
+    bad_magic[1] = 0;
+    assert!(matches!(
+        CiphertextFile::read(bad_magic.as_slice()),
+        Err(CiphertextFileError::BadMagic)
+    ));
+
+    let mut newer = bytes.clone();
+    newer[8..10].copy_from_slice(&(CIPHERTEXT_FORMAT_VERSION + 1).to_le_bytes());
+    assert!(matches!(
+        CiphertextFile::read(newer.as_slice()),
+        Err(CiphertextFileError::UnsupportedVersion { .. })
+    ));
+
+    let mut msb_first = bytes.clone();
+    msb_first[12] |= 0x02;
+    assert!(matches!(
+        CiphertextFile::read(msb_first.as_slice()),
+        Err(CiphertextFileError::UnsupportedFlags(0x03))
+    ));
+
+    // Header claims more integers than the body holds
+    let mut more = bytes.clone();
+    more[13..17].copy_from_slice(&3u32.to_le_bytes());
+    assert!(matches!(
+        CiphertextFile::read(more.as_slice()),
+        Err(CiphertextFileError::Truncated {
+            expected_bits: 24,
+            found_bits: 16
+        })
+    ));
+    assert!(CiphertextFile::read(&bytes[..bytes.len() - 10]).is_err());
+
+    let loaded = CiphertextFile::read(bytes.as_slice()).unwrap();
+    assert!(matches!(
+        loaded.verify_shape(16, true),
+        Err(CiphertextFileError::WidthMismatch {
+            expected: 16,
+            found: 8
+        })
+    ));
+    assert!(matches!(
+        loaded.verify_shape(8, false),
+        Err(CiphertextFileError::SignednessMismatch {
+            expected: false,
+            found: true
+        })
+    ));
+    assert!(matches!(
+        loaded.verify_count(1),
+        Err(CiphertextFileError::CountMismatch {
+            expected: 1,
+            found: 2
+        })
+    ));
+
+    // Integers that cannot be written are refused with an error, not a panic
+    assert!(matches!(
+        EncryptedIntegers::new(0, false, &info, vec![]),
+        Err(CiphertextFileError::UnsupportedWidth(0))
+    ));
+    assert!(matches!(
+        EncryptedIntegers::encrypt(client_key, &info, u16::MAX as usize + 1, false, &[]),
+        Err(CiphertextFileError::UnsupportedWidth(65536))
+    ));
+    assert!(matches!(
+        EncryptedIntegers::new(8, true, &info, vec![ct.values[0].clone(), vec![]]),
+        Err(CiphertextFileError::MalformedInteger {
+            index: 1,
+            expected: 8,
+            found: 0
+        })
+    ));
+    let mut empty = loaded.clone();
+    empty.width = 0;
+    empty.values = vec![vec![]];
+    assert!(matches!(
+        CiphertextFile::write(Vec::new(), &empty),
+        Err(CiphertextFileError::UnsupportedWidth(0))
+    ));
+    let mut ragged = loaded.clone();
+    ragged.values[1].pop();
+    assert!(matches!(
+        CiphertextFile::write(Vec::new(), &ragged),
+        Err(CiphertextFileError::MalformedInteger { index: 1, .. })
+    ));
+
+    let other_key = KeyInfo::new(ParameterSet::Default);
+    assert!(matches!(
+        loaded.verify_key(&other_key),
+        Err(CiphertextFileError::KeyMismatch { .. })
+    ));
+    let other_params = KeyInfo {
+        params: ParameterSet::TfheLib,
+        key_id: info.key_id,
+    };
+    assert!(matches!(
+        loaded.verify_key(&other_params),
+        Err(CiphertextFileError::ParameterMismatch { .. })
+    ));
+    println!("[✓] PASS: {fn_name}\n");
+}
@@ -0,0 +1,208 @@
+//! # Description
+//! Export of recorded circuits and `Controller` dataflow for inspection.
+//!
+//! * `netlist_to_dot` / `netlist_to_json`: a `Netlist` recorded by `CircuitRecorder`, one
+//!   node per gate labelled with its type and logical depth. Bootstrapped gates are drawn
+//!   as boxes, free gates (NOT, MUX) as ellipses.
+//! * `dataflow_to_dot` / `dataflow_to_json`: the instructions a `Controller` issued, grouped
+//!   by reservation station, with edges from the instruction that last wrote each operand.
+
+use crate::circuit_recorder::{Netlist, NodeOp};
+use serde::Serialize;
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod test_circuit_export;
+
+/// An instruction issued to a reservation station by the `Controller`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct IssuedInstruction {
+    pub station: String,
+    pub id: String,
+    pub op1: String,
+    pub op2: String,
+    pub dst: String,
+    /// `None` when the instruction covers the whole register
+    pub bit: Option<i8>,
+}
+
+#[derive(Serialize)]
+struct JsonNode {
+    id: usize,
+    op: String,
+    inputs: Vec<usize>,
+    bootstrap: bool,
+    depth: usize,
+}
+
+#[derive(Serialize)]
+struct JsonNetlist {
+    name: String,
+    gates: usize,
+    bootstraps: usize,
+    depth: usize,
+    nodes: Vec<JsonNode>,
+    outputs: Vec<usize>,
+}
+
+#[derive(Serialize)]
+struct JsonDependency {
+    from: usize,
+    to: usize,
+    register: String,
+}
+
+#[derive(Serialize)]
+struct JsonDataflow {
+    instructions: Vec<IssuedInstruction>,
+    dependencies: Vec<JsonDependency>,
+}
+
+fn op_label(op: &NodeOp) -> String {
+    match op {
+        NodeOp::Input => "INPUT".to_string(),
+        NodeOp::Const(c) => format!("CONST {}", *c as u8),
+        NodeOp::Not => "NOT".to_string(),
+        NodeOp::Gate(gate) => format!("{:?}", gate).to_uppercase(),
+        NodeOp::Mux => "MUX".to_string(),
+    }
+}
+
+/// For every instruction, the earlier instructions it reads a register from.
+fn dependencies(program: &[IssuedInstruction]) -> Vec<JsonDependency> {
+    let mut last_writer: HashMap<&str, usize> = HashMap::new();
+    let mut deps: Vec<JsonDependency> = Vec::new();
+    for (i, instr) in program.iter().enumerate() {
+        for op in [&instr.op1, &instr.op2] {
+            if let Some(from) = last_writer.get(op.as_str()) {
+                deps.push(JsonDependency {
+                    from: *from,
+                    to: i,
+                    register: op.clone(),
+                });
+            }
+        }
+        last_writer.insert(instr.dst.as_str(), i);
+    }
+    deps
+}
+
+pub struct CircuitExport;
+
+impl CircuitExport {
+    /// Renders a netlist as a Graphviz digraph.
+    pub fn netlist_to_dot(net: &Netlist, name: &str) -> String {
+        let depths = net.depths();
+        let mut dot = String::new();
+        dot.push_str(&format!("digraph \"{}\" {{\n", name));
+        dot.push_str("    rankdir=LR;\n");
+        dot.push_str(&format!(
+            "    label=\"{}: {} gates, {} bootstraps, depth {}\";\n",
+            name,
+            net.gate_count(),
+            net.bootstrap_count(),
+            net.depth()
+        ));
+        for (i, node) in net.nodes.iter().enumerate() {
+            let shape = match node.op {
+                NodeOp::Input | NodeOp::Const(_) => "plaintext",
+                _ if node.is_bootstrapped() => "box",
+                _ => "ellipse",
+            };
+            let style = if node.is_bootstrapped() {
+                ", style=filled, fillcolor=lightgrey"
+            } else {
+                ""
+            };
+            dot.push_str(&format!(
+                "    n{} [label=\"{}\\nd={}\", shape={}{}];\n",
+                i,
+                op_label(&node.op),
+                depths[i],
+                shape,
+                style
+            ));
+            for j in node.inputs.iter() {
+                dot.push_str(&format!("    n{} -> n{};\n", j, i));
+            }
+        }
+        for (k, o) in net.outputs.iter().enumerate() {
+            dot.push_str(&format!("    out{} [label=\"out[{}]\", shape=plaintext];\n", k, k));
+            dot.push_str(&format!("    n{} -> out{};\n", o, k));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Serialises a netlist as JSON: a node list in topological order plus summary counts.
+    pub fn netlist_to_json(net: &Netlist, name: &str) -> String {
+        let depths = net.depths();
+        let json = JsonNetlist {
+            name: name.to_string(),
+            gates: net.gate_count(),
+            bootstraps: net.bootstrap_count(),
+            depth: net.depth(),
+            nodes: net
+                .nodes
+                .iter()
+                .enumerate()
+                .map(|(i, node)| JsonNode {
+                    id: i,
+                    op: op_label(&node.op),
+                    inputs: node.inputs.clone(),
+                    bootstrap: node.is_bootstrapped(),
+                    depth: depths[i],
+                })
+                .collect(),
+            outputs: net.outputs.clone(),
+        };
+        serde_json::to_string_pretty(&json).expect("Netlist serialisation failed.")
+    }
+
+    /// Renders the instructions issued by a `Controller` as a Graphviz digraph with one
+    /// cluster per reservation station.
+    pub fn dataflow_to_dot(program: &[IssuedInstruction], name: &str) -> String {
+        let mut dot = String::new();
+        dot.push_str(&format!("digraph \"{}\" {{\n", name));
+        dot.push_str("    rankdir=TB;\n");
+
+        let mut stations: Vec<&str> = program.iter().map(|i| i.station.as_str()).collect();
+        stations.sort_unstable();
+        stations.dedup();
+        for station in stations.iter() {
+            dot.push_str(&format!("    subgraph \"cluster_{}\" {{\n", station));
+            dot.push_str(&format!("        label=\"{}\";\n", station));
+            for (i, instr) in program.iter().enumerate() {
+                if instr.station != *station {
+                    continue;
+                }
+                let bit = match instr.bit {
+                    Some(b) => format!("[{}]", b),
+                    None => String::new(),
+                };
+                dot.push_str(&format!(
+                    "        i{} [label=\"#{} {}\\n{}{} <- {}, {}\", shape=box];\n",
+                    i, i, instr.id, instr.dst, bit, instr.op1, instr.op2
+                ));
+            }
+            dot.push_str("    }\n");
+        }
+        for dep in dependencies(program) {
+            dot.push_str(&format!(
+                "    i{} -> i{} [label=\"{}\"];\n",
+                dep.from, dep.to, dep.register
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Serialises the instructions issued by a `Controller` and their register dependencies.
+    pub fn dataflow_to_json(program: &[IssuedInstruction]) -> String {
+        let json = JsonDataflow {
+            instructions: program.to_vec(),
+            dependencies: dependencies(program),
+        };
+        serde_json::to_string_pretty(&json).expect("Dataflow serialisation failed.")
+    }
+}
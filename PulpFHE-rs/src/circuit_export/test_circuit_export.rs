@@ -0,0 +1,47 @@
+#![allow(warnings)]
+use super::*; // Imports items from the parent module
+use crate::circuit_recorder::CircuitRecorder;
+use crate::processor_boolean::ProcessorBoolean;
+use crate::processor_circuits::ProcessorCircuits;
+use rand::Rng;
+use serial_test::serial;
+use crate::key_manager::KeyManager;
+use tfhe::boolean::prelude::*;
+
+fn encrypt(a: i8, size: usize, client_key: &ClientKey) -> Vec<Ciphertext> {
+    (0..size).map(|i| client_key.encrypt(((a >> i) & 1) != 0)).collect()
+}
+
+#[test]
+#[serial]
+fn test_export_adder() {
+    let fn_name = "export_adder";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+    let mut rng = rand::thread_rng();
+
+    let ct_a = encrypt(rng.r#gen(), 8, &client_key);
+    let ct_b = encrypt(rng.r#gen(), 8, &client_key);
+    let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 8];
+
+    CircuitRecorder::start(&[&ct_a, &ct_b]);
+    server.adder(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
+    let net = CircuitRecorder::finish(&ct_result);
+
+    let dot = CircuitExport::netlist_to_dot(&net, "adder");
+    println!("{dot}");
+    assert!(dot.starts_with("digraph \"adder\""));
+    assert_eq!(dot.matches("shape=box").count(), net.bootstrap_count());
+    assert_eq!(dot.matches("-> out").count(), 8);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&CircuitExport::netlist_to_json(&net, "adder")).unwrap();
+    assert_eq!(json["gates"], net.gate_count());
+    assert_eq!(json["bootstraps"], net.bootstrap_count());
+    assert_eq!(json["depth"], net.depth());
+    assert_eq!(json["nodes"].as_array().unwrap().len(), net.nodes.len());
+    assert_eq!(json["outputs"].as_array().unwrap().len(), 8);
+    println!("[✓] PASS: {fn_name}\n");
+}
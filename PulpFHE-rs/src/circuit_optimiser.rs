@@ -0,0 +1,269 @@
+//! # Description
+//! Optimisation passes over a `Netlist` recorded by `CircuitRecorder`.
+//!
+//! # Available Passes
+//! * cse: common-subexpression elimination (commutative gates are normalised)
+//! * dead_gate_elimination: removes gates that do not reach an output
+//! * push_not: merges negations into NAND/NOR/XNOR, De Morgan and multiplexer forms
+//! * remove_double_negation: NOT(NOT(x)) -> x
+//!
+//! `optimise` runs all passes until the netlist stops shrinking and prints the gate and
+//! bootstrap counts before and after every pass.
+
+use crate::circuit_recorder::{Netlist, Node, NodeOp};
+use crate::constant_fold_module::GateKind;
+use std::collections::HashMap;
+
+/// A rewrite of a netlist into an equivalent one.
+type Pass = fn(&Netlist) -> Netlist;
+
+#[cfg(test)]
+mod test_circuit_optimiser;
+
+/// Appends nodes to a new netlist, optionally reusing structurally identical nodes.
+struct NetlistBuilder {
+    nodes: Vec<Node>,
+    table: HashMap<Node, usize>,
+    share: bool,
+}
+
+impl NetlistBuilder {
+    fn new(share: bool) -> Self {
+        NetlistBuilder {
+            nodes: Vec::new(),
+            table: HashMap::new(),
+            share,
+        }
+    }
+
+    fn add(&mut self, mut node: Node) -> usize {
+        if let NodeOp::Gate(_) = node.op {
+            // All two-input gates are commutative
+            node.inputs.sort_unstable();
+        }
+        if self.share
+            && node.op != NodeOp::Input
+            && let Some(id) = self.table.get(&node)
+        {
+            return *id;
+        }
+        self.nodes.push(node.clone());
+        let id = self.nodes.len() - 1;
+        if node.op != NodeOp::Input {
+            self.table.entry(node).or_insert(id);
+        }
+        id
+    }
+
+    fn finish(self, outputs: Vec<usize>) -> Netlist {
+        Netlist {
+            nodes: self.nodes,
+            outputs,
+        }
+    }
+}
+
+fn invert(gate: GateKind) -> GateKind {
+    match gate {
+        GateKind::And => GateKind::Nand,
+        GateKind::Or => GateKind::Nor,
+        GateKind::Xor => GateKind::Xnor,
+        GateKind::Nand => GateKind::And,
+        GateKind::Nor => GateKind::Or,
+        GateKind::Xnor => GateKind::Xor,
+    }
+}
+
+/// The gate computing `gate(NOT a, NOT b)` from `a` and `b`.
+fn de_morgan(gate: GateKind) -> GateKind {
+    match gate {
+        GateKind::And => GateKind::Nor,
+        GateKind::Or => GateKind::Nand,
+        GateKind::Nand => GateKind::Or,
+        GateKind::Nor => GateKind::And,
+        GateKind::Xor => GateKind::Xor,
+        GateKind::Xnor => GateKind::Xnor,
+    }
+}
+
+pub struct CircuitOptimiser;
+
+impl CircuitOptimiser {
+    /// Rebuilds `net` node by node. `rewrite` receives the node with its inputs already
+    /// mapped into the new netlist and returns the id of its replacement.
+    fn rebuild<F>(net: &Netlist, share: bool, mut rewrite: F) -> Netlist
+    where
+        F: FnMut(&mut NetlistBuilder, usize, Node) -> usize,
+    {
+        let mut builder = NetlistBuilder::new(share);
+        let mut map: Vec<usize> = Vec::with_capacity(net.nodes.len());
+        for (i, node) in net.nodes.iter().enumerate() {
+            let mapped = Node {
+                op: node.op,
+                inputs: node.inputs.iter().map(|j| map[*j]).collect(),
+            };
+            let id = rewrite(&mut builder, i, mapped);
+            map.push(id);
+        }
+        let outputs = net.outputs.iter().map(|o| map[*o]).collect();
+        builder.finish(outputs)
+    }
+
+    /// Number of readers of every node, counting each output as a reader.
+    fn fanout(net: &Netlist) -> Vec<usize> {
+        let mut fanout: Vec<usize> = vec![0; net.nodes.len()];
+        for node in net.nodes.iter() {
+            for j in node.inputs.iter() {
+                fanout[*j] += 1;
+            }
+        }
+        for o in net.outputs.iter() {
+            fanout[*o] += 1;
+        }
+        fanout
+    }
+
+    /// Merges nodes that compute the same operation on the same inputs.
+    pub fn cse(net: &Netlist) -> Netlist {
+        Self::rebuild(net, true, |b, _, node| b.add(node))
+    }
+
+    /// Removes gates and constants whose value never reaches an output. Inputs are kept so
+    /// that the netlist keeps its interface.
+    pub fn dead_gate_elimination(net: &Netlist) -> Netlist {
+        let mut live: Vec<bool> = vec![false; net.nodes.len()];
+        for o in net.outputs.iter() {
+            live[*o] = true;
+        }
+        for i in (0..net.nodes.len()).rev() {
+            if live[i] || net.nodes[i].op == NodeOp::Input {
+                live[i] = true;
+                for j in net.nodes[i].inputs.iter() {
+                    live[*j] = true;
+                }
+            }
+        }
+
+        let mut builder = NetlistBuilder::new(false);
+        let mut map: Vec<usize> = vec![usize::MAX; net.nodes.len()];
+        for (i, node) in net.nodes.iter().enumerate() {
+            if live[i] {
+                map[i] = builder.add(Node {
+                    op: node.op,
+                    inputs: node.inputs.iter().map(|j| map[*j]).collect(),
+                });
+            }
+        }
+        builder.finish(net.outputs.iter().map(|o| map[*o]).collect())
+    }
+
+    /// Pushes negations into the gates around them:
+    /// * NOT(g(a, b)) -> g'(a, b) with AND/NAND, OR/NOR and XOR/XNOR swapped, when the
+    ///   gate has no other reader (otherwise a free NOT would become a bootstrapped gate)
+    /// * g(NOT a, NOT b) -> De Morgan form, XOR(NOT a, b) -> XNOR(a, b) and vice versa
+    /// * MUX(NOT s, t, e) -> MUX(s, e, t)
+    pub fn push_not(net: &Netlist) -> Netlist {
+        let fanout = Self::fanout(net);
+        Self::rebuild(net, false, |b, i, node| {
+            let not_input = |b: &NetlistBuilder, id: usize| -> Option<usize> {
+                match b.nodes[id].op {
+                    NodeOp::Not => Some(b.nodes[id].inputs[0]),
+                    _ => None,
+                }
+            };
+            match node.op {
+                NodeOp::Not => {
+                    let x = node.inputs[0];
+                    let old_x = net.nodes[i].inputs[0];
+                    if let NodeOp::Gate(gate) = b.nodes[x].op
+                        && fanout[old_x] == 1
+                    {
+                        let inputs = b.nodes[x].inputs.clone();
+                        return b.add(Node {
+                            op: NodeOp::Gate(invert(gate)),
+                            inputs,
+                        });
+                    }
+                    b.add(node)
+                }
+                NodeOp::Gate(gate) => {
+                    let (x, y) = (node.inputs[0], node.inputs[1]);
+                    match (not_input(b, x), not_input(b, y)) {
+                        (Some(nx), Some(ny)) => b.add(Node {
+                            op: NodeOp::Gate(de_morgan(gate)),
+                            inputs: vec![nx, ny],
+                        }),
+                        (Some(nx), None) if matches!(gate, GateKind::Xor | GateKind::Xnor) => b.add(Node {
+                            op: NodeOp::Gate(invert(gate)),
+                            inputs: vec![nx, y],
+                        }),
+                        (None, Some(ny)) if matches!(gate, GateKind::Xor | GateKind::Xnor) => b.add(Node {
+                            op: NodeOp::Gate(invert(gate)),
+                            inputs: vec![x, ny],
+                        }),
+                        _ => b.add(node),
+                    }
+                }
+                NodeOp::Mux => match not_input(b, node.inputs[0]) {
+                    Some(s) => b.add(Node {
+                        op: NodeOp::Mux,
+                        inputs: vec![s, node.inputs[2], node.inputs[1]],
+                    }),
+                    None => b.add(node),
+                },
+                _ => b.add(node),
+            }
+        })
+    }
+
+    /// Replaces NOT(NOT(x)) by x.
+    pub fn remove_double_negation(net: &Netlist) -> Netlist {
+        Self::rebuild(net, false, |b, _, node| {
+            if node.op == NodeOp::Not {
+                let x = node.inputs[0];
+                if b.nodes[x].op == NodeOp::Not {
+                    return b.nodes[x].inputs[0];
+                }
+            }
+            b.add(node)
+        })
+    }
+
+    fn report(pass: &str, before: &Netlist, after: &Netlist) {
+        println!(
+            "\t {:<24} gates {:>6} -> {:<6} bootstraps {:>6} -> {:<6}",
+            pass,
+            before.gate_count(),
+            after.gate_count(),
+            before.bootstrap_count(),
+            after.bootstrap_count()
+        );
+    }
+
+    /// Runs every pass until the netlist no longer shrinks, printing the gate and bootstrap
+    /// counts before and after each pass.
+    pub fn optimise(net: &Netlist) -> Netlist {
+        let passes: [(&str, Pass); 4] = [
+            ("remove_double_negation", Self::remove_double_negation),
+            ("push_not", Self::push_not),
+            ("cse", Self::cse),
+            ("dead_gate_elimination", Self::dead_gate_elimination),
+        ];
+
+        println!("[*] OPTIMISE");
+        let mut current = net.clone();
+        loop {
+            let size = current.nodes.len();
+            for (name, pass) in passes.iter() {
+                let next = pass(&current);
+                Self::report(name, &current, &next);
+                current = next;
+            }
+            if current.nodes.len() >= size {
+                break;
+            }
+        }
+        Self::report("total", net, &current);
+        current
+    }
+}
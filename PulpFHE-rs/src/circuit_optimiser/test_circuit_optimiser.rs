@@ -0,0 +1,119 @@
+#![allow(warnings)]
+use super::*; // Imports items from the parent module
+use crate::circuit_recorder::CircuitRecorder;
+use crate::processor_boolean::ProcessorBoolean;
+use crate::processor_circuits::ProcessorCircuits;
+use rand::Rng;
+use serial_test::serial;
+use crate::key_manager::KeyManager;
+use tfhe::boolean::prelude::*;
+
+fn encode(a: i8, size: usize) -> Vec<bool> {
+    // Convert to binary representation as a Vec<bool>
+    let mut bits = Vec::with_capacity(size);
+    for i in 0..size {
+        let bit = ((a >> i) & 1) != 0;
+        bits.push(bit);
+    }
+    bits
+}
+
+fn encrypt(bits: &[bool], client_key: &ClientKey) -> Vec<Ciphertext> {
+    bits.iter().map(|&bit| client_key.encrypt(bit)).collect()
+}
+
+fn node(op: NodeOp, inputs: &[usize]) -> Node {
+    Node {
+        op,
+        inputs: inputs.to_vec(),
+    }
+}
+
+// Checks that two netlists compute the same function on random inputs
+fn assert_equivalent(a: &Netlist, b: &Netlist) {
+    let mut rng = rand::thread_rng();
+    assert_eq!(a.input_count(), b.input_count());
+    for _ in 0..64 {
+        let inputs: Vec<bool> = (0..a.input_count()).map(|_| rng.r#gen()).collect();
+        assert_eq!(a.eval(&inputs), b.eval(&inputs));
+    }
+}
+
+#[test]
+fn test_passes() {
+    let fn_name = "optimiser_passes";
+    println!("[*] TEST: {fn_name}");
+
+    // out0 = NOT(NOT(a)), out1 = NOT(AND(a, b)), out2 = XOR(NOT a, b), out3 = AND(b, a)
+    let and = NodeOp::Gate(GateKind::And);
+    let net = Netlist {
+        nodes: vec![
+            node(NodeOp::Input, &[]),
+            node(NodeOp::Input, &[]),
+            node(NodeOp::Not, &[0]),
+            node(NodeOp::Not, &[2]),
+            node(and, &[0, 1]),
+            node(NodeOp::Not, &[4]),
+            node(NodeOp::Gate(GateKind::Xor), &[2, 1]),
+            node(and, &[1, 0]),
+            node(NodeOp::Gate(GateKind::Or), &[0, 1]),
+        ],
+        outputs: vec![3, 5, 6, 7],
+    };
+
+    let pass = CircuitOptimiser::remove_double_negation(&net);
+    assert_eq!(pass.outputs[0], 0);
+    assert_equivalent(&net, &pass);
+
+    let pass = CircuitOptimiser::cse(&net);
+    assert_eq!(pass.outputs[3], pass.nodes[pass.outputs[1]].inputs[0]);
+    assert_eq!(pass.gate_count(), net.gate_count() - 1);
+    assert_equivalent(&net, &pass);
+
+    let pass = CircuitOptimiser::dead_gate_elimination(&net);
+    assert_eq!(pass.gate_count(), net.gate_count() - 1);
+    assert_equivalent(&net, &pass);
+
+    let pass = CircuitOptimiser::push_not(&net);
+    assert_eq!(pass.nodes[pass.outputs[1]].op, NodeOp::Gate(GateKind::Nand));
+    assert_eq!(pass.nodes[pass.outputs[2]].op, NodeOp::Gate(GateKind::Xnor));
+    assert_equivalent(&net, &pass);
+
+    let opt = CircuitOptimiser::optimise(&net);
+    // NAND(a, b), XNOR(a, b) and AND(a, b) remain
+    assert_eq!(opt.gate_count(), 3);
+    assert_eq!(opt.bootstrap_count(), 3);
+    assert_equivalent(&net, &opt);
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_optimise_comparator() {
+    let fn_name = "optimise_comparator";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+    let mut rng = rand::thread_rng();
+
+    let a: i8 = rng.r#gen();
+    let b: i8 = rng.r#gen();
+    let ct_a = encrypt(&encode(a, 8), &client_key);
+    let ct_b = encrypt(&encode(b, 8), &client_key);
+
+    CircuitRecorder::start(&[&ct_a, &ct_b]);
+    let ct_result = server.comparator(&server_key, &ct_a, &ct_b, 1).unwrap();
+    let net = CircuitRecorder::finish(&[ct_result.clone()]);
+
+    // The recorded netlist computes what the encrypted circuit computed
+    let mut inputs = encode(a, 8);
+    inputs.extend(encode(b, 8));
+    assert_eq!(net.eval(&inputs), vec![client_key.decrypt(&ct_result)]);
+
+    let opt = CircuitOptimiser::optimise(&net);
+    assert!(opt.gate_count() < net.gate_count());
+    assert!(opt.bootstrap_count() <= net.bootstrap_count());
+    assert_equivalent(&net, &opt);
+    println!("[✓] PASS: {fn_name}\n");
+}
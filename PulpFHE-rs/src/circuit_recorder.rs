@@ -0,0 +1,210 @@
+//! # Description
+//! Records the gates that `ProcessorBoolean` evaluates into a netlist (gate graph).
+//!
+//! Recording is enabled per thread with `CircuitRecorder::start` and stopped with
+//! `CircuitRecorder::finish`, which returns the recorded `Netlist`. Ciphertexts are
+//! identified by their LWE contents, so copies of a ciphertext map onto the same node
+//! and trivial ciphertexts map onto constant nodes.
+
+use crate::constant_fold_module::GateKind;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use tfhe::boolean::ciphertext::Ciphertext;
+
+/// The operation performed by a netlist node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NodeOp {
+    /// An encrypted input bit
+    Input,
+    /// A trivial (known) bit
+    Const(bool),
+    /// A negation, which does not need bootstrapping
+    Not,
+    /// A bootstrapped two-input gate
+    Gate(GateKind),
+    /// A multiplexer with inputs (selector, then, else)
+    Mux,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Node {
+    pub op: NodeOp,
+    pub inputs: Vec<usize>,
+}
+
+impl Node {
+    /// Whether the output of this node is refreshed by `pitch_trim_bit`.
+    pub fn is_bootstrapped(&self) -> bool {
+        matches!(self.op, NodeOp::Gate(_))
+    }
+
+    /// Whether this node evaluates a gate (as opposed to an input or a constant).
+    pub fn is_gate(&self) -> bool {
+        matches!(self.op, NodeOp::Not | NodeOp::Gate(_) | NodeOp::Mux)
+    }
+}
+
+/// A gate graph in topological order. Every node only refers to nodes before it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Netlist {
+    pub nodes: Vec<Node>,
+    pub outputs: Vec<usize>,
+}
+
+impl Netlist {
+    /// Number of evaluated gates, including negations and multiplexers.
+    pub fn gate_count(&self) -> usize {
+        self.nodes.iter().filter(|n| n.is_gate()).count()
+    }
+
+    /// Number of gates followed by a bootstrap.
+    pub fn bootstrap_count(&self) -> usize {
+        self.nodes.iter().filter(|n| n.is_bootstrapped()).count()
+    }
+
+    /// Number of encrypted inputs.
+    pub fn input_count(&self) -> usize {
+        self.nodes.iter().filter(|n| n.op == NodeOp::Input).count()
+    }
+
+    /// Logical depth of every node; inputs and constants have depth 0.
+    pub fn depths(&self) -> Vec<usize> {
+        let mut depths: Vec<usize> = vec![0; self.nodes.len()];
+        for (i, node) in self.nodes.iter().enumerate() {
+            if node.is_gate() {
+                depths[i] = 1 + node.inputs.iter().map(|j| depths[*j]).max().unwrap_or(0);
+            }
+        }
+        depths
+    }
+
+    /// Logical depth of the circuit, i.e. the deepest output.
+    pub fn depth(&self) -> usize {
+        let depths = self.depths();
+        self.outputs.iter().map(|o| depths[*o]).max().unwrap_or(0)
+    }
+
+    /// Evaluates the netlist on plaintext inputs, given in the order the inputs were recorded.
+    pub fn eval(&self, inputs: &[bool]) -> Vec<bool> {
+        assert_eq!(inputs.len(), self.input_count(), "Wrong number of netlist inputs.");
+        let mut values: Vec<bool> = vec![false; self.nodes.len()];
+        let mut next_input: usize = 0;
+        for (i, node) in self.nodes.iter().enumerate() {
+            let x = |k: usize| values[node.inputs[k]];
+            values[i] = match node.op {
+                NodeOp::Input => {
+                    next_input += 1;
+                    inputs[next_input - 1]
+                }
+                NodeOp::Const(c) => c,
+                NodeOp::Not => !x(0),
+                NodeOp::Gate(gate) => gate.eval(x(0), x(1)),
+                NodeOp::Mux => {
+                    if x(0) {
+                        x(1)
+                    } else {
+                        x(2)
+                    }
+                }
+            };
+        }
+        self.outputs.iter().map(|o| values[*o]).collect()
+    }
+}
+
+#[derive(Default)]
+struct RecorderState {
+    nodes: Vec<Node>,
+    ids: HashMap<u64, usize>,
+    consts: [Option<usize>; 2],
+}
+
+impl RecorderState {
+    fn push(&mut self, node: Node) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    fn node_of(&mut self, ct: &Ciphertext) -> usize {
+        match ct {
+            Ciphertext::Trivial(b) => match self.consts[*b as usize] {
+                Some(id) => id,
+                None => {
+                    let id = self.push(Node { op: NodeOp::Const(*b), inputs: Vec::new() });
+                    self.consts[*b as usize] = Some(id);
+                    id
+                }
+            },
+            Ciphertext::Encrypted(_) => {
+                let key = fingerprint(ct);
+                match self.ids.get(&key) {
+                    Some(id) => *id,
+                    None => {
+                        // A ciphertext produced outside the recording is a fresh input
+                        let id = self.push(Node { op: NodeOp::Input, inputs: Vec::new() });
+                        self.ids.insert(key, id);
+                        id
+                    }
+                }
+            }
+        }
+    }
+}
+
+thread_local! {
+    static RECORDER: RefCell<Option<RecorderState>> = const { RefCell::new(None) };
+}
+
+fn fingerprint(ct: &Ciphertext) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    match ct {
+        Ciphertext::Encrypted(lwe) => lwe.as_ref().hash(&mut hasher),
+        Ciphertext::Trivial(b) => b.hash(&mut hasher),
+    }
+    hasher.finish()
+}
+
+pub struct CircuitRecorder;
+
+impl CircuitRecorder {
+    /// Starts recording on this thread. The bits of `inputs` become the netlist inputs,
+    /// in order; any other ciphertext met during recording is added as an extra input.
+    pub fn start(inputs: &[&[Ciphertext]]) {
+        let mut state = RecorderState::default();
+        for ct in inputs.iter().flat_map(|x| x.iter()) {
+            state.node_of(ct);
+        }
+        RECORDER.with(|r| *r.borrow_mut() = Some(state));
+    }
+
+    /// Whether a recording is in progress on this thread.
+    pub fn is_recording() -> bool {
+        RECORDER.with(|r| r.borrow().is_some())
+    }
+
+    /// Records a gate evaluation. Does nothing when not recording or when the output is a
+    /// constant.
+    pub fn record(op: NodeOp, inputs: &[&Ciphertext], output: &Ciphertext) {
+        RECORDER.with(|r| {
+            if let Some(state) = r.borrow_mut().as_mut() {
+                if let Ciphertext::Trivial(_) = output {
+                    return;
+                }
+                let inputs: Vec<usize> = inputs.iter().map(|ct| state.node_of(ct)).collect();
+                let id = state.push(Node { op, inputs });
+                state.ids.insert(fingerprint(output), id);
+            }
+        });
+    }
+
+    /// Stops recording and returns the netlist computing `outputs`.
+    pub fn finish(outputs: &[Ciphertext]) -> Netlist {
+        let mut state = RECORDER
+            .with(|r| r.borrow_mut().take())
+            .expect("`CircuitRecorder::finish` called without `start`.");
+        let outputs: Vec<usize> = outputs.iter().map(|ct| state.node_of(ct)).collect();
+        Netlist { nodes: state.nodes, outputs }
+    }
+}
@@ -0,0 +1,75 @@
+//! # Description
+//! Gate-count, depth and bootstrap-count statistics of a single circuit call, collected by
+//! `ProcessorBoolean::instrumented`. The numbers only depend on the circuit and on which
+//! operands are trivial, not on the encrypted values, so they are free of timing noise.
+
+use crate::circuit_recorder::{Netlist, NodeOp};
+use crate::constant_fold_module::GateKind;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CircuitStats {
+    pub and: usize,
+    pub or: usize,
+    pub xor: usize,
+    pub nand: usize,
+    pub nor: usize,
+    pub xnor: usize,
+    pub not: usize,
+    pub mux: usize,
+    /// Calls to `PitchTrimModule::pitch_trim_bit`
+    pub pitch_trims: usize,
+    /// Gates skipped because an operand was trivial
+    pub trivial_gates: usize,
+    /// Logical depth of the evaluated gates
+    pub depth: usize,
+}
+
+impl CircuitStats {
+    /// Counts the gates of a recorded netlist. `pitch_trims` and `trivial_gates` are not
+    /// visible in the netlist and are left at zero.
+    pub fn from_netlist(net: &Netlist) -> Self {
+        let mut stats = CircuitStats::default();
+        for node in net.nodes.iter() {
+            match node.op {
+                NodeOp::Gate(GateKind::And) => stats.and += 1,
+                NodeOp::Gate(GateKind::Or) => stats.or += 1,
+                NodeOp::Gate(GateKind::Xor) => stats.xor += 1,
+                NodeOp::Gate(GateKind::Nand) => stats.nand += 1,
+                NodeOp::Gate(GateKind::Nor) => stats.nor += 1,
+                NodeOp::Gate(GateKind::Xnor) => stats.xnor += 1,
+                NodeOp::Not => stats.not += 1,
+                NodeOp::Mux => stats.mux += 1,
+                NodeOp::Input | NodeOp::Const(_) => {}
+            }
+        }
+        stats.depth = net.depth();
+        stats
+    }
+
+    /// Total number of evaluated gates, including NOT and MUX.
+    pub fn gates(&self) -> usize {
+        self.and + self.or + self.xor + self.nand + self.nor + self.xnor + self.not + self.mux
+    }
+}
+
+impl fmt::Display for CircuitStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "gates={} (and={} or={} xor={} nand={} nor={} xnor={} not={} mux={}) pitch_trims={} trivial={} depth={}",
+            self.gates(),
+            self.and,
+            self.or,
+            self.xor,
+            self.nand,
+            self.nor,
+            self.xnor,
+            self.not,
+            self.mux,
+            self.pitch_trims,
+            self.trivial_gates,
+            self.depth
+        )
+    }
+}
@@ -0,0 +1,93 @@
+//! # Description
+//! Client side of the `protocol` module: uploads a server key once, then sends `Eval` and
+//! `Run` requests to a `Server` and returns the encrypted results. Decryption stays with
+//! the caller, who keeps the `ClientKey`.
+
+use crate::ciphertext_file::{CiphertextFile, EncryptedIntegers};
+use crate::evaluator::EvalOptions;
+use crate::key_manager::{KeyInfo, KeyManager};
+use crate::protocol::{MessageKind, Protocol, ProtocolError};
+use std::collections::HashMap;
+use std::io::{BufReader, BufWriter};
+use std::net::{TcpStream, ToSocketAddrs};
+use tfhe::boolean::prelude::ServerKey;
+
+pub struct Client {
+    reader: BufReader<TcpStream>,
+    writer: BufWriter<TcpStream>,
+}
+
+impl Client {
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> Result<Client, ProtocolError> {
+        let stream = TcpStream::connect(addr)?;
+        Ok(Client {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: BufWriter::new(stream),
+        })
+    }
+
+    /// Uploads the key the following requests are evaluated with.
+    pub fn upload_server_key(
+        &mut self,
+        key: &ServerKey,
+        info: &KeyInfo,
+    ) -> Result<(), ProtocolError> {
+        let mut payload = Vec::new();
+        KeyManager::write_server_key(&mut payload, key, info)?;
+        let reply = self.request(MessageKind::UploadKey, &payload, MessageKind::KeyAccepted)?;
+        let accepted = reply
+            .as_slice()
+            .try_into()
+            .map(u64::from_le_bytes)
+            .map_err(|_| ProtocolError::Encoding("malformed key acknowledgement".to_string()))?;
+        if accepted != info.key_id {
+            return Err(ProtocolError::Encoding(format!(
+                "server accepted key {accepted:#018x} instead of {:#018x}",
+                info.key_id
+            )));
+        }
+        Ok(())
+    }
+
+    /// Evaluates `op` (an `Operation` name) on `inputs`.
+    pub fn eval(
+        &mut self,
+        op: &str,
+        inputs: &[EncryptedIntegers],
+        options: &EvalOptions,
+    ) -> Result<EncryptedIntegers, ProtocolError> {
+        let payload = Protocol::encode_eval(op, options, inputs)?;
+        let reply = self.request(MessageKind::Eval, &payload, MessageKind::Result)?;
+        Ok(CiphertextFile::read(reply.as_slice())?)
+    }
+
+    /// Runs a `Program` source with one integer per `input` register and returns the
+    /// `output` registers by name.
+    pub fn run(
+        &mut self,
+        program: &str,
+        inputs: &[(&str, &EncryptedIntegers)],
+        signed: bool,
+    ) -> Result<HashMap<String, EncryptedIntegers>, ProtocolError> {
+        let payload = Protocol::encode_run(program, inputs, signed)?;
+        let reply = self.request(MessageKind::Run, &payload, MessageKind::Outputs)?;
+        Ok(Protocol::decode_outputs(&reply)?.into_iter().collect())
+    }
+
+    fn request(
+        &mut self,
+        kind: MessageKind,
+        payload: &[u8],
+        expected: MessageKind,
+    ) -> Result<Vec<u8>, ProtocolError> {
+        Protocol::write_frame(&mut self.writer, kind, payload)?;
+        match Protocol::read_frame(&mut self.reader)? {
+            Some((reply, payload)) if reply == expected => Ok(payload),
+            Some((MessageKind::Error, payload)) => {
+                Err(ProtocolError::Remote(Protocol::decode_error(&payload)?))
+            }
+            Some((reply, _)) => Err(ProtocolError::UnexpectedMessage(reply)),
+            None => Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into()),
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! # Description
+//! Constant propagation for `Ciphertext::Trivial` operands. When one side of a gate is a
+//! known constant the result is either a constant, the other operand, or its negation,
+//! so neither the gate nor `PitchTrimModule::pitch_trim_bit` has to run.
+//!
+//! The module keeps per-thread counters of the gates and bootstraps it has skipped.
+
+use crate::circuit_recorder::{CircuitRecorder, NodeOp};
+use std::cell::Cell;
+use tfhe::boolean::ciphertext::Ciphertext;
+use tfhe::boolean::prelude::ServerKey;
+
+/// The two-input gates the processor can evaluate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GateKind {
+    And,
+    Or,
+    Xor,
+    Nand,
+    Nor,
+    Xnor,
+}
+
+impl GateKind {
+    /// Evaluates the gate on plaintext bits.
+    pub fn eval(&self, a: bool, b: bool) -> bool {
+        match self {
+            GateKind::And => a & b,
+            GateKind::Or => a | b,
+            GateKind::Xor => a ^ b,
+            GateKind::Nand => !(a & b),
+            GateKind::Nor => !(a | b),
+            GateKind::Xnor => !(a ^ b),
+        }
+    }
+}
+
+/// Number of gates and bootstraps skipped by constant folding.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FoldStats {
+    pub gates: u64,
+    pub bootstraps: u64,
+}
+
+thread_local! {
+    static SAVED: Cell<FoldStats> = Cell::new(FoldStats::default());
+}
+
+pub struct ConstantFoldModule;
+
+impl ConstantFoldModule {
+    /// Folds a two-input gate if at least one operand is trivial. Returns `None` when both
+    /// operands are encrypted and the gate has to be evaluated.
+    pub fn fold_gate(
+        sk: &ServerKey,
+        gate: GateKind,
+        a: &Ciphertext,
+        b: &Ciphertext,
+    ) -> Option<Ciphertext> {
+        let (constant, other) = match (a, b) {
+            (Ciphertext::Trivial(a), Ciphertext::Trivial(b)) => {
+                Self::record(true);
+                return Some(Ciphertext::Trivial(gate.eval(*a, *b)));
+            }
+            (Ciphertext::Trivial(c), other) | (other, Ciphertext::Trivial(c)) => (*c, other),
+            _ => return None,
+        };
+
+        // With one input fixed the gate is a constant, a wire or an inverter
+        let when_false = gate.eval(constant, false);
+        let when_true = gate.eval(constant, true);
+        let result = match (when_false, when_true) {
+            (false, false) => Ciphertext::Trivial(false),
+            (true, true) => Ciphertext::Trivial(true),
+            (false, true) => other.clone(),
+            (true, false) => {
+                let negated = sk.not(other);
+                CircuitRecorder::record(NodeOp::Not, &[other], &negated);
+                negated
+            }
+        };
+        Self::record(true);
+        Some(result)
+    }
+
+    /// Folds a multiplexer with a trivial selector, or with trivial branches that make the
+    /// output a constant or a copy of the selector.
+    pub fn fold_mux(
+        sk: &ServerKey,
+        selector: &Ciphertext,
+        ct_then: &Ciphertext,
+        ct_else: &Ciphertext,
+    ) -> Option<Ciphertext> {
+        let result = match (selector, ct_then, ct_else) {
+            (Ciphertext::Trivial(s), _, _) => {
+                if *s {
+                    ct_then.clone()
+                } else {
+                    ct_else.clone()
+                }
+            }
+            (_, Ciphertext::Trivial(t), Ciphertext::Trivial(e)) => match (*t, *e) {
+                (true, false) => selector.clone(),
+                (false, true) => {
+                    let negated = sk.not(selector);
+                    CircuitRecorder::record(NodeOp::Not, &[selector], &negated);
+                    negated
+                }
+                (c, _) => Ciphertext::Trivial(c),
+            },
+            _ => return None,
+        };
+        // The multiplexer is not pitch-trimmed, so only the gate is saved
+        Self::record(false);
+        Some(result)
+    }
+
+    /// Returns the gates and bootstraps saved on this thread since the last `reset`.
+    pub fn saved() -> FoldStats {
+        SAVED.with(|s| s.get())
+    }
+
+    /// Clears the saved gate and bootstrap counters of this thread.
+    pub fn reset() {
+        SAVED.with(|s| s.set(FoldStats::default()));
+    }
+
+    fn record(bootstrap: bool) {
+        SAVED.with(|s| {
+            let mut stats = s.get();
+            stats.gates += 1;
+            if bootstrap {
+                stats.bootstraps += 1;
+            }
+            s.set(stats);
+        });
+    }
+}
@@ -1,11 +1,24 @@
+use crate::circuit_export::{CircuitExport, IssuedInstruction};
+use crate::constant_fold_module::GateKind;
 use crate::processor_boolean::*;
 use crate::processor_circuits::*;
 use crate::processor_gates::*;
+use crate::error::PulpError;
+use crate::evaluator::{self, EvalError, EvalOptions, Operation};
+use crate::oblivious_memory::ObliviousMemory;
+use crate::program::{Instruction, Operand, Program, Statement};
 use crate::register_table::{RegisterElement, RegisterTable, Subscriber};
 use crate::reservation_stations::*;
+use std::collections::HashMap;
 use tfhe::boolean::prelude::*;
 #[cfg(test)]
 mod test_controller_8;
+
+/// Register values and memories saved around the arms of a block.
+struct Snapshot {
+    registers: Vec<(String, Vec<Ciphertext>)>,
+    memories: Vec<(String, ObliviousMemory)>,
+}
 pub struct Controller {
     and_rs: AndRs,
     or_rs: OrRs,
@@ -16,6 +29,8 @@ pub struct Controller {
     register_tbl: RegisterTable,
     sk: ServerKey,
     processor: ProcessorBoolean,
+    issued: Vec<IssuedInstruction>,
+    memories: HashMap<String, ObliviousMemory>,
 }
 
 impl Controller {
@@ -30,6 +45,8 @@ impl Controller {
             register_tbl: RegisterTable::new(),
             sk: sk,
             processor: ProcessorBoolean::new(),
+            issued: Vec::new(),
+            memories: HashMap::new(),
         }
     }
 
@@ -65,6 +82,345 @@ impl Controller {
         &self.sk
     }
 
+    /// The instructions issued to the reservation stations so far, in program order.
+    pub fn get_issued(&self) -> &[IssuedInstruction] {
+        &self.issued
+    }
+
+    /// Graphviz dataflow of the issued instructions, clustered by reservation station.
+    pub fn dataflow_dot(&self, name: &str) -> String {
+        CircuitExport::dataflow_to_dot(&self.issued, name)
+    }
+
+    /// JSON dataflow of the issued instructions and their register dependencies.
+    pub fn dataflow_json(&self) -> String {
+        CircuitExport::dataflow_to_json(&self.issued)
+    }
+
+    /// Writes `value` to register `name`, creating the register if needed.
+    pub fn set_register(&mut self, name: &str, value: Vec<Ciphertext>) {
+        let element = RegisterElement::new(name.to_string(), "-".to_string(), Some(value), -2, 1);
+        if self.register_tbl.get_element_dst(name.to_string()).is_some() {
+            self.register_tbl.update_element_dst(element);
+        } else {
+            self.register_tbl.add_element(element);
+        }
+    }
+
+    /// The current (speculative) value of register `name`, if it holds one.
+    pub fn register(&self, name: &str) -> Option<&[Ciphertext]> {
+        self.register_tbl
+            .get_element_dst(name.to_string())
+            .and_then(|element| element.vec_ctxt.as_deref())
+    }
+
+    /// Commits the completed register writes to the architectural register file, in
+    /// program order, and returns how many were committed.
+    pub fn commit(&mut self) -> usize {
+        self.register_tbl.commit()
+    }
+
+    /// Completes the in-flight register write `tag` with `value` and wakes the reservation
+    /// station entries waiting for it. Returns false if `tag` is not in flight.
+    pub fn complete(&mut self, tag: usize, value: Vec<Ciphertext>) -> bool {
+        self.and_rs.wakeup(tag, &value);
+        self.or_rs.wakeup(tag, &value);
+        self.xor_rs.wakeup(tag, &value);
+        self.nand_rs.wakeup(tag, &value);
+        self.nor_rs.wakeup(tag, &value);
+        self.xnor_rs.wakeup(tag, &value);
+        self.register_tbl.complete(tag, value)
+    }
+
+    /// The oblivious memory `name`, if a program declared it.
+    pub fn memory(&self, name: &str) -> Option<&ObliviousMemory> {
+        self.memories.get(name)
+    }
+
+    /// Runs `program`, issuing every gate to its reservation station in program order.
+    /// `inputs` holds a value for every `input` register of the program; the returned map
+    /// holds the value of every `output` register. The register writes of a program that
+    /// runs to completion are committed.
+    pub fn run(
+        &mut self,
+        program: &Program,
+        inputs: &HashMap<String, Vec<Ciphertext>>,
+    ) -> Result<HashMap<String, Vec<Ciphertext>>, PulpError> {
+        let mut outputs = HashMap::new();
+        self.execute(&program.instructions, inputs, &mut outputs)?;
+        self.commit();
+        Ok(outputs)
+    }
+
+    /// Dispatches `op` to the circuit functional unit as a whole: the `srcs` registers are
+    /// read, the `ProcessorBoolean` circuit runs on them, and its results are written to
+    /// `dsts` in order, renamed like any other register write.
+    ///
+    /// Each group of `srcs` is one operand of `Evaluator::eval`, one integer per register,
+    /// and `options` carries the same plaintext arguments; the operands, options and results
+    /// follow the same rules, as both go through `evaluator::dispatch`.
+    pub fn issue_circuit(
+        &mut self,
+        op: Operation,
+        srcs: &[&[&str]],
+        dsts: &[&str],
+        options: &EvalOptions,
+    ) -> Result<(), PulpError> {
+        let name = op.name();
+        let values = srcs
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|src| self.register_value(src))
+                    .collect::<Result<Vec<_>, PulpError>>()
+            })
+            .collect::<Result<Vec<_>, PulpError>>()?;
+        let mut operands: Vec<evaluator::Operand<'_>> = Vec::new();
+        for group in values.iter() {
+            let width = group.first().map_or(0, |v| v.len());
+            for v in group.iter() {
+                PulpError::expect_width(name, "srcs", width, v.len())?;
+            }
+            operands.push(evaluator::Operand {
+                width,
+                signed: false,
+                values: group,
+            });
+        }
+        let results = evaluator::dispatch(&self.processor, &self.sk, op, &operands, options)
+            .map_err(|e| match e {
+                EvalError::Circuit(e) => e,
+                EvalError::Arity {
+                    expected, found, ..
+                } => PulpError::WidthMismatch {
+                    op: name,
+                    operand: "srcs",
+                    expected,
+                    found,
+                },
+                e => PulpError::invalid(name, e.to_string()),
+            })?
+            .values;
+        PulpError::expect_width(name, "dsts", results.len(), dsts.len())?;
+
+        // The dataflow records the first two sources of each result
+        let id = format!("{}_{}", name.to_uppercase(), self.issued.len());
+        let mut flat = srcs.iter().flat_map(|group| group.iter());
+        let (op1, op2) = (flat.next(), flat.next());
+        let source = |src: Option<&&str>| src.map_or("-", |src| *src).to_string();
+        for (dst, value) in dsts.iter().zip(results) {
+            self.issued.push(IssuedInstruction {
+                station: "CircuitUnit".to_string(),
+                id: id.clone(),
+                op1: source(op1),
+                op2: source(op2),
+                dst: dst.to_string(),
+                bit: None,
+            });
+            self.set_register(dst, value);
+        }
+        Ok(())
+    }
+
+    fn execute(
+        &mut self,
+        instructions: &[Instruction],
+        inputs: &HashMap<String, Vec<Ciphertext>>,
+        outputs: &mut HashMap<String, Vec<Ciphertext>>,
+    ) -> Result<(), PulpError> {
+        for instruction in instructions.iter() {
+            let line = instruction.line;
+            match &instruction.statement {
+                Statement::Input { name, width } => {
+                    let value = inputs.get(name).ok_or_else(|| {
+                        PulpError::program(line, format!("no value given for input `{name}`"))
+                    })?;
+                    if value.len() != *width {
+                        return Err(PulpError::program(
+                            line,
+                            format!("input `{name}` is {} bits wide, expected {width}", value.len()),
+                        ));
+                    }
+                    self.set_register(name, value.clone());
+                }
+                Statement::Reg { name, width } => {
+                    self.set_register(name, vec![Ciphertext::Trivial(false); *width]);
+                }
+                Statement::Gate { kind, dst, a, b } => {
+                    let station = match kind {
+                        GateKind::And => "AndRs",
+                        GateKind::Or => "OrRs",
+                        GateKind::Xor => "XorRs",
+                        GateKind::Nand => "NandRs",
+                        GateKind::Nor => "NorRs",
+                        GateKind::Xnor => "XnorRs",
+                    };
+                    let id = format!("{}_{line}", station.trim_end_matches("Rs").to_uppercase());
+                    // -1 selects the whole register; a single bit issues with its end index
+                    // equal to the destination bit so the station never takes the range path
+                    let bit = |op: &Operand| match op {
+                        Operand::Register(_) => -1,
+                        Operand::Bit(_, i) => *i as i8,
+                    };
+                    self.add_to_reservation_station(
+                        station,
+                        &id,
+                        a.register(),
+                        b.register(),
+                        dst.register(),
+                        bit(dst),
+                        bit(a),
+                        bit(b),
+                        bit(dst),
+                    )?;
+                }
+                Statement::Mem { name, words, width } => {
+                    self.memories
+                        .insert(name.clone(), ObliviousMemory::zeroed(*words, *width)?);
+                }
+                Statement::Load { dst, mem, index } => {
+                    let index = self.register_value(index)?;
+                    let memory = self.memories.get(mem).ok_or_else(|| {
+                        PulpError::program(line, format!("memory `{mem}` is not declared"))
+                    })?;
+                    let value = memory.read(&self.processor, &self.sk, &index)?;
+                    self.set_register(dst, value);
+                }
+                Statement::Store { mem, index, src } => {
+                    let index = self.register_value(index)?;
+                    let value = self.register_value(src)?;
+                    let memory = self.memories.get_mut(mem).ok_or_else(|| {
+                        PulpError::program(line, format!("memory `{mem}` is not declared"))
+                    })?;
+                    memory.write(&self.processor, &self.sk, &index, &value)?;
+                }
+                Statement::Output { name } => {
+                    let value = self.register(name).ok_or_else(|| {
+                        PulpError::program(line, format!("register `{name}` holds no value"))
+                    })?;
+                    outputs.insert(name.clone(), value.to_vec());
+                }
+                Statement::If { cond, then, other } => {
+                    // Both arms run from the same state; the else arm's writes are kept
+                    // where the condition is false
+                    let cond = self.condition(cond)?;
+                    let (mut registers, mut memories) = (Vec::new(), Vec::new());
+                    Self::collect_writes(then, &mut registers, &mut memories);
+                    Self::collect_writes(other, &mut registers, &mut memories);
+                    let before = self.snapshot(&registers, &memories)?;
+                    self.execute(then, inputs, outputs)?;
+                    let after_then = self.snapshot(&registers, &memories)?;
+                    self.restore(before);
+                    self.execute(other, inputs, outputs)?;
+                    self.merge(&cond, after_then)?;
+                }
+                Statement::Repeat { count, until, body } => {
+                    let (mut registers, mut memories) = (Vec::new(), Vec::new());
+                    Self::collect_writes(body, &mut registers, &mut memories);
+                    for _ in 0..*count {
+                        // An iteration that starts with the flag set is undone
+                        let exit = self.condition(until)?;
+                        let before = self.snapshot(&registers, &memories)?;
+                        self.execute(body, inputs, outputs)?;
+                        self.merge(&exit, before)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The encrypted bit named by a condition operand.
+    fn condition(&self, operand: &Operand) -> Result<Ciphertext, PulpError> {
+        let value = self.register_value(operand.register())?;
+        Ok(match operand {
+            Operand::Bit(_, i) => value[*i].clone(),
+            Operand::Register(_) => value[0].clone(),
+        })
+    }
+
+    /// The current values of `registers` and `memories`.
+    fn snapshot(&self, registers: &[String], memories: &[String]) -> Result<Snapshot, PulpError> {
+        let registers = registers
+            .iter()
+            .map(|name| Ok((name.clone(), self.register_value(name)?)))
+            .collect::<Result<_, PulpError>>()?;
+        let memories = memories
+            .iter()
+            .map(|name| {
+                let memory = self.memories.get(name).ok_or_else(|| {
+                    PulpError::program(0, format!("memory `{name}` is not declared"))
+                })?;
+                Ok((name.clone(), memory.clone()))
+            })
+            .collect::<Result<_, PulpError>>()?;
+        Ok(Snapshot {
+            registers,
+            memories,
+        })
+    }
+
+    /// Adds the registers and memories written by `instructions`, including nested blocks,
+    /// to `registers` and `memories`.
+    fn collect_writes(
+        instructions: &[Instruction],
+        registers: &mut Vec<String>,
+        memories: &mut Vec<String>,
+    ) {
+        for instruction in instructions.iter() {
+            let (names, name) = match &instruction.statement {
+                Statement::Gate { dst, .. } => (&mut *registers, dst.register()),
+                Statement::Load { dst, .. } => (&mut *registers, dst.as_str()),
+                Statement::Store { mem, .. } => (&mut *memories, mem.as_str()),
+                Statement::If { then, other, .. } => {
+                    Self::collect_writes(then, registers, memories);
+                    Self::collect_writes(other, registers, memories);
+                    continue;
+                }
+                Statement::Repeat { body, .. } => {
+                    Self::collect_writes(body, registers, memories);
+                    continue;
+                }
+                _ => continue,
+            };
+            if !names.iter().any(|n| n == name) {
+                names.push(name.to_string());
+            }
+        }
+    }
+
+    fn restore(&mut self, snapshot: Snapshot) {
+        for (name, value) in snapshot.registers {
+            self.set_register(&name, value);
+        }
+        for (name, memory) in snapshot.memories {
+            self.memories.insert(name, memory);
+        }
+    }
+
+    /// Replaces every register and memory of `chosen` by `select ? chosen : current`.
+    fn merge(&mut self, select: &Ciphertext, chosen: Snapshot) -> Result<(), PulpError> {
+        for (name, value) in chosen.registers {
+            let current = self.register_value(&name)?;
+            let mut merged = vec![Ciphertext::Trivial(false); value.len()];
+            self.processor
+                .e_mux(&self.sk, select, &value, &current, &mut merged)?;
+            self.set_register(&name, merged);
+        }
+        for (name, memory) in chosen.memories {
+            let current = self
+                .memories
+                .get_mut(&name)
+                .ok_or_else(|| PulpError::program(0, format!("memory `{name}` is not declared")))?;
+            for (word, value) in current.words.iter_mut().zip(memory.words.iter()) {
+                let old = word.clone();
+                self.processor.e_mux(&self.sk, select, value, &old, word)?;
+            }
+        }
+        Ok(())
+    }
+
     fn add_to_reservation_station(
         &mut self,
         name: &str,
@@ -76,21 +432,40 @@ impl Controller {
         op1_start_indx: i8,
         op2_start_indx: i8,
         end_indx: i8,
-    ) {
-        let ret_a = self.register_tbl.get_element_dst(op1.to_string());
+    ) -> Result<(), PulpError> {
+        let issued = IssuedInstruction {
+            station: name.to_string(),
+            id: id.to_string(),
+            op1: op1.to_string(),
+            op2: op2.to_string(),
+            dst: dst.to_string(),
+            bit: if result_start_indx == -1 {
+                None
+            } else {
+                Some(result_start_indx)
+            },
+        };
+
+        let ret_a = self
+            .register_tbl
+            .get_element_dst(op1.to_string())
+            .ok_or_else(|| PulpError::UnknownRegister(op1.to_string()))?;
 
-        let ret_b = self.register_tbl.get_element_dst(op2.to_string());
+        let ret_b = self
+            .register_tbl
+            .get_element_dst(op2.to_string())
+            .ok_or_else(|| PulpError::UnknownRegister(op2.to_string()))?;
         match name {
             "AndRs" => {
                 // If the waiting_for of retrieved a and b are -, then the values can be directly used
-                if ret_a.unwrap().waiting_for == "-" && ret_b.unwrap().waiting_for == "-" {
-                    let val1 = ret_a.unwrap().vec_ctxt.clone().unwrap();
-                    let val2 = ret_b.unwrap().vec_ctxt.clone().unwrap();
+                if ret_a.waiting_for == "-" && ret_b.waiting_for == "-" {
+                    let val1 = self.register_value(op1)?;
+                    let val2 = self.register_value(op2)?;
                     let mut result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); val1.len()];
 
                     // Perform AND operation
                     if result_start_indx == -1 {
-                        self.processor.e_and(&self.sk, &val1, &val2, &mut result);
+                        self.processor.e_and(&self.sk, &val1, &val2, &mut result)?;
 
                         // Move to the Register table
                         //  1. Does it exist in the register table?
@@ -123,17 +498,13 @@ impl Controller {
                             &mut result,
                             result_start_indx as usize,
                             end_indx as usize,
-                        );
+                        )?;
 
-                        // Move to the Register table
-                        //  1. Does it exist in the register table?
-                        //   2. If yes, then update the entry
-                        //   3. If no, then add a new entry
-                        let rt_entry = self.register_tbl.get_element_dst(dst.to_string());
-                        let mut temp = rt_entry.unwrap().vec_ctxt.clone().unwrap();
-                        for i in 0..result_start_indx + 1 {
-                            result[i as usize] = temp[i as usize].clone();
-                        }
+                        // Move to the Register table, keeping the destination bits outside
+                        // the range
+                        let mut temp = self.register_value(dst)?;
+                        let low = result_start_indx as usize;
+                        result[..low].clone_from_slice(&temp[..low]);
                         for i in end_indx as usize..temp.len() {
                             result[i] = temp[i].clone();
                         }
@@ -144,19 +515,12 @@ impl Controller {
                             -1,
                             1,
                         );
-                        match rt_entry {
-                            Some(_value) => {
-                                self.register_tbl.update_element_dst(updated);
-                            }
-                            None => {
-                                self.register_tbl.add_element(updated);
-                            }
-                        }
+                        self.register_tbl.update_element_dst(updated);
                     } else {
                         result[result_start_indx as usize] = self.processor.e_and_bit(
                             &self.sk,
                             &val1[op1_start_indx as usize],
-                            &val2[op1_start_indx as usize],
+                            &val2[op2_start_indx as usize],
                         );
                         let rt_entry = self.register_tbl.get_element_dst(dst.to_string());
                         match rt_entry {
@@ -186,31 +550,31 @@ impl Controller {
                             }
                         }
                     }
-                } else if ret_a.unwrap().waiting_for == "-" && ret_b.unwrap().waiting_for != "-" {
-                    let val1 = ret_a.unwrap().vec_ctxt.clone();
+                } else if ret_a.waiting_for == "-" && ret_b.waiting_for != "-" {
+                    let val1 = ret_a.vec_ctxt.clone();
                     self.and_rs.add_entry(
                         id.to_string(),
-                        "-".to_string(),
-                        ret_b.unwrap().dst.clone(),
+                        None,
+                        self.register_tbl.get_tag(op2),
                         Option::from(val1),
                         None,
                         dst.to_string(),
                     )
-                } else if ret_a.unwrap().waiting_for != "-" && ret_b.unwrap().waiting_for == "-" {
-                    let val2 = ret_b.unwrap().vec_ctxt.clone();
+                } else if ret_a.waiting_for != "-" && ret_b.waiting_for == "-" {
+                    let val2 = ret_b.vec_ctxt.clone();
                     self.and_rs.add_entry(
                         id.to_string(),
-                        ret_a.unwrap().dst.clone(),
-                        "-".to_string(),
+                        self.register_tbl.get_tag(op1),
+                        None,
                         None,
                         Option::from(val2),
                         dst.to_string(),
                     )
-                } else if ret_a.unwrap().waiting_for != "-" && ret_b.unwrap().waiting_for != "-" {
+                } else if ret_a.waiting_for != "-" && ret_b.waiting_for != "-" {
                     self.and_rs.add_entry(
                         id.to_string(),
-                        ret_a.unwrap().dst.clone(),
-                        ret_b.unwrap().dst.clone(),
+                        self.register_tbl.get_tag(op1),
+                        self.register_tbl.get_tag(op2),
                         None,
                         None,
                         dst.to_string(),
@@ -221,13 +585,13 @@ impl Controller {
             }
             "OrRs" => {
                 // If the waiting_for of retrieved a and b are -, then the values can be directly used
-                if ret_a.unwrap().waiting_for == "-" && ret_b.unwrap().waiting_for == "-" {
-                    let val1 = ret_a.unwrap().vec_ctxt.clone().unwrap();
-                    let val2 = ret_b.unwrap().vec_ctxt.clone().unwrap();
+                if ret_a.waiting_for == "-" && ret_b.waiting_for == "-" {
+                    let val1 = self.register_value(op1)?;
+                    let val2 = self.register_value(op2)?;
                     let mut result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); val1.len()];
                     // Perform or operation
                     if result_start_indx == -1 {
-                        self.processor.e_or(&self.sk, &val1, &val2, &mut result);
+                        self.processor.e_or(&self.sk, &val1, &val2, &mut result)?;
 
                         // Move to the Register table
                         //  1. Does it exist in the register table?
@@ -260,17 +624,13 @@ impl Controller {
                             &mut result,
                             result_start_indx as usize,
                             end_indx as usize,
-                        );
+                        )?;
 
-                        // Move to the Register table
-                        //  1. Does it exist in the register table?
-                        //   2. If yes, then update the entry
-                        //   3. If no, then add a new entry
-                        let rt_entry = self.register_tbl.get_element_dst(dst.to_string());
-                        let mut temp = rt_entry.unwrap().vec_ctxt.clone().unwrap();
-                        for i in 0..result_start_indx + 1 {
-                            result[i as usize] = temp[i as usize].clone();
-                        }
+                        // Move to the Register table, keeping the destination bits outside
+                        // the range
+                        let mut temp = self.register_value(dst)?;
+                        let low = result_start_indx as usize;
+                        result[..low].clone_from_slice(&temp[..low]);
                         for i in end_indx as usize..temp.len() {
                             result[i] = temp[i].clone();
                         }
@@ -281,19 +641,12 @@ impl Controller {
                             -1,
                             1,
                         );
-                        match rt_entry {
-                            Some(_value) => {
-                                self.register_tbl.update_element_dst(updated);
-                            }
-                            None => {
-                                self.register_tbl.add_element(updated);
-                            }
-                        }
+                        self.register_tbl.update_element_dst(updated);
                     } else {
                         result[result_start_indx as usize] = self.processor.e_or_bit(
                             &self.sk,
                             &val1[op1_start_indx as usize],
-                            &val2[op1_start_indx as usize],
+                            &val2[op2_start_indx as usize],
                         );
                         let rt_entry = self.register_tbl.get_element_dst(dst.to_string());
                         match rt_entry {
@@ -323,31 +676,31 @@ impl Controller {
                             }
                         }
                     }
-                } else if ret_a.unwrap().waiting_for == "-" && ret_b.unwrap().waiting_for != "-" {
-                    let val1 = ret_a.unwrap().vec_ctxt.clone();
+                } else if ret_a.waiting_for == "-" && ret_b.waiting_for != "-" {
+                    let val1 = ret_a.vec_ctxt.clone();
                     self.or_rs.add_entry(
                         id.to_string(),
-                        "-".to_string(),
-                        ret_b.unwrap().dst.clone(),
+                        None,
+                        self.register_tbl.get_tag(op2),
                         Option::from(val1),
                         None,
                         dst.to_string(),
                     )
-                } else if ret_a.unwrap().waiting_for != "-" && ret_b.unwrap().waiting_for == "-" {
-                    let val2 = ret_b.unwrap().vec_ctxt.clone();
+                } else if ret_a.waiting_for != "-" && ret_b.waiting_for == "-" {
+                    let val2 = ret_b.vec_ctxt.clone();
                     self.or_rs.add_entry(
                         id.to_string(),
-                        ret_a.unwrap().dst.clone(),
-                        "-".to_string(),
+                        self.register_tbl.get_tag(op1),
+                        None,
                         None,
                         Option::from(val2),
                         dst.to_string(),
                     )
-                } else if ret_a.unwrap().waiting_for != "-" && ret_b.unwrap().waiting_for != "-" {
+                } else if ret_a.waiting_for != "-" && ret_b.waiting_for != "-" {
                     self.or_rs.add_entry(
                         id.to_string(),
-                        ret_a.unwrap().dst.clone(),
-                        ret_b.unwrap().dst.clone(),
+                        self.register_tbl.get_tag(op1),
+                        self.register_tbl.get_tag(op2),
                         None,
                         None,
                         dst.to_string(),
@@ -358,13 +711,13 @@ impl Controller {
             }
             "XorRs" => {
                 // If the waiting_for of retrieved a and b are -, then the values can be directly used
-                if ret_a.unwrap().waiting_for == "-" && ret_b.unwrap().waiting_for == "-" {
-                    let val1 = ret_a.unwrap().vec_ctxt.clone().unwrap();
-                    let val2 = ret_b.unwrap().vec_ctxt.clone().unwrap();
+                if ret_a.waiting_for == "-" && ret_b.waiting_for == "-" {
+                    let val1 = self.register_value(op1)?;
+                    let val2 = self.register_value(op2)?;
                     let mut result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); val1.len()];
                     // Perform XOR operation
                     if result_start_indx == -1 {
-                        self.processor.e_xor(&self.sk, &val1, &val2, &mut result);
+                        self.processor.e_xor(&self.sk, &val1, &val2, &mut result)?;
 
                         // Move to the Register table
                         //  1. Does it exist in the register table?
@@ -397,17 +750,13 @@ impl Controller {
                             &mut result,
                             result_start_indx as usize,
                             end_indx as usize,
-                        );
+                        )?;
 
-                        // Move to the Register table
-                        //  1. Does it exist in the register table?
-                        //   2. If yes, then update the entry
-                        //   3. If no, then add a new entry
-                        let rt_entry = self.register_tbl.get_element_dst(dst.to_string());
-                        let mut temp = rt_entry.unwrap().vec_ctxt.clone().unwrap();
-                        for i in 0..result_start_indx + 1 {
-                            result[i as usize] = temp[i as usize].clone();
-                        }
+                        // Move to the Register table, keeping the destination bits outside
+                        // the range
+                        let mut temp = self.register_value(dst)?;
+                        let low = result_start_indx as usize;
+                        result[..low].clone_from_slice(&temp[..low]);
                         for i in end_indx as usize..temp.len() {
                             result[i] = temp[i].clone();
                         }
@@ -418,19 +767,12 @@ impl Controller {
                             -1,
                             1,
                         );
-                        match rt_entry {
-                            Some(_value) => {
-                                self.register_tbl.update_element_dst(updated);
-                            }
-                            None => {
-                                self.register_tbl.add_element(updated);
-                            }
-                        }
+                        self.register_tbl.update_element_dst(updated);
                     } else {
                         result[result_start_indx as usize] = self.processor.e_xor_bit(
                             &self.sk,
                             &val1[op1_start_indx as usize],
-                            &val2[op1_start_indx as usize],
+                            &val2[op2_start_indx as usize],
                         );
                         let rt_entry = self.register_tbl.get_element_dst(dst.to_string());
                         match rt_entry {
@@ -460,31 +802,31 @@ impl Controller {
                             }
                         }
                     }
-                } else if ret_a.unwrap().waiting_for == "-" && ret_b.unwrap().waiting_for != "-" {
-                    let val1 = ret_a.unwrap().vec_ctxt.clone();
+                } else if ret_a.waiting_for == "-" && ret_b.waiting_for != "-" {
+                    let val1 = ret_a.vec_ctxt.clone();
                     self.xor_rs.add_entry(
                         id.to_string(),
-                        "-".to_string(),
-                        ret_b.unwrap().dst.clone(),
+                        None,
+                        self.register_tbl.get_tag(op2),
                         Option::from(val1),
                         None,
                         dst.to_string(),
                     )
-                } else if ret_a.unwrap().waiting_for != "-" && ret_b.unwrap().waiting_for == "-" {
-                    let val2 = ret_b.unwrap().vec_ctxt.clone();
+                } else if ret_a.waiting_for != "-" && ret_b.waiting_for == "-" {
+                    let val2 = ret_b.vec_ctxt.clone();
                     self.xor_rs.add_entry(
                         id.to_string(),
-                        ret_a.unwrap().dst.clone(),
-                        "-".to_string(),
+                        self.register_tbl.get_tag(op1),
+                        None,
                         None,
                         Option::from(val2),
                         dst.to_string(),
                     )
-                } else if ret_a.unwrap().waiting_for != "-" && ret_b.unwrap().waiting_for != "-" {
+                } else if ret_a.waiting_for != "-" && ret_b.waiting_for != "-" {
                     self.xor_rs.add_entry(
                         id.to_string(),
-                        ret_a.unwrap().dst.clone(),
-                        ret_b.unwrap().dst.clone(),
+                        self.register_tbl.get_tag(op1),
+                        self.register_tbl.get_tag(op2),
                         None,
                         None,
                         dst.to_string(),
@@ -495,13 +837,13 @@ impl Controller {
             }
             "NandRs" => {
                 // If the waiting_for of retrieved a and b are -, then the values can be directly used
-                if ret_a.unwrap().waiting_for == "-" && ret_b.unwrap().waiting_for == "-" {
-                    let val1 = ret_a.unwrap().vec_ctxt.clone().unwrap();
-                    let val2 = ret_b.unwrap().vec_ctxt.clone().unwrap();
+                if ret_a.waiting_for == "-" && ret_b.waiting_for == "-" {
+                    let val1 = self.register_value(op1)?;
+                    let val2 = self.register_value(op2)?;
                     let mut result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); val1.len()];
                     // Perform NAND operation
                     if result_start_indx == -1 {
-                        self.processor.e_nand(&self.sk, &val1, &val2, &mut result);
+                        self.processor.e_nand(&self.sk, &val1, &val2, &mut result)?;
 
                         // Move to the Register table
                         //  1. Does it exist in the register table?
@@ -534,17 +876,13 @@ impl Controller {
                             &mut result,
                             result_start_indx as usize,
                             end_indx as usize,
-                        );
+                        )?;
 
-                        // Move to the Register table
-                        //  1. Does it exist in the register table?
-                        //   2. If yes, then update the entry
-                        //   3. If no, then add a new entry
-                        let rt_entry = self.register_tbl.get_element_dst(dst.to_string());
-                        let mut temp = rt_entry.unwrap().vec_ctxt.clone().unwrap();
-                        for i in 0..result_start_indx + 1 {
-                            result[i as usize] = temp[i as usize].clone();
-                        }
+                        // Move to the Register table, keeping the destination bits outside
+                        // the range
+                        let mut temp = self.register_value(dst)?;
+                        let low = result_start_indx as usize;
+                        result[..low].clone_from_slice(&temp[..low]);
                         for i in end_indx as usize..temp.len() {
                             result[i] = temp[i].clone();
                         }
@@ -555,19 +893,12 @@ impl Controller {
                             -1,
                             1,
                         );
-                        match rt_entry {
-                            Some(_value) => {
-                                self.register_tbl.update_element_dst(updated);
-                            }
-                            None => {
-                                self.register_tbl.add_element(updated);
-                            }
-                        }
+                        self.register_tbl.update_element_dst(updated);
                     } else {
                         result[result_start_indx as usize] = self.processor.e_nand_bit(
                             &self.sk,
                             &val1[op1_start_indx as usize],
-                            &val2[op1_start_indx as usize],
+                            &val2[op2_start_indx as usize],
                         );
                         let rt_entry = self.register_tbl.get_element_dst(dst.to_string());
                         match rt_entry {
@@ -597,31 +928,31 @@ impl Controller {
                             }
                         }
                     }
-                } else if ret_a.unwrap().waiting_for == "-" && ret_b.unwrap().waiting_for != "-" {
-                    let val1 = ret_a.unwrap().vec_ctxt.clone();
+                } else if ret_a.waiting_for == "-" && ret_b.waiting_for != "-" {
+                    let val1 = ret_a.vec_ctxt.clone();
                     self.nand_rs.add_entry(
                         id.to_string(),
-                        "-".to_string(),
-                        ret_b.unwrap().dst.clone(),
+                        None,
+                        self.register_tbl.get_tag(op2),
                         Option::from(val1),
                         None,
                         dst.to_string(),
                     )
-                } else if ret_a.unwrap().waiting_for != "-" && ret_b.unwrap().waiting_for == "-" {
-                    let val2 = ret_b.unwrap().vec_ctxt.clone();
+                } else if ret_a.waiting_for != "-" && ret_b.waiting_for == "-" {
+                    let val2 = ret_b.vec_ctxt.clone();
                     self.nand_rs.add_entry(
                         id.to_string(),
-                        ret_a.unwrap().dst.clone(),
-                        "-".to_string(),
+                        self.register_tbl.get_tag(op1),
+                        None,
                         None,
                         Option::from(val2),
                         dst.to_string(),
                     )
-                } else if ret_a.unwrap().waiting_for != "-" && ret_b.unwrap().waiting_for != "-" {
+                } else if ret_a.waiting_for != "-" && ret_b.waiting_for != "-" {
                     self.nand_rs.add_entry(
                         id.to_string(),
-                        ret_a.unwrap().dst.clone(),
-                        ret_b.unwrap().dst.clone(),
+                        self.register_tbl.get_tag(op1),
+                        self.register_tbl.get_tag(op2),
                         None,
                         None,
                         dst.to_string(),
@@ -632,13 +963,13 @@ impl Controller {
             }
             "NorRs" => {
                 // If the waiting_for of retrieved a and b are -, then the values can be directly used
-                if ret_a.unwrap().waiting_for == "-" && ret_b.unwrap().waiting_for == "-" {
-                    let val1 = ret_a.unwrap().vec_ctxt.clone().unwrap();
-                    let val2 = ret_b.unwrap().vec_ctxt.clone().unwrap();
+                if ret_a.waiting_for == "-" && ret_b.waiting_for == "-" {
+                    let val1 = self.register_value(op1)?;
+                    let val2 = self.register_value(op2)?;
                     let mut result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); val1.len()];
                     // Perform NOR operation
                     if result_start_indx == -1 {
-                        self.processor.e_nor(&self.sk, &val1, &val2, &mut result);
+                        self.processor.e_nor(&self.sk, &val1, &val2, &mut result)?;
 
                         // Move to the Register table
                         //  1. Does it exist in the register table?
@@ -671,17 +1002,13 @@ impl Controller {
                             &mut result,
                             result_start_indx as usize,
                             end_indx as usize,
-                        );
+                        )?;
 
-                        // Move to the Register table
-                        //  1. Does it exist in the register table?
-                        //   2. If yes, then update the entry
-                        //   3. If no, then add a new entry
-                        let rt_entry = self.register_tbl.get_element_dst(dst.to_string());
-                        let mut temp = rt_entry.unwrap().vec_ctxt.clone().unwrap();
-                        for i in 0..result_start_indx + 1 {
-                            result[i as usize] = temp[i as usize].clone();
-                        }
+                        // Move to the Register table, keeping the destination bits outside
+                        // the range
+                        let mut temp = self.register_value(dst)?;
+                        let low = result_start_indx as usize;
+                        result[..low].clone_from_slice(&temp[..low]);
                         for i in end_indx as usize..temp.len() {
                             result[i] = temp[i].clone();
                         }
@@ -692,19 +1019,12 @@ impl Controller {
                             -1,
                             1,
                         );
-                        match rt_entry {
-                            Some(_value) => {
-                                self.register_tbl.update_element_dst(updated);
-                            }
-                            None => {
-                                self.register_tbl.add_element(updated);
-                            }
-                        }
+                        self.register_tbl.update_element_dst(updated);
                     } else {
                         result[result_start_indx as usize] = self.processor.e_nor_bit(
                             &self.sk,
                             &val1[op1_start_indx as usize],
-                            &val2[op1_start_indx as usize],
+                            &val2[op2_start_indx as usize],
                         );
                         let rt_entry = self.register_tbl.get_element_dst(dst.to_string());
                         match rt_entry {
@@ -734,31 +1054,31 @@ impl Controller {
                             }
                         }
                     }
-                } else if ret_a.unwrap().waiting_for == "-" && ret_b.unwrap().waiting_for != "-" {
-                    let val1 = ret_a.unwrap().vec_ctxt.clone();
+                } else if ret_a.waiting_for == "-" && ret_b.waiting_for != "-" {
+                    let val1 = ret_a.vec_ctxt.clone();
                     self.nor_rs.add_entry(
                         id.to_string(),
-                        "-".to_string(),
-                        ret_b.unwrap().dst.clone(),
+                        None,
+                        self.register_tbl.get_tag(op2),
                         Option::from(val1),
                         None,
                         dst.to_string(),
                     )
-                } else if ret_a.unwrap().waiting_for != "-" && ret_b.unwrap().waiting_for == "-" {
-                    let val2 = ret_b.unwrap().vec_ctxt.clone();
+                } else if ret_a.waiting_for != "-" && ret_b.waiting_for == "-" {
+                    let val2 = ret_b.vec_ctxt.clone();
                     self.nor_rs.add_entry(
                         id.to_string(),
-                        ret_a.unwrap().dst.clone(),
-                        "-".to_string(),
+                        self.register_tbl.get_tag(op1),
+                        None,
                         None,
                         Option::from(val2),
                         dst.to_string(),
                     )
-                } else if ret_a.unwrap().waiting_for != "-" && ret_b.unwrap().waiting_for != "-" {
+                } else if ret_a.waiting_for != "-" && ret_b.waiting_for != "-" {
                     self.nor_rs.add_entry(
                         id.to_string(),
-                        ret_a.unwrap().dst.clone(),
-                        ret_b.unwrap().dst.clone(),
+                        self.register_tbl.get_tag(op1),
+                        self.register_tbl.get_tag(op2),
                         None,
                         None,
                         dst.to_string(),
@@ -769,13 +1089,13 @@ impl Controller {
             }
             "XnorRs" => {
                 // If the waiting_for of retrieved a and b are -, then the values can be directly used
-                if ret_a.unwrap().waiting_for == "-" && ret_b.unwrap().waiting_for == "-" {
-                    let val1 = ret_a.unwrap().vec_ctxt.clone().unwrap();
-                    let val2 = ret_b.unwrap().vec_ctxt.clone().unwrap();
+                if ret_a.waiting_for == "-" && ret_b.waiting_for == "-" {
+                    let val1 = self.register_value(op1)?;
+                    let val2 = self.register_value(op2)?;
                     let mut result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); val1.len()];
                     // Perform XNOR operation
                     if result_start_indx == -1 {
-                        self.processor.e_xnor(&self.sk, &val1, &val2, &mut result);
+                        self.processor.e_xnor(&self.sk, &val1, &val2, &mut result)?;
 
                         // Move to the Register table
                         //  1. Does it exist in the register table?
@@ -808,17 +1128,13 @@ impl Controller {
                             &mut result,
                             result_start_indx as usize,
                             end_indx as usize,
-                        );
+                        )?;
 
-                        // Move to the Register table
-                        //  1. Does it exist in the register table?
-                        //   2. If yes, then update the entry
-                        //   3. If no, then add a new entry
-                        let rt_entry = self.register_tbl.get_element_dst(dst.to_string());
-                        let mut temp = rt_entry.unwrap().vec_ctxt.clone().unwrap();
-                        for i in 0..result_start_indx + 1 {
-                            result[i as usize] = temp[i as usize].clone();
-                        }
+                        // Move to the Register table, keeping the destination bits outside
+                        // the range
+                        let mut temp = self.register_value(dst)?;
+                        let low = result_start_indx as usize;
+                        result[..low].clone_from_slice(&temp[..low]);
                         for i in end_indx as usize..temp.len() {
                             result[i] = temp[i].clone();
                         }
@@ -829,19 +1145,12 @@ impl Controller {
                             -1,
                             1,
                         );
-                        match rt_entry {
-                            Some(_value) => {
-                                self.register_tbl.update_element_dst(updated);
-                            }
-                            None => {
-                                self.register_tbl.add_element(updated);
-                            }
-                        }
+                        self.register_tbl.update_element_dst(updated);
                     } else {
                         result[result_start_indx as usize] = self.processor.e_xnor_bit(
                             &self.sk,
                             &val1[op1_start_indx as usize],
-                            &val2[op1_start_indx as usize],
+                            &val2[op2_start_indx as usize],
                         );
                         let rt_entry = self.register_tbl.get_element_dst(dst.to_string());
                         match rt_entry {
@@ -871,31 +1180,31 @@ impl Controller {
                             }
                         }
                     }
-                } else if ret_a.unwrap().waiting_for == "-" && ret_b.unwrap().waiting_for != "-" {
-                    let val1 = ret_a.unwrap().vec_ctxt.clone();
+                } else if ret_a.waiting_for == "-" && ret_b.waiting_for != "-" {
+                    let val1 = ret_a.vec_ctxt.clone();
                     self.xnor_rs.add_entry(
                         id.to_string(),
-                        "-".to_string(),
-                        ret_b.unwrap().dst.clone(),
+                        None,
+                        self.register_tbl.get_tag(op2),
                         Option::from(val1),
                         None,
                         dst.to_string(),
                     )
-                } else if ret_a.unwrap().waiting_for != "-" && ret_b.unwrap().waiting_for == "-" {
-                    let val2 = ret_b.unwrap().vec_ctxt.clone();
+                } else if ret_a.waiting_for != "-" && ret_b.waiting_for == "-" {
+                    let val2 = ret_b.vec_ctxt.clone();
                     self.xnor_rs.add_entry(
                         id.to_string(),
-                        ret_a.unwrap().dst.clone(),
-                        "-".to_string(),
+                        self.register_tbl.get_tag(op1),
+                        None,
                         None,
                         Option::from(val2),
                         dst.to_string(),
                     )
-                } else if ret_a.unwrap().waiting_for != "-" && ret_b.unwrap().waiting_for != "-" {
+                } else if ret_a.waiting_for != "-" && ret_b.waiting_for != "-" {
                     self.xnor_rs.add_entry(
                         id.to_string(),
-                        ret_a.unwrap().dst.clone(),
-                        ret_b.unwrap().dst.clone(),
+                        self.register_tbl.get_tag(op1),
+                        self.register_tbl.get_tag(op2),
                         None,
                         None,
                         dst.to_string(),
@@ -904,10 +1213,24 @@ impl Controller {
                     panic!("Error in adding an entry to XnorRs");
                 }
             }
-            _ => panic!("Ouch! Error in adding an element to a reservation station."),
+            _ => return Err(PulpError::UnknownStation(name.to_string())),
         }
+        self.issued.push(issued);
+        Ok(())
     }
-    fn adder(&mut self, a: &[Ciphertext], b: &[Ciphertext], result: &mut [Ciphertext]) {
+
+    /// The value of register `name`, which must hold one.
+    fn register_value(&self, name: &str) -> Result<Vec<Ciphertext>, PulpError> {
+        self.register(name)
+            .map(|value| value.to_vec())
+            .ok_or_else(|| PulpError::UnknownRegister(name.to_string()))
+    }
+    fn adder(
+        &mut self,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
         let a_reg = RegisterElement::new(
             "a".to_string(),
             "-".to_string(),
@@ -967,10 +1290,10 @@ impl Controller {
 
         // Compute a XOR b for all bits
         //----> e_xor(sk, a, b, &mut temp);
-        self.add_to_reservation_station("XorRs", "XOR_1", "a", "b", "temp", -1, -1, -1, -1);
+        self.add_to_reservation_station("XorRs", "XOR_1", "a", "b", "temp", -1, -1, -1, -1)?;
 
         //----> e_and(sk, a, b, &mut a_and_b);
-        self.add_to_reservation_station("AndRs", "AND_1", "a", "b", "a_and_b", -1, -1, -1, -1);
+        self.add_to_reservation_station("AndRs", "AND_1", "a", "b", "a_and_b", -1, -1, -1, -1)?;
 
         // Ripple-carry adder logic
         for i in 0..size {
@@ -978,7 +1301,7 @@ impl Controller {
             // -----> result[i] = e_xor_bit(sk, &carry[i], &temp[i]);
             self.add_to_reservation_station(
                 "XorRs", "XOR_2", "carry", "temp", "result", i as i8, i as i8, i as i8, i as i8,
-            );
+            )?;
 
             if i != size - 1 {
                 // Carry bit: carry[i+1] = (a[i] AND b[i]) OR (a[i] AND carry[i]) OR (b[i] AND carry[i])
@@ -993,7 +1316,7 @@ impl Controller {
                     i as i8,
                     i as i8,
                     i as i8,
-                );
+                )?;
                 // -----> let b_and_carry = e_and_bit(sk, &b[i], &carry[i]);
                 self.add_to_reservation_station(
                     "AndRs",
@@ -1005,7 +1328,7 @@ impl Controller {
                     i as i8,
                     i as i8,
                     i as i8,
-                );
+                )?;
                 // ----> let temp_carry = e_or_bit(sk, &a_and_b[i], &a_and_carry);
                 self.add_to_reservation_station(
                     "OrRs",
@@ -1017,7 +1340,7 @@ impl Controller {
                     i as i8,
                     i as i8,
                     i as i8,
-                );
+                )?;
                 // ----> carry[i + 1] = e_or_bit(sk, &temp_carry, &b_and_carry);
                 self.add_to_reservation_station(
                     "OrRs",
@@ -1029,7 +1352,7 @@ impl Controller {
                     i as i8,
                     i as i8,
                     i as i8,
-                );
+                )?;
             }
         }
         let res = self.register_tbl.get_element_dst("result".to_string());
@@ -1044,8 +1367,9 @@ impl Controller {
                 }
             }
             None => {
-                panic!("Error in retrieving result from register table");
+                return Err(PulpError::UnknownRegister("result".to_string()));
             }
         }
+        Ok(())
     }
 }
@@ -2,6 +2,9 @@
 use super::*; // Imports items from the parent module
 use rand::Rng;
 use serial_test::serial;
+use crate::key_manager::KeyManager;
+use crate::program::Program;
+use std::collections::HashMap;
 
 fn encode(a: i8, size: usize) -> Vec<bool> {
     // Convert to binary representation as a Vec<bool>
@@ -78,7 +81,7 @@ pub fn new_decrypt_decode(ciphertexts: &[Ciphertext], client_key: &ClientKey) ->
 #[test]
 #[serial]
 fn test_encrypt_decrypt() {
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -102,7 +105,7 @@ fn test_encrypt_decrypt() {
 fn test_adder() {
     let fn_name = "adder";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let mut controller = Controller::new(server_key.clone());
     let mut rng = rand::thread_rng();
@@ -115,7 +118,7 @@ fn test_adder() {
     let ct_b = encode_encrypt(b, 8, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    controller.adder(&ct_a, &ct_b, &mut ct_result);
+    controller.adder(&ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -128,7 +131,7 @@ fn test_adder() {
 fn test_sign_adder() {
     let fn_name = "adder";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -137,15 +140,28 @@ fn test_sign_adder() {
     let a: i8 = rng.gen_range(-50..50);
     let b: i8 = rng.gen_range(-50..50);
 
-    let ct_a = encode_encrypt(a, 8, &client_key);
-    let ct_b = encode_encrypt(b, 8, &client_key);
-    let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 8];
-
-    server.sign_adder(&server_key, &ct_a, &ct_b, &mut ct_result);
-
-    let dec_res = decrypt_decode(&ct_result, &client_key);
-    println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
-    assert_eq!(dec_res, a.wrapping_add(b));
+    // `sign_adder` works on sign-magnitude integers: the top bit is the sign and the
+    // other bits hold the magnitude. The fixed pairs take every sign path, the last one
+    // to a zero that may come back as negative zero.
+    let sign_magnitude = |v: i8| if v < 0 { -v | i8::MIN } else { v };
+    for (a, b) in [(a, b), (-7, 3), (7, -3), (-20, -30), (-5, 5)] {
+        let ct_a = encode_encrypt(sign_magnitude(a), 8, &client_key);
+        let ct_b = encode_encrypt(sign_magnitude(b), 8, &client_key);
+        let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 8];
+
+        server
+            .sign_adder(&server_key, &ct_a, &ct_b, &mut ct_result)
+            .unwrap();
+
+        let dec_res = decrypt_decode(&ct_result, &client_key);
+        let sum: i8 = if dec_res < 0 {
+            -(dec_res & i8::MAX)
+        } else {
+            dec_res
+        };
+        println!("\t {} {} {} = {}", a, b, fn_name, sum);
+        assert_eq!(sum, a + b);
+    }
     println!("[✓] PASS: {fn_name}\n");
 }
 
@@ -154,7 +170,7 @@ fn test_sign_adder() {
 fn test_subtracter() {
     let fn_name = "subtracter";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -171,7 +187,7 @@ fn test_subtracter() {
     println!("{a} - {b}");
     //server.ptxt_subtracter(&encode(a, 8), &encode(b, 8), &mut result);
 
-    server.subtracter(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.subtracter(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -184,7 +200,7 @@ fn test_subtracter() {
 fn test_multiplier() {
     let fn_name = "multiplier";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -204,7 +220,7 @@ fn test_multiplier() {
     println!("{a} * {b} = {}", decode(&res));
     println!("{:?} * {:?} = {:?}", enc_a, enc_b, res);
 
-    server.multiplier(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.multiplier(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -217,7 +233,7 @@ fn test_multiplier() {
 fn test_newmultiplier() {
     let fn_name = "multiplier";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -249,7 +265,7 @@ fn test_newmultiplier() {
 fn test_div() {
     let fn_name = "divider";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -267,7 +283,7 @@ fn test_div() {
     let ct_b = encode_encrypt(b, 8, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.divider(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.divider(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -279,25 +295,21 @@ fn test_div() {
 fn test_modulo() {
     let fn_name = "modulo";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
 
-    // Define two numbers and convert them to signed 8-bit representation.
-    let mut a: i8 = rng.gen_range(-50..50);
-    let mut b: i8 = rng.gen_range(-50..50);
-
-    while !(a >= b) {
-        a = rng.gen_range(-50..50);
-        b = rng.gen_range(-50..50);
-    }
+    // `modulo` takes the remainder of non-negative operands, so draw a non-negative
+    // dividend and a positive divisor
+    let a: i8 = rng.gen_range(0..50);
+    let b: i8 = rng.gen_range(1..50);
 
     let ct_a = encode_encrypt(a, 8, &client_key);
     let ct_b = encode_encrypt(b, 8, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.modulo(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.modulo(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -310,7 +322,7 @@ fn test_modulo() {
 fn test_max() {
     let fn_name = "max";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -329,7 +341,7 @@ fn test_max() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.max(&server_key, &ct_a, &mut ct_result);
+    server.max(&server_key, &ct_a, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     assert_eq!(dec_res, *a.iter().max().unwrap());
@@ -341,7 +353,7 @@ fn test_max() {
 fn test_min() {
     let fn_name = "min";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -360,7 +372,7 @@ fn test_min() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.min(&server_key, &ct_a, &mut ct_result);
+    server.min(&server_key, &ct_a, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     assert_eq!(dec_res, *a.iter().min().unwrap());
@@ -372,7 +384,7 @@ fn test_min() {
 fn test_relu() {
     let fn_name = "relu";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -388,8 +400,8 @@ fn test_relu() {
     let mut ct_result_a: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
     let mut ct_result_b: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_b.len()];
 
-    server.relu(&server_key, &ct_a, &mut ct_result_a);
-    server.relu(&server_key, &ct_b, &mut ct_result_b);
+    server.relu(&server_key, &ct_a, &mut ct_result_a).unwrap();
+    server.relu(&server_key, &ct_b, &mut ct_result_b).unwrap();
 
     let dec_res = decrypt_decode(&ct_result_a, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -405,7 +417,7 @@ fn test_relu() {
 fn test_sqrt() {
     let fn_name = "sqrt";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -415,7 +427,7 @@ fn test_sqrt() {
     let ct_a = encode_encrypt(a, 8, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.sqrt(&server_key, &ct_a, &mut ct_result);
+    server.sqrt(&server_key, &ct_a, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -427,7 +439,7 @@ fn test_sqrt() {
 fn test_mean() {
     let fn_name = "mean";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -446,7 +458,7 @@ fn test_mean() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.mean(&server_key, &ct_a, a.len(), &mut ct_result);
+    server.mean(&server_key, &ct_a, a.len(), &mut ct_result).unwrap();
 
     let ptxt_result: i8 = a.iter().sum();
 
@@ -459,7 +471,7 @@ fn test_mean() {
 fn test_variance() {
     let fn_name = "variance";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -478,7 +490,7 @@ fn test_variance() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.variance(&server_key, &ct_a, a.len(), &mut ct_result);
+    server.variance(&server_key, &ct_a, a.len(), &mut ct_result).unwrap();
 
     let ptxt_result: i8 = a.iter().sum();
     let mean:i8 = ptxt_result.wrapping_div(a.len() as i8);
@@ -497,7 +509,7 @@ fn test_variance() {
 fn test_standard_deviation() {
     let fn_name = "standard_deviation";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -516,7 +528,7 @@ fn test_standard_deviation() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.standard_deviation(&server_key, &ct_a, a.len(), &mut ct_result);
+    server.standard_deviation(&server_key, &ct_a, a.len(), &mut ct_result).unwrap();
 
     let ptxt_result: i8 = a.iter().sum();
     let mean:i8 = ptxt_result.wrapping_div(a.len() as i8);
@@ -532,3 +544,993 @@ fn test_standard_deviation() {
     assert_eq!(dec_res, stdev);
     println!("[✓] PASS: {fn_name}\n");
 }
+
+#[test]
+#[serial]
+fn test_adder_dataflow() {
+    let fn_name = "adder_dataflow";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let mut controller = Controller::new(server_key.clone());
+
+    let ct_a = encode_encrypt(21, 8, &client_key);
+    let ct_b = encode_encrypt(-6, 8, &client_key);
+    let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
+
+    controller.adder(&ct_a, &ct_b, &mut ct_result).unwrap();
+    assert_eq!(decrypt_decode(&ct_result, &client_key), 15);
+
+    // XOR_1 and AND_1, one XOR_2 per bit and four carry instructions per bit but the last
+    let issued = controller.get_issued();
+    assert_eq!(issued.len(), 2 + 8 + 7 * 4);
+    assert_eq!(issued[0].station, "XorRs");
+    assert_eq!(issued[0].bit, None);
+
+    let dot = controller.dataflow_dot("adder");
+    println!("{dot}");
+    for station in ["AndRs", "OrRs", "XorRs"] {
+        assert!(dot.contains(&format!("cluster_{station}")));
+    }
+
+    let json: serde_json::Value = serde_json::from_str(&controller.dataflow_json()).unwrap();
+    let deps = json["dependencies"].as_array().unwrap();
+    // The sum bit 1 reads the carry written by OR_2 for bit 0
+    let or_2 = issued.iter().position(|i| i.id == "OR_2").unwrap();
+    assert!(deps.iter().any(|d| d["from"] == or_2 && d["register"] == "carry"));
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_adder_renaming() {
+    let fn_name = "adder_renaming";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let mut controller = Controller::new(server_key.clone());
+
+    let ct_a = encode_encrypt(21, 8, &client_key);
+    let ct_b = encode_encrypt(-6, 8, &client_key);
+    let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
+    controller.adder(&ct_a, &ct_b, &mut ct_result).unwrap();
+
+    // The six working registers and every issued instruction are renamed writes
+    let table = controller.get_register_tbl();
+    let rob = table.get_rob();
+    assert_eq!(rob.len(), 6 + 2 + 8 + 7 * 4);
+    // `carry` is written once per bit but the last: each write has its own, newer tag
+    let carry: Vec<usize> = rob
+        .entries()
+        .iter()
+        .filter(|entry| entry.element.dst == "carry")
+        .map(|entry| entry.tag)
+        .collect();
+    assert_eq!(carry.len(), 8);
+    assert!(carry.windows(2).all(|pair| pair[0] < pair[1]));
+    assert_eq!(table.get_tag("carry"), carry.last().copied());
+
+    // The sum is speculative until it commits
+    assert!(table.get_architectural_dst("result").is_none());
+    let speculative = controller.register("result").unwrap();
+    assert_eq!(decrypt_decode(speculative, &client_key), 15);
+    assert_eq!(controller.commit(), 44);
+    let table = controller.get_register_tbl();
+    let committed = table.get_architectural_dst("result").unwrap();
+    assert_eq!(
+        decrypt_decode(committed.vec_ctxt.as_ref().unwrap(), &client_key),
+        15
+    );
+    assert!(table.get_rob().is_empty());
+    assert_eq!(table.get_tag("carry"), None);
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_station_waits_on_tags() {
+    let fn_name = "station_waits_on_tags";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let mut controller = Controller::new(server_key.clone());
+    controller.set_register("b", encode_encrypt(3, 8, &client_key));
+    // `a` waits for a write still in flight in another station
+    controller.register_tbl.add_element(RegisterElement::new(
+        "a".to_string(),
+        "OrRs".to_string(),
+        None,
+        -1,
+        1,
+    ));
+    let pending = controller.get_register_tbl().get_tag("a").unwrap();
+    controller
+        .add_to_reservation_station("AndRs", "and0", "a", "b", "c", -1, -1, -1, -1)
+        .unwrap();
+
+    // A later write to `a` (WAW) is issued before the consumer of the earlier one runs
+    controller.set_register("a", encode_encrypt(9, 8, &client_key));
+    let newest = controller.get_register_tbl().get_tag("a").unwrap();
+    assert_ne!(newest, pending);
+    let entry = &controller.get_and_rs().entries()[0];
+    assert_eq!((entry.rs1, entry.rs2), (Some(pending), None));
+    assert!(entry.val1.is_none());
+    assert_eq!(decrypt_decode(entry.val2.as_ref().unwrap(), &client_key), 3);
+
+    // Completing the newer write wakes nothing; the earlier one delivers its own value
+    assert!(controller.complete(newest, encode_encrypt(9, 8, &client_key)));
+    assert_eq!(controller.get_and_rs().entries()[0].rs1, Some(pending));
+    assert!(controller.complete(pending, encode_encrypt(6, 8, &client_key)));
+    let entry = &controller.get_and_rs().entries()[0];
+    assert_eq!(entry.rs1, None);
+    assert_eq!(decrypt_decode(entry.val1.as_ref().unwrap(), &client_key), 6);
+    assert_eq!(decrypt_decode(controller.register("a").unwrap(), &client_key), 9);
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_run_program() {
+    let fn_name = "run_program";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let mut controller = Controller::new(server_key.clone());
+
+    let program = Program::parse(
+        "input a 8\n\
+         input b 8\n\
+         reg t 8\n\
+         xor t, a, b\n\
+         and t[0], a[3], b[2]   # bits at different positions\n\
+         not t, t\n\
+         output t\n",
+    )
+    .unwrap();
+    let mut inputs = HashMap::new();
+    inputs.insert("a".to_string(), encode_encrypt(0b0110_1001, 8, &client_key));
+    inputs.insert("b".to_string(), encode_encrypt(0b0101_0100, 8, &client_key));
+    let outputs = controller.run(&program, &inputs).unwrap();
+
+    // t = a ^ b = 0b0011_1101, t[0] = a[3] & b[2] = 1, then inverted
+    let expected = !0b0011_1101u8 as i8;
+    assert_eq!(decrypt_decode(&outputs["t"], &client_key), expected);
+    assert_eq!(controller.get_issued().len(), 3);
+    assert_eq!(controller.get_issued()[2].station, "NandRs");
+
+    // Inputs are checked against their declarations
+    inputs.insert("b".to_string(), encode_encrypt(1, 4, &client_key));
+    let err = controller.run(&program, &inputs).unwrap_err();
+    assert_eq!(err.line(), Some(2));
+    inputs.remove("a");
+    assert_eq!(controller.run(&program, &inputs).unwrap_err().line(), Some(1));
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_run_memory_program() {
+    let fn_name = "run_memory_program";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let mut controller = Controller::new(server_key.clone());
+
+    // Swaps two table entries addressed by encrypted indices
+    let program = Program::parse(
+        "input i 2\n\
+         input j 2\n\
+         input a 8\n\
+         input b 8\n\
+         reg x 8\n\
+         reg y 8\n\
+         mem table 3 8\n\
+         store table, i, a\n\
+         store table, j, b\n\
+         load x, table, i\n\
+         load y, table, j\n\
+         store table, i, y\n\
+         store table, j, x\n\
+         load x, table, i\n\
+         output x\n",
+    )
+    .unwrap();
+    let mut inputs = HashMap::new();
+    inputs.insert("i".to_string(), encode_encrypt(2, 2, &client_key));
+    inputs.insert("j".to_string(), encode_encrypt(0, 2, &client_key));
+    inputs.insert("a".to_string(), encode_encrypt(-7, 8, &client_key));
+    inputs.insert("b".to_string(), encode_encrypt(42, 8, &client_key));
+    let outputs = controller.run(&program, &inputs).unwrap();
+
+    assert_eq!(decrypt_decode(&outputs["x"], &client_key), 42);
+    let table: Vec<i8> = controller
+        .memory("table")
+        .unwrap()
+        .words
+        .iter()
+        .map(|word| decrypt_decode(word, &client_key))
+        .collect();
+    println!("\t table = {table:?}");
+    assert_eq!(table, vec![-7, 0, 42]);
+    // Memory accesses are not issued to the reservation stations
+    assert!(controller.get_issued().is_empty());
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_run_branching_program() {
+    let fn_name = "run_branching_program";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    // Picks a ^ b or a & b, and counts in unary until the count reaches the one-hot limit
+    let program = Program::parse(
+        "input c 1\n\
+         input a 8\n\
+         input b 8\n\
+         input limit 4\n\
+         reg t 8\n\
+         reg one 1\n\
+         reg r 4\n\
+         reg m 4\n\
+         reg done 1\n\
+         not one, one\n\
+         if c {\n\
+             xor t, a, b\n\
+         } else {\n\
+             and t, a, b\n\
+             if c {         # never taken\n\
+                 not t, t\n\
+             }\n\
+         }\n\
+         repeat 4 until done[0] {\n\
+             or r[3], r[2], r[2]\n\
+             or r[2], r[1], r[1]\n\
+             or r[1], r[0], r[0]\n\
+             or r[0], one[0], one[0]\n\
+             and m, r, limit\n\
+             or done[0], m[0], m[1]\n\
+             or done[0], done[0], m[2]\n\
+             or done[0], done[0], m[3]\n\
+         }\n\
+         output t\n\
+         output r\n\
+         output done\n",
+    )
+    .unwrap();
+
+    let (a, b): (i8, i8) = (0b0110_1001, 0b0101_0100);
+    for (c, limit, t, r, done) in [(1, 0b0010, a ^ b, 0b0011, 1), (0, 0, a & b, 0b1111, 0)] {
+        let mut controller = Controller::new(server_key.clone());
+        let mut inputs = HashMap::new();
+        inputs.insert("c".to_string(), encode_encrypt(c, 1, &client_key));
+        inputs.insert("a".to_string(), encode_encrypt(a, 8, &client_key));
+        inputs.insert("b".to_string(), encode_encrypt(b, 8, &client_key));
+        inputs.insert("limit".to_string(), encode_encrypt(limit, 4, &client_key));
+        let outputs = controller.run(&program, &inputs).unwrap();
+
+        assert_eq!(decrypt_decode(&outputs["t"], &client_key), t);
+        assert_eq!(decrypt_decode(&outputs["r"], &client_key), r);
+        assert_eq!(decrypt_decode(&outputs["done"], &client_key), done);
+        // Both arms and every iteration are issued, whatever the encrypted values
+        assert_eq!(controller.get_issued().len(), 1 + 3 + 4 * 8);
+    }
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_single_bit_operand_indices() {
+    let fn_name = "single_bit_operand_indices";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    // Bit 5 of `b` is 0 but bits 1 and 2 are 1, so reading `b` at the bit index of `a`
+    // changes every gate's result in at least one of the two writes
+    let (a, b): (i8, i8) = (0b0000_0010, 0b0000_0110);
+    let gates: [(&str, fn(bool, bool) -> bool); 6] = [
+        ("AndRs", |x, y| x & y),
+        ("OrRs", |x, y| x | y),
+        ("XorRs", |x, y| x ^ y),
+        ("NandRs", |x, y| !(x & y)),
+        ("NorRs", |x, y| !(x | y)),
+        ("XnorRs", |x, y| !(x ^ y)),
+    ];
+    for (station, gate) in gates {
+        let mut controller = Controller::new(server_key.clone());
+        controller.set_register("a", encode_encrypt(a, 8, &client_key));
+        controller.set_register("b", encode_encrypt(b, 8, &client_key));
+        controller.set_register("t", encode_encrypt(0, 8, &client_key));
+        // t[3] = a[1] op b[5] and t[4] = a[2] op b[5]
+        controller
+            .add_to_reservation_station(station, "G_0", "a", "b", "t", 3, 1, 5, 0)
+            .unwrap();
+        controller
+            .add_to_reservation_station(station, "G_1", "a", "b", "t", 4, 2, 5, 0)
+            .unwrap();
+        let t = decrypt(controller.register("t").unwrap().to_vec(), &client_key);
+        assert_eq!(t[3], gate(true, false), "{station} t[3]");
+        assert_eq!(t[4], gate(false, false), "{station} t[4]");
+    }
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_range_write_keeps_outside_bits() {
+    let fn_name = "range_write_keeps_outside_bits";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    // t[2..5] = a[2..5] op b[2..5]; every other bit of t, including the ones next to the
+    // range, keeps its value
+    let (a, b, t0): (i8, i8, i8) = (0b0011_1100, 0b0101_1010, 0b0101_0101);
+    let gates: [(&str, fn(bool, bool) -> bool); 6] = [
+        ("AndRs", |x, y| x & y),
+        ("OrRs", |x, y| x | y),
+        ("XorRs", |x, y| x ^ y),
+        ("NandRs", |x, y| !(x & y)),
+        ("NorRs", |x, y| !(x | y)),
+        ("XnorRs", |x, y| !(x ^ y)),
+    ];
+    let bit = |v: i8, i: usize| (v >> i) & 1 == 1;
+    for (station, gate) in gates {
+        let mut controller = Controller::new(server_key.clone());
+        controller.set_register("a", encode_encrypt(a, 8, &client_key));
+        controller.set_register("b", encode_encrypt(b, 8, &client_key));
+        controller.set_register("t", encode_encrypt(t0, 8, &client_key));
+        controller
+            .add_to_reservation_station(station, "G_0", "a", "b", "t", 2, 2, 2, 5)
+            .unwrap();
+        let t = decrypt(controller.register("t").unwrap().to_vec(), &client_key);
+        for i in 0..8 {
+            let expected = if (2..5).contains(&i) {
+                gate(bit(a, i), bit(b, i))
+            } else {
+                bit(t0, i)
+            };
+            assert_eq!(t[i], expected, "{station} t[{i}]");
+        }
+    }
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_issue_errors() {
+    let fn_name = "issue_errors";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let mut controller = Controller::new(server_key.clone());
+    controller.set_register("a", encode_encrypt(3, 8, &client_key));
+
+    assert_eq!(
+        controller.add_to_reservation_station("AndRs", "AND_1", "a", "b", "t", -1, -1, -1, -1),
+        Err(PulpError::UnknownRegister("b".to_string()))
+    );
+    assert_eq!(
+        controller.add_to_reservation_station("AddRs", "ADD_1", "a", "a", "t", -1, -1, -1, -1),
+        Err(PulpError::UnknownStation("AddRs".to_string()))
+    );
+    // A range write needs the destination register to keep its other bits
+    assert_eq!(
+        controller.add_to_reservation_station("XorRs", "XOR_1", "a", "a", "t", 2, 2, 2, 4),
+        Err(PulpError::UnknownRegister("t".to_string()))
+    );
+    assert!(controller.get_issued().is_empty());
+    assert!(controller.register("t").is_none());
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+type ScalarCircuit = fn(
+    &ProcessorBoolean,
+    &ServerKey,
+    &[Ciphertext],
+    i64,
+    &mut [Ciphertext],
+) -> Result<(), PulpError>;
+type BinaryCircuit = fn(
+    &ProcessorBoolean,
+    &ServerKey,
+    &[Ciphertext],
+    &[Ciphertext],
+    &mut [Ciphertext],
+) -> Result<(), PulpError>;
+
+fn trivial(value: i64, width: usize) -> Vec<Ciphertext> {
+    (0..width)
+        .map(|i| Ciphertext::Trivial((value >> i) & 1 != 0))
+        .collect()
+}
+
+fn direct(
+    width: usize,
+    f: impl FnOnce(&mut [Ciphertext]) -> Result<(), PulpError>,
+) -> Vec<Vec<Ciphertext>> {
+    let mut r = vec![Ciphertext::Trivial(false); width];
+    f(&mut r).unwrap();
+    vec![r]
+}
+
+#[test]
+#[serial]
+fn test_issue_circuit() {
+    let fn_name = "issue_circuit";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let (sk, p) = (server_key, ProcessorBoolean);
+    let mut controller = Controller::new(server_key.clone());
+
+    // Trivial operands keep the sweep fast; both sides see the same ciphertexts
+    let xs: [i64; 6] = [23, -5, 7, 3, 12, 2];
+    let x: Vec<Vec<Ciphertext>> = xs.iter().map(|&v| trivial(v, 8)).collect();
+    let b: Vec<Ciphertext> = vec![Ciphertext::Trivial(true), Ciphertext::Trivial(false)];
+    for (i, value) in x.iter().enumerate() {
+        controller.set_register(&format!("x{i}"), value.clone());
+    }
+    controller.set_register("b0", vec![b[0].clone()]);
+    controller.set_register("b1", vec![b[1].clone()]);
+    let s = |range: std::ops::Range<usize>| -> Vec<&[Ciphertext]> {
+        x[range].iter().map(|v| v.as_slice()).collect()
+    };
+    let opts =
+        |imm: Option<i64>, select: Option<u8>, values: &[i64], cols: Option<usize>| EvalOptions {
+            imm,
+            select,
+            values: values.to_vec(),
+            cols,
+        };
+    let none = EvalOptions::default();
+
+    let mut checked = Vec::new();
+    let mut check = |controller: &mut Controller,
+                     op: Operation,
+                     srcs: &[&[&str]],
+                     options: &EvalOptions,
+                     expected: Vec<Vec<Ciphertext>>| {
+        let dsts: Vec<String> = (0..expected.len())
+            .map(|i| format!("{}_{i}", op.name()))
+            .collect();
+        let dsts: Vec<&str> = dsts.iter().map(|d| d.as_str()).collect();
+        controller.issue_circuit(op, srcs, &dsts, options).unwrap();
+        for (dst, value) in dsts.iter().zip(expected) {
+            let found = controller.register(dst).unwrap().to_vec();
+            assert_eq!(
+                decrypt(found, client_key),
+                decrypt(value, client_key),
+                "{op} {dst}"
+            );
+        }
+        checked.push(op);
+    };
+
+    let c = &mut controller;
+    let xy: [&[&str]; 2] = [&["x0"], &["x1"]];
+    check(
+        c,
+        Operation::And,
+        &xy,
+        &none,
+        direct(8, |r| p.e_and(sk, &x[0], &x[1], r)),
+    );
+    check(
+        c,
+        Operation::Or,
+        &xy,
+        &none,
+        direct(8, |r| p.e_or(sk, &x[0], &x[1], r)),
+    );
+    check(
+        c,
+        Operation::Xor,
+        &xy,
+        &none,
+        direct(8, |r| p.e_xor(sk, &x[0], &x[1], r)),
+    );
+    check(
+        c,
+        Operation::Nand,
+        &xy,
+        &none,
+        direct(8, |r| p.e_nand(sk, &x[0], &x[1], r)),
+    );
+    check(
+        c,
+        Operation::Nor,
+        &xy,
+        &none,
+        direct(8, |r| p.e_nor(sk, &x[0], &x[1], r)),
+    );
+    check(
+        c,
+        Operation::Xnor,
+        &xy,
+        &none,
+        direct(8, |r| p.e_xnor(sk, &x[0], &x[1], r)),
+    );
+    check(
+        c,
+        Operation::Not,
+        &[&["x0"]],
+        &none,
+        direct(8, |r| p.e_not(sk, &x[0], r)),
+    );
+    check(
+        c,
+        Operation::Mux,
+        &[&["b0"], &["x0"], &["x1"]],
+        &none,
+        direct(8, |r| p.e_mux(sk, &b[0], &x[0], &x[1], r)),
+    );
+
+    let amount = opts(Some(3), None, &[], None);
+    check(
+        c,
+        Operation::Shl,
+        &[&["x0"]],
+        &amount,
+        direct(8, |r| p.e_shl(&x[0], 3, r)),
+    );
+    check(
+        c,
+        Operation::Shr,
+        &[&["x0"]],
+        &amount,
+        direct(8, |r| p.e_shr(&x[0], 3, r)),
+    );
+    check(
+        c,
+        Operation::Rotl,
+        &[&["x0"]],
+        &amount,
+        direct(8, |r| p.e_rotl(&x[0], 3, r)),
+    );
+    check(
+        c,
+        Operation::Rotr,
+        &[&["x0"]],
+        &amount,
+        direct(8, |r| p.e_rotr(&x[0], 3, r)),
+    );
+
+    let lt = opts(Some(5), Some(1), &[], None);
+    let expected = vec![vec![p.comparator(sk, &x[0], &x[1], 1).unwrap()]];
+    check(c, Operation::Comparator, &xy, &lt, expected);
+    let expected = vec![vec![p.compare_bit(sk, &b[0], &b[1], &b[0])]];
+    check(
+        c,
+        Operation::CompareBit,
+        &[&["b0"], &["b1"], &["b0"]],
+        &none,
+        expected,
+    );
+    let expected = vec![vec![p.scalar_cmp(sk, &x[1], 5, 1).unwrap()]];
+    check(c, Operation::ScalarCmp, &[&["x1"]], &lt, expected);
+    let imm = opts(Some(5), None, &[], None);
+    let scalar: [(Operation, ScalarCircuit); 6] = [
+        (Operation::ScalarAnd, ProcessorBoolean::scalar_and),
+        (Operation::ScalarOr, ProcessorBoolean::scalar_or),
+        (Operation::ScalarXor, ProcessorBoolean::scalar_xor),
+        (Operation::ScalarAdd, ProcessorBoolean::scalar_add),
+        (Operation::ScalarMul, ProcessorBoolean::scalar_mul),
+        (Operation::ScalarDiv, ProcessorBoolean::scalar_div),
+    ];
+    for (op, f) in scalar {
+        check(
+            c,
+            op,
+            &[&["x0"]],
+            &imm,
+            direct(8, |r| f(&p, sk, &x[0], 5, r)),
+        );
+    }
+
+    let binary: [(Operation, BinaryCircuit); 6] = [
+        (Operation::Subtracter, ProcessorBoolean::subtracter),
+        (Operation::Adder, ProcessorBoolean::adder),
+        (Operation::SignAdder, ProcessorBoolean::sign_adder),
+        (Operation::Multiplier, ProcessorBoolean::multiplier),
+        (Operation::Divider, ProcessorBoolean::divider),
+        (Operation::Modulo, ProcessorBoolean::modulo),
+    ];
+    for (op, f) in binary {
+        check(
+            c,
+            op,
+            &[&["x0"], &["x2"]],
+            &none,
+            direct(8, |r| f(&p, sk, &x[0], &x[2], r)),
+        );
+    }
+    check(
+        c,
+        Operation::AddSupplement,
+        &xy,
+        &none,
+        direct(8, |r| p.add_supplement(sk, &x[0], &x[1], 8, r)),
+    );
+
+    let (mut carry, mut sum) = (Ciphertext::Trivial(false), Ciphertext::Trivial(false));
+    p.half_adder(sk, &b[0], &b[1], &mut carry, &mut sum);
+    check(
+        c,
+        Operation::HalfAdder,
+        &[&["b0"], &["b1"]],
+        &none,
+        vec![vec![sum, carry]],
+    );
+    let (mut carry, mut sum) = (Ciphertext::Trivial(false), Ciphertext::Trivial(false));
+    p.carry_save_adder(sk, &b[0], &b[1], &b[0], &mut carry, &mut sum);
+    let expected = vec![vec![sum, carry]];
+    check(
+        c,
+        Operation::CarrySaveAdder,
+        &[&["b0"], &["b1"], &["b0"]],
+        &none,
+        expected,
+    );
+
+    let mut words = vec![vec![Ciphertext::Trivial(false); 8]; 4];
+    let mut result: Vec<&mut [Ciphertext]> = words.iter_mut().map(|w| w.as_mut_slice()).collect();
+    p.blake3(sk, s(4..6), s(0..4), &mut result).unwrap();
+    let srcs: [&[&str]; 2] = [&["x4", "x5"], &["x0", "x1", "x2", "x3"]];
+    check(c, Operation::Blake3, &srcs, &none, words);
+
+    let all = ["x0", "x1", "x2", "x3", "x4", "x5"];
+    let every: [&[&str]; 1] = [&all];
+    check(
+        c,
+        Operation::Max,
+        &every,
+        &none,
+        direct(8, |r| p.max(sk, &s(0..6), r)),
+    );
+    check(
+        c,
+        Operation::Min,
+        &every,
+        &none,
+        direct(8, |r| p.min(sk, &s(0..6), r)),
+    );
+    let positive: [&[&str]; 1] = [&["x0", "x2", "x3", "x4"]];
+    let v = vec![x[0].as_slice(), &x[2], &x[3], &x[4]];
+    check(
+        c,
+        Operation::Mean,
+        &positive,
+        &none,
+        direct(8, |r| p.mean(sk, &v, 4, r)),
+    );
+    let expected = direct(8, |r| p.variance(sk, &v, 4, r));
+    check(c, Operation::Variance, &positive, &none, expected);
+    let expected = direct(8, |r| p.standard_deviation(sk, &v, 4, r));
+    check(c, Operation::StandardDeviation, &positive, &none, expected);
+    let thresholds = opts(None, None, &[0, 10], None);
+    let mut expected = Vec::new();
+    p.histogram(sk, &s(0..6), &[0, 10], &mut expected).unwrap();
+    assert_eq!(expected.len(), 3);
+    check(c, Operation::Histogram, &every, &thresholds, expected);
+
+    check(
+        c,
+        Operation::Relu,
+        &[&["x1"]],
+        &none,
+        direct(8, |r| p.relu(sk, &x[1], r)),
+    );
+    check(
+        c,
+        Operation::Sqrt,
+        &[&["x4"]],
+        &none,
+        direct(8, |r| p.sqrt(sk, &x[4], r)),
+    );
+
+    let mut expected = Vec::new();
+    p.dot_product(sk, &s(0..2), &s(2..4), &mut expected)
+        .unwrap();
+    check(
+        c,
+        Operation::DotProduct,
+        &[&all[..2], &all[2..4]],
+        &none,
+        vec![expected],
+    );
+    let weights = opts(None, None, &[3, -2], None);
+    let mut expected = Vec::new();
+    p.dot_product_plain(sk, &s(0..2), &[3, -2], &mut expected)
+        .unwrap();
+    check(
+        c,
+        Operation::DotProductPlain,
+        &[&all[..2]],
+        &weights,
+        vec![expected],
+    );
+
+    // A 2x2 matrix times a vector, and a 1x2 by 2x2 product
+    let mut expected = Vec::new();
+    p.matvec(sk, &vec![s(0..2), s(2..4)], &s(4..6), &mut expected)
+        .unwrap();
+    check(
+        c,
+        Operation::Matvec,
+        &[&all[..4], &all[4..]],
+        &none,
+        expected,
+    );
+    let matrix = opts(None, None, &[1, 2, 3, 4], None);
+    let mut expected = Vec::new();
+    p.matvec_plain(sk, &[vec![1, 2], vec![3, 4]], &s(0..2), &mut expected)
+        .unwrap();
+    check(c, Operation::MatvecPlain, &[&all[..2]], &matrix, expected);
+    let two_cols = opts(None, None, &[], Some(2));
+    let mut expected = Vec::new();
+    p.matmul(sk, &vec![s(0..2)], &vec![s(2..4), s(4..6)], &mut expected)
+        .unwrap();
+    let expected: Vec<Vec<Ciphertext>> = expected.into_iter().flatten().collect();
+    assert_eq!(expected.len(), 2);
+    check(
+        c,
+        Operation::Matmul,
+        &[&all[..2], &all[2..]],
+        &two_cols,
+        expected,
+    );
+    let weights = opts(None, None, &[1, -1, 2, 0], Some(2));
+    let mut expected = Vec::new();
+    let w = [vec![1, -1], vec![2, 0]];
+    p.matmul_plain(sk, &vec![s(0..2), s(2..4)], &w, &mut expected)
+        .unwrap();
+    let expected: Vec<Vec<Ciphertext>> = expected.into_iter().flatten().collect();
+    check(c, Operation::MatmulPlain, &[&all[..4]], &weights, expected);
+
+    // Counts are 4 bits wide for 8-bit operands
+    check(
+        c,
+        Operation::Clz,
+        &[&["x2"]],
+        &none,
+        direct(4, |r| p.clz(sk, &x[2], r)),
+    );
+    check(
+        c,
+        Operation::Ctz,
+        &[&["x4"]],
+        &none,
+        direct(4, |r| p.ctz(sk, &x[4], r)),
+    );
+    check(
+        c,
+        Operation::Popcount,
+        &[&["x1"]],
+        &none,
+        direct(4, |r| p.popcount(sk, &x[1], r)),
+    );
+    let expected = vec![vec![p.parity(sk, &x[0]).unwrap()]];
+    check(c, Operation::Parity, &[&["x0"]], &none, expected);
+    check(
+        c,
+        Operation::BitReverse,
+        &[&["x0"]],
+        &none,
+        direct(8, |r| p.bit_reverse(&x[0], r)),
+    );
+    let expected = direct(4, |r| p.hamming_distance(sk, &x[0], &x[1], r));
+    check(c, Operation::HammingDistance, &xy, &none, expected);
+    let threshold = opts(Some(3), None, &[], None);
+    let expected = vec![vec![p.match_below_threshold(sk, &x[0], &x[2], 3).unwrap()]];
+    check(
+        c,
+        Operation::MatchBelowThreshold,
+        &[&["x0"], &["x2"]],
+        &threshold,
+        expected,
+    );
+    let table = opts(Some(4), None, &[0, 1, 3, -1, -3, 7, -8, 2], None);
+    let bits = trivial(5, 3);
+    c.set_register("i", bits.clone());
+    let entries: Vec<u64> = table.values.iter().map(|&v| v as u64).collect();
+    let expected = direct(4, |r| p.lookup_table(sk, &bits, &entries, r));
+    check(c, Operation::LookupTable, &[&["i"]], &table, expected);
+    let expected = direct(8, |r| {
+        p.copy_to_from(r, &x[3]);
+        Ok(())
+    });
+    check(c, Operation::CopyToFrom, &[&["x3"]], &none, expected);
+
+    // Every operation went through the circuit functional unit
+    assert_eq!(checked.len(), Operation::ALL.len());
+    assert!(Operation::ALL.iter().all(|op| checked.contains(op)));
+    let issued = controller.get_issued();
+    assert!(issued.iter().all(|i| i.station == "CircuitUnit"));
+    assert_eq!(issued[0].op1, "x0");
+    assert_eq!(issued[0].op2, "x1");
+    assert_eq!(issued[0].dst, "and_0");
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_issue_circuit_encrypted() {
+    let fn_name = "issue_circuit_encrypted";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let server = ProcessorBoolean;
+    let mut controller = Controller::new(server_key.clone());
+
+    let (a, b): (i8, i8) = (-37, 11);
+    let ct_a = encode_encrypt(a, 8, &client_key);
+    let ct_b = encode_encrypt(b, 8, &client_key);
+    controller.set_register("a", ct_a.clone());
+    controller.set_register("b", ct_b.clone());
+
+    let none = EvalOptions::default();
+    controller
+        .issue_circuit(Operation::Subtracter, &[&["a"], &["b"]], &["d"], &none)
+        .unwrap();
+    controller
+        .issue_circuit(Operation::Multiplier, &[&["d"], &["b"]], &["m"], &none)
+        .unwrap();
+    let ge = EvalOptions {
+        select: Some(3),
+        ..EvalOptions::default()
+    };
+    controller
+        .issue_circuit(Operation::Comparator, &[&["a"], &["b"]], &["ge"], &ge)
+        .unwrap();
+
+    let mut d = vec![Ciphertext::Trivial(false); 8];
+    server
+        .subtracter(&server_key, &ct_a, &ct_b, &mut d)
+        .unwrap();
+    let mut m = vec![Ciphertext::Trivial(false); 8];
+    server.multiplier(&server_key, &d, &ct_b, &mut m).unwrap();
+    let ge = server.comparator(&server_key, &ct_a, &ct_b, 3).unwrap();
+
+    let d_found = decrypt_decode(controller.register("d").unwrap(), &client_key);
+    assert_eq!(d_found, decrypt_decode(&d, &client_key));
+    assert_eq!(d_found, a.wrapping_sub(b));
+    let m_found = decrypt_decode(controller.register("m").unwrap(), &client_key);
+    assert_eq!(m_found, decrypt_decode(&m, &client_key));
+    let ge_found = client_key.decrypt(&controller.register("ge").unwrap()[0]);
+    assert_eq!(ge_found, client_key.decrypt(&ge));
+
+    // One encrypted operation of every other dispatch shape: unary, scalar, reduction,
+    // matrix and count
+    controller
+        .issue_circuit(Operation::Not, &[&["a"]], &["n"], &none)
+        .unwrap();
+    let five = EvalOptions {
+        imm: Some(5),
+        ..EvalOptions::default()
+    };
+    controller
+        .issue_circuit(Operation::ScalarAdd, &[&["a"]], &["s"], &five)
+        .unwrap();
+    controller
+        .issue_circuit(Operation::Max, &[&["a", "b", "d"]], &["x"], &none)
+        .unwrap();
+    controller
+        .issue_circuit(
+            Operation::Matvec,
+            &[&["a", "b"], &["b", "a"]],
+            &["v"],
+            &none,
+        )
+        .unwrap();
+    controller
+        .issue_circuit(Operation::Popcount, &[&["a"]], &["c"], &none)
+        .unwrap();
+
+    let mut x = vec![Ciphertext::Trivial(false); 8];
+    let values: Vec<&[Ciphertext]> = vec![&ct_a, &ct_b, &d];
+    server.max(&server_key, &values, &mut x).unwrap();
+    let mut v = Vec::new();
+    server
+        .matvec(
+            &server_key,
+            &vec![values[..2].to_vec()],
+            &vec![&ct_b[..], &ct_a],
+            &mut v,
+        )
+        .unwrap();
+    let found = |name: &str| decrypt_decode(controller.register(name).unwrap(), &client_key);
+    assert_eq!(found("n"), !a);
+    assert_eq!(found("s"), a.wrapping_add(5));
+    assert_eq!(found("x"), decrypt_decode(&x, &client_key));
+    // `max` compares unsigned
+    let max = [a, b, a.wrapping_sub(b)].map(|v| v as u8).into_iter().max();
+    assert_eq!(found("x") as u8, max.unwrap());
+    // The product row is 2 * 8 + 1 bits wide
+    let row = decrypt(controller.register("v").unwrap().to_vec(), &client_key);
+    assert_eq!(row, decrypt(v[0].clone(), &client_key));
+    let row: i32 = row.iter().rev().fold(0, |acc, &bit| acc << 1 | bit as i32);
+    assert_eq!((row << 15) >> 15, 2 * a as i32 * b as i32);
+    assert_eq!(controller.register("c").unwrap().len(), 4);
+    assert_eq!(found("c"), a.count_ones() as i8);
+
+    // The results are renamed writes, committed like any other
+    assert_eq!(controller.get_register_tbl().get_rob().len(), 10);
+    assert_eq!(controller.commit(), 10);
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_issue_circuit_errors() {
+    let fn_name = "issue_circuit_errors";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let mut controller = Controller::new(server_key.clone());
+    controller.set_register("a", trivial(3, 8));
+    controller.set_register("b", trivial(5, 8));
+    let none = EvalOptions::default();
+
+    assert_eq!(
+        controller.issue_circuit(Operation::Adder, &[&["a"], &["c"]], &["t"], &none),
+        Err(PulpError::UnknownRegister("c".to_string()))
+    );
+    assert_eq!(
+        controller.issue_circuit(Operation::Adder, &[&["a"]], &["t"], &none),
+        Err(PulpError::WidthMismatch {
+            op: "adder",
+            operand: "srcs",
+            expected: 2,
+            found: 1,
+        })
+    );
+    assert_eq!(
+        controller.issue_circuit(Operation::Adder, &[&["a"], &["b"]], &["t", "u"], &none),
+        Err(PulpError::WidthMismatch {
+            op: "adder",
+            operand: "dsts",
+            expected: 1,
+            found: 2,
+        })
+    );
+    assert_eq!(
+        controller.issue_circuit(Operation::ScalarAdd, &[&["a"]], &["t"], &none),
+        Err(PulpError::invalid(
+            "scalar_add",
+            "`scalar_add` needs the `imm` option"
+        ))
+    );
+    assert_eq!(
+        controller.issue_circuit(
+            Operation::Matvec,
+            &[&["a", "b", "a"], &["b", "a"]],
+            &["t"],
+            &none
+        ),
+        Err(PulpError::invalid(
+            "matvec",
+            "`matvec` cannot split 3 values into rows of 2"
+        ))
+    );
+    // The registers of one operand share a width
+    controller.set_register("i", trivial(1, 1));
+    assert_eq!(
+        controller.issue_circuit(Operation::Max, &[&["a", "i"]], &["t"], &none),
+        Err(PulpError::WidthMismatch {
+            op: "max",
+            operand: "srcs",
+            expected: 8,
+            found: 1,
+        })
+    );
+    let huge = EvalOptions {
+        imm: Some(i64::MAX),
+        values: vec![0, 1],
+        ..EvalOptions::default()
+    };
+    assert_eq!(
+        controller.issue_circuit(Operation::LookupTable, &[&["i"]], &["t"], &huge),
+        Err(PulpError::UnsupportedWidth {
+            op: "lookup_table",
+            width: i64::MAX as usize,
+        })
+    );
+    assert!(controller.get_issued().is_empty());
+    assert!(controller.register("t").is_none());
+    println!("[✓] PASS: {fn_name}\n");
+}
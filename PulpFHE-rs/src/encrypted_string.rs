@@ -0,0 +1,346 @@
+//! # Description
+//! Encrypted byte strings built on `ProcessorCircuits`.
+//!
+//! An `EncryptedString` is a fixed number of bytes, each eight `Ciphertext` bits (LSB
+//! first). The text is followed by NUL bytes up to the capacity, as in C strings, so the
+//! text itself must not contain NUL. `EncryptedString::encrypt` pads with encrypted NUL
+//! bytes: only the capacity is visible to the server, not the length of the text.
+//!
+//! Every operation runs over the whole capacity and returns encrypted results; operands
+//! of different capacities are compared as if the shorter one were padded with NUL bytes.
+//! Plaintext needles are `EncryptedString::trivial` strings, whose gates are folded away.
+//!
+//! | operation | result |
+//! |-----------|--------|
+//! | `equals`, `compare` | encrypted bit; `compare` is lexicographic over unsigned bytes |
+//! | `starts_with`, `ends_with`, `contains` | encrypted bit; the empty needle always matches |
+//! | `to_ascii_lowercase`, `to_ascii_uppercase` | string of the same capacity |
+
+use crate::error::PulpError;
+use crate::processor_circuits::ProcessorCircuits;
+use crate::processor_gates::ProcessorGate;
+use tfhe::boolean::prelude::*;
+
+#[cfg(test)]
+mod test_encrypted_string;
+
+/// Bits per character.
+pub const BYTE_BITS: usize = 8;
+
+/// An encrypted, NUL padded byte string.
+#[derive(Clone, Debug)]
+pub struct EncryptedString {
+    /// One entry of `BYTE_BITS` bits per byte
+    pub bytes: Vec<Vec<Ciphertext>>,
+}
+
+impl EncryptedString {
+    pub fn from_bytes(bytes: Vec<Vec<Ciphertext>>) -> Result<Self, PulpError> {
+        for byte in bytes.iter() {
+            PulpError::expect_width("string", "bytes", BYTE_BITS, byte.len())?;
+        }
+        Ok(EncryptedString { bytes })
+    }
+
+    /// Encrypts `text` padded with NUL bytes to `capacity` bytes.
+    pub fn encrypt(
+        client_key: &ClientKey,
+        text: &[u8],
+        capacity: usize,
+    ) -> Result<Self, PulpError> {
+        Self::check_text(text, capacity)?;
+        let bytes = Self::padded(text, capacity)
+            .map(|byte| {
+                (0..BYTE_BITS)
+                    .map(|i| client_key.encrypt((byte >> i) & 1 != 0))
+                    .collect()
+            })
+            .collect();
+        Ok(EncryptedString { bytes })
+    }
+
+    /// A plaintext string, e.g. a needle, that can be mixed with encrypted strings; gates
+    /// with a constant input are folded away.
+    pub fn trivial(text: &[u8]) -> Result<Self, PulpError> {
+        Self::check_text(text, text.len())?;
+        let bytes = text
+            .iter()
+            .map(|byte| {
+                (0..BYTE_BITS)
+                    .map(|i| Ciphertext::Trivial((byte >> i) & 1 != 0))
+                    .collect()
+            })
+            .collect();
+        Ok(EncryptedString { bytes })
+    }
+
+    /// Decrypts the text, without the NUL padding.
+    pub fn decrypt(&self, client_key: &ClientKey) -> Vec<u8> {
+        self.bytes
+            .iter()
+            .map(|byte| {
+                byte.iter()
+                    .enumerate()
+                    .map(|(i, bit)| (client_key.decrypt(bit) as u8) << i)
+                    .sum::<u8>()
+            })
+            .take_while(|&byte| byte != 0)
+            .collect()
+    }
+
+    /// Number of bytes, text and padding.
+    pub fn capacity(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// The same text with (plaintext) NUL bytes added up to `capacity` bytes, which may
+    /// not be less than the current capacity.
+    pub fn pad(&self, capacity: usize) -> Result<EncryptedString, PulpError> {
+        PulpError::expect_at_least("string_pad", "capacity", self.capacity(), capacity)?;
+        Ok(EncryptedString {
+            bytes: self.padded_bytes(capacity),
+        })
+    }
+
+    /// Whether both strings hold the same text.
+    pub fn equals<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        other: &EncryptedString,
+    ) -> Result<Ciphertext, PulpError> {
+        let capacity: usize = self.capacity().max(other.capacity());
+        let (a, b) = (self.padded_bytes(capacity), other.padded_bytes(capacity));
+        let c = Circuit { p: processor, sk };
+        let mut equal: Ciphertext = Ciphertext::Trivial(true);
+        for (x, y) in a.iter().zip(b.iter()) {
+            equal = processor.e_and_bit(sk, &equal, &c.byte_eq(x, y)?);
+        }
+        Ok(equal)
+    }
+
+    /// Compares the texts lexicographically and returns an encrypted bit; a proper prefix
+    /// orders first. `select` uses the codes of `ProcessorCircuits::comparator`: 0 `==`,
+    /// 1 `<`, 2 `>`, 3 `>=`, 4 `<=`, 5 `!=`.
+    pub fn compare<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        other: &EncryptedString,
+        select: u8,
+    ) -> Result<Ciphertext, PulpError> {
+        if select > 5 {
+            return Err(PulpError::invalid(
+                "string_compare",
+                format!("unknown comparison selector {select}"),
+            ));
+        }
+        let capacity: usize = self.capacity().max(other.capacity());
+        let (a, b) = (self.padded_bytes(capacity), other.padded_bytes(capacity));
+        let c = Circuit { p: processor, sk };
+
+        // From the last byte back: the first differing byte decides, NUL padding orders a
+        // prefix before any longer text
+        let mut less: Ciphertext = Ciphertext::Trivial(false);
+        let mut equal: Ciphertext = Ciphertext::Trivial(true);
+        for (x, y) in a.iter().zip(b.iter()).rev() {
+            let byte_less: Ciphertext = processor.comparator(sk, x, y, 1)?;
+            let byte_equal: Ciphertext = c.byte_eq(x, y)?;
+            let tail_less: Ciphertext = processor.e_and_bit(sk, &byte_equal, &less);
+            less = processor.e_or_bit(sk, &byte_less, &tail_less);
+            equal = processor.e_and_bit(sk, &byte_equal, &equal);
+        }
+        Ok(match select {
+            0 => equal,
+            1 => less,
+            2 => processor.e_nor_bit(sk, &less, &equal),
+            3 => processor.e_not_bit(sk, &less),
+            4 => processor.e_or_bit(sk, &less, &equal),
+            _ => processor.e_not_bit(sk, &equal),
+        })
+    }
+
+    /// Whether the text begins with the text of `needle`.
+    pub fn starts_with<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        needle: &EncryptedString,
+    ) -> Result<Ciphertext, PulpError> {
+        let c = Circuit { p: processor, sk };
+        let ended: Vec<Ciphertext> = c.ended(needle)?;
+        c.match_at(self, needle, &ended, 0, false)
+    }
+
+    /// Whether the text ends with the text of `needle`.
+    pub fn ends_with<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        needle: &EncryptedString,
+    ) -> Result<Ciphertext, PulpError> {
+        let c = Circuit { p: processor, sk };
+        let ended: Vec<Ciphertext> = c.ended(needle)?;
+        // The needle and its terminating NUL must match at some offset
+        c.match_any(self, needle, &ended, true)
+    }
+
+    /// Whether the text of `needle` occurs anywhere in the text.
+    pub fn contains<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        needle: &EncryptedString,
+    ) -> Result<Ciphertext, PulpError> {
+        let c = Circuit { p: processor, sk };
+        let ended: Vec<Ciphertext> = c.ended(needle)?;
+        c.match_any(self, needle, &ended, false)
+    }
+
+    /// Maps `A`..=`Z` to `a`..=`z`, leaving every other byte as it is.
+    pub fn to_ascii_lowercase<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+    ) -> Result<EncryptedString, PulpError> {
+        self.fold_case(processor, sk, b'A', true)
+    }
+
+    /// Maps `a`..=`z` to `A`..=`Z`, leaving every other byte as it is.
+    pub fn to_ascii_uppercase<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+    ) -> Result<EncryptedString, PulpError> {
+        self.fold_case(processor, sk, b'a', false)
+    }
+
+    /// Flips the case bit (`0x20`) of the 26 letters starting at `first`, setting it when
+    /// `lower`.
+    fn fold_case<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        first: u8,
+        lower: bool,
+    ) -> Result<EncryptedString, PulpError> {
+        let low: Vec<Ciphertext> = constant(first - 1);
+        let high: Vec<Ciphertext> = constant(first + 26);
+        let bytes = self
+            .bytes
+            .iter()
+            .map(|byte| {
+                let above: Ciphertext = processor.comparator(sk, byte, &low, 2)?;
+                let below: Ciphertext = processor.comparator(sk, byte, &high, 1)?;
+                let letter: Ciphertext = processor.e_and_bit(sk, &above, &below);
+                let mut folded: Vec<Ciphertext> = byte.clone();
+                folded[5] = if lower {
+                    processor.e_or_bit(sk, &byte[5], &letter)
+                } else {
+                    let keep: Ciphertext = processor.e_not_bit(sk, &letter);
+                    processor.e_and_bit(sk, &byte[5], &keep)
+                };
+                Ok(folded)
+            })
+            .collect::<Result<_, PulpError>>()?;
+        Ok(EncryptedString { bytes })
+    }
+
+    fn check_text(text: &[u8], capacity: usize) -> Result<(), PulpError> {
+        if text.contains(&0) {
+            return Err(PulpError::invalid("string", "the text contains a NUL byte"));
+        }
+        PulpError::expect_at_least("string", "capacity", text.len(), capacity)
+    }
+
+    fn padded(text: &[u8], capacity: usize) -> impl Iterator<Item = u8> + '_ {
+        text.iter()
+            .copied()
+            .chain(std::iter::repeat(0))
+            .take(capacity)
+    }
+
+    fn padded_bytes(&self, capacity: usize) -> Vec<Vec<Ciphertext>> {
+        let mut bytes: Vec<Vec<Ciphertext>> = self.bytes.clone();
+        bytes.resize(capacity, constant(0));
+        bytes
+    }
+}
+
+/// The bits of a plaintext byte.
+fn constant(byte: u8) -> Vec<Ciphertext> {
+    (0..BYTE_BITS)
+        .map(|i| Ciphertext::Trivial((byte >> i) & 1 != 0))
+        .collect()
+}
+
+/// The processor and key, with the byte matching shared by the search operations.
+struct Circuit<'a, P> {
+    p: &'a P,
+    sk: &'a ServerKey,
+}
+
+impl<P: ProcessorGate + ProcessorCircuits> Circuit<'_, P> {
+    fn byte_eq(&self, a: &[Ciphertext], b: &[Ciphertext]) -> Result<Ciphertext, PulpError> {
+        let mut same: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); BYTE_BITS];
+        self.p.e_xnor(self.sk, a, b, &mut same)?;
+        Ok(same.iter().skip(1).fold(same[0].clone(), |acc, bit| {
+            self.p.e_and_bit(self.sk, &acc, bit)
+        }))
+    }
+
+    /// `ended[j]`: the needle text is shorter than `j` bytes, i.e. one of its first `j`
+    /// bytes is NUL. Has one more entry than the needle, which is always false.
+    fn ended(&self, needle: &EncryptedString) -> Result<Vec<Ciphertext>, PulpError> {
+        let zero: Vec<Ciphertext> = constant(0);
+        let mut ended: Vec<Ciphertext> = vec![Ciphertext::Trivial(false)];
+        for byte in needle.bytes.iter() {
+            let nul: Ciphertext = self.byte_eq(byte, &zero)?;
+            let shorter: Ciphertext = self.p.e_or_bit(self.sk, &ended[ended.len() - 1], &nul);
+            ended.push(shorter);
+        }
+        Ok(ended)
+    }
+
+    /// Whether the needle text occurs in `text` at byte `offset`. With `terminated`, the
+    /// NUL following the needle text must match as well, i.e. the text ends there.
+    fn match_at(
+        &self,
+        text: &EncryptedString,
+        needle: &EncryptedString,
+        ended: &[Ciphertext],
+        offset: usize,
+        terminated: bool,
+    ) -> Result<Ciphertext, PulpError> {
+        let zero: Vec<Ciphertext> = constant(0);
+        let compared: usize = needle.capacity() + terminated as usize;
+        let mut matched: Ciphertext = Ciphertext::Trivial(true);
+        for j in 0..compared {
+            let x: &[Ciphertext] = text.bytes.get(offset + j).unwrap_or(&zero);
+            let y: &[Ciphertext] = needle.bytes.get(j).unwrap_or(&zero);
+            // Past the needle text every byte matches; with `terminated` the NUL at the
+            // end of the needle is still compared, so `ended` lags by one byte
+            let done: &Ciphertext = if terminated { &ended[j] } else { &ended[j + 1] };
+            let same: Ciphertext = self.byte_eq(x, y)?;
+            let ok: Ciphertext = self.p.e_or_bit(self.sk, &same, done);
+            matched = self.p.e_and_bit(self.sk, &matched, &ok);
+        }
+        Ok(matched)
+    }
+
+    /// Whether `match_at` holds at any offset of `text`.
+    fn match_any(
+        &self,
+        text: &EncryptedString,
+        needle: &EncryptedString,
+        ended: &[Ciphertext],
+        terminated: bool,
+    ) -> Result<Ciphertext, PulpError> {
+        let mut found: Ciphertext = Ciphertext::Trivial(false);
+        for offset in 0..=text.capacity() {
+            let here: Ciphertext = self.match_at(text, needle, ended, offset, terminated)?;
+            found = self.p.e_or_bit(self.sk, &found, &here);
+        }
+        Ok(found)
+    }
+}
@@ -0,0 +1,138 @@
+#![allow(warnings)]
+use super::*; // Imports items from the parent module
+use crate::key_manager::KeyManager;
+use crate::processor_boolean::ProcessorBoolean;
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn test_string_encrypt() {
+    let fn_name = "string_encrypt";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, _) = KeyManager::cached_keys();
+
+    let text = EncryptedString::encrypt(client_key, b"pulp", 8).unwrap();
+    assert_eq!(text.capacity(), 8);
+    assert_eq!(text.decrypt(client_key), b"pulp");
+    // The padding is encrypted, so the length is not visible
+    assert!(text.bytes[7]
+        .iter()
+        .all(|bit| !matches!(bit, Ciphertext::Trivial(_))));
+    assert_eq!(text.pad(10).unwrap().decrypt(client_key), b"pulp");
+
+    assert_eq!(
+        EncryptedString::encrypt(client_key, b"pulp", 3).unwrap_err(),
+        PulpError::WidthMismatch {
+            op: "string",
+            operand: "capacity",
+            expected: 4,
+            found: 3
+        }
+    );
+    assert!(matches!(
+        EncryptedString::trivial(b"a\0b"),
+        Err(PulpError::InvalidArgument { op: "string", .. })
+    ));
+    assert!(matches!(
+        text.pad(4),
+        Err(PulpError::WidthMismatch {
+            op: "string_pad",
+            ..
+        })
+    ));
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_string_compare() {
+    let fn_name = "string_compare";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let server = ProcessorBoolean;
+
+    let cases: [(&[u8], &[u8]); 5] = [
+        (b"cab", b"cab"),
+        (b"cab", b"cb"),
+        (b"ca", b"cab"),
+        (b"z", b"\xe9"),
+        (b"", b"a"),
+    ];
+    for (a, b) in cases {
+        let ct_a = EncryptedString::encrypt(client_key, a, 3).unwrap();
+        let ct_b = EncryptedString::encrypt(client_key, b, 4).unwrap();
+        let equal: bool = client_key.decrypt(&ct_a.equals(&server, server_key, &ct_b).unwrap());
+        println!("\t {a:?} == {b:?}: {equal}");
+        assert_eq!(equal, a == b);
+
+        let expected = [a == b, a < b, a > b, a >= b, a <= b, a != b];
+        for (select, expected) in expected.into_iter().enumerate() {
+            let bit = ct_a
+                .compare(&server, server_key, &ct_b, select as u8)
+                .unwrap();
+            assert_eq!(
+                client_key.decrypt(&bit),
+                expected,
+                "{a:?} vs {b:?}, select {select}"
+            );
+        }
+    }
+    let ct_a = EncryptedString::trivial(b"a").unwrap();
+    assert!(matches!(
+        ct_a.compare(&server, server_key, &ct_a, 6),
+        Err(PulpError::InvalidArgument { .. })
+    ));
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_string_search() {
+    let fn_name = "string_search";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let server = ProcessorBoolean;
+
+    let text = EncryptedString::encrypt(client_key, b"banana", 7).unwrap();
+    let needles: [&[u8]; 6] = [b"ban", b"na", b"nan", b"", b"bx", b"banana!"];
+    for needle in needles {
+        // Plaintext needles are folded into the circuit, encrypted ones hide their length
+        let plain = EncryptedString::trivial(needle).unwrap();
+        let secret = EncryptedString::encrypt(client_key, needle, 7).unwrap();
+        for (kind, ct_needle) in [("plaintext", &plain), ("encrypted", &secret)] {
+            let starts: bool =
+                client_key.decrypt(&text.starts_with(&server, server_key, ct_needle).unwrap());
+            let ends: bool =
+                client_key.decrypt(&text.ends_with(&server, server_key, ct_needle).unwrap());
+            let contains: bool =
+                client_key.decrypt(&text.contains(&server, server_key, ct_needle).unwrap());
+            println!("\t {kind} {needle:?}: starts {starts}, ends {ends}, contains {contains}");
+            assert_eq!(starts, b"banana".starts_with(needle));
+            assert_eq!(ends, b"banana".ends_with(needle));
+            let found: bool =
+                needle.is_empty() || b"banana".windows(needle.len()).any(|w| w == needle);
+            assert_eq!(contains, found);
+        }
+    }
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_string_case_folding() {
+    let fn_name = "string_case_folding";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let server = ProcessorBoolean;
+
+    let text: &[u8] = b"@AZaz[`{";
+    let ct_text = EncryptedString::encrypt(client_key, text, 9).unwrap();
+    let lower = ct_text.to_ascii_lowercase(&server, server_key).unwrap();
+    let upper = ct_text.to_ascii_uppercase(&server, server_key).unwrap();
+    println!("\t lowercase = {:?}", lower.decrypt(client_key));
+    println!("\t uppercase = {:?}", upper.decrypt(client_key));
+    assert_eq!(lower.decrypt(client_key), text.to_ascii_lowercase());
+    assert_eq!(upper.decrypt(client_key), text.to_ascii_uppercase());
+    assert_eq!(lower.capacity(), 9);
+    println!("[✓] PASS: {fn_name}\n");
+}
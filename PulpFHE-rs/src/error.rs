@@ -0,0 +1,115 @@
+//! # Description
+//! `PulpError`, the error returned by the gates, circuits and the `Controller` instead of
+//! panicking or exiting, so that a bad request fails alone in a long-running service.
+
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PulpError {
+    /// An operand or result does not have the width the operation needs
+    WidthMismatch {
+        op: &'static str,
+        operand: &'static str,
+        expected: usize,
+        found: usize,
+    },
+    /// The operation is not implemented for operands of this width
+    UnsupportedWidth { op: &'static str, width: usize },
+    /// A plaintext argument the operation cannot use, e.g. a zero divisor
+    InvalidArgument { op: &'static str, reason: String },
+    /// The controller was asked for a register that is not in its register table
+    UnknownRegister(String),
+    /// The controller was asked to issue to a reservation station that does not exist
+    UnknownStation(String),
+    /// A `Program` that does not parse or does not fit its inputs; `line` is 1-based, 0
+    /// when the error is not tied to a line
+    MalformedProgram { line: usize, message: String },
+}
+
+impl PulpError {
+    pub(crate) fn invalid(op: &'static str, reason: impl Into<String>) -> Self {
+        PulpError::InvalidArgument {
+            op,
+            reason: reason.into(),
+        }
+    }
+
+    pub(crate) fn program(line: usize, message: impl Into<String>) -> Self {
+        PulpError::MalformedProgram {
+            line,
+            message: message.into(),
+        }
+    }
+
+    /// The source line of a `MalformedProgram` error.
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            PulpError::MalformedProgram { line, .. } => Some(*line),
+            _ => None,
+        }
+    }
+
+    /// Checks that `operand` of `op` is `expected` bits (or elements) long.
+    pub(crate) fn expect_width(
+        op: &'static str,
+        operand: &'static str,
+        expected: usize,
+        found: usize,
+    ) -> Result<(), PulpError> {
+        if expected != found {
+            return Err(PulpError::WidthMismatch {
+                op,
+                operand,
+                expected,
+                found,
+            });
+        }
+        Ok(())
+    }
+
+    /// Checks that `operand` of `op` is at least `needed` bits (or elements) long.
+    pub(crate) fn expect_at_least(
+        op: &'static str,
+        operand: &'static str,
+        needed: usize,
+        found: usize,
+    ) -> Result<(), PulpError> {
+        if found < needed {
+            return Err(PulpError::WidthMismatch {
+                op,
+                operand,
+                expected: needed,
+                found,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for PulpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PulpError::WidthMismatch {
+                op,
+                operand,
+                expected,
+                found,
+            } => write!(f, "`{op}`: `{operand}` has width {found}, expected {expected}"),
+            PulpError::UnsupportedWidth { op, width } => {
+                write!(f, "`{op}` does not support {width}-bit operands")
+            }
+            PulpError::InvalidArgument { op, reason } => write!(f, "`{op}`: {reason}"),
+            PulpError::UnknownRegister(name) => write!(f, "unknown register `{name}`"),
+            PulpError::UnknownStation(name) => write!(f, "unknown reservation station `{name}`"),
+            PulpError::MalformedProgram { line, message } => {
+                if *line == 0 {
+                    write!(f, "{message}")
+                } else {
+                    write!(f, "line {line}: {message}")
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for PulpError {}
@@ -0,0 +1,869 @@
+//! # Description
+//! Dispatch of named operations on `EncryptedIntegers`, for callers that only hold a
+//! `ServerKey` (the command-line tool and the evaluation server). The dispatch itself,
+//! `dispatch`, reads borrowed `Operand`s and is shared with `Controller::issue_circuit`.
+//!
+//! Every `ProcessorGate` and `ProcessorCircuits` operation is available under its method
+//! name; `add`, `sub`, `mul`, `div` and `mod` are accepted as short names. Operands are
+//! applied element-wise when an input holds several integers, except for the reductions
+//! (`max`, `min`, `mean`, `variance`, `standard_deviation`, `histogram`) which run over all
+//! integers of all inputs, and the vector and matrix operations. Matrices are stored row
+//! major; their column count is given with `EvalOptions::cols`.
+
+use crate::ciphertext_file::{CiphertextFileError, EncryptedIntegers};
+use crate::error::PulpError;
+use crate::key_manager::KeyInfo;
+use crate::processor_boolean::ProcessorBoolean;
+use crate::processor_circuits::ProcessorCircuits;
+use crate::processor_gates::ProcessorGate;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use tfhe::boolean::prelude::{Ciphertext, ServerKey};
+
+#[cfg(test)]
+mod test_evaluator;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operation {
+    And,
+    Or,
+    Xor,
+    Nand,
+    Nor,
+    Xnor,
+    Not,
+    Mux,
+    Shl,
+    Shr,
+    Rotl,
+    Rotr,
+    Comparator,
+    CompareBit,
+    ScalarCmp,
+    ScalarAnd,
+    ScalarOr,
+    ScalarXor,
+    ScalarAdd,
+    ScalarMul,
+    ScalarDiv,
+    Subtracter,
+    Adder,
+    SignAdder,
+    HalfAdder,
+    CarrySaveAdder,
+    AddSupplement,
+    Multiplier,
+    Blake3,
+    Max,
+    Min,
+    Relu,
+    Divider,
+    Modulo,
+    Mean,
+    Sqrt,
+    Variance,
+    StandardDeviation,
+    Histogram,
+    DotProduct,
+    DotProductPlain,
+    Matvec,
+    MatvecPlain,
+    Matmul,
+    MatmulPlain,
+    Clz,
+    Ctz,
+    Popcount,
+    Parity,
+    BitReverse,
+    HammingDistance,
+    MatchBelowThreshold,
+    LookupTable,
+    CopyToFrom,
+}
+
+impl Operation {
+    pub const ALL: [Operation; 54] = [
+        Operation::And,
+        Operation::Or,
+        Operation::Xor,
+        Operation::Nand,
+        Operation::Nor,
+        Operation::Xnor,
+        Operation::Not,
+        Operation::Mux,
+        Operation::Shl,
+        Operation::Shr,
+        Operation::Rotl,
+        Operation::Rotr,
+        Operation::Comparator,
+        Operation::CompareBit,
+        Operation::ScalarCmp,
+        Operation::ScalarAnd,
+        Operation::ScalarOr,
+        Operation::ScalarXor,
+        Operation::ScalarAdd,
+        Operation::ScalarMul,
+        Operation::ScalarDiv,
+        Operation::Subtracter,
+        Operation::Adder,
+        Operation::SignAdder,
+        Operation::HalfAdder,
+        Operation::CarrySaveAdder,
+        Operation::AddSupplement,
+        Operation::Multiplier,
+        Operation::Blake3,
+        Operation::Max,
+        Operation::Min,
+        Operation::Relu,
+        Operation::Divider,
+        Operation::Modulo,
+        Operation::Mean,
+        Operation::Sqrt,
+        Operation::Variance,
+        Operation::StandardDeviation,
+        Operation::Histogram,
+        Operation::DotProduct,
+        Operation::DotProductPlain,
+        Operation::Matvec,
+        Operation::MatvecPlain,
+        Operation::Matmul,
+        Operation::MatmulPlain,
+        Operation::Clz,
+        Operation::Ctz,
+        Operation::Popcount,
+        Operation::Parity,
+        Operation::BitReverse,
+        Operation::HammingDistance,
+        Operation::MatchBelowThreshold,
+        Operation::LookupTable,
+        Operation::CopyToFrom,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::And => "and",
+            Operation::Or => "or",
+            Operation::Xor => "xor",
+            Operation::Nand => "nand",
+            Operation::Nor => "nor",
+            Operation::Xnor => "xnor",
+            Operation::Not => "not",
+            Operation::Mux => "mux",
+            Operation::Shl => "shl",
+            Operation::Shr => "shr",
+            Operation::Rotl => "rotl",
+            Operation::Rotr => "rotr",
+            Operation::Comparator => "comparator",
+            Operation::CompareBit => "compare_bit",
+            Operation::ScalarCmp => "scalar_cmp",
+            Operation::ScalarAnd => "scalar_and",
+            Operation::ScalarOr => "scalar_or",
+            Operation::ScalarXor => "scalar_xor",
+            Operation::ScalarAdd => "scalar_add",
+            Operation::ScalarMul => "scalar_mul",
+            Operation::ScalarDiv => "scalar_div",
+            Operation::Subtracter => "subtracter",
+            Operation::Adder => "adder",
+            Operation::SignAdder => "sign_adder",
+            Operation::HalfAdder => "half_adder",
+            Operation::CarrySaveAdder => "carry_save_adder",
+            Operation::AddSupplement => "add_supplement",
+            Operation::Multiplier => "multiplier",
+            Operation::Blake3 => "blake3",
+            Operation::Max => "max",
+            Operation::Min => "min",
+            Operation::Relu => "relu",
+            Operation::Divider => "divider",
+            Operation::Modulo => "modulo",
+            Operation::Mean => "mean",
+            Operation::Sqrt => "sqrt",
+            Operation::Variance => "variance",
+            Operation::StandardDeviation => "standard_deviation",
+            Operation::Histogram => "histogram",
+            Operation::DotProduct => "dot_product",
+            Operation::DotProductPlain => "dot_product_plain",
+            Operation::Matvec => "matvec",
+            Operation::MatvecPlain => "matvec_plain",
+            Operation::Matmul => "matmul",
+            Operation::MatmulPlain => "matmul_plain",
+            Operation::Clz => "clz",
+            Operation::Ctz => "ctz",
+            Operation::Popcount => "popcount",
+            Operation::Parity => "parity",
+            Operation::BitReverse => "bit_reverse",
+            Operation::HammingDistance => "hamming_distance",
+            Operation::MatchBelowThreshold => "match_below_threshold",
+            Operation::LookupTable => "lookup_table",
+            Operation::CopyToFrom => "copy_to_from",
+        }
+    }
+
+    /// Number of ciphertext inputs the operation takes; `None` for the reductions, which
+    /// take one or more.
+    pub fn arity(&self) -> Option<usize> {
+        match self {
+            Operation::Max
+            | Operation::Min
+            | Operation::Mean
+            | Operation::Variance
+            | Operation::StandardDeviation
+            | Operation::Histogram => None,
+            Operation::Not
+            | Operation::Shl
+            | Operation::Shr
+            | Operation::Rotl
+            | Operation::Rotr
+            | Operation::ScalarCmp
+            | Operation::ScalarAnd
+            | Operation::ScalarOr
+            | Operation::ScalarXor
+            | Operation::ScalarAdd
+            | Operation::ScalarMul
+            | Operation::ScalarDiv
+            | Operation::Relu
+            | Operation::Sqrt
+            | Operation::DotProductPlain
+            | Operation::MatvecPlain
+            | Operation::MatmulPlain
+            | Operation::Clz
+            | Operation::Ctz
+            | Operation::Popcount
+            | Operation::Parity
+            | Operation::BitReverse
+            | Operation::LookupTable
+            | Operation::CopyToFrom => Some(1),
+            Operation::Mux | Operation::CompareBit | Operation::CarrySaveAdder => Some(3),
+            _ => Some(2),
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for Operation {
+    type Err = EvalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = match s {
+            "add" => "adder",
+            "sub" => "subtracter",
+            "mul" => "multiplier",
+            "div" => "divider",
+            "mod" => "modulo",
+            other => other,
+        };
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|op| op.name() == name)
+            .ok_or_else(|| EvalError::UnknownOperation(s.to_string()))
+    }
+}
+
+/// Plaintext arguments of an operation.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvalOptions {
+    /// Plaintext operand of the `scalar_*` operations, shift/rotate amount,
+    /// `match_below_threshold` threshold and `lookup_table` output width
+    pub imm: Option<i64>,
+    /// Comparison selector of `comparator` and `scalar_cmp` (0 = eq, 1 = lt, 2 = gt,
+    /// 3 = geq, 4 = leq, 5 = neq)
+    pub select: Option<u8>,
+    /// Histogram thresholds, plaintext weights (row major for matrices) or table entries
+    pub values: Vec<i64>,
+    /// Column count of the first matrix operand
+    pub cols: Option<usize>,
+}
+
+#[derive(Debug)]
+pub enum EvalError {
+    UnknownOperation(String),
+    Arity {
+        op: Operation,
+        expected: usize,
+        found: usize,
+    },
+    MissingOption {
+        op: Operation,
+        option: &'static str,
+    },
+    /// Operands whose widths, counts or matrix shapes do not fit the operation
+    Shape(String),
+    /// An operand belongs to another key or is malformed
+    Operand(CiphertextFileError),
+    /// The circuit refused its operands
+    Circuit(PulpError),
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::UnknownOperation(name) => write!(f, "unknown operation `{name}`"),
+            EvalError::Arity {
+                op,
+                expected,
+                found,
+            } => write!(f, "`{op}` takes {expected} inputs, {found} given"),
+            EvalError::MissingOption { op, option } => {
+                write!(f, "`{op}` needs the `{option}` option")
+            }
+            EvalError::Shape(msg) => write!(f, "{msg}"),
+            EvalError::Operand(e) => write!(f, "{e}"),
+            EvalError::Circuit(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for EvalError {}
+
+impl From<CiphertextFileError> for EvalError {
+    fn from(e: CiphertextFileError) -> Self {
+        EvalError::Operand(e)
+    }
+}
+
+impl From<PulpError> for EvalError {
+    fn from(e: PulpError) -> Self {
+        EvalError::Circuit(e)
+    }
+}
+
+/// Integers of one width, LSB first: an operand of `dispatch`.
+#[derive(Clone, Copy, Debug)]
+pub struct Operand<'a> {
+    pub width: usize,
+    pub signed: bool,
+    pub values: &'a [Vec<Ciphertext>],
+}
+
+impl<'a> From<&'a EncryptedIntegers> for Operand<'a> {
+    fn from(input: &'a EncryptedIntegers) -> Self {
+        Operand {
+            width: input.width,
+            signed: input.signed,
+            values: &input.values,
+        }
+    }
+}
+
+/// The result of `dispatch`.
+#[derive(Clone, Debug)]
+pub struct Integers {
+    pub width: usize,
+    pub signed: bool,
+    pub values: Vec<Vec<Ciphertext>>,
+}
+
+pub struct Evaluator<'a> {
+    sk: &'a ServerKey,
+    key: KeyInfo,
+    processor: ProcessorBoolean,
+}
+
+impl<'a> Evaluator<'a> {
+    /// `key` is the identity of `sk`, as read from its key file; inputs encrypted under
+    /// another key are rejected.
+    pub fn new(sk: &'a ServerKey, key: KeyInfo) -> Self {
+        Evaluator {
+            sk,
+            key,
+            processor: ProcessorBoolean::new(),
+        }
+    }
+
+    pub fn eval(
+        &self,
+        op: Operation,
+        inputs: &[EncryptedIntegers],
+        options: &EvalOptions,
+    ) -> Result<EncryptedIntegers, EvalError> {
+        for input in inputs.iter() {
+            input.verify_key(&self.key)?;
+        }
+        let operands: Vec<Operand<'_>> = inputs.iter().map(Operand::from).collect();
+        let result = dispatch(&self.processor, self.sk, op, &operands, options)?;
+        Ok(EncryptedIntegers::new(
+            result.width,
+            result.signed,
+            &self.key,
+            result.values,
+        )?)
+    }
+}
+
+/// Runs `op` on `inputs` with the operand rules described above; `Evaluator::eval` and
+/// `Controller::issue_circuit` both dispatch through it.
+pub fn dispatch(
+    p: &ProcessorBoolean,
+    sk: &ServerKey,
+    op: Operation,
+    inputs: &[Operand<'_>],
+    options: &EvalOptions,
+) -> Result<Integers, EvalError> {
+    match op.arity() {
+        Some(n) if inputs.len() != n => {
+            return Err(EvalError::Arity {
+                op,
+                expected: n,
+                found: inputs.len(),
+            });
+        }
+        None if inputs.is_empty() => {
+            return Err(EvalError::Arity {
+                op,
+                expected: 1,
+                found: 0,
+            });
+        }
+        _ => {}
+    }
+    for input in inputs.iter() {
+        if input.values.is_empty() {
+            return Err(EvalError::Shape(format!("`{op}` got an empty input")));
+        }
+    }
+
+    let first = &inputs[0];
+    let (width, signed) = (first.width, first.signed);
+    let imm = || {
+        options
+            .imm
+            .ok_or(EvalError::MissingOption { op, option: "imm" })
+    };
+    let select = || {
+        options.select.ok_or(EvalError::MissingOption {
+            op,
+            option: "select",
+        })
+    };
+    let shift = || -> Result<usize, EvalError> {
+        usize::try_from(imm()?)
+            .map_err(|_| EvalError::Shape(format!("`{op}` needs a non-negative amount")))
+    };
+
+    let values = match op {
+        Operation::And => zip2(op, inputs, |a, b, r| p.e_and(sk, a, b, r))?,
+        Operation::Or => zip2(op, inputs, |a, b, r| p.e_or(sk, a, b, r))?,
+        Operation::Xor => zip2(op, inputs, |a, b, r| p.e_xor(sk, a, b, r))?,
+        Operation::Nand => zip2(op, inputs, |a, b, r| p.e_nand(sk, a, b, r))?,
+        Operation::Nor => zip2(op, inputs, |a, b, r| p.e_nor(sk, a, b, r))?,
+        Operation::Xnor => zip2(op, inputs, |a, b, r| p.e_xnor(sk, a, b, r))?,
+        Operation::Not => map1(first, |a, r| p.e_not(sk, a, r))?,
+        Operation::Mux => {
+            let selector = &inputs[0];
+            expect_width(op, selector, 1)?;
+            let (then, other) = (&inputs[1], &inputs[2]);
+            same_shape(op, then, other)?;
+            same_count(op, selector, then)?;
+            same_count(op, then, other)?;
+            let result = (0..then.values.len())
+                .map(|i| {
+                    let mut r = vec![Ciphertext::Trivial(false); then.width];
+                    p.e_mux(
+                        sk,
+                        &selector.values[i][0],
+                        &then.values[i],
+                        &other.values[i],
+                        &mut r,
+                    )?;
+                    Ok(r)
+                })
+                .collect::<Result<_, PulpError>>()?;
+            return output(then.width, then.signed, result);
+        }
+        Operation::Shl => {
+            let n = shift()?;
+            map1(first, |a, r| p.e_shl(a, n, r))?
+        }
+        Operation::Shr => {
+            let n = shift()?;
+            map1(first, |a, r| p.e_shr(a, n, r))?
+        }
+        Operation::Rotl => {
+            let n = shift()?;
+            map1(first, |a, r| p.e_rotl(a, n, r))?
+        }
+        Operation::Rotr => {
+            let n = shift()?;
+            map1(first, |a, r| p.e_rotr(a, n, r))?
+        }
+        Operation::Comparator => {
+            let s = select()?;
+            same_shape(op, &inputs[0], &inputs[1])?;
+            same_count(op, &inputs[0], &inputs[1])?;
+            let result = inputs[0]
+                .values
+                .iter()
+                .zip(inputs[1].values.iter())
+                .map(|(a, b)| Ok(vec![p.comparator(sk, a, b, s)?]))
+                .collect::<Result<_, PulpError>>()?;
+            return output(1, false, result);
+        }
+        Operation::CompareBit => {
+            for input in inputs.iter() {
+                expect_width(op, input, 1)?;
+                same_count(op, first, input)?;
+            }
+            let result = (0..first.values.len())
+                .map(|i| {
+                    vec![p.compare_bit(
+                        sk,
+                        &inputs[0].values[i][0],
+                        &inputs[1].values[i][0],
+                        &inputs[2].values[i][0],
+                    )]
+                })
+                .collect();
+            return output(1, false, result);
+        }
+        Operation::ScalarCmp => {
+            let (b, s) = (imm()?, select()?);
+            let result = first
+                .values
+                .iter()
+                .map(|a| Ok(vec![p.scalar_cmp(sk, a, b, s)?]))
+                .collect::<Result<_, PulpError>>()?;
+            return output(1, false, result);
+        }
+        Operation::ScalarAnd => {
+            let b = imm()?;
+            map1(first, |a, r| p.scalar_and(sk, a, b, r))?
+        }
+        Operation::ScalarOr => {
+            let b = imm()?;
+            map1(first, |a, r| p.scalar_or(sk, a, b, r))?
+        }
+        Operation::ScalarXor => {
+            let b = imm()?;
+            map1(first, |a, r| p.scalar_xor(sk, a, b, r))?
+        }
+        Operation::ScalarAdd => {
+            let b = imm()?;
+            map1(first, |a, r| p.scalar_add(sk, a, b, r))?
+        }
+        Operation::ScalarMul => {
+            let b = imm()?;
+            map1(first, |a, r| p.scalar_mul(sk, a, b, r))?
+        }
+        Operation::ScalarDiv => {
+            let b = imm()?;
+            map1(first, |a, r| p.scalar_div(sk, a, b, r))?
+        }
+        Operation::Subtracter => zip2(op, inputs, |a, b, r| p.subtracter(sk, a, b, r))?,
+        Operation::Adder => zip2(op, inputs, |a, b, r| p.adder(sk, a, b, r))?,
+        Operation::SignAdder => zip2(op, inputs, |a, b, r| p.sign_adder(sk, a, b, r))?,
+        Operation::AddSupplement => {
+            zip2(op, inputs, |a, b, r| p.add_supplement(sk, a, b, a.len(), r))?
+        }
+        Operation::Multiplier => zip2(op, inputs, |a, b, r| p.multiplier(sk, a, b, r))?,
+        Operation::Divider => zip2(op, inputs, |a, b, r| p.divider(sk, a, b, r))?,
+        Operation::Modulo => zip2(op, inputs, |a, b, r| p.modulo(sk, a, b, r))?,
+        Operation::HalfAdder | Operation::CarrySaveAdder => {
+            // The sum and carry bits form a 2-bit unsigned result
+            for input in inputs.iter() {
+                expect_width(op, input, 1)?;
+                same_count(op, first, input)?;
+            }
+            let result = (0..first.values.len())
+                .map(|i| {
+                    let mut carry = Ciphertext::Trivial(false);
+                    let mut sum = Ciphertext::Trivial(false);
+                    let (a, b) = (&inputs[0].values[i][0], &inputs[1].values[i][0]);
+                    if op == Operation::HalfAdder {
+                        p.half_adder(sk, a, b, &mut carry, &mut sum);
+                    } else {
+                        let cin = &inputs[2].values[i][0];
+                        p.carry_save_adder(sk, a, b, cin, &mut carry, &mut sum);
+                    }
+                    vec![sum, carry]
+                })
+                .collect();
+            return output(2, false, result);
+        }
+        Operation::Blake3 => {
+            let (msg, v) = (&inputs[0], &inputs[1]);
+            same_shape(op, msg, v)?;
+            let mut words: Vec<Vec<Ciphertext>> = vec![vec![Ciphertext::Trivial(false); width]; 4];
+            let mut result: Vec<&mut [Ciphertext]> =
+                words.iter_mut().map(|w| w.as_mut_slice()).collect();
+            p.blake3(sk, slices(msg), slices(v), &mut result)?;
+            words
+        }
+        Operation::Relu => map1(first, |a, r| p.relu(sk, a, r))?,
+        Operation::Sqrt => map1(first, |a, r| p.sqrt(sk, a, r))?,
+        Operation::Clz | Operation::Ctz | Operation::Popcount => {
+            // Counts are unsigned and just wide enough to hold `width`
+            let count_width = (usize::BITS - width.leading_zeros()) as usize;
+            let result = map_to(first, count_width, |a, r| match op {
+                Operation::Clz => p.clz(sk, a, r),
+                Operation::Ctz => p.ctz(sk, a, r),
+                _ => p.popcount(sk, a, r),
+            })?;
+            return output(count_width, false, result);
+        }
+        Operation::Parity => {
+            let result = first
+                .values
+                .iter()
+                .map(|a| Ok(vec![p.parity(sk, a)?]))
+                .collect::<Result<_, PulpError>>()?;
+            return output(1, false, result);
+        }
+        Operation::BitReverse => map1(first, |a, r| p.bit_reverse(a, r))?,
+        Operation::HammingDistance => {
+            same_shape(op, &inputs[0], &inputs[1])?;
+            same_count(op, &inputs[0], &inputs[1])?;
+            let count_width = (usize::BITS - width.leading_zeros()) as usize;
+            let result = inputs[0]
+                .values
+                .iter()
+                .zip(inputs[1].values.iter())
+                .map(|(a, b)| {
+                    let mut r = vec![Ciphertext::Trivial(false); count_width];
+                    p.hamming_distance(sk, a, b, &mut r)?;
+                    Ok(r)
+                })
+                .collect::<Result<_, PulpError>>()?;
+            return output(count_width, false, result);
+        }
+        Operation::MatchBelowThreshold => {
+            let threshold = usize::try_from(imm()?)
+                .map_err(|_| EvalError::Shape(format!("`{op}` needs a non-negative imm")))?;
+            same_shape(op, &inputs[0], &inputs[1])?;
+            same_count(op, &inputs[0], &inputs[1])?;
+            let result = inputs[0]
+                .values
+                .iter()
+                .zip(inputs[1].values.iter())
+                .map(|(a, b)| Ok(vec![p.match_below_threshold(sk, a, b, threshold)?]))
+                .collect::<Result<_, PulpError>>()?;
+            return output(1, false, result);
+        }
+        Operation::LookupTable => {
+            let out_width = usize::try_from(imm()?)
+                .map_err(|_| EvalError::Shape(format!("`{op}` needs a non-negative imm")))?;
+            // Checked before the results are allocated
+            if out_width == 0 || out_width > 64 {
+                return Err(EvalError::Circuit(PulpError::UnsupportedWidth {
+                    op: "lookup_table",
+                    width: out_width,
+                }));
+            }
+            // Negative entries are stored in two's complement and read back as signed
+            let table: Vec<u64> = options.values.iter().map(|&v| v as u64).collect();
+            let result = map_to(first, out_width, |a, r| p.lookup_table(sk, a, &table, r))?;
+            let signed = options.values.iter().any(|&v| v < 0);
+            return output(out_width, signed, result);
+        }
+        Operation::CopyToFrom => map1(first, |a, r| {
+            p.copy_to_from(r, a);
+            Ok(())
+        })?,
+        Operation::Max
+        | Operation::Min
+        | Operation::Mean
+        | Operation::Variance
+        | Operation::StandardDeviation => {
+            for input in inputs.iter() {
+                same_shape(op, first, input)?;
+            }
+            let all: Vec<&[Ciphertext]> = inputs
+                .iter()
+                .flat_map(|input| input.values.iter().map(|v| v.as_slice()))
+                .collect();
+            let mut r = vec![Ciphertext::Trivial(false); width];
+            match op {
+                Operation::Max => p.max(sk, &all, &mut r)?,
+                Operation::Min => p.min(sk, &all, &mut r)?,
+                Operation::Mean => p.mean(sk, &all, all.len(), &mut r)?,
+                Operation::Variance => p.variance(sk, &all, all.len(), &mut r)?,
+                _ => p.standard_deviation(sk, &all, all.len(), &mut r)?,
+            }
+            vec![r]
+        }
+        Operation::Histogram => {
+            for input in inputs.iter() {
+                same_shape(op, first, input)?;
+            }
+            let all: Vec<&[Ciphertext]> = inputs
+                .iter()
+                .flat_map(|input| input.values.iter().map(|v| v.as_slice()))
+                .collect();
+            let mut result: Vec<Vec<Ciphertext>> = Vec::new();
+            p.histogram(sk, &all, &options.values, &mut result)?;
+            return uniform_output(false, result);
+        }
+        Operation::DotProduct => {
+            same_shape(op, &inputs[0], &inputs[1])?;
+            same_count(op, &inputs[0], &inputs[1])?;
+            let mut result: Vec<Ciphertext> = Vec::new();
+            p.dot_product(sk, &slices(&inputs[0]), &slices(&inputs[1]), &mut result)?;
+            vec![result]
+        }
+        Operation::DotProductPlain => {
+            expect_len(op, "weights", options.values.len(), first.values.len())?;
+            let mut result: Vec<Ciphertext> = Vec::new();
+            p.dot_product_plain(sk, &slices(first), &options.values, &mut result)?;
+            vec![result]
+        }
+        Operation::Matvec => {
+            let (m, x) = (&inputs[0], &inputs[1]);
+            same_shape(op, m, x)?;
+            let rows = row_count(op, m.values.len(), x.values.len())?;
+            let m: Vec<Vec<&[Ciphertext]>> = rows_of(&slices(m), x.values.len(), rows);
+            let mut result: Vec<Vec<Ciphertext>> = Vec::new();
+            p.matvec(sk, &m, &slices(x), &mut result)?;
+            return uniform_output(signed, result);
+        }
+        Operation::MatvecPlain => {
+            let cols = first.values.len();
+            let rows = row_count(op, options.values.len(), cols)?;
+            let m: Vec<Vec<i64>> = options.values.chunks(cols).map(|r| r.to_vec()).collect();
+            debug_assert_eq!(m.len(), rows);
+            let mut result: Vec<Vec<Ciphertext>> = Vec::new();
+            p.matvec_plain(sk, &m, &slices(first), &mut result)?;
+            return uniform_output(signed, result);
+        }
+        Operation::Matmul => {
+            let (a, b) = (&inputs[0], &inputs[1]);
+            same_shape(op, a, b)?;
+            let inner = options
+                .cols
+                .ok_or(EvalError::MissingOption { op, option: "cols" })?;
+            let rows = row_count(op, a.values.len(), inner)?;
+            let cols = row_count(op, b.values.len(), inner)?;
+            let a_rows = rows_of(&slices(a), inner, rows);
+            let b_rows = rows_of(&slices(b), cols, inner);
+            let mut result: Vec<Vec<Vec<Ciphertext>>> = Vec::new();
+            p.matmul(sk, &a_rows, &b_rows, &mut result)?;
+            return uniform_output(signed, result.into_iter().flatten().collect());
+        }
+        Operation::MatmulPlain => {
+            let inner = options
+                .cols
+                .ok_or(EvalError::MissingOption { op, option: "cols" })?;
+            let rows = row_count(op, first.values.len(), inner)?;
+            let cols = row_count(op, options.values.len(), inner)?;
+            let a_rows = rows_of(&slices(first), inner, rows);
+            let w: Vec<Vec<i64>> = options.values.chunks(cols).map(|r| r.to_vec()).collect();
+            let mut result: Vec<Vec<Vec<Ciphertext>>> = Vec::new();
+            p.matmul_plain(sk, &a_rows, &w, &mut result)?;
+            return uniform_output(signed, result.into_iter().flatten().collect());
+        }
+    };
+    let out_width = values.first().map_or(width, |v| v.len());
+    output(out_width, signed, values)
+}
+
+fn output(width: usize, signed: bool, values: Vec<Vec<Ciphertext>>) -> Result<Integers, EvalError> {
+    Ok(Integers {
+        width,
+        signed,
+        values,
+    })
+}
+
+fn uniform_output(signed: bool, values: Vec<Vec<Ciphertext>>) -> Result<Integers, EvalError> {
+    let width = values.first().map_or(1, |v| v.len());
+    output(width, signed, values)
+}
+
+fn slices<'a>(input: &Operand<'a>) -> Vec<&'a [Ciphertext]> {
+    input.values.iter().map(|v| v.as_slice()).collect()
+}
+
+/// Applies a same-width unary circuit to every integer.
+fn map1<F: Fn(&[Ciphertext], &mut [Ciphertext]) -> Result<(), PulpError>>(
+    a: &Operand<'_>,
+    f: F,
+) -> Result<Vec<Vec<Ciphertext>>, EvalError> {
+    map_to(a, a.width, f)
+}
+
+/// Applies a unary circuit with `width`-bit results to every integer.
+fn map_to<F: Fn(&[Ciphertext], &mut [Ciphertext]) -> Result<(), PulpError>>(
+    a: &Operand<'_>,
+    width: usize,
+    f: F,
+) -> Result<Vec<Vec<Ciphertext>>, EvalError> {
+    a.values
+        .iter()
+        .map(|v| {
+            let mut r = vec![Ciphertext::Trivial(false); width];
+            f(v, &mut r)?;
+            Ok(r)
+        })
+        .collect()
+}
+
+/// Applies a same-width binary circuit to every pair of integers.
+fn zip2<F: Fn(&[Ciphertext], &[Ciphertext], &mut [Ciphertext]) -> Result<(), PulpError>>(
+    op: Operation,
+    inputs: &[Operand<'_>],
+    f: F,
+) -> Result<Vec<Vec<Ciphertext>>, EvalError> {
+    let (a, b) = (&inputs[0], &inputs[1]);
+    same_shape(op, a, b)?;
+    same_count(op, a, b)?;
+    a.values
+        .iter()
+        .zip(b.values.iter())
+        .map(|(x, y)| {
+            let mut r = vec![Ciphertext::Trivial(false); a.width];
+            f(x, y, &mut r)?;
+            Ok(r)
+        })
+        .collect()
+}
+
+fn same_shape(op: Operation, a: &Operand<'_>, b: &Operand<'_>) -> Result<(), EvalError> {
+    if a.width != b.width {
+        return Err(EvalError::Shape(format!(
+            "`{op}` operands have widths {} and {}",
+            a.width, b.width
+        )));
+    }
+    Ok(())
+}
+
+fn same_count(op: Operation, a: &Operand<'_>, b: &Operand<'_>) -> Result<(), EvalError> {
+    expect_len(op, "operands", b.values.len(), a.values.len())
+}
+
+fn expect_width(op: Operation, a: &Operand<'_>, width: usize) -> Result<(), EvalError> {
+    if a.width != width {
+        return Err(EvalError::Shape(format!(
+            "`{op}` needs {width}-bit operands, got {} bits",
+            a.width
+        )));
+    }
+    Ok(())
+}
+
+fn expect_len(op: Operation, what: &str, found: usize, expected: usize) -> Result<(), EvalError> {
+    if found != expected {
+        return Err(EvalError::Shape(format!(
+            "`{op}` {what} hold {found} values, expected {expected}"
+        )));
+    }
+    Ok(())
+}
+
+/// Number of rows of a row-major matrix of `len` elements with `cols` columns.
+fn row_count(op: Operation, len: usize, cols: usize) -> Result<usize, EvalError> {
+    if cols == 0 || len == 0 || !len.is_multiple_of(cols) {
+        return Err(EvalError::Shape(format!(
+            "`{op}` cannot split {len} values into rows of {cols}"
+        )));
+    }
+    Ok(len / cols)
+}
+
+fn rows_of<'b>(flat: &[&'b [Ciphertext]], cols: usize, rows: usize) -> Vec<Vec<&'b [Ciphertext]>> {
+    flat.chunks(cols).take(rows).map(|r| r.to_vec()).collect()
+}
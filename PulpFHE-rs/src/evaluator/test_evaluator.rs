@@ -0,0 +1,147 @@
+#![allow(warnings)]
+use super::*; // Imports items from the parent module
+use crate::key_manager::{KeyManager, ParameterSet};
+use serial_test::serial;
+
+#[test]
+#[serial]
+fn test_eval_elementwise() {
+    let fn_name = "eval_elementwise";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let info = KeyManager::cached_key_info();
+    let evaluator = Evaluator::new(server_key, info);
+
+    let a = EncryptedIntegers::encrypt(client_key, &info, 8, true, &[42, -7]).unwrap();
+    let b = EncryptedIntegers::encrypt(client_key, &info, 8, true, &[5, 3]).unwrap();
+    let sum = evaluator
+        .eval(
+            "add".parse().unwrap(),
+            &[a.clone(), b.clone()],
+            &EvalOptions::default(),
+        )
+        .unwrap();
+    assert!(sum.verify_shape(8, true).is_ok());
+    assert_eq!(sum.decrypt(client_key), vec![47, -4]);
+
+    let options = EvalOptions {
+        imm: Some(5),
+        select: Some(1),
+        ..EvalOptions::default()
+    };
+    let lt = evaluator
+        .eval(Operation::ScalarCmp, &[a.clone()], &options)
+        .unwrap();
+    assert!(lt.verify_shape(1, false).is_ok());
+    assert_eq!(lt.decrypt(client_key), vec![0, 1]);
+
+    // A signed table gives signed results of the requested width
+    let x = EncryptedIntegers::encrypt(client_key, &info, 3, false, &[2, 5]).unwrap();
+    let table = EvalOptions {
+        imm: Some(4),
+        values: vec![0, 1, 3, -1, -3, 7, -8, 2],
+        ..EvalOptions::default()
+    };
+    let looked_up = evaluator
+        .eval(Operation::LookupTable, &[x], &table)
+        .unwrap();
+    assert!(looked_up.verify_shape(4, true).is_ok());
+    assert_eq!(looked_up.decrypt(client_key), vec![3, 7]);
+
+    // Reductions run over every integer of every input
+    let c = EncryptedIntegers::encrypt(client_key, &info, 8, false, &[9, 200]).unwrap();
+    let d = EncryptedIntegers::encrypt(client_key, &info, 8, false, &[17]).unwrap();
+    let max = evaluator
+        .eval(Operation::Max, &[c, d], &EvalOptions::default())
+        .unwrap();
+    assert_eq!(max.decrypt(client_key), vec![200]);
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_eval_rejects_bad_operands() {
+    let fn_name = "eval_rejects_bad_operands";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let info = KeyManager::cached_key_info();
+    let evaluator = Evaluator::new(server_key, info);
+    let a = EncryptedIntegers::encrypt(client_key, &info, 8, false, &[1]).unwrap();
+    let wide = EncryptedIntegers::encrypt(client_key, &info, 16, false, &[1]).unwrap();
+
+    for op in Operation::ALL {
+        assert_eq!(op.name().parse::<Operation>().unwrap(), op);
+    }
+    assert!(matches!(
+        "bogus".parse::<Operation>(),
+        Err(EvalError::UnknownOperation(_))
+    ));
+    assert!(matches!(
+        evaluator.eval(Operation::Adder, &[a.clone()], &EvalOptions::default()),
+        Err(EvalError::Arity {
+            expected: 2,
+            found: 1,
+            ..
+        })
+    ));
+    assert!(matches!(
+        evaluator.eval(Operation::ScalarAdd, &[a.clone()], &EvalOptions::default()),
+        Err(EvalError::MissingOption { option: "imm", .. })
+    ));
+    assert!(matches!(
+        evaluator.eval(Operation::Xor, &[a.clone(), wide], &EvalOptions::default()),
+        Err(EvalError::Shape(_))
+    ));
+    // Operands holding different numbers of integers are refused, not truncated
+    let pair = EncryptedIntegers::encrypt(client_key, &info, 8, false, &[1, 2]).unwrap();
+    let bit = EncryptedIntegers::encrypt(client_key, &info, 1, false, &[1, 0]).unwrap();
+    assert!(matches!(
+        evaluator.eval(
+            Operation::Mux,
+            &[bit, pair.clone(), a.clone()],
+            &EvalOptions::default()
+        ),
+        Err(EvalError::Shape(_))
+    ));
+    let lt = EvalOptions {
+        select: Some(1),
+        ..EvalOptions::default()
+    };
+    assert!(matches!(
+        evaluator.eval(Operation::Comparator, &[pair, a.clone()], &lt),
+        Err(EvalError::Shape(_))
+    ));
+    let zero = EvalOptions {
+        imm: Some(0),
+        ..EvalOptions::default()
+    };
+    assert!(matches!(
+        evaluator.eval(Operation::ScalarDiv, &[a.clone()], &zero),
+        Err(EvalError::Circuit(PulpError::InvalidArgument { .. }))
+    ));
+
+    // Lookup widths are checked before any result is allocated
+    for width in [0, 65, i64::MAX] {
+        let table = EvalOptions {
+            imm: Some(width),
+            values: vec![0, 1],
+            ..EvalOptions::default()
+        };
+        let bit = EncryptedIntegers::encrypt(client_key, &info, 1, false, &[1]).unwrap();
+        assert!(matches!(
+            evaluator.eval(Operation::LookupTable, &[bit], &table),
+            Err(EvalError::Circuit(PulpError::UnsupportedWidth {
+                op: "lookup_table",
+                ..
+            }))
+        ));
+    }
+
+    // Inputs encrypted under another key are refused before any gate runs
+    let other = Evaluator::new(server_key, KeyInfo::new(ParameterSet::Default));
+    assert!(matches!(
+        other.eval(Operation::Not, &[a], &EvalOptions::default()),
+        Err(EvalError::Operand(CiphertextFileError::KeyMismatch { .. }))
+    ));
+    println!("[✓] PASS: {fn_name}\n");
+}
@@ -0,0 +1,367 @@
+//! # Description
+//! Encrypted fixed-point numbers built on `ProcessorCircuits`.
+//!
+//! A `FixedPoint` is a `total_bits`-wide two's complement integer (LSB first, like every
+//! circuit operand) whose low `frac_bits` bits are the fraction: the raw integer `r`
+//! stands for `r / 2^frac_bits`. `FixedFormat` converts between `f64` and raw integers on
+//! the client side.
+//!
+//! Operations evaluate on widened operands and clamp the result to the range of the format
+//! instead of wrapping around:
+//!
+//! | operation | rounding                               | overflow              |
+//! |-----------|----------------------------------------|-----------------------|
+//! | `add`, `sub` | exact                               | saturates             |
+//! | `mul`     | to nearest, ties towards +infinity     | saturates             |
+//! | `div`     | towards zero                           | saturates; `x / 0` saturates to the sign of `x` |
+//! | `sqrt`    | down                                   | cannot overflow; negative inputs give 0 |
+//!
+//! Both operands of a binary operation must have the same format.
+
+use crate::error::PulpError;
+use crate::processor_circuits::ProcessorCircuits;
+use crate::processor_gates::ProcessorGate;
+use std::fmt;
+use tfhe::boolean::prelude::*;
+
+#[cfg(test)]
+mod test_fixed_point;
+
+/// The layout of a fixed-point number: `total_bits` bits, of which the low `frac_bits`
+/// are the fraction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FixedFormat {
+    pub total_bits: usize,
+    pub frac_bits: usize,
+}
+
+impl FixedFormat {
+    /// Checks that `total_bits` is between 2 and 64 and that at least the sign bit is
+    /// left for the integer part.
+    pub fn new(total_bits: usize, frac_bits: usize) -> Result<Self, PulpError> {
+        if !(2..=64).contains(&total_bits) {
+            return Err(PulpError::UnsupportedWidth {
+                op: "fixed_point",
+                width: total_bits,
+            });
+        }
+        if frac_bits >= total_bits {
+            return Err(PulpError::invalid(
+                "fixed_point",
+                format!("{frac_bits} fractional bits do not fit in {total_bits} bits"),
+            ));
+        }
+        Ok(FixedFormat {
+            total_bits,
+            frac_bits,
+        })
+    }
+
+    /// The largest raw value.
+    pub fn max_raw(&self) -> i64 {
+        ((1u64 << (self.total_bits - 1)) - 1) as i64
+    }
+
+    /// The smallest raw value.
+    pub fn min_raw(&self) -> i64 {
+        -self.max_raw() - 1
+    }
+
+    /// The value of the raw integer 1.
+    pub fn resolution(&self) -> f64 {
+        1.0 / self.scale()
+    }
+
+    fn scale(&self) -> f64 {
+        2f64.powi(self.frac_bits as i32)
+    }
+
+    /// Converts `x` to the nearest raw value, saturating at the range of the format. NaN
+    /// encodes as 0.
+    pub fn encode(&self, x: f64) -> i64 {
+        if x.is_nan() {
+            return 0;
+        }
+        let raw: f64 = (x * self.scale()).round();
+        if raw >= self.max_raw() as f64 {
+            self.max_raw()
+        } else if raw <= self.min_raw() as f64 {
+            self.min_raw()
+        } else {
+            raw as i64
+        }
+    }
+
+    /// Converts a raw value back to `f64`.
+    pub fn decode(&self, raw: i64) -> f64 {
+        raw as f64 / self.scale()
+    }
+}
+
+impl fmt::Display for FixedFormat {
+    /// Q notation: integer bits (including the sign) and fractional bits, e.g. `Q4.4`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Q{}.{}",
+            self.total_bits - self.frac_bits,
+            self.frac_bits
+        )
+    }
+}
+
+/// An encrypted fixed-point number.
+#[derive(Clone, Debug)]
+pub struct FixedPoint {
+    pub format: FixedFormat,
+    /// `format.total_bits` bits, LSB first
+    pub bits: Vec<Ciphertext>,
+}
+
+impl FixedPoint {
+    pub fn from_bits(format: FixedFormat, bits: Vec<Ciphertext>) -> Result<Self, PulpError> {
+        PulpError::expect_width("fixed_point", "bits", format.total_bits, bits.len())?;
+        Ok(FixedPoint { format, bits })
+    }
+
+    /// Encrypts `x`, rounded to the nearest value of `format`.
+    pub fn encrypt(client_key: &ClientKey, format: FixedFormat, x: f64) -> Self {
+        let raw: i64 = format.encode(x);
+        let bits = (0..format.total_bits)
+            .map(|i| client_key.encrypt((raw >> i) & 1 != 0))
+            .collect();
+        FixedPoint { format, bits }
+    }
+
+    /// A plaintext constant that can be mixed with encrypted operands; gates with a
+    /// constant input are folded away.
+    pub fn trivial(format: FixedFormat, x: f64) -> Self {
+        let raw: i64 = format.encode(x);
+        let bits = (0..format.total_bits)
+            .map(|i| Ciphertext::Trivial((raw >> i) & 1 != 0))
+            .collect();
+        FixedPoint { format, bits }
+    }
+
+    /// Decrypts the raw two's complement integer.
+    pub fn decrypt_raw(&self, client_key: &ClientKey) -> i64 {
+        let mut raw: i64 = 0;
+        for (i, bit) in self.bits.iter().enumerate() {
+            if client_key.decrypt(bit) {
+                raw |= 1 << i;
+            }
+        }
+        let unused: u32 = 64 - self.format.total_bits as u32;
+        (raw << unused) >> unused
+    }
+
+    pub fn decrypt(&self, client_key: &ClientKey) -> f64 {
+        self.format.decode(self.decrypt_raw(client_key))
+    }
+
+    /// Saturating `self + other`.
+    pub fn add<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        other: &FixedPoint,
+    ) -> Result<FixedPoint, PulpError> {
+        self.check_format("fixed_add", other)?;
+        let width: usize = self.format.total_bits + 1;
+        let mut sum: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); width];
+        processor.adder(sk, &self.widen(width), &other.widen(width), &mut sum)?;
+        self.saturate(processor, sk, &sum)
+    }
+
+    /// Saturating `self - other`.
+    pub fn sub<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        other: &FixedPoint,
+    ) -> Result<FixedPoint, PulpError> {
+        self.check_format("fixed_sub", other)?;
+        let width: usize = self.format.total_bits + 1;
+        let mut diff: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); width];
+        processor.subtracter(sk, &self.widen(width), &other.widen(width), &mut diff)?;
+        self.saturate(processor, sk, &diff)
+    }
+
+    /// Saturating `self * other`, rescaled by `2^-frac_bits` with rounding to nearest.
+    pub fn mul<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        other: &FixedPoint,
+    ) -> Result<FixedPoint, PulpError> {
+        self.check_format("fixed_mul", other)?;
+        let frac: usize = self.format.frac_bits;
+        // The full product of two n-bit values fits in 2n bits, with room for the rounding
+        // constant since frac_bits < n
+        let width: usize = 2 * self.format.total_bits;
+        let mut product: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); width];
+        processor.multiplier(sk, &self.widen(width), &other.widen(width), &mut product)?;
+        if frac > 0 {
+            let mut rounded: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); width];
+            processor.scalar_add(sk, &product, 1 << (frac - 1), &mut rounded)?;
+            product = rounded;
+        }
+        // Dropping the low bits is the arithmetic right shift by frac_bits
+        self.saturate(processor, sk, &product[frac..])
+    }
+
+    /// Saturating `self / other`, rounded towards zero. Dividing by zero gives the largest
+    /// value of the sign of `self`.
+    pub fn div<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        other: &FixedPoint,
+    ) -> Result<FixedPoint, PulpError> {
+        self.check_format("fixed_div", other)?;
+        let frac: usize = self.format.frac_bits;
+        // The dividend is scaled up by 2^frac_bits; one more bit keeps its magnitude
+        // representable when `divider` negates it
+        let width: usize = self.format.total_bits + frac + 1;
+        let mut dividend: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); width];
+        processor.copy_to_from(&mut dividend[frac..], &self.widen(width - frac));
+        let mut quotient: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); width];
+        processor.divider(sk, &dividend, &other.widen(width), &mut quotient)?;
+        let quotient: FixedPoint = self.saturate(processor, sk, &quotient)?;
+
+        let mut nonzero: Ciphertext = other.bits[0].clone();
+        for bit in other.bits.iter().skip(1) {
+            nonzero = processor.e_or_bit(sk, &nonzero, bit);
+        }
+        let limit: Vec<Ciphertext> = self.limit(processor, sk, &self.bits[self.msb()]);
+        let mut bits: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); self.format.total_bits];
+        processor.e_mux(sk, &nonzero, &quotient.bits, &limit, &mut bits)?;
+        FixedPoint::from_bits(self.format, bits)
+    }
+
+    /// Square root, rounded down. Negative values give 0.
+    pub fn sqrt<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+    ) -> Result<FixedPoint, PulpError> {
+        PulpError::expect_width("fixed_sqrt", "a", self.format.total_bits, self.bits.len())?;
+        let total: usize = self.format.total_bits;
+        let frac: usize = self.format.frac_bits;
+        // sqrt(r / 2^f) * 2^f = sqrt(r * 2^f); the root of a (total + frac)-bit value with
+        // frac < total fits in total - 1 bits
+        let width: usize = total + frac;
+        let positive: Ciphertext = processor.e_not_bit(sk, &self.bits[self.msb()]);
+        let mut radicand: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); width];
+        for i in 0..total - 1 {
+            radicand[frac + i] = processor.e_and_bit(sk, &self.bits[i], &positive);
+        }
+        let mut root: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); width];
+        processor.sqrt(sk, &radicand, &mut root)?;
+        root.truncate(total);
+        FixedPoint::from_bits(self.format, root)
+    }
+
+    /// Compares `self` with `other` and returns an encrypted bit. `select` uses the codes
+    /// of `ProcessorCircuits::comparator`: 0 `==`, 1 `<`, 2 `>`, 3 `>=`, 4 `<=`, 5 `!=`.
+    pub fn compare<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        other: &FixedPoint,
+        select: u8,
+    ) -> Result<Ciphertext, PulpError> {
+        self.check_format("fixed_compare", other)?;
+        if select > 5 {
+            return Err(PulpError::invalid(
+                "fixed_compare",
+                format!("unknown comparison selector {select}"),
+            ));
+        }
+        // The widened difference cannot overflow, so its sign is `self < other`
+        let width: usize = self.format.total_bits + 1;
+        let mut diff: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); width];
+        processor.subtracter(sk, &self.widen(width), &other.widen(width), &mut diff)?;
+        let less: Ciphertext = diff[width - 1].clone();
+        if select == 1 || select == 3 {
+            return Ok(if select == 1 {
+                less
+            } else {
+                processor.e_not_bit(sk, &less)
+            });
+        }
+
+        let mut nonzero: Ciphertext = diff[0].clone();
+        for bit in diff.iter().skip(1) {
+            nonzero = processor.e_or_bit(sk, &nonzero, bit);
+        }
+        Ok(match select {
+            0 => processor.e_not_bit(sk, &nonzero),
+            2 => {
+                let not_less: Ciphertext = processor.e_not_bit(sk, &less);
+                processor.e_and_bit(sk, &not_less, &nonzero)
+            }
+            4 => {
+                let equal: Ciphertext = processor.e_not_bit(sk, &nonzero);
+                processor.e_or_bit(sk, &less, &equal)
+            }
+            _ => nonzero,
+        })
+    }
+
+    fn check_format(&self, op: &'static str, other: &FixedPoint) -> Result<(), PulpError> {
+        if self.format != other.format {
+            return Err(PulpError::invalid(
+                op,
+                format!("operands are {} and {}", self.format, other.format),
+            ));
+        }
+        PulpError::expect_width(op, "a", self.format.total_bits, self.bits.len())?;
+        PulpError::expect_width(op, "b", other.format.total_bits, other.bits.len())
+    }
+
+    fn msb(&self) -> usize {
+        self.format.total_bits - 1
+    }
+
+    /// Sign-extends the bits to `width`.
+    fn widen(&self, width: usize) -> Vec<Ciphertext> {
+        let mut bits: Vec<Ciphertext> = self.bits.clone();
+        bits.resize(width, self.bits[self.msb()].clone());
+        bits
+    }
+
+    /// The largest value when `sign` is 0, the smallest when it is 1.
+    fn limit<P: ProcessorGate>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        sign: &Ciphertext,
+    ) -> Vec<Ciphertext> {
+        let not_sign: Ciphertext = processor.e_not_bit(sk, sign);
+        let mut bits: Vec<Ciphertext> = vec![not_sign; self.format.total_bits];
+        bits[self.msb()] = sign.clone();
+        bits
+    }
+
+    /// Narrows a wide two's complement value to the format, clamping it to the range.
+    fn saturate<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        wide: &[Ciphertext],
+    ) -> Result<FixedPoint, PulpError> {
+        let total: usize = self.format.total_bits;
+        let sign: &Ciphertext = &wide[wide.len() - 1];
+        // The value fits when every bit from the target sign bit up equals the wide sign
+        let mut fits: Ciphertext = Ciphertext::Trivial(true);
+        for bit in &wide[total - 1..wide.len() - 1] {
+            let same: Ciphertext = processor.e_xnor_bit(sk, bit, sign);
+            fits = processor.e_and_bit(sk, &fits, &same);
+        }
+        let limit: Vec<Ciphertext> = self.limit(processor, sk, sign);
+        let mut bits: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); total];
+        processor.e_mux(sk, &fits, &wide[..total], &limit, &mut bits)?;
+        FixedPoint::from_bits(self.format, bits)
+    }
+}
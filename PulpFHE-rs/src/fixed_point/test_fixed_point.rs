@@ -0,0 +1,168 @@
+#![allow(warnings)]
+use super::*; // Imports items from the parent module
+use crate::key_manager::KeyManager;
+use crate::processor_boolean::ProcessorBoolean;
+use serial_test::serial;
+
+fn q4_4() -> FixedFormat {
+    FixedFormat::new(8, 4).unwrap()
+}
+
+#[test]
+fn test_fixed_format() {
+    let fn_name = "fixed_format";
+    println!("[*] TEST: {fn_name}");
+    let q = q4_4();
+    assert_eq!(q.to_string(), "Q4.4");
+    assert_eq!((q.min_raw(), q.max_raw()), (-128, 127));
+    assert_eq!(q.resolution(), 0.0625);
+
+    // Rounds to nearest and saturates at the range
+    assert_eq!(q.encode(1.5), 24);
+    assert_eq!(q.encode(-0.03), 0);
+    assert_eq!(q.encode(0.04), 1);
+    assert_eq!(q.encode(100.0), 127);
+    assert_eq!(q.encode(-100.0), -128);
+    assert_eq!(q.encode(f64::NAN), 0);
+    assert_eq!(q.decode(-40), -2.5);
+    assert_eq!(FixedFormat::new(64, 32).unwrap().max_raw(), i64::MAX);
+
+    assert_eq!(
+        FixedFormat::new(1, 0),
+        Err(PulpError::UnsupportedWidth {
+            op: "fixed_point",
+            width: 1
+        })
+    );
+    assert!(matches!(
+        FixedFormat::new(8, 8),
+        Err(PulpError::InvalidArgument { .. })
+    ));
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_fixed_add_sub() {
+    let fn_name = "fixed_add_sub";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let server = ProcessorBoolean;
+    let q = q4_4();
+
+    let cases: [(f64, f64, f64, f64); 4] = [
+        // a, b, a + b, a - b
+        (1.5, 2.25, 3.75, -0.75),
+        (-3.125, 1.0, -2.125, -4.125),
+        (7.5, -1.0, 6.5, 7.9375),
+        (-6.0, -5.0, -8.0, -1.0),
+    ];
+    for (a, b, sum, diff) in cases {
+        let ct_a = FixedPoint::encrypt(client_key, q, a);
+        let ct_b = FixedPoint::encrypt(client_key, q, b);
+        let ct_sum = ct_a.add(&server, server_key, &ct_b).unwrap();
+        let ct_diff = ct_a.sub(&server, server_key, &ct_b).unwrap();
+        println!("\t {a} + {b} = {}", ct_sum.decrypt(client_key));
+        println!("\t {a} - {b} = {}", ct_diff.decrypt(client_key));
+        assert_eq!(ct_sum.decrypt(client_key), sum);
+        assert_eq!(ct_diff.decrypt(client_key), diff);
+    }
+
+    // Plaintext constants mix with encrypted operands
+    let ct_a = FixedPoint::encrypt(client_key, q, 7.0);
+    let one = FixedPoint::trivial(q, 1.0);
+    let ct_sum = ct_a.add(&server, server_key, &one).unwrap();
+    assert_eq!(ct_sum.decrypt(client_key), 7.9375);
+
+    let other = FixedPoint::trivial(FixedFormat::new(8, 3).unwrap(), 1.0);
+    assert!(matches!(
+        ct_a.add(&server, server_key, &other),
+        Err(PulpError::InvalidArgument {
+            op: "fixed_add",
+            ..
+        })
+    ));
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_fixed_mul_div() {
+    let fn_name = "fixed_mul_div";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let server = ProcessorBoolean;
+    let q = q4_4();
+
+    let products: [(f64, f64, f64); 4] = [
+        (1.5, 2.25, 3.375),
+        (2.5, -1.25, -3.125),
+        // 0.03125 is a tie and rounds up
+        (0.0625, 0.5, 0.0625),
+        (4.0, -4.0, -8.0),
+    ];
+    for (a, b, expected) in products {
+        let ct_a = FixedPoint::encrypt(client_key, q, a);
+        let ct_b = FixedPoint::encrypt(client_key, q, b);
+        let ct_res = ct_a.mul(&server, server_key, &ct_b).unwrap();
+        println!("\t {a} * {b} = {}", ct_res.decrypt(client_key));
+        assert_eq!(ct_res.decrypt(client_key), expected);
+    }
+
+    let quotients: [(f64, f64, f64); 5] = [
+        (3.0, 1.5, 2.0),
+        // 1 / 3 = 0.333.. truncates to 5/16
+        (1.0, 3.0, 0.3125),
+        (-2.5, 0.5, -5.0),
+        (6.0, 0.25, 7.9375),
+        (-1.0, 0.0, -8.0),
+    ];
+    for (a, b, expected) in quotients {
+        let ct_a = FixedPoint::encrypt(client_key, q, a);
+        let ct_b = FixedPoint::encrypt(client_key, q, b);
+        let ct_res = ct_a.div(&server, server_key, &ct_b).unwrap();
+        println!("\t {a} / {b} = {}", ct_res.decrypt(client_key));
+        assert_eq!(ct_res.decrypt(client_key), expected);
+    }
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_fixed_sqrt_compare() {
+    let fn_name = "fixed_sqrt_compare";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let server = ProcessorBoolean;
+    let q = q4_4();
+
+    // sqrt(2) = 1.414.. rounds down to 1.375
+    for (a, expected) in [(2.25, 1.5), (2.0, 1.375), (7.9375, 2.8125), (-1.0, 0.0)] {
+        let ct_a = FixedPoint::encrypt(client_key, q, a);
+        let ct_res = ct_a.sqrt(&server, server_key).unwrap();
+        println!("\t sqrt({a}) = {}", ct_res.decrypt(client_key));
+        assert_eq!(ct_res.decrypt(client_key), expected);
+    }
+
+    for (a, b) in [(1.5, 2.25), (-0.5, -0.5), (3.0, -7.0)] {
+        let ct_a = FixedPoint::encrypt(client_key, q, a);
+        let ct_b = FixedPoint::encrypt(client_key, q, b);
+        let expected = [a == b, a < b, a > b, a >= b, a <= b, a != b];
+        for (select, expected) in expected.into_iter().enumerate() {
+            let bit = ct_a
+                .compare(&server, server_key, &ct_b, select as u8)
+                .unwrap();
+            assert_eq!(
+                client_key.decrypt(&bit),
+                expected,
+                "{a} vs {b}, select {select}"
+            );
+        }
+    }
+    let ct_a = FixedPoint::encrypt(client_key, q, 1.0);
+    assert!(matches!(
+        ct_a.compare(&server, server_key, &ct_a, 6),
+        Err(PulpError::InvalidArgument { .. })
+    ));
+    println!("[✓] PASS: {fn_name}\n");
+}
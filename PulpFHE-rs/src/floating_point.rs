@@ -0,0 +1,738 @@
+//! # Description
+//! Encrypted floating-point numbers in the bfloat16 and IEEE-754 binary16 layouts, built on
+//! `ProcessorGate` and `ProcessorCircuits`.
+//!
+//! A `FloatingPoint` holds `1 + exp_bits + mant_bits` encrypted bits, LSB first like every
+//! circuit operand: the fraction, then the biased exponent, then the sign. Subnormals,
+//! signed zeros and infinities follow IEEE-754. Every operation rounds to nearest, ties to
+//! even, so results are bit-exact with a correctly rounded plaintext implementation such as
+//! the `half` crate. A NaN result is always the canonical quiet NaN (positive sign, top
+//! fraction bit set); NaN inputs are recognised whatever their payload.
+//!
+//! `add` aligns the smaller operand with an encrypted barrel shift that keeps guard, round
+//! and sticky bits, and renormalises with an encrypted leading-zero count. `mul` multiplies
+//! the significands with `multiplier` and renormalises the same way, shifting right into
+//! the subnormal range when the exponent underflows.
+
+use crate::error::PulpError;
+use crate::processor_circuits::ProcessorCircuits;
+use crate::processor_gates::ProcessorGate;
+use tfhe::boolean::prelude::*;
+
+#[cfg(test)]
+mod test_floating_point;
+
+/// The layout of a floating-point number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FloatFormat {
+    pub exp_bits: usize,
+    pub mant_bits: usize,
+}
+
+impl FloatFormat {
+    /// Brain floating point: the exponent range of `f32` with 8 significant bits
+    pub const BFLOAT16: FloatFormat = FloatFormat {
+        exp_bits: 8,
+        mant_bits: 7,
+    };
+    /// IEEE-754 binary16 (half precision)
+    pub const BINARY16: FloatFormat = FloatFormat {
+        exp_bits: 5,
+        mant_bits: 10,
+    };
+
+    pub fn width(&self) -> usize {
+        1 + self.exp_bits + self.mant_bits
+    }
+
+    fn bias(&self) -> i64 {
+        (1 << (self.exp_bits - 1)) - 1
+    }
+
+    fn exp_mask(&self) -> u64 {
+        (1 << self.exp_bits) - 1
+    }
+
+    /// The bits of the canonical quiet NaN.
+    pub fn nan(&self) -> u64 {
+        (self.exp_mask() << self.mant_bits) | (1 << (self.mant_bits - 1))
+    }
+
+    /// The bits of the infinity with the given sign.
+    pub fn infinity(&self, negative: bool) -> u64 {
+        ((negative as u64) << (self.exp_bits + self.mant_bits))
+            | (self.exp_mask() << self.mant_bits)
+    }
+
+    /// Rounds `x` to the nearest value of the format, ties to even, and returns its bits.
+    /// Values beyond the largest finite value round to infinity, NaN gives the canonical NaN.
+    pub fn encode(&self, x: f64) -> u64 {
+        if x.is_nan() {
+            return self.nan();
+        }
+        let negative: bool = x.is_sign_negative();
+        let sign: u64 = (negative as u64) << (self.exp_bits + self.mant_bits);
+        if x.is_infinite() {
+            return self.infinity(negative);
+        }
+        if x == 0.0 {
+            return sign;
+        }
+
+        // |x| = mant * 2^exp exactly
+        let raw: u64 = x.abs().to_bits();
+        let (mant, exp): (u64, i64) = if raw >> 52 == 0 {
+            (raw, -1074)
+        } else {
+            (
+                (raw & ((1 << 52) - 1)) | (1 << 52),
+                (raw >> 52) as i64 - 1075,
+            )
+        };
+        let m: i64 = self.mant_bits as i64;
+        let top: i64 = exp + 63 - mant.leading_zeros() as i64;
+        // Scale of the last significant bit: one step finer than the leading bit would allow
+        // in the subnormal range
+        let quantum: i64 = top.max(1 - self.bias()) - m;
+        let shift: i64 = quantum - exp;
+        // n is the significand at that scale, below 2^(m + 1)
+        let mut n: u64 = if shift <= 0 {
+            mant << -shift
+        } else if shift >= 64 {
+            0
+        } else {
+            // Round to nearest, ties to even
+            let kept: u64 = mant >> shift;
+            let rest: u64 = mant & ((1 << shift) - 1);
+            let half: u64 = 1 << (shift - 1);
+            kept + (rest > half || (rest == half && kept & 1 == 1)) as u64
+        };
+
+        let mut field: i64 = quantum + m + self.bias();
+        if n >> (m + 1) != 0 {
+            // Rounding carried into a new leading bit
+            n >>= 1;
+            field += 1;
+        }
+        if n >> m == 0 {
+            // Subnormal (or rounded to zero); a carry into bit m is handled above
+            return sign | n;
+        }
+        if field >= self.exp_mask() as i64 {
+            return self.infinity(negative);
+        }
+        sign | ((field as u64) << m) | (n & ((1 << m) - 1))
+    }
+
+    /// Converts bits of the format to `f64`, which represents every value exactly.
+    pub fn decode(&self, bits: u64) -> f64 {
+        let m: usize = self.mant_bits;
+        let frac: u64 = bits & ((1 << m) - 1);
+        let field: u64 = (bits >> m) & self.exp_mask();
+        let sign: f64 = if (bits >> (m + self.exp_bits)) & 1 == 1 {
+            -1.0
+        } else {
+            1.0
+        };
+        if field == self.exp_mask() {
+            return if frac == 0 {
+                sign * f64::INFINITY
+            } else {
+                f64::NAN
+            };
+        }
+        let (significand, exp): (u64, i64) = if field == 0 {
+            (frac, 1 - self.bias())
+        } else {
+            (frac | (1 << m), field as i64 - self.bias())
+        };
+        sign * significand as f64 * 2f64.powi((exp - m as i64) as i32)
+    }
+}
+
+/// An encrypted floating-point number.
+#[derive(Clone, Debug)]
+pub struct FloatingPoint {
+    pub format: FloatFormat,
+    /// `format.width()` bits, LSB first: fraction, exponent, sign
+    pub bits: Vec<Ciphertext>,
+}
+
+/// The fields of an operand, with the exponent of subnormals set to 1 and the hidden bit
+/// made explicit.
+struct Unpacked {
+    sign: Ciphertext,
+    /// `exp_bits` bits
+    exp: Vec<Ciphertext>,
+    /// `mant_bits + 1` bits
+    sig: Vec<Ciphertext>,
+    zero: Ciphertext,
+    inf: Ciphertext,
+    nan: Ciphertext,
+}
+
+impl FloatingPoint {
+    pub fn from_bits(format: FloatFormat, bits: Vec<Ciphertext>) -> Result<Self, PulpError> {
+        PulpError::expect_width("floating_point", "bits", format.width(), bits.len())?;
+        Ok(FloatingPoint { format, bits })
+    }
+
+    /// Encrypts the bits of a value of `format`, e.g. from `FloatFormat::encode`.
+    pub fn encrypt_bits(client_key: &ClientKey, format: FloatFormat, bits: u64) -> Self {
+        let bits = (0..format.width())
+            .map(|i| client_key.encrypt((bits >> i) & 1 == 1))
+            .collect();
+        FloatingPoint { format, bits }
+    }
+
+    /// Encrypts `x`, rounded to the nearest value of `format`.
+    pub fn encrypt(client_key: &ClientKey, format: FloatFormat, x: f64) -> Self {
+        Self::encrypt_bits(client_key, format, format.encode(x))
+    }
+
+    /// A plaintext constant that can be mixed with encrypted operands.
+    pub fn trivial(format: FloatFormat, x: f64) -> Self {
+        FloatingPoint {
+            format,
+            bits: constant(format.encode(x), format.width()),
+        }
+    }
+
+    pub fn decrypt_bits(&self, client_key: &ClientKey) -> u64 {
+        self.bits
+            .iter()
+            .enumerate()
+            .map(|(i, bit)| (client_key.decrypt(bit) as u64) << i)
+            .sum()
+    }
+
+    pub fn decrypt(&self, client_key: &ClientKey) -> f64 {
+        self.format.decode(self.decrypt_bits(client_key))
+    }
+
+    /// `self + other`, correctly rounded.
+    pub fn add<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        other: &FloatingPoint,
+    ) -> Result<FloatingPoint, PulpError> {
+        self.check_format("float_add", other)?;
+        let c = Circuit { p: processor, sk };
+        let (e, m): (usize, usize) = (self.format.exp_bits, self.format.mant_bits);
+
+        // Order the operands by magnitude, so that the result takes the sign of x and the
+        // aligned difference x - y cannot go negative
+        let swap: Ciphertext = c.less(&self.bits[..e + m], &other.bits[..e + m])?;
+        let big: Vec<Ciphertext> = c.mux(&swap, &other.bits, &self.bits)?;
+        let small: Vec<Ciphertext> = c.mux(&swap, &self.bits, &other.bits)?;
+        let x: Unpacked = self.format.unpack(&c, &big);
+        let y: Unpacked = self.format.unpack(&c, &small);
+
+        // Significands with guard, round and sticky bits below them
+        let width: usize = m + 4;
+        let xs: Vec<Ciphertext> = shift_up(&x.sig, 3);
+        let distance: Vec<Ciphertext> = c.sub(&x.exp, &y.exp)?;
+        let ys: Vec<Ciphertext> = c.shr_sticky(&shift_up(&y.sig, 3), &distance)?;
+
+        let subtract: Ciphertext = c.p.e_xor_bit(sk, &x.sign, &y.sign);
+        let sum: Vec<Ciphertext> = c.add(&zext(&xs, width + 1), &zext(&ys, width + 1))?;
+        let diff: Vec<Ciphertext> = c.sub(&zext(&xs, width + 1), &zext(&ys, width + 1))?;
+        let sum: Vec<Ciphertext> = c.mux(&subtract, &diff, &sum)?;
+
+        // A carry out shifts right by one; otherwise shift the leading one up to the hidden
+        // bit, but not below the smallest exponent
+        let carry: Ciphertext = sum[width].clone();
+        let mut halved: Vec<Ciphertext> = sum[1..].to_vec();
+        halved[0] = c.p.e_or_bit(sk, &sum[0], &sum[1]);
+        let exp_up: Vec<Ciphertext> = c.add_const(&zext(&x.exp, e + 1), 1)?;
+
+        let zeros: Vec<Ciphertext> = c.leading_zeros(&sum[..width])?;
+        let k: usize = zeros.len().max(e) + 1;
+        let room: Vec<Ciphertext> = c.add_const(&zext(&x.exp, k), -1)?;
+        let too_far: Ciphertext = c.less(&room, &zext(&zeros, k))?;
+        let amount: Vec<Ciphertext> = c.mux(&too_far, &room, &zext(&zeros, k))?;
+        let shifted: Vec<Ciphertext> = c.shl(&sum[..width], &amount[..zeros.len()])?;
+        let exp_down: Vec<Ciphertext> = c.sub(&zext(&x.exp, k), &amount)?;
+
+        let norm: Vec<Ciphertext> = c.mux(&carry, &halved, &shifted)?;
+        let exp: Vec<Ciphertext> = c.mux(&carry, &exp_up[..e], &exp_down[..e])?;
+        let overflow: Ciphertext = c.p.e_and_bit(sk, &carry, &c.all(&exp_up[..e]));
+        let sticky: Ciphertext = c.p.e_or_bit(sk, &norm[0], &norm[1]);
+        let rounded: Vec<Ciphertext> = self.format.round(&c, &norm[2..], &sticky, &exp);
+
+        // An exact zero is +0, unless both operands are -0
+        let is_zero: Ciphertext = c.p.e_not_bit(sk, &c.any(&sum));
+        let both_negative: Ciphertext = c.p.e_and_bit(sk, &x.sign, &y.sign);
+        let sign: Ciphertext = c.p.e_mux_bit(sk, &is_zero, &both_negative, &x.sign);
+
+        let inf_minus_inf: Ciphertext = c.and3(&x.inf, &y.inf, &subtract);
+        let nan: Ciphertext = c.or3(&x.nan, &y.nan, &inf_minus_inf);
+        let result: Vec<Ciphertext> = self.format.finish(&c, &sign, &rounded, &overflow)?;
+        let result: Vec<Ciphertext> = c.mux(&x.inf, &big, &result)?;
+        let result: Vec<Ciphertext> =
+            c.mux(&nan, &constant(self.format.nan(), e + m + 1), &result)?;
+        FloatingPoint::from_bits(self.format, result)
+    }
+
+    /// `self - other`, correctly rounded.
+    pub fn sub<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        other: &FloatingPoint,
+    ) -> Result<FloatingPoint, PulpError> {
+        self.check_format("float_sub", other)?;
+        let mut negated: FloatingPoint = other.clone();
+        let msb: usize = self.format.width() - 1;
+        negated.bits[msb] = processor.e_not_bit(sk, &other.bits[msb]);
+        self.add(processor, sk, &negated)
+    }
+
+    /// `self * other`, correctly rounded.
+    pub fn mul<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        other: &FloatingPoint,
+    ) -> Result<FloatingPoint, PulpError> {
+        self.check_format("float_mul", other)?;
+        let c = Circuit { p: processor, sk };
+        let (e, m): (usize, usize) = (self.format.exp_bits, self.format.mant_bits);
+        let x: Unpacked = self.format.unpack(&c, &self.bits);
+        let y: Unpacked = self.format.unpack(&c, &other.bits);
+        let sign: Ciphertext = c.p.e_xor_bit(sk, &x.sign, &y.sign);
+
+        // The exact product of the significands, normalised so that its leading one is the
+        // top bit
+        let width: usize = 2 * m + 2;
+        let mut product: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); width];
+        c.p.multiplier(sk, &zext(&x.sig, width), &zext(&y.sig, width), &mut product)?;
+        let zeros: Vec<Ciphertext> = c.leading_zeros(&product)?;
+        let product: Vec<Ciphertext> = c.shl(&product, &zeros)?;
+
+        // Biased exponent of the leading one: ex + ey - bias + 1 - zeros, in a signed width
+        // that holds both the underflow and the overflow range
+        let k: usize = e + 3;
+        let exp: Vec<Ciphertext> = c.add(&zext(&x.exp, k), &zext(&y.exp, k))?;
+        let exp: Vec<Ciphertext> = c.add_const(&exp, 1 - self.format.bias())?;
+        let exp: Vec<Ciphertext> = c.sub(&exp, &zext(&zeros, k))?;
+
+        // Below the smallest exponent, shift right into the subnormal range
+        let below: Vec<Ciphertext> = c.add_const(&exp, -1)?;
+        let subnormal: Ciphertext = below[k - 1].clone();
+        let distance: Vec<Ciphertext> = c.sub(&constant(0, k), &below)?;
+        let denormal: Vec<Ciphertext> = c.shr_sticky(&product, &distance)?;
+        let product: Vec<Ciphertext> = c.mux(&subnormal, &denormal, &product)?;
+        let exp: Vec<Ciphertext> = c.mux(&subnormal, &constant(0, k), &exp)?;
+        let past_max: Vec<Ciphertext> = c.add_const(&exp, -(self.format.exp_mask() as i64))?;
+        let overflow: Ciphertext = c.p.e_not_bit(sk, &past_max[k - 1]);
+
+        // Keep the significand and guard bit, fold the rest into sticky
+        let sticky: Ciphertext = c.any(&product[..m]);
+        let rounded: Vec<Ciphertext> = self.format.round(&c, &product[m..], &sticky, &exp[..e]);
+
+        let zero: Ciphertext = c.p.e_or_bit(sk, &x.zero, &y.zero);
+        let inf: Ciphertext = c.p.e_or_bit(sk, &x.inf, &y.inf);
+        let inf_times_zero: Ciphertext = c.p.e_and_bit(sk, &inf, &zero);
+        let nan: Ciphertext = c.or3(&x.nan, &y.nan, &inf_times_zero);
+        let result: Vec<Ciphertext> = self.format.finish(&c, &sign, &rounded, &overflow)?;
+        let mut signed_zero: Vec<Ciphertext> = constant(0, e + m + 1);
+        signed_zero[e + m] = sign.clone();
+        let result: Vec<Ciphertext> = c.mux(&zero, &signed_zero, &result)?;
+        let result: Vec<Ciphertext> = c.mux(&inf, &self.format.infinity_bits(&sign), &result)?;
+        let result: Vec<Ciphertext> =
+            c.mux(&nan, &constant(self.format.nan(), e + m + 1), &result)?;
+        FloatingPoint::from_bits(self.format, result)
+    }
+
+    /// Compares `self` with `other` and returns an encrypted bit. `select` uses the codes of
+    /// `ProcessorCircuits::comparator`: 0 `==`, 1 `<`, 2 `>`, 3 `>=`, 4 `<=`, 5 `!=`. As in
+    /// IEEE-754, `-0 == +0` and every comparison with NaN is false except `!=`.
+    pub fn compare<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        other: &FloatingPoint,
+        select: u8,
+    ) -> Result<Ciphertext, PulpError> {
+        self.check_format("float_compare", other)?;
+        if select > 5 {
+            return Err(PulpError::invalid(
+                "float_compare",
+                format!("unknown comparison selector {select}"),
+            ));
+        }
+        let c = Circuit { p: processor, sk };
+        let n: usize = self.format.exp_bits + self.format.mant_bits;
+        let x: Unpacked = self.format.unpack(&c, &self.bits);
+        let y: Unpacked = self.format.unpack(&c, &other.bits);
+        let ordered: Ciphertext = c.p.e_nor_bit(sk, &x.nan, &y.nan);
+        let both_zero: Ciphertext = c.p.e_and_bit(sk, &x.zero, &y.zero);
+
+        let mut same_bits: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); n + 1];
+        c.p.e_xnor(sk, &self.bits, &other.bits, &mut same_bits)?;
+        let identical: Ciphertext = c.all(&same_bits);
+        let equal: Ciphertext = c.p.e_or_bit(sk, &identical, &both_zero);
+        let equal: Ciphertext = c.p.e_and_bit(sk, &equal, &ordered);
+
+        // Same signs compare by magnitude, reversed for negative numbers
+        let smaller: Ciphertext = c.less(&self.bits[..n], &other.bits[..n])?;
+        let larger: Ciphertext = c.less(&other.bits[..n], &self.bits[..n])?;
+        let by_magnitude: Ciphertext = c.p.e_mux_bit(sk, &x.sign, &larger, &smaller);
+        let not_both_zero: Ciphertext = c.p.e_not_bit(sk, &both_zero);
+        let by_sign: Ciphertext = c.p.e_and_bit(sk, &x.sign, &not_both_zero);
+        let signs_differ: Ciphertext = c.p.e_xor_bit(sk, &x.sign, &y.sign);
+        let less: Ciphertext = c.p.e_mux_bit(sk, &signs_differ, &by_sign, &by_magnitude);
+        let less: Ciphertext = c.p.e_and_bit(sk, &less, &ordered);
+        let not_less: Ciphertext = c.p.e_not_bit(sk, &less);
+        let not_equal: Ciphertext = c.p.e_not_bit(sk, &equal);
+        let greater: Ciphertext = c.and3(&ordered, &not_less, &not_equal);
+
+        Ok(match select {
+            0 => equal,
+            1 => less,
+            2 => greater,
+            3 => c.p.e_or_bit(sk, &greater, &equal),
+            4 => c.p.e_or_bit(sk, &less, &equal),
+            _ => not_equal,
+        })
+    }
+
+    /// Converts a two's complement encrypted integer of at least 2 bits, correctly rounded.
+    /// Integers beyond the largest finite value become infinity.
+    pub fn from_integer<P: ProcessorGate + ProcessorCircuits>(
+        processor: &P,
+        sk: &ServerKey,
+        format: FloatFormat,
+        a: &[Ciphertext],
+    ) -> Result<FloatingPoint, PulpError> {
+        if a.len() < 2 {
+            return Err(PulpError::UnsupportedWidth {
+                op: "float_from_integer",
+                width: a.len(),
+            });
+        }
+        let c = Circuit { p: processor, sk };
+        let (e, m, w): (usize, usize, usize) = (format.exp_bits, format.mant_bits, a.len());
+
+        // |a| as a w-bit unsigned integer, normalised so that its leading one is the top bit
+        let sign: Ciphertext = a[w - 1].clone();
+        let mut flipped: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); w];
+        for i in 0..w {
+            flipped[i] = c.p.e_xor_bit(sk, &a[i], &sign);
+        }
+        let magnitude: Vec<Ciphertext> = c.increment(&flipped, &sign);
+        let zeros: Vec<Ciphertext> = c.leading_zeros(&magnitude)?;
+        let norm: Vec<Ciphertext> = c.shl(&magnitude, &zeros)?;
+        // Pad short integers so that there is always a guard bit
+        let pad: usize = (m + 2).saturating_sub(w);
+        let norm: Vec<Ciphertext> = shift_up(&norm, pad);
+
+        // The leading one is worth 2^(w - 1 - zeros)
+        let k: usize = e.max(zeros.len()) + 2;
+        let exp: Vec<Ciphertext> = c.sub(
+            &constant(format.bias() as u64 + w as u64 - 1, k),
+            &zext(&zeros, k),
+        )?;
+        let past_max: Vec<Ciphertext> = c.add_const(&exp, -(format.exp_mask() as i64))?;
+        let overflow: Ciphertext = c.p.e_not_bit(sk, &past_max[k - 1]);
+
+        let low: usize = norm.len() - 2 - m;
+        let sticky: Ciphertext = c.any(&norm[..low]);
+        let rounded: Vec<Ciphertext> = format.round(&c, &norm[low..], &sticky, &exp[..e]);
+        let result: Vec<Ciphertext> = format.finish(&c, &sign, &rounded, &overflow)?;
+        let zero: Ciphertext = c.p.e_not_bit(sk, &c.any(&magnitude));
+        let result: Vec<Ciphertext> = c.mux(&zero, &constant(0, e + m + 1), &result)?;
+        FloatingPoint::from_bits(format, result)
+    }
+
+    /// Converts to a `width`-bit two's complement integer, rounding to nearest with ties to
+    /// even. Values out of range and infinities saturate; NaN gives 0.
+    pub fn to_integer<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        width: usize,
+    ) -> Result<Vec<Ciphertext>, PulpError> {
+        PulpError::expect_width(
+            "float_to_integer",
+            "a",
+            self.format.width(),
+            self.bits.len(),
+        )?;
+        if !(2..=64).contains(&width) {
+            return Err(PulpError::UnsupportedWidth {
+                op: "float_to_integer",
+                width,
+            });
+        }
+        let c = Circuit { p: processor, sk };
+        let (m, w): (usize, usize) = (self.format.mant_bits, width);
+        let x: Unpacked = self.format.unpack(&c, &self.bits);
+
+        // value * 2^(m + 2) = sig << (exp - bias + 2): the low m + 2 bits are the fraction
+        let frac: usize = m + 2;
+        let k: usize = self
+            .format
+            .exp_bits
+            .max(usize::BITS as usize - w.leading_zeros() as usize)
+            + 2;
+        let shift: Vec<Ciphertext> = c.add_const(&zext(&x.exp, k), 2 - self.format.bias())?;
+        let tiny: Ciphertext = shift[k - 1].clone();
+        // |value| >= 2^(w - 1) does not fit, whatever the rounding
+        let past_max: Vec<Ciphertext> = c.add_const(&shift, -(w as i64 + 1))?;
+        let overflow: Ciphertext = c.p.e_not_bit(sk, &past_max[k - 1]);
+        let overflow: Ciphertext = c.p.e_or_bit(sk, &overflow, &x.inf);
+
+        let stages: usize = usize::BITS as usize - w.leading_zeros() as usize;
+        let scaled: Vec<Ciphertext> = c.shl(&zext(&x.sig, frac + w), &shift[..stages])?;
+        let guard: Ciphertext = scaled[frac - 1].clone();
+        let sticky: Ciphertext = c.any(&scaled[..frac - 1]);
+        let lsb_or_sticky: Ciphertext = c.p.e_or_bit(sk, &scaled[frac], &sticky);
+        let up: Ciphertext = c.p.e_and_bit(sk, &guard, &lsb_or_sticky);
+        let magnitude: Vec<Ciphertext> = c.increment(&scaled[frac..], &up);
+
+        // Rounding up may still reach 2^(w - 1), which only fits when negative
+        let mut negated: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); w];
+        for i in 0..w {
+            negated[i] = c.p.e_not_bit(sk, &magnitude[i]);
+        }
+        let negated: Vec<Ciphertext> = c.add_const(&negated, 1)?;
+        let value: Vec<Ciphertext> = c.mux(&x.sign, &negated, &magnitude)?;
+        let positive: Ciphertext = c.p.e_not_bit(sk, &x.sign);
+        let too_big: Ciphertext = c.p.e_and_bit(sk, &positive, &magnitude[w - 1]);
+        let saturate: Ciphertext = c.p.e_or_bit(sk, &overflow, &too_big);
+
+        let mut limit: Vec<Ciphertext> = vec![positive; w];
+        limit[w - 1] = x.sign.clone();
+        let result: Vec<Ciphertext> = c.mux(&tiny, &constant(0, w), &value)?;
+        let result: Vec<Ciphertext> = c.mux(&saturate, &limit, &result)?;
+        c.mux(&x.nan, &constant(0, w), &result)
+    }
+
+    fn check_format(&self, op: &'static str, other: &FloatingPoint) -> Result<(), PulpError> {
+        if self.format != other.format {
+            return Err(PulpError::invalid(
+                op,
+                format!("operands are {:?} and {:?}", self.format, other.format),
+            ));
+        }
+        PulpError::expect_width(op, "a", self.format.width(), self.bits.len())?;
+        PulpError::expect_width(op, "b", other.format.width(), other.bits.len())
+    }
+}
+
+impl FloatFormat {
+    fn unpack<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        c: &Circuit<P>,
+        bits: &[Ciphertext],
+    ) -> Unpacked {
+        let (e, m): (usize, usize) = (self.exp_bits, self.mant_bits);
+        let field: &[Ciphertext] = &bits[m..m + e];
+        let normal: Ciphertext = c.any(field);
+        let saturated: Ciphertext = c.all(field);
+        let fraction: Ciphertext = c.any(&bits[..m]);
+
+        let mut exp: Vec<Ciphertext> = field.to_vec();
+        exp[0] = c.p.e_or_bit(c.sk, &field[0], &c.p.e_not_bit(c.sk, &normal));
+        let mut sig: Vec<Ciphertext> = bits[..m].to_vec();
+        sig.push(normal.clone());
+        let no_fraction: Ciphertext = c.p.e_not_bit(c.sk, &fraction);
+        Unpacked {
+            sign: bits[m + e].clone(),
+            exp,
+            sig,
+            zero: c.p.e_nor_bit(c.sk, &normal, &fraction),
+            inf: c.p.e_and_bit(c.sk, &saturated, &no_fraction),
+            nan: c.p.e_and_bit(c.sk, &saturated, &fraction),
+        }
+    }
+
+    /// Rounds to nearest, ties to even. `sig` holds the guard bit followed by the
+    /// `mant_bits + 1` significand bits and `exp` the exponent field, which is cleared when
+    /// the hidden bit is (subnormals and zero). Returns the fraction and exponent fields,
+    /// where a carry out of the fraction moves into the exponent, possibly to infinity.
+    fn round<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        c: &Circuit<P>,
+        sig: &[Ciphertext],
+        sticky: &Ciphertext,
+        exp: &[Ciphertext],
+    ) -> Vec<Ciphertext> {
+        let (e, m): (usize, usize) = (self.exp_bits, self.mant_bits);
+        let hidden: Ciphertext = sig[m + 1].clone();
+        let lsb_or_sticky: Ciphertext = c.p.e_or_bit(c.sk, &sig[1], sticky);
+        let up: Ciphertext = c.p.e_and_bit(c.sk, &sig[0], &lsb_or_sticky);
+
+        let mut packed: Vec<Ciphertext> = sig[1..m + 1].to_vec();
+        for bit in &exp[..e] {
+            packed.push(c.p.e_and_bit(c.sk, bit, &hidden));
+        }
+        c.increment(&packed, &up)
+    }
+
+    /// Appends the sign to the rounded fields, or returns infinity on overflow.
+    fn finish<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        c: &Circuit<P>,
+        sign: &Ciphertext,
+        rounded: &[Ciphertext],
+        overflow: &Ciphertext,
+    ) -> Result<Vec<Ciphertext>, PulpError> {
+        let mut finite: Vec<Ciphertext> = rounded.to_vec();
+        finite.push(sign.clone());
+        c.mux(overflow, &self.infinity_bits(sign), &finite)
+    }
+
+    fn infinity_bits(&self, sign: &Ciphertext) -> Vec<Ciphertext> {
+        let mut bits: Vec<Ciphertext> = constant(self.infinity(false), self.width());
+        bits[self.width() - 1] = sign.clone();
+        bits
+    }
+}
+
+/// The low `width` bits of `value` as constant bits.
+fn constant(value: u64, width: usize) -> Vec<Ciphertext> {
+    (0..width)
+        .map(|i| Ciphertext::Trivial(i < 64 && (value >> i) & 1 == 1))
+        .collect()
+}
+
+/// Zero-extends (or truncates) to `width` bits.
+fn zext(a: &[Ciphertext], width: usize) -> Vec<Ciphertext> {
+    let mut bits: Vec<Ciphertext> = a.to_vec();
+    bits.resize(width, Ciphertext::Trivial(false));
+    bits
+}
+
+/// Prepends `count` zero bits, i.e. multiplies by `2^count`.
+fn shift_up(a: &[Ciphertext], count: usize) -> Vec<Ciphertext> {
+    let mut bits: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); count];
+    bits.extend_from_slice(a);
+    bits
+}
+
+/// The processor and key, with helpers for the building blocks shared by the operations.
+struct Circuit<'a, P> {
+    p: &'a P,
+    sk: &'a ServerKey,
+}
+
+impl<P: ProcessorGate + ProcessorCircuits> Circuit<'_, P> {
+    fn mux(
+        &self,
+        select: &Ciphertext,
+        then: &[Ciphertext],
+        other: &[Ciphertext],
+    ) -> Result<Vec<Ciphertext>, PulpError> {
+        let mut result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); then.len()];
+        self.p.e_mux(self.sk, select, then, other, &mut result)?;
+        Ok(result)
+    }
+
+    fn add(&self, a: &[Ciphertext], b: &[Ciphertext]) -> Result<Vec<Ciphertext>, PulpError> {
+        let mut result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); a.len()];
+        self.p.adder(self.sk, a, b, &mut result)?;
+        Ok(result)
+    }
+
+    fn sub(&self, a: &[Ciphertext], b: &[Ciphertext]) -> Result<Vec<Ciphertext>, PulpError> {
+        let mut result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); a.len()];
+        self.p.subtracter(self.sk, a, b, &mut result)?;
+        Ok(result)
+    }
+
+    fn add_const(&self, a: &[Ciphertext], b: i64) -> Result<Vec<Ciphertext>, PulpError> {
+        let mut result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); a.len()];
+        self.p.scalar_add(self.sk, a, b, &mut result)?;
+        Ok(result)
+    }
+
+    /// Adds one encrypted bit, dropping the carry out.
+    fn increment(&self, a: &[Ciphertext], bit: &Ciphertext) -> Vec<Ciphertext> {
+        let mut result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); a.len()];
+        let mut carry: Ciphertext = bit.clone();
+        let mut next: Ciphertext = Ciphertext::Trivial(false);
+        for i in 0..a.len() {
+            self.p
+                .half_adder(self.sk, &a[i], &carry, &mut next, &mut result[i]);
+            carry = next.clone();
+        }
+        result
+    }
+
+    /// `a < b` for unsigned operands of the same width.
+    fn less(&self, a: &[Ciphertext], b: &[Ciphertext]) -> Result<Ciphertext, PulpError> {
+        let diff: Vec<Ciphertext> = self.sub(&zext(a, a.len() + 1), &zext(b, b.len() + 1))?;
+        Ok(diff[a.len()].clone())
+    }
+
+    fn any(&self, a: &[Ciphertext]) -> Ciphertext {
+        a.iter().fold(Ciphertext::Trivial(false), |acc, bit| {
+            self.p.e_or_bit(self.sk, &acc, bit)
+        })
+    }
+
+    fn all(&self, a: &[Ciphertext]) -> Ciphertext {
+        a.iter().fold(Ciphertext::Trivial(true), |acc, bit| {
+            self.p.e_and_bit(self.sk, &acc, bit)
+        })
+    }
+
+    fn and3(&self, a: &Ciphertext, b: &Ciphertext, c: &Ciphertext) -> Ciphertext {
+        self.p
+            .e_and_bit(self.sk, &self.p.e_and_bit(self.sk, a, b), c)
+    }
+
+    fn or3(&self, a: &Ciphertext, b: &Ciphertext, c: &Ciphertext) -> Ciphertext {
+        self.p.e_or_bit(self.sk, &self.p.e_or_bit(self.sk, a, b), c)
+    }
+
+    /// Number of leading zero bits of `a`, in enough bits to hold `a.len()`.
+    fn leading_zeros(&self, a: &[Ciphertext]) -> Result<Vec<Ciphertext>, PulpError> {
+        let width: usize = (usize::BITS - a.len().leading_zeros()) as usize;
+        let mut count: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); width];
+        self.p.clz(self.sk, a, &mut count)?;
+        Ok(count)
+    }
+
+    /// Shifts `a` left by the unsigned encrypted `amount`; bits shifted out are lost.
+    fn shl(&self, a: &[Ciphertext], amount: &[Ciphertext]) -> Result<Vec<Ciphertext>, PulpError> {
+        let n: usize = a.len();
+        let mut bits: Vec<Ciphertext> = a.to_vec();
+        for (j, select) in amount.iter().enumerate() {
+            let step: usize = 1usize.checked_shl(j as u32).unwrap_or(usize::MAX);
+            let mut shifted: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); n];
+            if step < n {
+                shifted[step..].clone_from_slice(&bits[..n - step]);
+            }
+            bits = self.mux(select, &shifted, &bits)?;
+        }
+        Ok(bits)
+    }
+
+    /// Shifts `a` right by the unsigned encrypted `amount`, ORing every bit shifted out into
+    /// bit 0 (the sticky bit).
+    fn shr_sticky(
+        &self,
+        a: &[Ciphertext],
+        amount: &[Ciphertext],
+    ) -> Result<Vec<Ciphertext>, PulpError> {
+        let n: usize = a.len();
+        let mut bits: Vec<Ciphertext> = a.to_vec();
+        for (j, select) in amount.iter().enumerate() {
+            let step: usize = 1usize.checked_shl(j as u32).unwrap_or(usize::MAX);
+            let mut shifted: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); n];
+            if step < n {
+                shifted[1..n - step].clone_from_slice(&bits[step + 1..]);
+                shifted[0] = self.any(&bits[..step + 1]);
+            } else {
+                shifted[0] = self.any(&bits);
+            }
+            bits = self.mux(select, &shifted, &bits)?;
+        }
+        Ok(bits)
+    }
+}
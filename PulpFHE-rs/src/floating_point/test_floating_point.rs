@@ -0,0 +1,275 @@
+#![allow(warnings)]
+use super::*; // Imports items from the parent module
+use crate::key_manager::KeyManager;
+use crate::processor_boolean::ProcessorBoolean;
+use half::{bf16, f16};
+use serial_test::serial;
+
+const F16: FloatFormat = FloatFormat::BINARY16;
+const BF16: FloatFormat = FloatFormat::BFLOAT16;
+
+/// `half`'s encoding of `x` in `format`.
+fn half_encode(format: FloatFormat, x: f64) -> u64 {
+    if format == F16 {
+        f16::from_f64(x).to_bits().into()
+    } else {
+        bf16::from_f64(x).to_bits().into()
+    }
+}
+
+/// `half`'s value of `bits` in `format`.
+fn half_decode(format: FloatFormat, bits: u64) -> f64 {
+    if format == F16 {
+        f16::from_bits(bits as u16).to_f64()
+    } else {
+        bf16::from_bits(bits as u16).to_f64()
+    }
+}
+
+/// `a + b` and `a * b` in `format`, computed by `half`.
+fn half_sum_product(format: FloatFormat, a: u64, b: u64) -> (u64, u64) {
+    if format == F16 {
+        let (x, y) = (f16::from_bits(a as u16), f16::from_bits(b as u16));
+        ((x + y).to_bits().into(), (x * y).to_bits().into())
+    } else {
+        let (x, y) = (bf16::from_bits(a as u16), bf16::from_bits(b as u16));
+        ((x + y).to_bits().into(), (x * y).to_bits().into())
+    }
+}
+
+fn is_nan(format: FloatFormat, bits: u64) -> bool {
+    half_decode(format, bits).is_nan()
+}
+
+fn encode_bits(num: i64, size: usize, ck: &ClientKey) -> Vec<Ciphertext> {
+    (0..size).map(|i| ck.encrypt((num >> i) & 1 == 1)).collect()
+}
+
+fn decode_bits(ciphertexts: &[Ciphertext], ck: &ClientKey) -> i64 {
+    let raw: u64 = ciphertexts
+        .iter()
+        .enumerate()
+        .map(|(i, bit)| (ck.decrypt(bit) as u64) << i)
+        .sum();
+    let unused: u32 = 64 - ciphertexts.len() as u32;
+    ((raw << unused) as i64) >> unused
+}
+
+#[test]
+fn test_float_encode() {
+    let fn_name = "float_encode";
+    println!("[*] TEST: {fn_name}");
+    let values = [
+        1.0,
+        -2.0,
+        std::f64::consts::PI,
+        0.1,
+        65504.0,
+        65519.0,
+        65520.0,
+        2f64.powi(-24),
+        2f64.powi(-25),
+        3.0 * 2f64.powi(-25),
+        1.0 + 2f64.powi(-8),
+        1.0 + 3.0 * 2f64.powi(-8),
+        1e-40,
+        f64::MAX,
+        -0.0,
+        f64::NEG_INFINITY,
+    ];
+    for format in [F16, BF16] {
+        for x in values {
+            assert_eq!(format.encode(x), half_encode(format, x), "{x:e}");
+        }
+        assert!(is_nan(format, format.encode(f64::NAN)));
+        assert!(is_nan(format, format.nan()));
+    }
+    assert_eq!(F16.nan(), 0x7E00);
+    assert_eq!(BF16.nan(), 0x7FC0);
+
+    for bits in [0x0001, 0x03FF, 0x0400, 0x3C00, 0x7BFF, 0xFBFF, 0x8000] {
+        assert_eq!(F16.decode(bits), half_decode(F16, bits));
+        assert_eq!(F16.encode(F16.decode(bits)), bits);
+    }
+    assert!(F16.decode(0x7E01).is_nan());
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_float_add() {
+    let fn_name = "float_add";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let server = ProcessorBoolean;
+
+    let cases: [(FloatFormat, u64, u64); 14] = [
+        (F16, 0x3C00, 0x4000),
+        // 1 + 2^-11 is a tie and rounds to even, 1 + 2^-10 + 2^-11 rounds up
+        (F16, 0x3C00, 0x1000),
+        (F16, 0x3C01, 0x1000),
+        (F16, 0x3E00, 0xBD00),
+        (F16, 0x4248, 0xC248),
+        (F16, 0x8000, 0x8000),
+        (F16, 0x0001, 0x03FF),
+        (F16, 0x0400, 0x8001),
+        (F16, 0x7BFF, 0x7BFF),
+        (F16, 0x7BFF, 0x0001),
+        (F16, 0x7C00, 0xFC00),
+        (F16, 0xFC00, 0x3C00),
+        (BF16, 0x4049, 0xC0A0),
+        (BF16, 0x7F7F, 0x7F00),
+    ];
+    for (format, a, b) in cases {
+        let ct_a = FloatingPoint::encrypt_bits(client_key, format, a);
+        let ct_b = FloatingPoint::encrypt_bits(client_key, format, b);
+        let sum = ct_a.add(&server, server_key, &ct_b).unwrap();
+        let dec_res = sum.decrypt_bits(client_key);
+        let expected = half_sum_product(format, a, b).0;
+        println!("\t {a:#06x} + {b:#06x} = {dec_res:#06x}");
+        if is_nan(format, expected) {
+            assert_eq!(dec_res, format.nan());
+        } else {
+            assert_eq!(dec_res, expected, "{a:#06x} + {b:#06x}");
+        }
+    }
+
+    let ct_a = FloatingPoint::encrypt(client_key, BF16, 1.5);
+    let ct_b = FloatingPoint::encrypt(client_key, BF16, 0.375);
+    let diff = ct_a.sub(&server, server_key, &ct_b).unwrap();
+    assert_eq!(diff.decrypt(client_key), 1.125);
+    let nan = FloatingPoint::encrypt_bits(client_key, F16, 0x7E01);
+    let one = FloatingPoint::trivial(F16, 1.0);
+    let sum = nan.add(&server, server_key, &one).unwrap();
+    assert_eq!(sum.decrypt_bits(client_key), F16.nan());
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_float_mul() {
+    let fn_name = "float_mul";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let server = ProcessorBoolean;
+
+    let cases: [(FloatFormat, u64, u64); 13] = [
+        (F16, 0x3E00, 0x4080),
+        (F16, 0xBE00, 0x4000),
+        (F16, 0x3C01, 0x3C01),
+        (F16, 0x5C00, 0x5C00),
+        // Results in the subnormal range, including ties to even
+        (F16, 0x0400, 0x3800),
+        (F16, 0x0001, 0x3800),
+        (F16, 0x0003, 0x3800),
+        (F16, 0x0001, 0x7800),
+        (F16, 0x7C00, 0x0000),
+        (F16, 0xC000, 0x7C00),
+        (F16, 0x8000, 0x4200),
+        (BF16, 0x4049, 0x4049),
+        (BF16, 0x0080, 0x3E80),
+    ];
+    for (format, a, b) in cases {
+        let ct_a = FloatingPoint::encrypt_bits(client_key, format, a);
+        let ct_b = FloatingPoint::encrypt_bits(client_key, format, b);
+        let product = ct_a.mul(&server, server_key, &ct_b).unwrap();
+        let dec_res = product.decrypt_bits(client_key);
+        let expected = half_sum_product(format, a, b).1;
+        println!("\t {a:#06x} * {b:#06x} = {dec_res:#06x}");
+        if is_nan(format, expected) {
+            assert_eq!(dec_res, format.nan());
+        } else {
+            assert_eq!(dec_res, expected, "{a:#06x} * {b:#06x}");
+        }
+    }
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_float_compare() {
+    let fn_name = "float_compare";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let server = ProcessorBoolean;
+
+    let cases: [(u64, u64); 6] = [
+        (0x3C00, 0x4000),
+        (0x8000, 0x0000),
+        (0xBC00, 0xC000),
+        (0xFC00, 0x0001),
+        (0x4248, 0x4248),
+        (0x7E00, 0x3C00),
+    ];
+    for (a, b) in cases {
+        let (x, y) = (half_decode(F16, a), half_decode(F16, b));
+        let ct_a = FloatingPoint::encrypt_bits(client_key, F16, a);
+        let ct_b = FloatingPoint::encrypt_bits(client_key, F16, b);
+        let expected = [x == y, x < y, x > y, x >= y, x <= y, x != y];
+        for (select, expected) in expected.into_iter().enumerate() {
+            let bit = ct_a
+                .compare(&server, server_key, &ct_b, select as u8)
+                .unwrap();
+            assert_eq!(
+                client_key.decrypt(&bit),
+                expected,
+                "{a:#06x} vs {b:#06x}, select {select}"
+            );
+        }
+    }
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_float_integer_conversion() {
+    let fn_name = "float_integer_conversion";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let server = ProcessorBoolean;
+
+    let integers: [(FloatFormat, usize, i64); 8] = [
+        (F16, 8, 0),
+        (F16, 8, -1),
+        (F16, 8, -128),
+        (F16, 8, 100),
+        // 2049 is a tie between 2048 and 2050
+        (F16, 16, 2049),
+        (F16, 32, 2051),
+        (F16, 32, 70000),
+        (BF16, 32, -2147483648),
+    ];
+    for (format, size, a) in integers {
+        let ct_a = encode_bits(a, size, client_key);
+        let ct_res = FloatingPoint::from_integer(&server, server_key, format, &ct_a).unwrap();
+        let dec_res = ct_res.decrypt_bits(client_key);
+        println!("\t {a} as float = {dec_res:#06x}");
+        assert_eq!(dec_res, half_encode(format, a as f64));
+    }
+
+    let floats: [u64; 10] = [
+        0x4100, 0x4300, 0xC100, 0x3800, 0x3A00, 0x5A40, 0xDCB0, 0x57FC, 0x7C00, 0x7E00,
+    ];
+    for a in floats {
+        let x = half_decode(F16, a);
+        let ct_a = FloatingPoint::encrypt_bits(client_key, F16, a);
+        let ct_res = ct_a.to_integer(&server, server_key, 8).unwrap();
+        let dec_res = decode_bits(&ct_res, client_key);
+        let expected = if x.is_nan() {
+            0
+        } else {
+            x.round_ties_even().clamp(-128.0, 127.0) as i64
+        };
+        println!("\t {x} as i8 = {dec_res}");
+        assert_eq!(dec_res, expected);
+    }
+    assert_eq!(
+        FloatingPoint::from_integer(&server, server_key, F16, &encode_bits(0, 1, client_key))
+            .unwrap_err(),
+        PulpError::UnsupportedWidth {
+            op: "float_from_integer",
+            width: 1
+        }
+    );
+    println!("[✓] PASS: {fn_name}\n");
+}
@@ -0,0 +1,413 @@
+//! # Description
+//! Generation, storage and reuse of `ClientKey`/`ServerKey` pairs.
+//!
+//! A key file is a fixed header followed by the bincode encoding of the key:
+//!
+//! | bytes | field                                                                  |
+//! |-------|------------------------------------------------------------------------|
+//! | 8     | magic `PULPKEY\0`                                                      |
+//! | 2     | format version, little endian                                          |
+//! | 1     | key kind (0 = client, 1 = server)                                      |
+//! | 1     | parameter set id (see `ParameterSet`)                                  |
+//! | 8     | key id (see `KeyInfo`), little endian                                  |
+//! | 8     | parameter fingerprint (see `ParameterSet::fingerprint`), little endian |
+//!
+//! Files with another magic, format version or key kind, or whose fingerprint does not
+//! match the parameters the set id names in this build, are rejected with a `KeyError`
+//! before any key material is decoded.
+//!
+//! `KeyManager::cached_keys` hands out one key pair per process. The pair is loaded from
+//! the directory in `PULPFHE_KEY_CACHE` (default: `pulpfhe-keys` in `$XDG_CACHE_HOME` or
+//! `~/.cache`) and generated and written there on first use. On Unix the directory is
+//! created with mode 0700 and client keys are written with mode 0600.
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::fs::{self, DirBuilder, File, OpenOptions};
+use std::hash::{BuildHasher, Hasher};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tfhe::boolean::parameters::*;
+use tfhe::boolean::prelude::{BooleanParameters, ClientKey, ServerKey};
+
+#[cfg(test)]
+mod test_key_manager;
+
+pub const KEY_MAGIC: [u8; 8] = *b"PULPKEY\0";
+/// Bumped whenever the header or the key encoding changes
+pub const KEY_FORMAT_VERSION: u16 = 3;
+const HEADER_LEN: usize = 28;
+
+/// The tfhe boolean parameter sets keys can be generated for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ParameterSet {
+    Default,
+    DefaultKsPbs,
+    ErrorProb2Pow165,
+    ErrorProb2Pow165KsPbs,
+    TfheLib,
+}
+
+impl ParameterSet {
+    pub const ALL: [ParameterSet; 5] = [
+        ParameterSet::Default,
+        ParameterSet::DefaultKsPbs,
+        ParameterSet::ErrorProb2Pow165,
+        ParameterSet::ErrorProb2Pow165KsPbs,
+        ParameterSet::TfheLib,
+    ];
+
+    pub fn parameters(&self) -> BooleanParameters {
+        match self {
+            ParameterSet::Default => DEFAULT_PARAMETERS,
+            ParameterSet::DefaultKsPbs => DEFAULT_PARAMETERS_KS_PBS,
+            ParameterSet::ErrorProb2Pow165 => PARAMETERS_ERROR_PROB_2_POW_MINUS_165,
+            ParameterSet::ErrorProb2Pow165KsPbs => PARAMETERS_ERROR_PROB_2_POW_MINUS_165_KS_PBS,
+            ParameterSet::TfheLib => TFHE_LIB_PARAMETERS,
+        }
+    }
+
+    /// Identifier stored in file headers.
+    pub fn id(&self) -> u8 {
+        match self {
+            ParameterSet::Default => 0,
+            ParameterSet::DefaultKsPbs => 1,
+            ParameterSet::ErrorProb2Pow165 => 2,
+            ParameterSet::ErrorProb2Pow165KsPbs => 3,
+            ParameterSet::TfheLib => 4,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<Self> {
+        Self::ALL.iter().copied().find(|p| p.id() == id)
+    }
+
+    /// FNV-1a hash of the bincode encoding of the parameters. Unlike `id` it changes if
+    /// tfhe changes the parameter values behind a name.
+    pub fn fingerprint(&self) -> u64 {
+        let bytes = bincode::serialize(&self.parameters()).expect("Parameters always encode.");
+        bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &b| {
+            (hash ^ b as u64).wrapping_mul(0x0000_0100_0000_01b3)
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ParameterSet::Default => "default",
+            ParameterSet::DefaultKsPbs => "default-ks-pbs",
+            ParameterSet::ErrorProb2Pow165 => "error-prob-2-pow-165",
+            ParameterSet::ErrorProb2Pow165KsPbs => "error-prob-2-pow-165-ks-pbs",
+            ParameterSet::TfheLib => "tfhe-lib",
+        }
+    }
+}
+
+impl fmt::Display for ParameterSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl FromStr for ParameterSet {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|p| p.name() == s)
+            .ok_or_else(|| format!("unknown parameter set `{s}`"))
+    }
+}
+
+/// Which half of a key pair a file holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyKind {
+    Client,
+    Server,
+}
+
+impl KeyKind {
+    fn id(&self) -> u8 {
+        match self {
+            KeyKind::Client => 0,
+            KeyKind::Server => 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum KeyError {
+    Io(io::Error),
+    /// The file does not start with `KEY_MAGIC`
+    BadMagic,
+    /// The file was written with an incompatible format version
+    UnsupportedVersion {
+        found: u16,
+        expected: u16,
+    },
+    UnknownParameterSet(u8),
+    /// A client key was found where a server key was expected, or the other way around
+    WrongKind {
+        expected: KeyKind,
+    },
+    /// The key does not match the parameter set recorded in its header
+    ParameterMismatch(ParameterSet),
+    Encoding(String),
+}
+
+impl fmt::Display for KeyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyError::Io(e) => write!(f, "key file I/O error: {e}"),
+            KeyError::BadMagic => write!(f, "not a PulpFHE key file"),
+            KeyError::UnsupportedVersion { found, expected } => write!(
+                f,
+                "key file format version {found} is not supported (expected {expected})"
+            ),
+            KeyError::UnknownParameterSet(id) => write!(f, "unknown parameter set id {id}"),
+            KeyError::WrongKind { expected } => {
+                write!(f, "expected a {expected:?} key file")
+            }
+            KeyError::ParameterMismatch(p) => {
+                write!(f, "key does not use the `{p}` parameter set of its header")
+            }
+            KeyError::Encoding(e) => write!(f, "key encoding error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for KeyError {}
+
+impl From<io::Error> for KeyError {
+    fn from(e: io::Error) -> Self {
+        KeyError::Io(e)
+    }
+}
+
+impl From<bincode::Error> for KeyError {
+    fn from(e: bincode::Error) -> Self {
+        match *e {
+            bincode::ErrorKind::Io(e) => KeyError::Io(e),
+            e => KeyError::Encoding(e.to_string()),
+        }
+    }
+}
+
+/// Identity of a key pair: its parameter set and a random id drawn at generation time.
+/// Both key files of a pair and every ciphertext file encrypted under it carry the id.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyInfo {
+    pub params: ParameterSet,
+    pub key_id: u64,
+}
+
+impl KeyInfo {
+    /// Draws a fresh key id for `params`.
+    pub fn new(params: ParameterSet) -> Self {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u128(
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_nanos()),
+        );
+        hasher.write_u32(std::process::id());
+        KeyInfo {
+            params,
+            key_id: hasher.finish(),
+        }
+    }
+}
+
+pub struct KeyManager;
+
+impl KeyManager {
+    pub fn generate(params: ParameterSet) -> (ClientKey, ServerKey, KeyInfo) {
+        let client_key = ClientKey::new(&params.parameters());
+        let server_key = ServerKey::new(&client_key);
+        (client_key, server_key, KeyInfo::new(params))
+    }
+
+    pub fn write_client_key<W: Write>(
+        writer: W,
+        key: &ClientKey,
+        info: &KeyInfo,
+    ) -> Result<(), KeyError> {
+        Self::write_key(writer, KeyKind::Client, info, key)
+    }
+
+    pub fn write_server_key<W: Write>(
+        writer: W,
+        key: &ServerKey,
+        info: &KeyInfo,
+    ) -> Result<(), KeyError> {
+        Self::write_key(writer, KeyKind::Server, info, key)
+    }
+
+    /// Reads a client key and checks its header fingerprint, and the parameters stored in
+    /// the key itself, against the parameter set in its header.
+    pub fn read_client_key<R: Read>(reader: R) -> Result<(ClientKey, KeyInfo), KeyError> {
+        let (key, info): (ClientKey, KeyInfo) = Self::read_key(reader, KeyKind::Client)?;
+        let (_, _, key_params) = key.clone().into_raw_parts();
+        if key_params != info.params.parameters() {
+            return Err(KeyError::ParameterMismatch(info.params));
+        }
+        Ok((key, info))
+    }
+
+    /// Reads a server key and checks its header fingerprint against the parameter set in
+    /// its header.
+    pub fn read_server_key<R: Read>(reader: R) -> Result<(ServerKey, KeyInfo), KeyError> {
+        Self::read_key(reader, KeyKind::Server)
+    }
+
+    /// Writes a client key to a file only its owner can read.
+    pub fn save_client_key(path: &Path, key: &ClientKey, info: &KeyInfo) -> Result<(), KeyError> {
+        let mut options = OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        Self::write_client_key(BufWriter::new(options.open(path)?), key, info)
+    }
+
+    pub fn save_server_key(path: &Path, key: &ServerKey, info: &KeyInfo) -> Result<(), KeyError> {
+        Self::write_server_key(BufWriter::new(File::create(path)?), key, info)
+    }
+
+    pub fn load_client_key(path: &Path) -> Result<(ClientKey, KeyInfo), KeyError> {
+        Self::read_client_key(BufReader::new(File::open(path)?))
+    }
+
+    pub fn load_server_key(path: &Path) -> Result<(ServerKey, KeyInfo), KeyError> {
+        Self::read_server_key(BufReader::new(File::open(path)?))
+    }
+
+    /// Loads the key pair for `params` from `dir`, or generates it and writes it there.
+    /// Unreadable, incompatible or mismatched cached files are replaced.
+    pub fn load_or_generate(
+        dir: &Path,
+        params: ParameterSet,
+    ) -> Result<(ClientKey, ServerKey, KeyInfo), KeyError> {
+        let client_path = dir.join(format!("{params}.client.key"));
+        let server_path = dir.join(format!("{params}.server.key"));
+
+        if let (Ok((client_key, client_info)), Ok((server_key, server_info))) = (
+            Self::load_client_key(&client_path),
+            Self::load_server_key(&server_path),
+        ) && client_info == server_info
+            && client_info.params == params
+        {
+            return Ok((client_key, server_key, client_info));
+        }
+
+        let (client_key, server_key, info) = Self::generate(params);
+        let mut builder = DirBuilder::new();
+        builder.recursive(true);
+        #[cfg(unix)]
+        builder.mode(0o700);
+        builder.create(dir)?;
+        // Write under a per-process name first so concurrent test binaries never see a
+        // partially written key
+        let tmp = |path: &Path| path.with_extension(format!("key.{}", std::process::id()));
+        Self::save_client_key(&tmp(&client_path), &client_key, &info)?;
+        Self::save_server_key(&tmp(&server_path), &server_key, &info)?;
+        fs::rename(tmp(&client_path), &client_path)?;
+        fs::rename(tmp(&server_path), &server_path)?;
+        Ok((client_key, server_key, info))
+    }
+
+    /// Key pair for the default parameter set, shared by everything in this process.
+    /// Falls back to fresh keys if there is no cache directory or it cannot be written.
+    pub fn cached_keys() -> (&'static ClientKey, &'static ServerKey) {
+        let (client_key, server_key, _) = Self::cached();
+        (client_key, server_key)
+    }
+
+    /// `KeyInfo` of the pair returned by `cached_keys`.
+    pub fn cached_key_info() -> KeyInfo {
+        Self::cached().2
+    }
+
+    fn cached() -> &'static (ClientKey, ServerKey, KeyInfo) {
+        static KEYS: OnceLock<(ClientKey, ServerKey, KeyInfo)> = OnceLock::new();
+        KEYS.get_or_init(|| {
+            Self::cache_dir()
+                .and_then(|dir| Self::load_or_generate(&dir, ParameterSet::Default).ok())
+                .unwrap_or_else(|| Self::generate(ParameterSet::Default))
+        })
+    }
+
+    /// `PULPFHE_KEY_CACHE`, else `pulpfhe-keys` in the per-user cache directory.
+    /// `None` if neither `XDG_CACHE_HOME` nor `HOME` names an absolute path.
+    pub fn cache_dir() -> Option<PathBuf> {
+        let absolute = |name: &str| {
+            std::env::var_os(name)
+                .map(PathBuf::from)
+                .filter(|path| path.is_absolute())
+        };
+        if let Some(dir) = std::env::var_os("PULPFHE_KEY_CACHE") {
+            return Some(PathBuf::from(dir));
+        }
+        absolute("XDG_CACHE_HOME")
+            .or_else(|| absolute("HOME").map(|home| home.join(".cache")))
+            .map(|cache| cache.join("pulpfhe-keys"))
+    }
+
+    fn write_key<W: Write, T: Serialize>(
+        mut writer: W,
+        kind: KeyKind,
+        info: &KeyInfo,
+        key: &T,
+    ) -> Result<(), KeyError> {
+        let mut header = [0u8; HEADER_LEN];
+        header[..8].copy_from_slice(&KEY_MAGIC);
+        header[8..10].copy_from_slice(&KEY_FORMAT_VERSION.to_le_bytes());
+        header[10] = kind.id();
+        header[11] = info.params.id();
+        header[12..20].copy_from_slice(&info.key_id.to_le_bytes());
+        header[20..28].copy_from_slice(&info.params.fingerprint().to_le_bytes());
+        writer.write_all(&header)?;
+        bincode::serialize_into(&mut writer, key)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn read_key<R: Read, T: DeserializeOwned>(
+        mut reader: R,
+        kind: KeyKind,
+    ) -> Result<(T, KeyInfo), KeyError> {
+        let mut header = [0u8; HEADER_LEN];
+        reader.read_exact(&mut header).map_err(|e| match e.kind() {
+            io::ErrorKind::UnexpectedEof => KeyError::BadMagic,
+            _ => KeyError::Io(e),
+        })?;
+        if header[..8] != KEY_MAGIC {
+            return Err(KeyError::BadMagic);
+        }
+        let version = u16::from_le_bytes([header[8], header[9]]);
+        if version != KEY_FORMAT_VERSION {
+            return Err(KeyError::UnsupportedVersion {
+                found: version,
+                expected: KEY_FORMAT_VERSION,
+            });
+        }
+        if header[10] != kind.id() {
+            return Err(KeyError::WrongKind { expected: kind });
+        }
+        let params =
+            ParameterSet::from_id(header[11]).ok_or(KeyError::UnknownParameterSet(header[11]))?;
+        let key_id = u64::from_le_bytes(header[12..20].try_into().unwrap());
+        if u64::from_le_bytes(header[20..28].try_into().unwrap()) != params.fingerprint() {
+            return Err(KeyError::ParameterMismatch(params));
+        }
+        let key: T = bincode::deserialize_from(reader)?;
+        Ok((key, KeyInfo { params, key_id }))
+    }
+}
@@ -0,0 +1,191 @@
+#![allow(warnings)]
+use super::*; // Imports items from the parent module
+use serial_test::serial;
+use tfhe::boolean::prelude::*;
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pulpfhe-test-{}-{name}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+#[serial]
+fn test_key_roundtrip() {
+    let fn_name = "key_roundtrip";
+    println!("[*] TEST: {fn_name}");
+    let dir = scratch_dir(fn_name);
+    let (client_key, server_key, info) = KeyManager::generate(ParameterSet::Default);
+
+    KeyManager::save_client_key(&dir.join("client.key"), &client_key, &info).unwrap();
+    KeyManager::save_server_key(&dir.join("server.key"), &server_key, &info).unwrap();
+    let (loaded_client, client_info) =
+        KeyManager::load_client_key(&dir.join("client.key")).unwrap();
+    assert_eq!(client_info, info);
+    let (loaded_server, server_info) =
+        KeyManager::load_server_key(&dir.join("server.key")).unwrap();
+    assert_eq!(server_info, info);
+    // Every generated pair gets its own id
+    assert_ne!(KeyInfo::new(ParameterSet::Default).key_id, info.key_id);
+
+    // Ciphertexts cross between the original and the loaded keys
+    let a = client_key.encrypt(true);
+    let b = loaded_client.encrypt(false);
+    assert!(loaded_client.decrypt(&a));
+    assert!(client_key.decrypt(&loaded_server.or(&a, &b)));
+    assert!(!client_key.decrypt(&loaded_server.and(&a, &b)));
+
+    fs::remove_dir_all(&dir).unwrap();
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_key_rejects_incompatible_files() {
+    let fn_name = "key_rejects_incompatible_files";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, _) = KeyManager::cached_keys();
+    let mut bytes: Vec<u8> = Vec::new();
+    let info = KeyManager::cached_key_info();
+    KeyManager::write_client_key(&mut bytes, client_key, &info).unwrap();
+    assert!(KeyManager::read_client_key(bytes.as_slice()).is_ok());
+
+    // Newer format version
+    let mut newer = bytes.clone();
+    newer[8..10].copy_from_slice(&(KEY_FORMAT_VERSION + 1).to_le_bytes());
+    assert!(matches!(
+        KeyManager::read_client_key(newer.as_slice()),
+        Err(KeyError::UnsupportedVersion { found, expected }) if found == KEY_FORMAT_VERSION + 1 && expected == KEY_FORMAT_VERSION
+    ));
+
+    let mut bad_magic = bytes.clone();
+    bad_magic[0] = b'X';
+    assert!(matches!(
+        KeyManager::read_client_key(bad_magic.as_slice()),
+        Err(KeyError::BadMagic)
+    ));
+    assert!(matches!(
+        KeyManager::read_client_key(&bytes[..4]),
+        Err(KeyError::BadMagic)
+    ));
+
+    // A client key is not a server key
+    assert!(matches!(
+        KeyManager::read_server_key(bytes.as_slice()),
+        Err(KeyError::WrongKind {
+            expected: KeyKind::Server
+        })
+    ));
+
+    // Header claims a parameter set the key was not generated with
+    let mut relabelled = bytes.clone();
+    relabelled[11] = ParameterSet::TfheLib.id();
+    assert!(matches!(
+        KeyManager::read_client_key(relabelled.as_slice()),
+        Err(KeyError::ParameterMismatch(ParameterSet::TfheLib))
+    ));
+
+    // Parameters that changed behind the same set id, caught for both kinds of key
+    let (_, server_key) = KeyManager::cached_keys();
+    let mut server_bytes: Vec<u8> = Vec::new();
+    KeyManager::write_server_key(&mut server_bytes, server_key, &info).unwrap();
+    server_bytes[20] ^= 1;
+    let mut refingerprinted = bytes.clone();
+    refingerprinted[20] ^= 1;
+    assert!(matches!(
+        KeyManager::read_client_key(refingerprinted.as_slice()),
+        Err(KeyError::ParameterMismatch(p)) if p == info.params
+    ));
+    assert!(matches!(
+        KeyManager::read_server_key(server_bytes.as_slice()),
+        Err(KeyError::ParameterMismatch(p)) if p == info.params
+    ));
+
+    let mut unknown = bytes.clone();
+    unknown[11] = 200;
+    assert!(matches!(
+        KeyManager::read_client_key(unknown.as_slice()),
+        Err(KeyError::UnknownParameterSet(200))
+    ));
+
+    let truncated = &bytes[..bytes.len() / 2];
+    assert!(KeyManager::read_client_key(truncated).is_err());
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_load_or_generate() {
+    let fn_name = "load_or_generate";
+    println!("[*] TEST: {fn_name}");
+    let dir = scratch_dir(fn_name);
+
+    let (client_key, _, info) = KeyManager::load_or_generate(&dir, ParameterSet::Default).unwrap();
+    let (cached_client, cached_server, cached_info) =
+        KeyManager::load_or_generate(&dir, ParameterSet::Default).unwrap();
+    // The second call reuses the stored pair
+    assert_eq!(cached_info, info);
+    let ct = client_key.encrypt(true);
+    assert!(cached_client.decrypt(&ct));
+    assert!(!client_key.decrypt(&cached_server.not(&ct)));
+
+    // A corrupt cache entry is regenerated
+    fs::write(dir.join("default.server.key"), b"garbage").unwrap();
+    let (client_key, server_key, new_info) =
+        KeyManager::load_or_generate(&dir, ParameterSet::Default).unwrap();
+    assert_ne!(new_info, info);
+    assert_eq!(
+        KeyManager::load_server_key(&dir.join("default.server.key"))
+            .unwrap()
+            .1,
+        new_info
+    );
+    let ct = client_key.encrypt(true);
+    assert!(client_key.decrypt(&server_key.and(&ct, &ct)));
+
+    assert!(std::ptr::eq(
+        KeyManager::cached_keys().1,
+        KeyManager::cached_keys().1
+    ));
+    fs::remove_dir_all(&dir).unwrap();
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[cfg(unix)]
+#[test]
+#[serial]
+fn test_key_cache_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+    let fn_name = "key_cache_permissions";
+    println!("[*] TEST: {fn_name}");
+    let root = scratch_dir(fn_name);
+    let dir = root.join("keys");
+
+    KeyManager::load_or_generate(&dir, ParameterSet::Default).unwrap();
+    let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+    assert_eq!(mode(&dir), 0o700);
+    assert_eq!(mode(&dir.join("default.client.key")), 0o600);
+    fs::remove_dir_all(&root).unwrap();
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+fn test_parameter_set_names() {
+    let fn_name = "parameter_set_names";
+    println!("[*] TEST: {fn_name}");
+    for params in ParameterSet::ALL {
+        assert_eq!(params.name().parse::<ParameterSet>(), Ok(params));
+        assert_eq!(ParameterSet::from_id(params.id()), Some(params));
+    }
+    // Distinct parameter values give distinct fingerprints
+    assert_ne!(
+        ParameterSet::Default.fingerprint(),
+        ParameterSet::TfheLib.fingerprint()
+    );
+    assert_eq!(
+        ParameterSet::Default.fingerprint(),
+        ParameterSet::Default.fingerprint()
+    );
+    assert!("unknown".parse::<ParameterSet>().is_err());
+    println!("[✓] PASS: {fn_name}\n");
+}
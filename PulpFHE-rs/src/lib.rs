@@ -0,0 +1,27 @@
+pub mod processor_boolean;
+pub mod processor_circuits;
+pub mod processor_gates;
+pub mod error;
+pub mod fixed_point;
+pub mod floating_point;
+pub mod encrypted_string;
+pub mod oblivious_memory;
+pub mod rv32i;
+pub mod pitch_trim_module;
+pub mod constant_fold_module;
+pub mod circuit_recorder;
+pub mod circuit_optimiser;
+pub mod circuit_export;
+pub mod circuit_stats;
+pub mod benchmark;
+pub mod key_manager;
+pub mod ciphertext_file;
+pub mod evaluator;
+pub mod program;
+pub mod protocol;
+pub mod server;
+pub mod client;
+pub mod register_table;
+pub mod reorder_buffer;
+pub mod reservation_stations;
+pub mod controller;
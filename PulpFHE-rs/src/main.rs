@@ -1,11 +1,217 @@
-mod processor_boolean;
-mod processor_circuits;
-mod processor_gates;
-mod pitch_trim_module;
-mod register_table;
-mod reservation_stations;
-mod controller;
-
-fn main() {
-    println!("Hello, world!");
+//! # Description
+//! Command-line front end: generate keys, encrypt integers, evaluate operations or
+//! controller programs on ciphertext files, and decrypt the results. `eval` and `run` only
+//! need the server key.
+
+use clap::{Parser, Subcommand};
+use pulpfhe::ciphertext_file::{CiphertextFile, EncryptedIntegers};
+use pulpfhe::controller::Controller;
+use pulpfhe::evaluator::{EvalOptions, Evaluator, Operation};
+use pulpfhe::key_manager::{KeyManager, ParameterSet};
+use pulpfhe::program::Program;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+#[derive(Parser)]
+#[command(
+    name = "pulpfhe",
+    about = "Encrypted integer arithmetic on TFHE boolean circuits"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generate a client and a server key
+    Keygen {
+        /// Parameter set: default, default-ks-pbs, error-prob-2-pow-165,
+        /// error-prob-2-pow-165-ks-pbs or tfhe-lib
+        #[arg(long, default_value = "default")]
+        params: ParameterSet,
+        /// Directory receiving client.key and server.key
+        #[arg(long, default_value = ".")]
+        out_dir: PathBuf,
+    },
+    /// Encrypt integers into a ciphertext file
+    Encrypt {
+        #[arg(long, default_value = "client.key")]
+        key: PathBuf,
+        #[arg(long)]
+        width: usize,
+        #[arg(long)]
+        signed: bool,
+        #[arg(short, long)]
+        output: PathBuf,
+        #[arg(required = true, allow_negative_numbers = true)]
+        values: Vec<i64>,
+    },
+    /// Evaluate one operation on ciphertext files
+    Eval {
+        /// Operation name, e.g. adder (add), comparator, scalar_mul, matvec
+        op: Operation,
+        #[arg(required = true)]
+        inputs: Vec<PathBuf>,
+        #[arg(long, default_value = "server.key")]
+        server_key: PathBuf,
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Plaintext operand of scalar operations, or the shift amount
+        #[arg(long, allow_negative_numbers = true)]
+        imm: Option<i64>,
+        /// Comparison: eq, lt, gt, geq, leq, neq or its code 0..=5
+        #[arg(long, value_parser = parse_select)]
+        select: Option<u8>,
+        /// Histogram thresholds or plaintext weights, comma separated
+        #[arg(long, value_delimiter = ',', allow_negative_numbers = true)]
+        values: Vec<i64>,
+        /// Column count of the first matrix operand
+        #[arg(long)]
+        cols: Option<usize>,
+    },
+    /// Run a controller program
+    Run {
+        program: PathBuf,
+        #[arg(long, default_value = "server.key")]
+        server_key: PathBuf,
+        /// Input register and its ciphertext file, as NAME=FILE
+        #[arg(long = "input", value_parser = parse_binding)]
+        inputs: Vec<(String, PathBuf)>,
+        /// Directory receiving NAME.ct for every output register
+        #[arg(long, default_value = ".")]
+        out_dir: PathBuf,
+        /// Mark the outputs as signed integers
+        #[arg(long)]
+        signed: bool,
+    },
+    /// Decrypt a ciphertext file and print its integers
+    Decrypt {
+        #[arg(long, default_value = "client.key")]
+        key: PathBuf,
+        file: PathBuf,
+    },
+}
+
+fn parse_select(s: &str) -> Result<u8, String> {
+    let code = match s {
+        "eq" => 0,
+        "lt" => 1,
+        "gt" => 2,
+        "geq" => 3,
+        "leq" => 4,
+        "neq" => 5,
+        _ => s.parse().map_err(|_| format!("unknown comparison `{s}`"))?,
+    };
+    if code > 5 {
+        return Err(format!("comparison code {code} is not in 0..=5"));
+    }
+    Ok(code)
+}
+
+fn parse_binding(s: &str) -> Result<(String, PathBuf), String> {
+    let (name, path) = s
+        .split_once('=')
+        .ok_or_else(|| format!("expected NAME=FILE, got `{s}`"))?;
+    Ok((name.to_string(), PathBuf::from(path)))
+}
+
+fn execute(command: Command) -> Result<(), Box<dyn Error>> {
+    match command {
+        Command::Keygen { params, out_dir } => {
+            fs::create_dir_all(&out_dir)?;
+            let (client_key, server_key, info) = KeyManager::generate(params);
+            KeyManager::save_client_key(&out_dir.join("client.key"), &client_key, &info)?;
+            KeyManager::save_server_key(&out_dir.join("server.key"), &server_key, &info)?;
+            println!(
+                "{params} key pair {:#018x} written to {}",
+                info.key_id,
+                out_dir.display()
+            );
+        }
+        Command::Encrypt {
+            key,
+            width,
+            signed,
+            output,
+            values,
+        } => {
+            if width == 0 || width > u16::MAX as usize {
+                return Err(format!("width must be 1..={}", u16::MAX).into());
+            }
+            let (client_key, info) = KeyManager::load_client_key(&key)?;
+            let integers = EncryptedIntegers::encrypt(&client_key, &info, width, signed, &values)?;
+            CiphertextFile::save(&output, &integers)?;
+        }
+        Command::Eval {
+            op,
+            inputs,
+            server_key,
+            output,
+            imm,
+            select,
+            values,
+            cols,
+        } => {
+            let (server_key, info) = KeyManager::load_server_key(&server_key)?;
+            let inputs = inputs
+                .iter()
+                .map(|path| CiphertextFile::load(path))
+                .collect::<Result<Vec<_>, _>>()?;
+            let options = EvalOptions {
+                imm,
+                select,
+                values,
+                cols,
+            };
+            let result = Evaluator::new(&server_key, info).eval(op, &inputs, &options)?;
+            CiphertextFile::save(&output, &result)?;
+        }
+        Command::Run {
+            program,
+            server_key,
+            inputs,
+            out_dir,
+            signed,
+        } => {
+            let program = Program::parse(&fs::read_to_string(&program)?)?;
+            let (server_key, info) = KeyManager::load_server_key(&server_key)?;
+            let mut values = HashMap::new();
+            for (name, path) in inputs {
+                let integers = CiphertextFile::load(&path)?;
+                integers.verify_key(&info)?;
+                integers.verify_count(1)?;
+                values.insert(name, integers.values.into_iter().next().unwrap());
+            }
+            let outputs = Controller::new(server_key).run(&program, &values)?;
+            fs::create_dir_all(&out_dir)?;
+            for name in program.outputs() {
+                let bits = outputs[name].clone();
+                let integers = EncryptedIntegers::new(bits.len(), signed, &info, vec![bits])?;
+                CiphertextFile::save(&out_dir.join(format!("{name}.ct")), &integers)?;
+            }
+        }
+        Command::Decrypt { key, file } => {
+            let (client_key, info) = KeyManager::load_client_key(&key)?;
+            let integers = CiphertextFile::load(&file)?;
+            integers.verify_key(&info)?;
+            for value in integers.decrypt(&client_key) {
+                println!("{value}");
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match execute(Cli::parse().command) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
 }
@@ -0,0 +1,157 @@
+//! # Description
+//! Oblivious memory: an array of encrypted words addressed by an encrypted index.
+//!
+//! Every access touches every word, so neither the index nor the accessed word can be
+//! told apart from the gates that are evaluated. Both accesses cost a number of gates
+//! linear in the number of words:
+//!
+//! * `read` selects the word with a tree of `e_mux` over the index bits (`len - 1`
+//!   multiplexers per word bit);
+//! * `write` decodes the index into an encrypted one-hot vector (about `2 * len` gates)
+//!   and multiplexes the new value into the selected word.
+//!
+//! Indices are unsigned, LSB first, and may have any width. An index past the last word
+//! reads as zero and writes nothing.
+
+use crate::error::PulpError;
+use crate::processor_circuits::ProcessorCircuits;
+use crate::processor_gates::ProcessorGate;
+use tfhe::boolean::prelude::*;
+
+#[cfg(test)]
+mod test_oblivious_memory;
+
+#[derive(Clone, Debug)]
+pub struct ObliviousMemory {
+    /// Bits per word
+    pub width: usize,
+    /// The words, each `width` bits, LSB first
+    pub words: Vec<Vec<Ciphertext>>,
+}
+
+impl ObliviousMemory {
+    /// A memory holding `words`, which must be at least one word of one non-zero width.
+    pub fn new(words: Vec<Vec<Ciphertext>>) -> Result<Self, PulpError> {
+        PulpError::expect_at_least("oblivious_memory", "words", 1, words.len())?;
+        let width: usize = words[0].len();
+        if width == 0 {
+            return Err(PulpError::UnsupportedWidth {
+                op: "oblivious_memory",
+                width,
+            });
+        }
+        for word in words.iter() {
+            PulpError::expect_width("oblivious_memory", "words", width, word.len())?;
+        }
+        Ok(ObliviousMemory { width, words })
+    }
+
+    /// `len` words of `width` bits, all (trivial) zero. A memory too large to allocate is an
+    /// error rather than an abort.
+    pub fn zeroed(len: usize, width: usize) -> Result<Self, PulpError> {
+        let too_large =
+            |_| PulpError::invalid("oblivious_memory", format!("cannot allocate {len} words"));
+        let mut words: Vec<Vec<Ciphertext>> = Vec::new();
+        words.try_reserve_exact(len).map_err(too_large)?;
+        for _ in 0..len {
+            let mut word: Vec<Ciphertext> = Vec::new();
+            word.try_reserve_exact(width).map_err(too_large)?;
+            word.resize(width, Ciphertext::Trivial(false));
+            words.push(word);
+        }
+        Self::new(words)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Number of index bits needed to address every word.
+    pub fn index_width(&self) -> usize {
+        (usize::BITS - self.len().saturating_sub(1).leading_zeros()).max(1) as usize
+    }
+
+    /// The word at the encrypted `index`, zero if `index` is past the last word.
+    pub fn read<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        index: &[Ciphertext],
+    ) -> Result<Vec<Ciphertext>, PulpError> {
+        Self::check_index("oblivious_read", index)?;
+        // Each level halves the candidates with one index bit, LSB first; missing words
+        // are trivial zeros whose multiplexers fold away
+        let zero: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); self.width];
+        let mut level: Vec<Vec<Ciphertext>> = self.words.clone();
+        for bit in index.iter() {
+            level = level
+                .chunks(2)
+                .map(|pair| {
+                    let odd: &[Ciphertext] = pair.get(1).unwrap_or(&zero);
+                    let mut word: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); self.width];
+                    processor.e_mux(sk, bit, odd, &pair[0], &mut word)?;
+                    Ok(word)
+                })
+                .collect::<Result<_, PulpError>>()?;
+        }
+        Ok(level.swap_remove(0))
+    }
+
+    /// Stores `value` at the encrypted `index`; nothing changes if `index` is past the last
+    /// word.
+    pub fn write<P: ProcessorGate + ProcessorCircuits>(
+        &mut self,
+        processor: &P,
+        sk: &ServerKey,
+        index: &[Ciphertext],
+        value: &[Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_index("oblivious_write", index)?;
+        PulpError::expect_width("oblivious_write", "value", self.width, value.len())?;
+        let select: Vec<Ciphertext> = self.decode(processor, sk, index);
+        for (word, hot) in self.words.iter_mut().zip(select.iter()) {
+            let old: Vec<Ciphertext> = word.clone();
+            processor.e_mux(sk, hot, value, &old, word)?;
+        }
+        Ok(())
+    }
+
+    /// One encrypted bit per word, set only for the word at `index`.
+    pub fn decode<P: ProcessorGate>(
+        &self,
+        processor: &P,
+        sk: &ServerKey,
+        index: &[Ciphertext],
+    ) -> Vec<Ciphertext> {
+        // After `j` index bits, entry `v` is set when the low `j` bits of the index are `v`;
+        // entries past the last word are never needed
+        let mut select: Vec<Ciphertext> = vec![Ciphertext::Trivial(true)];
+        for (j, bit) in index.iter().enumerate() {
+            let clear: Ciphertext = processor.e_not_bit(sk, bit);
+            let half: usize = 1usize.checked_shl(j as u32).unwrap_or(usize::MAX);
+            let mut next: Vec<Ciphertext> = select
+                .iter()
+                .map(|hot| processor.e_and_bit(sk, hot, &clear))
+                .collect();
+            for (v, hot) in select.iter().enumerate() {
+                if v.saturating_add(half) < self.len() {
+                    next.push(processor.e_and_bit(sk, hot, bit));
+                }
+            }
+            select = next;
+        }
+        select.resize(self.len(), Ciphertext::Trivial(false));
+        select
+    }
+
+    fn check_index(op: &'static str, index: &[Ciphertext]) -> Result<(), PulpError> {
+        if index.is_empty() {
+            return Err(PulpError::UnsupportedWidth { op, width: 0 });
+        }
+        Ok(())
+    }
+}
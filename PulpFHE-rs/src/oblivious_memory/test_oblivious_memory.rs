@@ -0,0 +1,125 @@
+#![allow(warnings)]
+use super::*; // Imports items from the parent module
+use crate::key_manager::KeyManager;
+use crate::processor_boolean::ProcessorBoolean;
+use serial_test::serial;
+
+fn encode_encrypt(num: u64, size: usize, ck: &ClientKey) -> Vec<Ciphertext> {
+    (0..size).map(|i| ck.encrypt((num >> i) & 1 == 1)).collect()
+}
+
+fn decrypt_decode(ciphertexts: &[Ciphertext], ck: &ClientKey) -> u64 {
+    ciphertexts
+        .iter()
+        .enumerate()
+        .map(|(i, bit)| (ck.decrypt(bit) as u64) << i)
+        .sum()
+}
+
+#[test]
+#[serial]
+fn test_oblivious_read_write() {
+    let fn_name = "oblivious_read_write";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let server = ProcessorBoolean;
+
+    let values: [u64; 5] = [3, 14, 15, 9, 2];
+    let words: Vec<Vec<Ciphertext>> = values
+        .iter()
+        .map(|&v| encode_encrypt(v, 4, client_key))
+        .collect();
+    let mut memory = ObliviousMemory::new(words).unwrap();
+    assert_eq!(memory.index_width(), 3);
+
+    for index in 0..5u64 {
+        let ct_index = encode_encrypt(index, 3, client_key);
+        let word = memory.read(&server, server_key, &ct_index).unwrap();
+        println!("\t memory[{index}] = {}", decrypt_decode(&word, client_key));
+        assert_eq!(decrypt_decode(&word, client_key), values[index as usize]);
+    }
+
+    let ct_index = encode_encrypt(3, 3, client_key);
+    memory
+        .write(
+            &server,
+            server_key,
+            &ct_index,
+            &encode_encrypt(6, 4, client_key),
+        )
+        .unwrap();
+    // Past the last word: reads are zero and writes change nothing
+    let past = encode_encrypt(6, 3, client_key);
+    memory
+        .write(
+            &server,
+            server_key,
+            &past,
+            &encode_encrypt(5, 4, client_key),
+        )
+        .unwrap();
+    let read = memory.read(&server, server_key, &past).unwrap();
+    assert_eq!(decrypt_decode(&read, client_key), 0);
+
+    let stored: Vec<u64> = memory
+        .words
+        .iter()
+        .map(|word| decrypt_decode(word, client_key))
+        .collect();
+    println!("\t memory = {stored:?}");
+    assert_eq!(stored, vec![3, 14, 15, 6, 2]);
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_oblivious_decode() {
+    let fn_name = "oblivious_decode";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let server = ProcessorBoolean;
+
+    // A wider index than needed still selects exactly one word
+    let memory = ObliviousMemory::zeroed(6, 2).unwrap();
+    for index in [0u64, 4, 5, 9] {
+        let select = memory.decode(&server, server_key, &encode_encrypt(index, 4, client_key));
+        let hot: Vec<bool> = select.iter().map(|bit| client_key.decrypt(bit)).collect();
+        let expected: Vec<bool> = (0..6).map(|i| i == index).collect();
+        assert_eq!(hot, expected, "index {index}");
+    }
+
+    assert_eq!(
+        ObliviousMemory::new(vec![vec![Ciphertext::Trivial(false); 2], vec![]]).unwrap_err(),
+        PulpError::WidthMismatch {
+            op: "oblivious_memory",
+            operand: "words",
+            expected: 2,
+            found: 0
+        }
+    );
+    assert!(matches!(
+        ObliviousMemory::zeroed(usize::MAX, 8),
+        Err(PulpError::InvalidArgument {
+            op: "oblivious_memory",
+            ..
+        })
+    ));
+    let mut memory = ObliviousMemory::zeroed(2, 2).unwrap();
+    assert!(matches!(
+        memory.write(
+            &server,
+            server_key,
+            &[],
+            &vec![Ciphertext::Trivial(true); 2]
+        ),
+        Err(PulpError::UnsupportedWidth { width: 0, .. })
+    ));
+    assert!(matches!(
+        memory.write(&server, server_key, &[Ciphertext::Trivial(true)], &[]),
+        Err(PulpError::WidthMismatch {
+            operand: "value",
+            ..
+        })
+    ));
+    println!("[✓] PASS: {fn_name}\n");
+}
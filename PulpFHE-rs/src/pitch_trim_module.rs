@@ -1,6 +1,11 @@
 use tfhe::boolean::ciphertext::Ciphertext;
 use tfhe::boolean::prelude::ServerKey;
 use tfhe::boolean::server_key::RefreshEngine;
+use std::cell::Cell;
+
+thread_local! {
+    static CALLS: Cell<usize> = const { Cell::new(0) };
+}
 
 pub struct PitchTrimModule;
 
@@ -8,6 +13,7 @@ impl PitchTrimModule {
     pub fn pitch_trim(sk: &ServerKey, ctxt: &mut [Ciphertext]) -> Vec<Ciphertext> {
         let mut fresh: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ctxt.len()];
         for c in ctxt {
+            CALLS.with(|n| n.set(n.get() + 1));
             let f = sk.bootstrap(c);
             fresh.push(f);
         }
@@ -15,7 +21,13 @@ impl PitchTrimModule {
     }
 
     pub fn pitch_trim_bit(sk: &ServerKey, ctxt: &Ciphertext) -> Ciphertext {
+        CALLS.with(|n| n.set(n.get() + 1));
         let mut fresh: Ciphertext = sk.bootstrap(ctxt);
         fresh
     }
-}
\ No newline at end of file
+
+    /// Number of bootstraps performed on this thread so far.
+    pub fn calls() -> usize {
+        CALLS.with(|n| n.get())
+    }
+}
@@ -3,20 +3,32 @@
 //! This module includes the implementation of the circuits (functional units) that
 //! the processor can perform on encrypted data. The gates' operation is not bootstrapped.
 //! Bootstrapping can be performed manually by invoking the ` pitch_trim () ` function.
+//! Gates with a trivial (constant) operand are folded by `ConstantFoldModule` and are
+//! neither evaluated nor bootstrapped.
 //!
 //! # Available Circuits
-//!     * BITWISE: and, or, xor, not, nand, nor, xnor
-//!     * MATH: add, sub, mul, div, mod, shl, shr, rotl, rotr
-//!     * COMPARISON: eq, neq, lt, lte, gt, gte, min, max, relu
-//!     * COMPLEX: blake3, mean, variance, standard_deviation
+//! * BITWISE: and, or, xor, not, nand, nor, xnor
+//! * MATH: add, sub, mul, div, mod, shl, shr, rotl, rotr
+//! * COMPARISON: eq, neq, lt, lte, gt, gte, min, max, relu, scalar_cmp
+//! * SCALAR: scalar_add, scalar_mul, scalar_div, scalar_and, scalar_or, scalar_xor
+//! * COMPLEX: blake3, mean, variance, standard_deviation, histogram
+//! * LINEAR ALGEBRA: dot_product, matvec, matmul (encrypted or plaintext weights)
+//! * BIT COUNTING: clz, ctz, popcount, parity, bit_reverse, hamming_distance,
+//!   match_below_threshold
+//! * TABLES: lookup_table
+//!
 //! The following piece of code shows how to generate keys and run a small Boolean circuit
 //! homomorphically.
 //!
 
+use crate::circuit_recorder::{CircuitRecorder, NodeOp};
+use crate::circuit_stats::CircuitStats;
+use crate::constant_fold_module::{ConstantFoldModule, GateKind};
+use crate::error::PulpError;
 use crate::pitch_trim_module::PitchTrimModule;
 use crate::processor_circuits::ProcessorCircuits;
 use crate::processor_gates::ProcessorGate;
-use std::process::exit;
+use std::collections::HashMap;
 use tfhe::boolean::prelude::*;
 
 #[cfg(test)]
@@ -25,13 +37,151 @@ mod test_processor_boolean_16;
 mod test_processor_boolean_32;
 #[cfg(test)]
 mod test_processor_boolean_8;
+#[cfg(test)]
+mod test_processor_boolean_widths;
 
 pub struct ProcessorBoolean;
-use std::time::{Duration, Instant};
 impl ProcessorBoolean {
     pub fn new() -> Self {
         ProcessorBoolean
     }
+
+    /// Runs `circuit` in instrumented mode and returns the per-gate-type counts, the number
+    /// of `pitch_trim_bit` calls, the number of gates folded away because of trivial operands
+    /// and the logical depth of the call. `inputs` are the encrypted operands; the closure
+    /// returns the circuit outputs, which the depth is measured to. Instrumented calls
+    /// cannot be nested: one made from inside another `circuit` is an error.
+    pub fn instrumented<F>(
+        &self,
+        inputs: &[&[Ciphertext]],
+        circuit: F,
+    ) -> Result<CircuitStats, PulpError>
+    where
+        F: FnOnce(&Self) -> Vec<Ciphertext>,
+    {
+        if CircuitRecorder::is_recording() {
+            return Err(PulpError::invalid(
+                "instrumented",
+                "instrumented circuit calls cannot be nested",
+            ));
+        }
+        let pitch_trims: usize = PitchTrimModule::calls();
+        let folded: u64 = ConstantFoldModule::saved().gates;
+
+        CircuitRecorder::start(inputs);
+        let outputs = circuit(self);
+        let net = CircuitRecorder::finish(&outputs);
+
+        let mut stats = CircuitStats::from_netlist(&net);
+        stats.pitch_trims = PitchTrimModule::calls() - pitch_trims;
+        stats.trivial_gates = (ConstantFoldModule::saved().gates - folded) as usize;
+        Ok(stats)
+    }
+
+    /// Checks the operands of a one-operand gate or circuit: `result` at least as wide as `a`.
+    fn check_unary(
+        op: &'static str,
+        a: &[Ciphertext],
+        result: &[Ciphertext],
+    ) -> Result<(), PulpError> {
+        PulpError::expect_at_least(op, "result", a.len(), result.len())
+    }
+
+    /// Checks the operands of a two-operand gate or circuit: `b` as wide as `a` and `result`
+    /// at least as wide.
+    fn check_binary(
+        op: &'static str,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        result: &[Ciphertext],
+    ) -> Result<(), PulpError> {
+        PulpError::expect_width(op, "b", a.len(), b.len())?;
+        Self::check_unary(op, a, result)
+    }
+
+    /// Checks that `index_low..index_high` is a range of every operand in `operands`.
+    fn check_range(
+        op: &'static str,
+        operands: &[(&'static str, usize)],
+        index_low: usize,
+        index_high: usize,
+    ) -> Result<(), PulpError> {
+        if index_low > index_high {
+            return Err(PulpError::invalid(
+                op,
+                format!("index_low = {index_low} is above index_high = {index_high}"),
+            ));
+        }
+        for (operand, len) in operands {
+            PulpError::expect_at_least(op, operand, index_high, *len)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that a circuit which reads the sign (or any) bit gets at least one bit.
+    fn check_nonempty(op: &'static str, a: &[Ciphertext]) -> Result<(), PulpError> {
+        if a.is_empty() {
+            return Err(PulpError::UnsupportedWidth { op, width: 0 });
+        }
+        Ok(())
+    }
+
+    /// Checks that `v` holds at least one element and that all elements have the same
+    /// non-zero width, which is returned.
+    fn check_elements(
+        op: &'static str,
+        operand: &'static str,
+        v: &[&[Ciphertext]],
+    ) -> Result<usize, PulpError> {
+        PulpError::expect_at_least(op, operand, 1, v.len())?;
+        Self::check_nonempty(op, v[0])?;
+        for x in v.iter() {
+            PulpError::expect_width(op, operand, v[0].len(), x.len())?;
+        }
+        Ok(v[0].len())
+    }
+
+    /// Checks that every element of `v` is `width` bits wide.
+    fn check_element_width(
+        op: &'static str,
+        operand: &'static str,
+        v: &[&[Ciphertext]],
+        width: usize,
+    ) -> Result<(), PulpError> {
+        for x in v.iter() {
+            PulpError::expect_width(op, operand, width, x.len())?;
+        }
+        Ok(())
+    }
+
+    /// Checks the inputs of the statistics circuits: `count` between 1 and `a.len()`, and
+    /// the first `count` elements of one width of at least 2 bits, which is returned.
+    fn check_samples(
+        op: &'static str,
+        a: &[&[Ciphertext]],
+        count: usize,
+    ) -> Result<usize, PulpError> {
+        if count == 0 || count > a.len() {
+            return Err(PulpError::invalid(
+                op,
+                format!("count = {count} is not between 1 and {}", a.len()),
+            ));
+        }
+        let width: usize = Self::check_elements(op, "a", &a[..count])?;
+        if width < 2 {
+            return Err(PulpError::UnsupportedWidth { op, width });
+        }
+        Ok(width)
+    }
+
+    /// Checks a comparison selector of `comparator` and `scalar_cmp`.
+    fn check_select(op: &'static str, select: u8) -> Result<(), PulpError> {
+        if select > 5 {
+            return Err(PulpError::invalid(op, format!("unknown comparison selector {select}")));
+        }
+        Ok(())
+    }
+
     /// A helper function for performing division and modulo operations.
     fn e_shl_p(&self, a: &[Ciphertext], LSB: &Ciphertext, result: &mut [Ciphertext]) {
         for i in 1..a.len() {
@@ -40,6 +190,204 @@ impl ProcessorBoolean {
         result[0] = LSB.clone();
     }
 
+    /// Evaluates a bootstrapped two-input gate, or folds it away when an operand is trivial.
+    fn e_gate(&self, sk: &ServerKey, gate: GateKind, a: &Ciphertext, b: &Ciphertext) -> Ciphertext {
+        if let Some(folded) = ConstantFoldModule::fold_gate(sk, gate, a, b) {
+            return folded;
+        }
+        let result: Ciphertext = match gate {
+            GateKind::And => sk.and(a, b),
+            GateKind::Or => sk.or(a, b),
+            GateKind::Xor => sk.xor(a, b),
+            GateKind::Nand => sk.nand(a, b),
+            GateKind::Nor => sk.nor(a, b),
+            GateKind::Xnor => sk.xnor(a, b),
+        };
+        let result: Ciphertext = PitchTrimModule::pitch_trim_bit(sk, &result);
+        CircuitRecorder::record(NodeOp::Gate(gate), &[a, b], &result);
+        result
+    }
+
+    /// Evaluates a negation, which needs no bootstrapping.
+    fn e_not_gate(&self, sk: &ServerKey, a: &Ciphertext) -> Ciphertext {
+        let result: Ciphertext = sk.not(a);
+        CircuitRecorder::record(NodeOp::Not, &[a], &result);
+        result
+    }
+
+    /// Evaluates a multiplexer, or folds it away when the selector or both branches are trivial.
+    fn e_mux_gate(
+        &self,
+        sk: &ServerKey,
+        selector: &Ciphertext,
+        ct_then: &Ciphertext,
+        ct_else: &Ciphertext,
+    ) -> Ciphertext {
+        if let Some(folded) = ConstantFoldModule::fold_mux(sk, selector, ct_then, ct_else) {
+            return folded;
+        }
+        let result: Ciphertext = sk.mux(selector, ct_then, ct_else);
+        CircuitRecorder::record(NodeOp::Mux, &[selector, ct_then, ct_else], &result);
+        result
+    }
+
+    /// Sign-extends (or truncates) a two's complement value to `size` bits.
+    fn e_sign_extend(&self, a: &[Ciphertext], size: usize) -> Vec<Ciphertext> {
+        let mut result: Vec<Ciphertext> = vec![a[a.len() - 1].clone(); size];
+        for i in 0..size.min(a.len()) {
+            result[i] = a[i].clone();
+        }
+        result
+    }
+
+    /// Number of extra accumulator bits needed to sum `count` values without overflow.
+    fn ceil_log2(count: usize) -> usize {
+        (usize::BITS - count.saturating_sub(1).leading_zeros()) as usize
+    }
+
+    /// Multiplexer tree selecting `leaves[a]`, splitting on the most significant index
+    /// bit first. Constant leaves give trivial bits and equal halves skip the multiplexer;
+    /// subtrees already in `shared` are reused.
+    fn lookup_subtree(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        leaves: &[bool],
+        shared: &mut HashMap<Vec<bool>, Ciphertext>,
+    ) -> Ciphertext {
+        if leaves.iter().all(|&leaf| leaf == leaves[0]) {
+            return Ciphertext::Trivial(leaves[0]);
+        }
+        if let Some(subtree) = shared.get(leaves) {
+            return subtree.clone();
+        }
+        let half: usize = leaves.len() / 2;
+        let (low, high) = leaves.split_at(half);
+        let subtree: Ciphertext = if low == high {
+            self.lookup_subtree(sk, a, low, shared)
+        } else {
+            let ct_low: Ciphertext = self.lookup_subtree(sk, a, low, shared);
+            let ct_high: Ciphertext = self.lookup_subtree(sk, a, high, shared);
+            let bit: usize = half.trailing_zeros() as usize;
+            self.e_mux_bit(sk, &a[bit], &ct_high, &ct_low)
+        };
+        shared.insert(leaves.to_vec(), subtree.clone());
+        subtree
+    }
+
+    /// Number of bits needed to hold the unsigned count `n`, i.e. `floor(log2(n)) + 1`.
+    fn count_width(n: usize) -> usize {
+        (usize::BITS - n.leading_zeros()) as usize
+    }
+
+    /// Number of bits needed to hold `w` in two's complement.
+    fn signed_width(w: i64) -> usize {
+        let magnitude = if w < 0 { !w } else { w };
+        (65 - magnitude.leading_zeros()) as usize
+    }
+
+    /// Recodes `w` into canonical signed digits (LSB first, each -1, 0 or 1), which
+    /// minimises the number of non-zero digits and therefore the shift-and-add cost.
+    fn csd_digits(w: i64) -> Vec<i8> {
+        let mut digits: Vec<i8> = Vec::new();
+        let mut x: i128 = w as i128;
+        while x != 0 {
+            if x & 1 == 1 {
+                let d: i8 = if x.rem_euclid(4) == 1 { 1 } else { -1 };
+                x -= d as i128;
+                digits.push(d);
+            } else {
+                digits.push(0);
+            }
+            x >>= 1;
+        }
+        digits
+    }
+
+    /// Encrypted dot product accumulated in `acc_size` bits.
+    fn dot_into(
+        &self,
+        sk: &ServerKey,
+        a: &[&[Ciphertext]],
+        b: &[&[Ciphertext]],
+        acc_size: usize,
+    ) -> Result<Vec<Ciphertext>, PulpError> {
+        PulpError::expect_width("dot_product", "b", a.len(), b.len())?;
+        let product_size: usize = (2 * a[0].len()).min(acc_size);
+        let mut acc: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); acc_size];
+        let mut tmp_acc: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); acc_size];
+        let mut product: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); product_size];
+
+        for i in 0..a.len() {
+            // Signed operands extended to the product width give the exact signed product
+            let a_ext = self.e_sign_extend(a[i], product_size);
+            let b_ext = self.e_sign_extend(b[i], product_size);
+            self.multiplier(sk, &a_ext, &b_ext, &mut product)?;
+
+            let product_ext = self.e_sign_extend(&product, acc_size);
+            if i == 0 {
+                self.copy_to_from(&mut acc, &product_ext);
+            } else {
+                self.adder(sk, &acc, &product_ext, &mut tmp_acc)?;
+                self.copy_to_from(&mut acc, &tmp_acc);
+            }
+        }
+        Ok(acc)
+    }
+
+    /// Dot product with plaintext weights accumulated in `acc_size` bits using shift-and-add.
+    fn plain_dot_into(
+        &self,
+        sk: &ServerKey,
+        a: &[&[Ciphertext]],
+        w: &[i64],
+        acc_size: usize,
+    ) -> Result<Vec<Ciphertext>, PulpError> {
+        PulpError::expect_width("dot_product_plain", "w", a.len(), w.len())?;
+        let mut acc: Option<Vec<Ciphertext>> = None;
+        let mut tmp: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); acc_size];
+
+        for i in 0..a.len() {
+            let a_ext = self.e_sign_extend(a[i], acc_size);
+            for (j, d) in Self::csd_digits(w[i]).iter().enumerate() {
+                // Digits at or above the accumulator width vanish modulo 2^acc_size
+                if *d == 0 || j >= acc_size {
+                    continue;
+                }
+                let shifted_size = acc_size - j;
+                match acc.as_mut() {
+                    None => {
+                        // First term: the accumulator is still zero
+                        let mut first: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); acc_size];
+                        if *d > 0 {
+                            self.copy_to_from(&mut first[j..], &a_ext[..shifted_size]);
+                        } else {
+                            let zero: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); shifted_size];
+                            self.subtracter(sk, &zero, &a_ext[..shifted_size], &mut first[j..])?;
+                        }
+                        acc = Some(first);
+                    }
+                    Some(acc) => {
+                        // The low j bits of (a << j) are zero, so only acc[j..] changes
+                        if *d > 0 {
+                            self.adder(sk, &acc[j..], &a_ext[..shifted_size], &mut tmp[..shifted_size])?;
+                        } else {
+                            self.subtracter(sk, &acc[j..], &a_ext[..shifted_size], &mut tmp[..shifted_size])?;
+                        }
+                        self.copy_to_from(&mut acc[j..], &tmp[..shifted_size]);
+                    }
+                }
+            }
+        }
+        Ok(acc.unwrap_or_else(|| vec![Ciphertext::Trivial(false); acc_size]))
+    }
+
+    /// Accumulator width for a plaintext-weighted sum of `count` terms.
+    fn plain_acc_size(size: usize, w: &[i64], count: usize) -> usize {
+        let weight_size = w.iter().map(|w| Self::signed_width(*w)).max().unwrap_or(1);
+        size + weight_size + Self::ceil_log2(count)
+    }
+
     /// Tracking functions
 
     fn ptxt_and(&self, a: &[bool], b: &[bool], result: &mut [bool]) {
@@ -510,409 +858,32 @@ impl ProcessorBoolean {
         // Subtract the final term from the result
         self.sub_vec(sk, result, &term);
     }*/
+}
 
-    fn e_and_bench(
-        &self,
-        sk: &ServerKey,
-        a: &[Ciphertext],
-        b: &[Ciphertext],
-        result: &mut [Ciphertext],
-    ) {
-        let size: usize = a.len();
-
-        println!("====================AND====================");
-        let start = Instant::now();
-        for i in 0..size {
-            result[i] = sk.and(&a[i], &b[i]);
-        }
-        let duration1 = start.elapsed();
-
-        let start = Instant::now();
-        for i in 0..size {
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &result[i]);
-        }
-        let duration2 = start.elapsed();
-
-        println!(
-            "Gate Operation Time: {:.3?}, Bootstrapping Time: {:.3?}",
-            duration1.as_millis(),
-            duration2.as_millis()
-        );
-        if duration1.as_millis() == 0 {
-            println!(
-                "Bootstrapping / Gate (0): {:.3?}",
-                duration2.as_millis()
-            );
-        }else {
-            println!(
-                "Bootstrapping / Gate: {:.3?}",
-                duration2.as_millis() / duration1.as_millis()
-            );
-        }
-        println!("==========================================\n");
-    }
-    fn e_or_bench(
-        &self,
-        sk: &ServerKey,
-        a: &[Ciphertext],
-        b: &[Ciphertext],
-        result: &mut [Ciphertext],
-    ) {
-        let size: usize = a.len();
-        println!("====================OR====================");
-        let start = Instant::now();
-        for i in 0..size {
-            result[i] = sk.or(&a[i], &b[i]);
-        }
-        let duration1 = start.elapsed();
-
-        let start = Instant::now();
-        for i in 0..size {
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &result[i]);
-        }
-        let duration2 = start.elapsed();
-
-        println!(
-            "Gate Operation Time: {:.3?}, Bootstrapping Time: {:.3?}",
-            duration1.as_millis(),
-            duration2.as_millis()
-        );
-        if duration1.as_millis() == 0 {
-            println!(
-                "Bootstrapping / Gate (0): {:.3?}",
-                duration2.as_millis()
-            );
-        }else {
-            println!(
-                "Bootstrapping / Gate: {:.3?}",
-                duration2.as_millis() / duration1.as_millis()
-            );
-        }
-        println!("==========================================\n");
-    }
-    fn e_xor_bench(
-        &self,
-        sk: &ServerKey,
-        a: &[Ciphertext],
-        b: &[Ciphertext],
-        result: &mut [Ciphertext],
-    ) {
-        let size: usize = a.len();
-        println!("====================XOR====================");
-        let start = Instant::now();
-
-        for i in 0..size {
-            result[i] = sk.xor(&a[i], &b[i]);
-        }
-        let duration1 = start.elapsed();
-
-        let start = Instant::now();
-        for i in 0..size {
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &result[i]);
-        }
-        let duration2 = start.elapsed();
-
-        println!(
-            "Gate Operation Time: {:.3?}, Bootstrapping Time: {:.3?}",
-            duration1.as_millis(),
-            duration2.as_millis()
-        );
-        if duration1.as_millis() == 0 {
-            println!(
-                "Bootstrapping / Gate (0): {:.3?}",
-                duration2.as_millis()
-            );
-        }else {
-            println!(
-                "Bootstrapping / Gate: {:.3?}",
-                duration2.as_millis() / duration1.as_millis()
-            );
-        }
-        println!("==========================================\n");
-    }
-    fn e_nand_bench(
-        &self,
-        sk: &ServerKey,
-        a: &[Ciphertext],
-        b: &[Ciphertext],
-        result: &mut [Ciphertext],
-    ) {
-        let size: usize = a.len();
-        println!("====================NAND====================");
-
-        let start = Instant::now();
-        for i in 0..size {
-            result[i] = sk.nand(&a[i], &b[i]);
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &mut result[i]);
-        }
-        let duration1 = start.elapsed();
-
-        let start = Instant::now();
-        for i in 0..size {
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &result[i]);
-        }
-        let duration2 = start.elapsed();
-
-        println!(
-            "Gate Operation Time: {:.3?}, Bootstrapping Time: {:.3?}",
-            duration1.as_millis(),
-            duration2.as_millis()
-        );
-        if duration1.as_millis() == 0 {
-            println!(
-                "Bootstrapping / Gate (0): {:.3?}",
-                duration2.as_millis()
-            );
-        }else {
-            println!(
-                "Bootstrapping / Gate: {:.3?}",
-                duration2.as_millis() / duration1.as_millis()
-            );
-        }
-        println!("==========================================\n");
-    }
-
-    fn e_nor_bench(
-        &self,
-        sk: &ServerKey,
-        a: &[Ciphertext],
-        b: &[Ciphertext],
-        result: &mut [Ciphertext],
-    ) {
-        let size: usize = a.len();
-        println!("====================NOR====================");
-
-        let start = Instant::now();
-        for i in 0..size {
-            result[i] = sk.nor(&a[i], &b[i]);
-        }
-
-        let duration1 = start.elapsed();
-
-        let start = Instant::now();
-        for i in 0..size {
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &result[i]);
-        }
-        let duration2 = start.elapsed();
-
-        println!(
-            "Gate Operation Time: {:.3?}, Bootstrapping Time: {:.3?}",
-            duration1.as_millis(),
-            duration2.as_millis()
-        );
-        if duration1.as_millis() == 0 {
-            println!(
-                "Bootstrapping / Gate (0): {:.3?}",
-                duration2.as_millis()
-            );
-        }else {
-            println!(
-                "Bootstrapping / Gate: {:.3?}",
-                duration2.as_millis() / duration1.as_millis()
-            );
-        }
-        println!("==========================================\n");
-    }
-
-    fn e_xnor_bench(
-        &self,
-        sk: &ServerKey,
-        a: &[Ciphertext],
-        b: &[Ciphertext],
-        result: &mut [Ciphertext],
-    ) {
-        let size: usize = a.len();
-        println!("====================XNOR====================");
-
-        let start = Instant::now();
-        for i in 0..size {
-            result[i] = sk.xnor(&a[i], &b[i]);
-        }
-        let duration1 = start.elapsed();
-
-        let start = Instant::now();
-        for i in 0..size {
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &result[i]);
-        }
-        let duration2 = start.elapsed();
-
-        println!(
-            "Gate Operation Time: {:.3?}, Bootstrapping Time: {:.3?}",
-            duration1.as_millis(),
-            duration2.as_millis()
-        );
-        if duration1.as_millis() == 0 {
-            println!(
-                "Bootstrapping / Gate (0): {:.3?}",
-                duration2.as_millis()
-            );
-        }else {
-            println!(
-                "Bootstrapping / Gate: {:.3?}",
-                duration2.as_millis() / duration1.as_millis()
-            );
-        }
-        println!("==========================================\n");
-    }
-
-
-
-    fn e_and_bench_nobootstrapping(
-        &self,
-        sk: &ServerKey,
-        a: &[Ciphertext],
-        b: &[Ciphertext],
-        result: &mut [Ciphertext],
-    ) {
-        let size: usize = a.len();
-
-        println!("====================AND====================");
-        let start = Instant::now();
-        for i in 0..size {
-            result[i] = sk.and(&a[i], &b[i]);
-        }
-        let duration1 = start.elapsed();
-
-        println!(
-            "Gate Operation Time: {:.3?}",
-            duration1,
-        );
-        println!("==========================================\n");
-    }
-    fn e_or_bench_nobootstrapping(
-        &self,
-        sk: &ServerKey,
-        a: &[Ciphertext],
-        b: &[Ciphertext],
-        result: &mut [Ciphertext],
-    ) {
-        let size: usize = a.len();
-        println!("====================OR====================");
-        let start = Instant::now();
-        for i in 0..size {
-            result[i] = sk.or(&a[i], &b[i]);
-        }
-        let duration1 = start.elapsed();
-
-        println!(
-            "Gate Operation Time: {:.3?}",
-            duration1,
-        );
-        
-        println!("==========================================\n");
-    }
-    fn e_xor_bench_nobootstrapping(
-        &self,
-        sk: &ServerKey,
-        a: &[Ciphertext],
-        b: &[Ciphertext],
-        result: &mut [Ciphertext],
-    ) {
-        let size: usize = a.len();
-        println!("====================XOR====================");
-        let start = Instant::now();
-
-        for i in 0..size {
-            result[i] = sk.xor(&a[i], &b[i]);
-        }
-        let duration1 = start.elapsed();
-
-        println!(
-            "Gate Operation Time: {:.3?}",
-            duration1,
-        );
-        println!("==========================================\n");
-    }
-    fn e_nand_bench_nobootstrapping(
-        &self,
-        sk: &ServerKey,
-        a: &[Ciphertext],
-        b: &[Ciphertext],
-        result: &mut [Ciphertext],
-    ) {
-        let size: usize = a.len();
-        println!("====================NAND====================");
-
-        let start = Instant::now();
-        for i in 0..size {
-            result[i] = sk.nand(&a[i], &b[i]);
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &mut result[i]);
-        }
-        let duration1 = start.elapsed();
-
-        println!(
-            "Gate Operation Time: {:.3?}",
-            duration1,
-        );
-        println!("==========================================\n");
-    }
-
-    fn e_nor_bench_nobootstrapping(
-        &self,
-        sk: &ServerKey,
-        a: &[Ciphertext],
-        b: &[Ciphertext],
-        result: &mut [Ciphertext],
-    ) {
-        let size: usize = a.len();
-        println!("====================NOR====================");
-
-        let start = Instant::now();
-        for i in 0..size {
-            result[i] = sk.nor(&a[i], &b[i]);
-        }
-
-        let duration1 = start.elapsed();
-
-        println!(
-            "Gate Operation Time: {:.3?}",
-            duration1,
-        );
-        println!("==========================================\n");
-    }
-
-    fn e_xnor_bench_nobootstrapping(
+impl ProcessorGate for ProcessorBoolean {
+    fn e_and(
         &self,
         sk: &ServerKey,
         a: &[Ciphertext],
         b: &[Ciphertext],
         result: &mut [Ciphertext],
-    ) {
-        let size: usize = a.len();
-        println!("====================XNOR====================");
-
-        let start = Instant::now();
-        for i in 0..size {
-            result[i] = sk.xnor(&a[i], &b[i]);
-        }
-        let duration1 = start.elapsed();
-
-        println!(
-            "Gate Operation Time: {:.3?}",
-            duration1,
-        );
-        println!("==========================================\n");
-    }
-}
-
-impl ProcessorGate for ProcessorBoolean {
-    fn e_and(&self, sk: &ServerKey, a: &[Ciphertext], b: &[Ciphertext], result: &mut [Ciphertext]) {
+    ) -> Result<(), PulpError> {
+        Self::check_binary("e_and", a, b, result)?;
         let size: usize = a.len();
 
         for i in 0..size {
-            result[i] = sk.and(&a[i], &b[i]);
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &result[i]);
+            result[i] = self.e_gate(sk, GateKind::And, &a[i], &b[i]);
         }
 
         // #[cfg(debug_assertions)]
         // {
         //     println!("DEBUG: `e_and` -> len(a)={}, len(b)={}", size, b.len());
         // }
+        Ok(())
     }
 
     fn e_and_bit(&self, sk: &ServerKey, a: &Ciphertext, b: &Ciphertext) -> Ciphertext {
-        let mut result: Ciphertext = sk.and(a, b);
-        result = PitchTrimModule::pitch_trim_bit(sk, &mut result);
+        let result: Ciphertext = self.e_gate(sk, GateKind::And, a, b);
 
         // #[cfg(debug_assertions)]
         // {
@@ -929,17 +900,16 @@ impl ProcessorGate for ProcessorBoolean {
         result: &mut [Ciphertext],
         index_low: usize,
         index_high: usize,
-    ) {
-        assert!(
-            index_low < index_high,
-            "index_low = {}, index_high = {}",
+    ) -> Result<(), PulpError> {
+        Self::check_range(
+            "e_and_range",
+            &[("a", a.len()), ("b", b.len()), ("result", result.len())],
             index_low,
-            index_high
-        );
+            index_high,
+        )?;
 
         for i in index_low..index_high {
-            result[i] = sk.and(&a[i], &b[i]);
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &mut result[i]);
+            result[i] = self.e_gate(sk, GateKind::And, &a[i], &b[i]);
         }
 
         // #[cfg(debug_assertions)]
@@ -952,6 +922,7 @@ impl ProcessorGate for ProcessorBoolean {
         //         index_high
         //     );
         // }
+        Ok(())
     }
 
     /// Compute the encrypted OR gate on the `Ciphertext` vectors `a` and `b`. The result is stored in
@@ -963,22 +934,28 @@ impl ProcessorGate for ProcessorBoolean {
     /// * `b`: &[Ciphertext] - The second operand, a vector of ciphertexts, each element encrypting a single bit.
     /// * `result`: &mut [Ciphertext] - A vector holding the result of the operation.
     ///
-    fn e_or(&self, sk: &ServerKey, a: &[Ciphertext], b: &[Ciphertext], result: &mut [Ciphertext]) {
+    fn e_or(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_binary("e_or", a, b, result)?;
         let size: usize = a.len();
         for i in 0..size {
-            result[i] = sk.or(&a[i], &b[i]);
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &mut result[i]);
+            result[i] = self.e_gate(sk, GateKind::Or, &a[i], &b[i]);
         }
 
         // #[cfg(debug_assertions)]
         // {
         //     println!("DEBUG: `e_or` -> len(a)={}, len(b)={}", size, b.len());
         // }
+        Ok(())
     }
 
     fn e_or_bit(&self, sk: &ServerKey, a: &Ciphertext, b: &Ciphertext) -> Ciphertext {
-        let mut result: Ciphertext = sk.or(a, b);
-        result = PitchTrimModule::pitch_trim_bit(sk, &mut result);
+        let result: Ciphertext = self.e_gate(sk, GateKind::Or, a, b);
 
         // #[cfg(debug_assertions)]
         // {
@@ -995,17 +972,16 @@ impl ProcessorGate for ProcessorBoolean {
         result: &mut [Ciphertext],
         index_low: usize,
         index_high: usize,
-    ) {
-        assert!(
-            index_low < index_high,
-            "index_low = {}, index_high = {}",
+    ) -> Result<(), PulpError> {
+        Self::check_range(
+            "e_or_range",
+            &[("a", a.len()), ("b", b.len()), ("result", result.len())],
             index_low,
-            index_high
-        );
+            index_high,
+        )?;
 
         for i in index_low..index_high {
-            result[i] = sk.or(&a[i], &b[i]);
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &mut result[i]);
+            result[i] = self.e_gate(sk, GateKind::Or, &a[i], &b[i]);
         }
 
         // #[cfg(debug_assertions)]
@@ -1018,6 +994,7 @@ impl ProcessorGate for ProcessorBoolean {
         //         index_high
         //     );
         // }
+        Ok(())
     }
 
     /// Compute the encrypted XOR gate on the `Ciphertext` vectors `a` and `b`. The result is stored in
@@ -1029,22 +1006,28 @@ impl ProcessorGate for ProcessorBoolean {
     /// * `b`: &[Ciphertext] - The second operand, a vector of ciphertexts, each element encrypting a single bit.
     /// * `result`: &mut [Ciphertext] - A vector holding the result of the operation.
     ///
-    fn e_xor(&self, sk: &ServerKey, a: &[Ciphertext], b: &[Ciphertext], result: &mut [Ciphertext]) {
+    fn e_xor(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_binary("e_xor", a, b, result)?;
         let size: usize = a.len();
         for i in 0..size {
-            result[i] = sk.xor(&a[i], &b[i]);
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &mut result[i]);
+            result[i] = self.e_gate(sk, GateKind::Xor, &a[i], &b[i]);
         }
 
         // #[cfg(debug_assertions)]
         // {
         //     println!("DEBUG: `e_xor` -> len(a)={}, len(b)={}", size, b.len());
         // }
+        Ok(())
     }
 
     fn e_xor_bit(&self, sk: &ServerKey, a: &Ciphertext, b: &Ciphertext) -> Ciphertext {
-        let mut result: Ciphertext = sk.xor(a, b);
-        result = PitchTrimModule::pitch_trim_bit(sk, &mut result);
+        let result: Ciphertext = self.e_gate(sk, GateKind::Xor, a, b);
 
         // #[cfg(debug_assertions)]
         // {
@@ -1061,17 +1044,16 @@ impl ProcessorGate for ProcessorBoolean {
         result: &mut [Ciphertext],
         index_low: usize,
         index_high: usize,
-    ) {
-        assert!(
-            index_low < index_high,
-            "index_low = {}, index_high = {}",
+    ) -> Result<(), PulpError> {
+        Self::check_range(
+            "e_xor_range",
+            &[("a", a.len()), ("b", b.len()), ("result", result.len())],
             index_low,
-            index_high
-        );
+            index_high,
+        )?;
 
         for i in index_low..index_high {
-            result[i] = sk.xor(&a[i], &b[i]);
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &mut result[i]);
+            result[i] = self.e_gate(sk, GateKind::Xor, &a[i], &b[i]);
         }
 
         // #[cfg(debug_assertions)]
@@ -1084,6 +1066,7 @@ impl ProcessorGate for ProcessorBoolean {
         //         index_high
         //     );
         // }
+        Ok(())
     }
 
     /// Compute the encrypted NAND gate on the `Ciphertext` vectors `a` and `b`. The result is stored in
@@ -1101,22 +1084,22 @@ impl ProcessorGate for ProcessorBoolean {
         a: &[Ciphertext],
         b: &[Ciphertext],
         result: &mut [Ciphertext],
-    ) {
+    ) -> Result<(), PulpError> {
+        Self::check_binary("e_nand", a, b, result)?;
         let size: usize = a.len();
         for i in 0..size {
-            result[i] = sk.nand(&a[i], &b[i]);
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &mut result[i]);
+            result[i] = self.e_gate(sk, GateKind::Nand, &a[i], &b[i]);
         }
 
         // #[cfg(debug_assertions)]
         // {
         //     println!("DEBUG: `e_nand` -> len(a)={}, len(b)={}", size, b.len());
         // }
+        Ok(())
     }
 
     fn e_nand_bit(&self, sk: &ServerKey, a: &Ciphertext, b: &Ciphertext) -> Ciphertext {
-        let mut result: Ciphertext = sk.nand(a, b);
-        result = PitchTrimModule::pitch_trim_bit(sk, &mut result);
+        let result: Ciphertext = self.e_gate(sk, GateKind::Nand, a, b);
         // #[cfg(debug_assertions)]
         // {
         //     println!("DEBUG: `e_nand_bit`");
@@ -1132,17 +1115,16 @@ impl ProcessorGate for ProcessorBoolean {
         result: &mut [Ciphertext],
         index_low: usize,
         index_high: usize,
-    ) {
-        assert!(
-            index_low < index_high,
-            "index_low = {}, index_high = {}",
+    ) -> Result<(), PulpError> {
+        Self::check_range(
+            "e_nand_range",
+            &[("a", a.len()), ("b", b.len()), ("result", result.len())],
             index_low,
-            index_high
-        );
+            index_high,
+        )?;
 
         for i in index_low..index_high {
-            result[i] = sk.nand(&a[i], &b[i]);
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &mut result[i]);
+            result[i] = self.e_gate(sk, GateKind::Nand, &a[i], &b[i]);
         }
 
         // #[cfg(debug_assertions)]
@@ -1155,6 +1137,7 @@ impl ProcessorGate for ProcessorBoolean {
         //         index_high
         //     );
         // }
+        Ok(())
     }
 
     /// Compute the encrypted NOR gate on the `Ciphertext` vectors `a` and `b`. The result is stored in
@@ -1166,22 +1149,28 @@ impl ProcessorGate for ProcessorBoolean {
     /// * `b`: &[Ciphertext] - The second operand, a vector of ciphertexts, each element encrypting a single bit.
     /// * `result`: &mut [Ciphertext] - A vector holding the result of the operation.
     ///
-    fn e_nor(&self, sk: &ServerKey, a: &[Ciphertext], b: &[Ciphertext], result: &mut [Ciphertext]) {
+    fn e_nor(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_binary("e_nor", a, b, result)?;
         let size: usize = a.len();
         for i in 0..size {
-            result[i] = sk.nor(&a[i], &b[i]);
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &mut result[i]);
+            result[i] = self.e_gate(sk, GateKind::Nor, &a[i], &b[i]);
         }
 
         // #[cfg(debug_assertions)]
         // {
         //     println!("DEBUG: `e_nor` -> len(a)={}, len(b)={}", size, b.len());
         // }
+        Ok(())
     }
 
     fn e_nor_bit(&self, sk: &ServerKey, a: &Ciphertext, b: &Ciphertext) -> Ciphertext {
-        let mut result: Ciphertext = sk.nor(a, b);
-        result = PitchTrimModule::pitch_trim_bit(sk, &mut result);
+        let result: Ciphertext = self.e_gate(sk, GateKind::Nor, a, b);
         // #[cfg(debug_assertions)]
         // {
         //     println!("DEBUG: `e_nor_bit`");
@@ -1197,17 +1186,16 @@ impl ProcessorGate for ProcessorBoolean {
         result: &mut [Ciphertext],
         index_low: usize,
         index_high: usize,
-    ) {
-        assert!(
-            index_low < index_high,
-            "index_low = {}, index_high = {}",
+    ) -> Result<(), PulpError> {
+        Self::check_range(
+            "e_nor_range",
+            &[("a", a.len()), ("b", b.len()), ("result", result.len())],
             index_low,
-            index_high
-        );
+            index_high,
+        )?;
 
         for i in index_low..index_high {
-            result[i] = sk.nor(&a[i], &b[i]);
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &mut result[i]);
+            result[i] = self.e_gate(sk, GateKind::Nor, &a[i], &b[i]);
         }
 
         // #[cfg(debug_assertions)]
@@ -1220,6 +1208,7 @@ impl ProcessorGate for ProcessorBoolean {
         //         index_high
         //     );
         // }
+        Ok(())
     }
 
     /// Compute the encrypted XNOR gate on the `Ciphertext` vectors `a` and `b`. The result is stored in
@@ -1237,22 +1226,22 @@ impl ProcessorGate for ProcessorBoolean {
         a: &[Ciphertext],
         b: &[Ciphertext],
         result: &mut [Ciphertext],
-    ) {
+    ) -> Result<(), PulpError> {
+        Self::check_binary("e_xnor", a, b, result)?;
         let size: usize = a.len();
         for i in 0..size {
-            result[i] = sk.xnor(&a[i], &b[i]);
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &mut result[i]);
+            result[i] = self.e_gate(sk, GateKind::Xnor, &a[i], &b[i]);
         }
 
         // #[cfg(debug_assertions)]
         // {
         //     println!("DEBUG: `e_xnor` -> len(a)={}, len(b)={}", size, b.len());
         // }
+        Ok(())
     }
 
     fn e_xnor_bit(&self, sk: &ServerKey, a: &Ciphertext, b: &Ciphertext) -> Ciphertext {
-        let mut result: Ciphertext = sk.xnor(a, b);
-        result = PitchTrimModule::pitch_trim_bit(sk, &mut result);
+        let result: Ciphertext = self.e_gate(sk, GateKind::Xnor, a, b);
 
         // #[cfg(debug_assertions)]
         // {
@@ -1269,17 +1258,16 @@ impl ProcessorGate for ProcessorBoolean {
         result: &mut [Ciphertext],
         index_low: usize,
         index_high: usize,
-    ) {
-        assert!(
-            index_low < index_high,
-            "index_low = {}, index_high = {}",
+    ) -> Result<(), PulpError> {
+        Self::check_range(
+            "e_xnor_range",
+            &[("a", a.len()), ("b", b.len()), ("result", result.len())],
             index_low,
-            index_high
-        );
+            index_high,
+        )?;
 
         for i in index_low..index_high {
-            result[i] = sk.xnor(&a[i], &b[i]);
-            result[i] = PitchTrimModule::pitch_trim_bit(sk, &mut result[i]);
+            result[i] = self.e_gate(sk, GateKind::Xnor, &a[i], &b[i]);
         }
 
         // #[cfg(debug_assertions)]
@@ -1292,6 +1280,7 @@ impl ProcessorGate for ProcessorBoolean {
         //         index_high
         //     );
         // }
+        Ok(())
     }
 
     /// Compute the encrypted NOT gate on the `Ciphertext` vector `a`. The result is stored in
@@ -1302,20 +1291,27 @@ impl ProcessorGate for ProcessorBoolean {
     /// * `a`: &[Ciphertext] - A vector of ciphertexts, each element is encrypting a single bit.
     /// * `result`: &mut [Ciphertext] - A vector holding the result of the operation.
     ///
-    fn e_not(&self, sk: &ServerKey, a: &[Ciphertext], result: &mut [Ciphertext]) {
+    fn e_not(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_unary("e_not", a, result)?;
         let size: usize = a.len();
         for i in 0..size {
-            result[i] = sk.not(&a[i]);
+            result[i] = self.e_not_gate(sk, &a[i]);
         }
 
         // #[cfg(debug_assertions)]
         // {
         //     println!("DEBUG: `e_not` -> len(a)={}", size);
         // }
+        Ok(())
     }
 
     fn e_not_bit(&self, sk: &ServerKey, a: &Ciphertext) -> Ciphertext {
-        let result: Ciphertext = sk.not(a);
+        let result: Ciphertext = self.e_not_gate(sk, a);
 
         // #[cfg(debug_assertions)]
         // {
@@ -1331,16 +1327,16 @@ impl ProcessorGate for ProcessorBoolean {
         result: &mut [Ciphertext],
         index_low: usize,
         index_high: usize,
-    ) {
-        assert!(
-            index_low < index_high,
-            "index_low = {}, index_high = {}",
+    ) -> Result<(), PulpError> {
+        Self::check_range(
+            "e_not_range",
+            &[("a", a.len()), ("result", result.len())],
             index_low,
-            index_high
-        );
+            index_high,
+        )?;
 
         for i in index_low..index_high {
-            result[i] = sk.not(&a[i]);
+            result[i] = self.e_not_gate(sk, &a[i]);
         }
 
         // #[cfg(debug_assertions)]
@@ -1352,6 +1348,7 @@ impl ProcessorGate for ProcessorBoolean {
         //         index_high
         //     );
         // }
+        Ok(())
     }
     fn e_mux(
         &self,
@@ -1360,10 +1357,12 @@ impl ProcessorGate for ProcessorBoolean {
         ct_then: &[Ciphertext],
         ct_else: &[Ciphertext],
         result: &mut [Ciphertext],
-    ) {
+    ) -> Result<(), PulpError> {
+        Self::check_binary("e_mux", ct_then, ct_else, result)?;
         for i in 0..ct_then.len() {
-            result[i] = sk.mux(&selector, &ct_then[i], &ct_else[i]);
+            result[i] = self.e_mux_gate(sk, selector, &ct_then[i], &ct_else[i]);
         }
+        Ok(())
     }
 
     fn e_mux_bit(
@@ -1373,7 +1372,7 @@ impl ProcessorGate for ProcessorBoolean {
         ct_then: &Ciphertext,
         ct_else: &Ciphertext,
     ) -> Ciphertext {
-        let result: Ciphertext = sk.mux(selector, ct_then, ct_else);
+        let result: Ciphertext = self.e_mux_gate(sk, selector, ct_then, ct_else);
         result
     }
 
@@ -1386,27 +1385,53 @@ impl ProcessorGate for ProcessorBoolean {
         result: &mut [Ciphertext],
         index_low: usize,
         index_high: usize,
-    ) {
+    ) -> Result<(), PulpError> {
+        Self::check_range(
+            "e_mux_range",
+            &[
+                ("selector", selector.len()),
+                ("ct_then", ct_then.len()),
+                ("ct_else", ct_else.len()),
+                ("result", result.len()),
+            ],
+            index_low,
+            index_high,
+        )?;
         for i in index_low..index_high {
-            result[i] = sk.mux(&selector[i], &ct_then[i], &ct_else[i]);
+            result[i] = self.e_mux_gate(sk, &selector[i], &ct_then[i], &ct_else[i]);
         }
+        Ok(())
     }
 }
 impl ProcessorCircuits for ProcessorBoolean {
-    fn e_shl(&self, a: &[Ciphertext], shift_amt: usize, result: &mut [Ciphertext]) {
-        // Arithmetic shift left (same as logical shift left)
+    fn e_shl(
+        &self,
+        a: &[Ciphertext],
+        shift_amt: usize,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_unary("e_shl", a, result)?;
+        // Arithmetic shift left (same as logical shift left); wide shifts leave only zeros
+        let shift_amt: usize = shift_amt.min(a.len());
         let mut a = a.to_vec();
         a.reverse();
         let mut tmp = a[shift_amt..].to_vec();
         tmp.extend(vec![Ciphertext::Trivial(false); shift_amt]);
         tmp.reverse();
         self.copy_to_from(result, &tmp);
+        Ok(())
     }
 
-    fn e_shr(&self, a: &[Ciphertext], shift_amt: usize, result: &mut [Ciphertext]) {
+    fn e_shr(
+        &self,
+        a: &[Ciphertext],
+        shift_amt: usize,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_unary("e_shr", a, result)?;
         if a.is_empty() {
             self.copy_to_from(result, a);
-            return;
+            return Ok(());
         }
         let mut a = a.to_vec();
         a.reverse();
@@ -1414,20 +1439,37 @@ impl ProcessorCircuits for ProcessorBoolean {
         shifted.extend_from_slice(&a[..a.len().saturating_sub(shift_amt)]);
         shifted.reverse();
         self.copy_to_from(result, &shifted);
+        Ok(())
     }
 
-    fn e_rotr(&self, a: &[Ciphertext], rot_amt: usize, result: &mut [Ciphertext]) {
+    fn e_rotr(
+        &self,
+        a: &[Ciphertext],
+        rot_amt: usize,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_unary("e_rotr", a, result)?;
+        Self::check_nonempty("e_rotr", a)?;
         let mut tmp = a.to_vec();
         // The reverse of the operation because of the binary encoding is LSB...MSB
         tmp.rotate_left(rot_amt % a.len());
         self.copy_to_from(result, &tmp);
+        Ok(())
     }
 
-    fn e_rotl(&self, a: &[Ciphertext], rot_amt: usize, result: &mut [Ciphertext]) {
+    fn e_rotl(
+        &self,
+        a: &[Ciphertext],
+        rot_amt: usize,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_unary("e_rotl", a, result)?;
+        Self::check_nonempty("e_rotl", a)?;
         let mut tmp = a.to_vec();
         // The reverse of the operation because of the binary encoding is LSB...MSB
         tmp.rotate_right(rot_amt % a.len());
         self.copy_to_from(result, &tmp);
+        Ok(())
     }
 
     fn comparator(
@@ -1436,14 +1478,16 @@ impl ProcessorCircuits for ProcessorBoolean {
         a: &[Ciphertext],
         b: &[Ciphertext],
         select: u8,
-    ) -> Ciphertext {
+    ) -> Result<Ciphertext, PulpError> {
+        Self::check_select("comparator", select)?;
+        PulpError::expect_width("comparator", "b", a.len(), b.len())?;
         let size: usize = a.len();
 
         let mut not_a: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
-        self.e_not(sk, a, &mut not_a);
+        self.e_not(sk, a, &mut not_a)?;
 
         let mut not_b: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
-        self.e_not(sk, b, &mut not_b);
+        self.e_not(sk, b, &mut not_b)?;
 
         let mut temp: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 10];
         let mut greater_than: Ciphertext = sk.trivial_encrypt(false);
@@ -1451,7 +1495,7 @@ impl ProcessorCircuits for ProcessorBoolean {
         let mut equal: Ciphertext = sk.trivial_encrypt(true);
         for i in (0..size).rev() {
             temp[0] = self.e_not_bit(sk, &greater_than);
-            temp[1] = self.e_nand_bit(sk, &a[i], &not_b[0]);
+            temp[1] = self.e_nand_bit(sk, &a[i], &not_b[i]);
             temp[2] = self.e_nand_bit(sk, &temp[1], &equal);
             temp[3] = self.e_not_bit(sk, &temp[2]);
             greater_than = self.e_nand_bit(sk, &temp[0], &temp[3]);
@@ -1460,7 +1504,7 @@ impl ProcessorCircuits for ProcessorBoolean {
 
             // compute less than a path
             temp[4] = self.e_not_bit(sk, &less_than);
-            temp[5] = self.e_nand_bit(sk, &not_a[0], &b[i]);
+            temp[5] = self.e_nand_bit(sk, &not_a[i], &b[i]);
             temp[6] = self.e_nand_bit(sk, &temp[5], &equal);
             temp[7] = self.e_not_bit(sk, &temp[6]);
             less_than = self.e_nand_bit(sk, &temp[7], &temp[4]);
@@ -1493,7 +1537,7 @@ impl ProcessorCircuits for ProcessorBoolean {
             result = self.e_not_bit(sk, &equal);
         }
 
-        result
+        Ok(result)
     }
 
     fn compare_bit(
@@ -1508,13 +1552,264 @@ impl ProcessorCircuits for ProcessorBoolean {
         result
     }
 
+    fn scalar_cmp(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: i64,
+        select: u8,
+    ) -> Result<Ciphertext, PulpError> {
+        Self::check_select("scalar_cmp", select)?;
+        let size: usize = a.len();
+
+        // A `b` outside the range of `a` is above or below every value of `a`
+        if (1..64).contains(&size) {
+            let half: i64 = 1 << (size - 1);
+            if !(-half..half).contains(&b) {
+                let above: bool = b >= half;
+                let result: bool = match select {
+                    0 => false,
+                    1 | 4 => above,
+                    2 | 3 => !above,
+                    _ => true,
+                };
+                return Ok(Ciphertext::Trivial(result));
+            }
+        }
+
+        if select == 0 || select == 5 {
+            // XNOR with a known bit is either the bit itself or its negation
+            let mut equal: Ciphertext = sk.trivial_encrypt(true);
+            for (i, a_i) in a.iter().enumerate() {
+                let b_bit = ((b >> i.min(63)) & 1) != 0;
+                let bit = if b_bit {
+                    a_i.clone()
+                } else {
+                    self.e_not_bit(sk, a_i)
+                };
+                equal = if i == 0 {
+                    bit
+                } else {
+                    self.e_and_bit(sk, &equal, &bit)
+                };
+            }
+            if select == 5 {
+                return Ok(self.e_not_bit(sk, &equal));
+            }
+            return Ok(equal);
+        }
+
+        // a < b is the borrow out of a - b, and a <= b is the borrow out of a - b - 1.
+        // Flipping both sign bits maps the signed order onto the unsigned one.
+        let or_equal: bool = select == 2 || select == 4;
+        let mut borrow: Ciphertext = sk.trivial_encrypt(or_equal);
+        for (i, a_i) in a.iter().enumerate() {
+            let msb = i == size - 1;
+            let b_bit = (((b >> i.min(63)) & 1) != 0) ^ msb;
+            let not_a = if msb {
+                a_i.clone()
+            } else {
+                self.e_not_bit(sk, a_i)
+            };
+            borrow = if b_bit {
+                self.e_or_bit(sk, &not_a, &borrow)
+            } else {
+                self.e_and_bit(sk, &not_a, &borrow)
+            };
+        }
+
+        let mut result: Ciphertext = Ciphertext::Trivial(false);
+        if select == 1 || select == 4 {
+            // ecmpl, ecmpleq
+            result = borrow;
+        } else if select == 2 || select == 3 {
+            // ecmpg = NOT(ecmpleq), ecmpgeq = NOT(ecmpl)
+            result = self.e_not_bit(sk, &borrow);
+        }
+        Ok(result)
+    }
+
+    fn scalar_and(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: i64,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_unary("scalar_and", a, result)?;
+        for i in 0..a.len() {
+            let b_bit = ((b >> i.min(63)) & 1) != 0;
+            result[i] = if b_bit {
+                a[i].clone()
+            } else {
+                sk.trivial_encrypt(false)
+            };
+        }
+        Ok(())
+    }
+
+    fn scalar_or(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: i64,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_unary("scalar_or", a, result)?;
+        for i in 0..a.len() {
+            let b_bit = ((b >> i.min(63)) & 1) != 0;
+            result[i] = if b_bit {
+                sk.trivial_encrypt(true)
+            } else {
+                a[i].clone()
+            };
+        }
+        Ok(())
+    }
+
+    fn scalar_xor(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: i64,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_unary("scalar_xor", a, result)?;
+        for i in 0..a.len() {
+            let b_bit = ((b >> i.min(63)) & 1) != 0;
+            result[i] = if b_bit {
+                self.e_not_bit(sk, &a[i])
+            } else {
+                a[i].clone()
+            };
+        }
+        Ok(())
+    }
+
+    fn scalar_add(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: i64,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_unary("scalar_add", a, result)?;
+        let size: usize = a.len();
+
+        // `None` while the carry is still known to be zero
+        let mut carry: Option<Ciphertext> = None;
+        for i in 0..size {
+            let b_bit = ((b >> i.min(63)) & 1) != 0;
+            let last = i == size - 1;
+            match carry.take() {
+                None => {
+                    if b_bit {
+                        // a + 1: sum = NOT a, carry = a
+                        result[i] = self.e_not_bit(sk, &a[i]);
+                        carry = Some(a[i].clone());
+                    } else {
+                        result[i] = a[i].clone();
+                    }
+                }
+                Some(c) => {
+                    if b_bit {
+                        // a + 1 + c: sum = XNOR(a, c), carry = a OR c
+                        result[i] = self.e_xnor_bit(sk, &a[i], &c);
+                        if !last {
+                            carry = Some(self.e_or_bit(sk, &a[i], &c));
+                        }
+                    } else {
+                        // a + 0 + c: sum = XOR(a, c), carry = a AND c
+                        result[i] = self.e_xor_bit(sk, &a[i], &c);
+                        if !last {
+                            carry = Some(self.e_and_bit(sk, &a[i], &c));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn scalar_mul(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: i64,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_unary("scalar_mul", a, result)?;
+        let product = self.plain_dot_into(sk, &[a], &[b], a.len())?;
+        self.copy_to_from(result, &product);
+        Ok(())
+    }
+
+    fn scalar_div(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: i64,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        if b == 0 {
+            return Err(PulpError::invalid("scalar_div", "division by zero"));
+        }
+        Self::check_nonempty("scalar_div", a)?;
+        Self::check_unary("scalar_div", a, result)?;
+        let size: usize = a.len();
+        let divisor: u64 = b.unsigned_abs();
+        // The partial remainder needs one spare bit so that its sign flags A < M
+        let rem_size: usize = size.max((64 - divisor.leading_zeros()) as usize) + 1;
+        let neg_divisor: i64 = (divisor as i64).wrapping_neg();
+
+        // Take the magnitude of the dividend
+        let mut Q: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
+        let mut Q_tmp: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
+        self.scalar_xor(sk, a, -1, &mut Q_tmp)?;
+        self.scalar_add(sk, &Q_tmp, 1, &mut Q)?;
+        self.copy_to_from(&mut Q_tmp, &Q);
+        self.e_mux(sk, &a[size - 1], &Q_tmp, a, &mut Q)?;
+
+        let mut A: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); rem_size];
+        let mut A_tmp: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); rem_size];
+        let mut A_m: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); rem_size];
+
+        for i in (0..size).rev() {
+            // Bring down the next dividend bit
+            self.e_shl_p(&A, &Q[i], &mut A_tmp);
+
+            // Compute A - M with the divisor folded into the adder
+            self.scalar_add(sk, &A_tmp, neg_divisor, &mut A_m)?;
+
+            // The quotient bit is set when the difference is non-negative
+            Q[i] = self.e_not_bit(sk, &A_m[rem_size - 1]);
+            if i != 0 {
+                self.e_mux(sk, &Q[i], &A_m, &A_tmp, &mut A)?;
+            }
+        }
+
+        // The quotient is negative when exactly one operand is negative
+        let sign_bit: Ciphertext = if b < 0 {
+            self.e_not_bit(sk, &a[size - 1])
+        } else {
+            a[size - 1].clone()
+        };
+        self.scalar_xor(sk, &Q, -1, &mut Q_tmp)?;
+        let mut inv_res: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
+        self.scalar_add(sk, &Q_tmp, 1, &mut inv_res)?;
+        self.e_mux(sk, &sign_bit, &inv_res, &Q, result)?;
+        Ok(())
+    }
+
     fn subtracter(
         &self,
         sk: &ServerKey,
         a: &[Ciphertext],
         b: &[Ciphertext],
         result: &mut [Ciphertext],
-    ) {
+    ) -> Result<(), PulpError> {
+        Self::check_nonempty("subtracter", a)?;
+        Self::check_binary("subtracter", a, b, result)?;
         let size: usize = a.len();
 
         let mut borrow: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
@@ -1527,8 +1822,8 @@ impl ProcessorCircuits for ProcessorBoolean {
         temp_0[0] = self.e_not_bit(sk, &a[0]);
         borrow[0] = self.e_and_bit(sk, &temp_0[0], &b[0]);
 
-        self.e_xor_range(sk, &a, &b, &mut temp_0, 1, size);
-        self.e_not_range(sk, &a, &mut temp_1, 1, size);
+        self.e_xor_range(sk, &a, &b, &mut temp_0, 1, size)?;
+        self.e_not_range(sk, &a, &mut temp_1, 1, size)?;
 
         for i in 1..size {
             // Calculate the difference
@@ -1541,9 +1836,17 @@ impl ProcessorCircuits for ProcessorBoolean {
                 borrow[i] = self.e_or_bit(sk, &temp_2[i], &temp_1[i]);
             }
         }
+        Ok(())
     }
 
-    fn adder(&self, sk: &ServerKey, a: &[Ciphertext], b: &[Ciphertext], result: &mut [Ciphertext]) {
+    fn adder(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_binary("adder", a, b, result)?;
         // Validate input lengths
         let size = a.len();
 
@@ -1553,8 +1856,8 @@ impl ProcessorCircuits for ProcessorBoolean {
         let mut a_and_b: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
 
         // Compute an XOR b for all bits
-        self.e_xor(sk, a, b, &mut temp);
-        self.e_and(sk, a, b, &mut a_and_b);
+        self.e_xor(sk, a, b, &mut temp)?;
+        self.e_and(sk, a, b, &mut a_and_b)?;
         // Ripple-carry adder logic
         for i in 0..size {
             // Sum bit: result[i] = a[i] XOR b[i] XOR carry[i]
@@ -1568,6 +1871,7 @@ impl ProcessorCircuits for ProcessorBoolean {
                 carry[i + 1] = self.e_or_bit(sk, &temp_carry, &b_and_carry);
             }
         }
+        Ok(())
     }
 
     fn sign_adder(
@@ -1576,11 +1880,15 @@ impl ProcessorCircuits for ProcessorBoolean {
         a: &[Ciphertext],
         b: &[Ciphertext],
         result: &mut [Ciphertext],
-    ) {
+    ) -> Result<(), PulpError> {
+        Self::check_binary("sign_adder", a, b, result)?;
         let size: usize = a.len();
         if size < 2 {
-            // Handle edge case if necessary
-            return;
+            // One bit is only a sign, there is no magnitude to add
+            return Err(PulpError::UnsupportedWidth {
+                op: "sign_adder",
+                width: size,
+            });
         }
         let mag_size: usize = size - 1;
 
@@ -1595,7 +1903,7 @@ impl ProcessorCircuits for ProcessorBoolean {
         // Compute add_mag: magnitude addition with initial carry 0
         let mut add_mag: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); mag_size];
         let mut temp: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); mag_size];
-        self.e_xor(sk, mag_a, mag_b, &mut temp);
+        self.e_xor(sk, mag_a, mag_b, &mut temp)?;
         let mut carry: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); mag_size + 1];
         carry[0] = sk.trivial_encrypt(false);
         for i in 0..mag_size {
@@ -1609,12 +1917,12 @@ impl ProcessorCircuits for ProcessorBoolean {
         /*for i in 0..mag_size {
             inv_mag_b[i] = self.e_xor_bit(sk, &mag_b[i], &true_ct);
         }*/
-        self.e_not(sk, mag_b, &mut inv_mag_b);
+        self.e_not(sk, mag_b, &mut inv_mag_b)?;
 
         // Compute sub_mag_a_minus_b: mag_a - mag_b with initial carry 1
         //let mut sub_mag_a_minus_b: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); mag_size];
         let mut temp_sub: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); mag_size];
-        self.e_xor(sk, mag_a, &inv_mag_b, &mut temp_sub);
+        self.e_xor(sk, mag_a, &inv_mag_b, &mut temp_sub)?;
         let mut carry_sub: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); mag_size + 1];
         carry_sub[0] = sk.trivial_encrypt(true);
         for i in 0..mag_size {
@@ -1626,8 +1934,8 @@ impl ProcessorCircuits for ProcessorBoolean {
         // Larger and smaller
         let mut larger_mag: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); mag_size];
         let mut smaller_mag: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); mag_size];
-        self.e_mux(sk, &is_a_ge_b, &mag_a, &mag_b, &mut larger_mag);
-        self.e_mux(sk, &is_a_ge_b, &mag_b, &mag_a, &mut smaller_mag);
+        self.e_mux(sk, &is_a_ge_b, &mag_a, &mag_b, &mut larger_mag)?;
+        self.e_mux(sk, &is_a_ge_b, &mag_b, &mag_a, &mut smaller_mag)?;
 
         /* for i in 0..mag_size {
             larger_mag[i] = self.e_mux_bit(sk, &is_a_ge_b, &mag_a[i], &mag_b[i]);
@@ -1637,7 +1945,7 @@ impl ProcessorCircuits for ProcessorBoolean {
 
         // Compute inv_smaller
         let mut inv_smaller: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); mag_size];
-        self.e_not(sk, &smaller_mag, &mut inv_smaller);
+        self.e_not(sk, &smaller_mag, &mut inv_smaller)?;
         /*for i in 0..mag_size {
             inv_smaller[i] = self.e_xor_bit(sk, &smaller_mag[i], &true_ct);
         }*/
@@ -1645,7 +1953,7 @@ impl ProcessorCircuits for ProcessorBoolean {
         // Compute sub_mag: larger_mag - smaller_mag with initial carry 1
         let mut sub_mag: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); mag_size];
         let mut temp_diff: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); mag_size];
-        self.e_xor(sk, &larger_mag, &inv_smaller, &mut temp_diff);
+        self.e_xor(sk, &larger_mag, &inv_smaller, &mut temp_diff)?;
         let mut carry_diff: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); mag_size + 1];
         carry_diff[0] = sk.trivial_encrypt(true);
         for i in 0..mag_size {
@@ -1655,7 +1963,7 @@ impl ProcessorCircuits for ProcessorBoolean {
 
         // Final result_mag and result_sign
         let mut result_mag: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); mag_size];
-        self.e_mux(sk, &same_sign, &sub_mag, &add_mag, &mut result_mag);
+        self.e_mux(sk, &same_sign, &sub_mag, &add_mag, &mut result_mag)?;
         /*for i in 0..mag_size {
             result_mag[i] = self.e_mux_bit(sk, &same_sign, &sub_mag[i], &add_mag[i]);
         }*/
@@ -1666,6 +1974,7 @@ impl ProcessorCircuits for ProcessorBoolean {
             result[i] = result_mag[i].clone();
         }
         result[size - 1] = result_sign;
+        Ok(())
     }
 
     fn half_adder(
@@ -1707,17 +2016,20 @@ impl ProcessorCircuits for ProcessorBoolean {
         b: &[Ciphertext],
         size: usize,
         result: &mut [Ciphertext],
-    ) {
+    ) -> Result<(), PulpError> {
         if size == 0 {
-            return;
+            return Ok(());
         }
+        PulpError::expect_at_least("add_supplement", "a", size, a.len())?;
+        PulpError::expect_at_least("add_supplement", "b", size, b.len())?;
+        PulpError::expect_at_least("add_supplement", "result", size, result.len())?;
         let mut carry: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size + 1];
         let mut temp: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
 
-        self.e_xor_range(sk, a, b, &mut temp, 0, size);
+        self.e_xor_range(sk, a, b, &mut temp, 0, size)?;
         let mut a_and_b: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
         let mut tmp_c: Ciphertext = sk.trivial_encrypt(false);
-        self.e_and_range(sk, &a, &b, &mut a_and_b, 0, size);
+        self.e_and_range(sk, &a, &b, &mut a_and_b, 0, size)?;
 
         for i in 0..size {
             tmp_c = self.e_and_bit(sk, &temp[i], &carry[i]);
@@ -1725,7 +2037,8 @@ impl ProcessorCircuits for ProcessorBoolean {
             carry[i + 1] = self.e_or_bit(sk, &a_and_b[i], &tmp_c)
         }
 
-        self.e_xor_range(sk, &carry, &temp, result, 0, size);
+        self.e_xor_range(sk, &carry, &temp, result, 0, size)?;
+        Ok(())
     }
 
     fn multiplier(
@@ -1734,7 +2047,8 @@ impl ProcessorCircuits for ProcessorBoolean {
         a: &[Ciphertext],
         b: &[Ciphertext],
         result: &mut [Ciphertext],
-    ) {
+    ) -> Result<(), PulpError> {
+        Self::check_binary("multiplier", a, b, result)?;
         let size: usize = a.len();
         let mut tmp_array: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
         let mut sum: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
@@ -1743,11 +2057,12 @@ impl ProcessorCircuits for ProcessorBoolean {
             for j in 0..size - i {
                 tmp_array[j] = self.e_and_bit(sk, &a[i], &b[j]);
             }
-            self.add_supplement(sk, &mut tmp_array, &sum[i..], size - i, &mut temp_sum[i..]);
+            self.add_supplement(sk, &mut tmp_array, &sum[i..], size - i, &mut temp_sum[i..])?;
             self.copy_to_from(&mut sum, &temp_sum);
         }
 
         self.copy_to_from(result, &sum);
+        Ok(())
     }
 
     fn blake3(
@@ -1756,8 +2071,18 @@ impl ProcessorCircuits for ProcessorBoolean {
         msg: Vec<&[Ciphertext]>,
         v: Vec<&[Ciphertext]>,
         result: &mut Vec<&mut [Ciphertext]>,
-    ) {
-        let size: usize = msg[0].len();
+    ) -> Result<(), PulpError> {
+        // Two message words and four state words of one width
+        PulpError::expect_width("blake3", "msg", 2, msg.len())?;
+        PulpError::expect_width("blake3", "v", 4, v.len())?;
+        PulpError::expect_width("blake3", "result", 4, result.len())?;
+        let size: usize = Self::check_elements("blake3", "msg", &msg)?;
+        for word in v.iter() {
+            PulpError::expect_width("blake3", "v", size, word.len())?;
+        }
+        for word in result.iter() {
+            PulpError::expect_at_least("blake3", "result", size, word.len())?;
+        }
         let mut a: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
         let mut b: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
         let mut c: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
@@ -1780,56 +2105,63 @@ impl ProcessorCircuits for ProcessorBoolean {
 
         // Step 1: a = a + b + m0
         // Step 1.1: a = a + b
-        self.adder(sk, &a, &b, &mut tmp_a);
+        self.adder(sk, &a, &b, &mut tmp_a)?;
         // Step 1.2: a = a + m0
-        self.adder(sk, &tmp_a, &m0, &mut a);
+        self.adder(sk, &tmp_a, &m0, &mut a)?;
 
         // Step 2: d = (d XOR a) >>> 16
         // Step 2.1: d = d XOR a
-        self.e_xor(sk, &d, &a, &mut tmp_d);
-        self.e_rotr(&tmp_d, 16, &mut d);
+        self.e_xor(sk, &d, &a, &mut tmp_d)?;
+        self.e_rotr(&tmp_d, 16, &mut d)?;
 
         // Step 3: c = c + d
         tmp_c.clone_from(&c);
-        self.adder(sk, &tmp_c, &d, &mut c);
+        self.adder(sk, &tmp_c, &d, &mut c)?;
 
         // Step 4: b = (b XOR c) >>> 12
         // Step 4.1: b = b XOR c
-        self.e_xor(sk, &b, &c, &mut tmp_b);
+        self.e_xor(sk, &b, &c, &mut tmp_b)?;
         // Step 4.2: b = b >>> 12
-        self.e_rotr(&tmp_b, 12, &mut b);
+        self.e_rotr(&tmp_b, 12, &mut b)?;
 
         // Step 5: a = a + b + m1
         // Step 5.1: a = a + b
-        self.adder(sk, &a, &b, &mut tmp_a);
+        self.adder(sk, &a, &b, &mut tmp_a)?;
         // Step 5.2: a = a + m1
-        self.adder(sk, &tmp_a, &m1, &mut a);
+        self.adder(sk, &tmp_a, &m1, &mut a)?;
 
         // Step 6: d = (d XOR a) >>> 8
         // Step 6.1: d = d XOR a
-        self.e_xor(sk, &d, &a, &mut tmp_d);
+        self.e_xor(sk, &d, &a, &mut tmp_d)?;
         // Step 6.2: d = d >>> 8
-        self.e_rotr(&tmp_d, 8, &mut d);
+        self.e_rotr(&tmp_d, 8, &mut d)?;
 
         // Step 7: c = c + d
         tmp_c.clone_from(&c);
-        self.adder(sk, &tmp_c, &d, &mut c);
+        self.adder(sk, &tmp_c, &d, &mut c)?;
 
         // Step 8: b = (b XOR c) >>> 12
         // Step 8.1: b = b XOR c
-        self.e_xor(sk, &b, &c, &mut tmp_b);
+        self.e_xor(sk, &b, &c, &mut tmp_b)?;
         // Step 8.2: b = b >>> 7
-        self.e_rotr(&tmp_b, 7, &mut b);
+        self.e_rotr(&tmp_b, 7, &mut b)?;
 
         self.copy_to_from(result[0], &a);
         self.copy_to_from(result[1], &b);
         self.copy_to_from(result[2], &c);
         self.copy_to_from(result[3], &d);
+        Ok(())
     }
 
-    fn max(&self, sk: &ServerKey, a: &Vec<&[Ciphertext]>, result: &mut [Ciphertext]) {
+    fn max(
+        &self,
+        sk: &ServerKey,
+        a: &Vec<&[Ciphertext]>,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
         //does not handle negative numbers
-        let size = a[0].len();
+        let size = Self::check_elements("max", "a", a)?;
+        PulpError::expect_at_least("max", "result", size, result.len())?;
         let mut max: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
         let mut selector: Ciphertext = sk.trivial_encrypt(false);
         let mut current: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
@@ -1844,16 +2176,23 @@ impl ProcessorCircuits for ProcessorBoolean {
             for i in 0..size {
                 selector = self.compare_bit(sk, &max[i], &current[i], &selector);
             }
-            self.e_mux(sk, &selector, &max, &current, result);
+            self.e_mux(sk, &selector, &max, &current, result)?;
 
             if indx != size - 1 {
                 self.copy_to_from(&mut max, result);
             }
         }
+        Ok(())
     }
 
-    fn min(&self, sk: &ServerKey, a: &Vec<&[Ciphertext]>, result: &mut [Ciphertext]) {
-        let size = a[0].len();
+    fn min(
+        &self,
+        sk: &ServerKey,
+        a: &Vec<&[Ciphertext]>,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        let size = Self::check_elements("min", "a", a)?;
+        PulpError::expect_at_least("min", "result", size, result.len())?;
         let mut min: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
         let mut selector: Ciphertext = sk.trivial_encrypt(false);
         let mut current: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
@@ -1869,19 +2208,28 @@ impl ProcessorCircuits for ProcessorBoolean {
                 selector = self.compare_bit(sk, &min[i], &current[i], &selector);
             }
 
-            self.e_mux(sk, &selector, &current, &min, result);
+            self.e_mux(sk, &selector, &current, &min, result)?;
 
             if indx != size - 1 {
                 self.copy_to_from(&mut min, result);
             }
         }
+        Ok(())
     }
 
-    fn relu(&self, sk: &ServerKey, a: &[Ciphertext], result: &mut [Ciphertext]) {
+    fn relu(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_nonempty("relu", a)?;
+        Self::check_unary("relu", a, result)?;
         let size = a.len();
         let sign = &a[size - 1];
         let zero = vec![Ciphertext::Trivial(false); size];
-        self.e_mux(sk, &sign, &zero, &a, result);
+        self.e_mux(sk, &sign, &zero, &a, result)?;
+        Ok(())
     }
 
     // For negatives?
@@ -1891,7 +2239,9 @@ impl ProcessorCircuits for ProcessorBoolean {
         a: &[Ciphertext],
         b: &[Ciphertext],
         result: &mut [Ciphertext],
-    ) {
+    ) -> Result<(), PulpError> {
+        Self::check_nonempty("divider", a)?;
+        Self::check_binary("divider", a, b, result)?;
         let size = a.len();
         //  Q is the dividend, copy the bits from a to Q
         let mut Q: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
@@ -1919,14 +2269,14 @@ impl ProcessorCircuits for ProcessorBoolean {
         // self.copy_to_from(&mut M, &b);
 
         // Compute the two's complement of Q
-        self.e_not(sk, &a, &mut Q);
-        self.adder(sk, &Q, &ones, &mut Q_tmp);
-        self.e_mux(sk, &a[size - 1], &Q_tmp, &a, &mut Q);
+        self.e_not(sk, &a, &mut Q)?;
+        self.adder(sk, &Q, &ones, &mut Q_tmp)?;
+        self.e_mux(sk, &a[size - 1], &Q_tmp, &a, &mut Q)?;
 
         // Compute the two's complement of M
-        self.e_not(sk, &b, &mut M);
-        self.adder(sk, &M, &ones, &mut M_tmp);
-        self.e_mux(sk, &b[size - 1], &M_tmp, &b, &mut M);
+        self.e_not(sk, &b, &mut M)?;
+        self.adder(sk, &M, &ones, &mut M_tmp)?;
+        self.e_mux(sk, &b[size - 1], &M_tmp, &b, &mut M)?;
 
         for i in 0..size {
             // Left shift Q and replace the LSB with 0
@@ -1942,7 +2292,7 @@ impl ProcessorCircuits for ProcessorBoolean {
             self.e_shl_p(&A, &MSB, &mut A_tmp);
 
             // Compute A = A-M
-            self.subtracter(sk, &A_tmp, &M, &mut A);
+            self.subtracter(sk, &A_tmp, &M, &mut A)?;
 
             // Get the MSB of A
             A_msb = A[size - 1].clone();
@@ -1954,19 +2304,20 @@ impl ProcessorCircuits for ProcessorBoolean {
             Q[0] = not_A_msb.clone();
             if i != size - 1 {
                 // Compute A = A + M
-                self.adder(sk, &A, &M, &mut A_m);
+                self.adder(sk, &A, &M, &mut A_m)?;
 
                 // If MSB of A is 1, use an A + M result, else keep A unchanged
-                self.e_mux(sk, &A_msb, &A_m, &A, &mut A_tmp);
+                self.e_mux(sk, &A_msb, &A_m, &A, &mut A_tmp)?;
                 self.copy_to_from(&mut A, &A_tmp);
             }
         }
 
         // Perform the two's complement
-        self.e_not(sk, &Q, &mut inv_res);
-        self.adder(sk, &inv_res, &ones, &mut inv_res_tmp);
-        self.e_mux(sk, &sign_bit, &inv_res_tmp, &Q, result);
+        self.e_not(sk, &Q, &mut inv_res)?;
+        self.adder(sk, &inv_res, &ones, &mut inv_res_tmp)?;
+        self.e_mux(sk, &sign_bit, &inv_res_tmp, &Q, result)?;
         // self.copy_to_from(result, &Q);
+        Ok(())
     }
 
     fn modulo(
@@ -1975,7 +2326,9 @@ impl ProcessorCircuits for ProcessorBoolean {
         a: &[Ciphertext],
         b: &[Ciphertext],
         result: &mut [Ciphertext],
-    ) {
+    ) -> Result<(), PulpError> {
+        Self::check_nonempty("modulo", a)?;
+        Self::check_binary("modulo", a, b, result)?;
         let size = a.len();
         //  Q is the dividend, copy the bits from a to Q
         let mut Q: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
@@ -2015,7 +2368,7 @@ impl ProcessorCircuits for ProcessorBoolean {
             self.e_shl_p(&A, &MSB, &mut A_tmp);
 
             // Compute A = A-M
-            self.subtracter(sk, &A_tmp, &M, &mut A);
+            self.subtracter(sk, &A_tmp, &M, &mut A)?;
 
             // Get the MSB of A
             A_msb = A[size - 1].clone();
@@ -2027,18 +2380,19 @@ impl ProcessorCircuits for ProcessorBoolean {
             Q[0] = not_A_msb.clone();
 
             // Compute A = A + M
-            self.adder(sk, &A, &M, &mut A_m);
+            self.adder(sk, &A, &M, &mut A_m)?;
 
             // If MSB of A is 1, use an A + M result, else keep A unchanged
-            self.e_mux(sk, &A_msb, &A_m, &A, &mut A_tmp);
+            self.e_mux(sk, &A_msb, &A_m, &A, &mut A_tmp)?;
             self.copy_to_from(&mut A, &A_tmp);
         }
 
         // Perform the two's complement
-        self.e_not(sk, &A, &mut inv_res);
-        self.adder(sk, &inv_res, &ones, &mut inv_res_tmp);
-        self.e_mux(sk, &sign_bit, &inv_res_tmp, &A, result);
+        self.e_not(sk, &A, &mut inv_res)?;
+        self.adder(sk, &inv_res, &ones, &mut inv_res_tmp)?;
+        self.e_mux(sk, &sign_bit, &inv_res_tmp, &A, result)?;
         self.copy_to_from(result, &A);
+        Ok(())
     }
 
     fn mean(
@@ -2047,70 +2401,74 @@ impl ProcessorCircuits for ProcessorBoolean {
         a: &Vec<&[Ciphertext]>,
         count: usize,
         result: &mut [Ciphertext],
-    ) {
-        let size: usize = a[0].len();
+    ) -> Result<(), PulpError> {
+        let size: usize = Self::check_samples("mean", a, count)?;
+        PulpError::expect_at_least("mean", "result", size, result.len())?;
         let mut sum: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
         let mut tmp_sum: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
         self.copy_to_from(&mut sum, a[0]);
 
         for i in 1..count {
-            self.adder(sk, &sum, a[i], &mut tmp_sum);
+            self.adder(sk, &sum, a[i], &mut tmp_sum)?;
             self.copy_to_from(&mut sum, &tmp_sum);
         }
 
-        // Trivially encrypt the bits of count
-        let mut bits: Vec<bool> = Vec::new();
-        for i in 0..a[0].len() {
-            let bit = ((count >> i) & 1) != 0;
-            bits.push(bit);
-        }
-        let c_count: Vec<_> = bits.iter().map(|&bit| sk.trivial_encrypt(bit)).collect();
-
-        self.divider(sk, &sum, &c_count, result);
+        self.scalar_div(sk, &sum, count as i64, result)?;
+        Ok(())
     }
 
-    fn sqrt(&self, sk: &ServerKey, a: &[Ciphertext], result: &mut [Ciphertext]) {
-        let size: usize = a.len();
-
-        let mut x: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
-        let mut y: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
-        let mut x_y: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
-        let mut two: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
-
-        let mut n_iters: u8 = 0;
-        if size == 8 {
-            n_iters = 5;
-        } else if size == 16 {
-            n_iters = 10;
-        } else if size == 32 {
-            n_iters = 20;
-        } else {
-            eprintln!("An error occurred at `e_sqrt`, the supported data sizes 8, 16, and 32");
-            exit(1);
-        }
-        self.copy_to_from(&mut x, &a);
+    fn sqrt(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_nonempty("sqrt", a)?;
+        Self::check_unary("sqrt", a, result)?;
+        // Digit recurrence: one root bit per pair of operand bits, MSB pair first
+        let pairs: usize = a.len().div_ceil(2);
+        // The remainder stays below 2 * root + 1 < 2^(pairs + 1), so the shifted remainder
+        // and the trial value 4 * root + 1 fit in pairs + 3 bits, plus one sign bit
+        let rem_size: usize = pairs + 4;
+        let bit = |i: usize| a.get(i).cloned().unwrap_or(Ciphertext::Trivial(false));
+
+        let mut root: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); pairs];
+        let mut rem: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); rem_size];
+        let mut shifted: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); rem_size];
+        let mut trial: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); rem_size];
+        let mut diff: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); rem_size];
+
+        for i in (0..pairs).rev() {
+            // shifted = (rem << 2) | a[2i + 1..=2i]
+            shifted[0] = bit(2 * i);
+            shifted[1] = bit(2 * i + 1);
+            self.copy_to_from(&mut shifted[2..], &rem);
+
+            // trial = (root << 2) | 1, where root holds the bits found so far
+            trial[0] = Ciphertext::Trivial(true);
+            trial[1] = Ciphertext::Trivial(false);
+            let found: usize = pairs - 1 - i;
+            for j in 0..rem_size - 2 {
+                trial[j + 2] = if j < found {
+                    root[i + 1 + j].clone()
+                } else {
+                    Ciphertext::Trivial(false)
+                };
+            }
 
-        y[0] = sk.trivial_encrypt(true);
-        for i in 1..size {
-            y[i] = sk.trivial_encrypt(false);
-        }
-        for i in 0..size {
-            if i == 1 {
-                two[i] = sk.trivial_encrypt(true);
-            } else {
-                two[i] = sk.trivial_encrypt(false);
+            // The root bit is set when the trial value fits into the remainder
+            self.subtracter(sk, &shifted, &trial, &mut diff)?;
+            root[i] = self.e_not_bit(sk, &diff[rem_size - 1]);
+            // The remainder is not needed after the last root bit
+            if i != 0 {
+                self.e_mux(sk, &root[i], &diff, &shifted, &mut rem)?;
             }
         }
 
-        for i in 0..n_iters {
-            self.adder(sk, &x, &y, &mut x_y);
-            self.divider(sk, &x_y, &two, &mut x);
-
-            if i != n_iters - 1 {
-                self.divider(sk, &a, &x, &mut y);
-            }
+        for (i, bit) in result.iter_mut().take(a.len()).enumerate() {
+            *bit = root.get(i).cloned().unwrap_or(Ciphertext::Trivial(false));
         }
-        self.copy_to_from(result, &x);
+        Ok(())
     }
 
     fn variance(
@@ -2119,10 +2477,11 @@ impl ProcessorCircuits for ProcessorBoolean {
         a: &Vec<&[Ciphertext]>,
         count: usize,
         result: &mut [Ciphertext],
-    ) {
-        let size: usize = a[0].len();
+    ) -> Result<(), PulpError> {
+        let size: usize = Self::check_samples("variance", a, count)?;
+        PulpError::expect_at_least("variance", "result", size, result.len())?;
         let mut m: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
-        self.mean(sk, a, count, &mut m);
+        self.mean(sk, a, count, &mut m)?;
 
         let mut subs_squars: Vec<Vec<Ciphertext>> = Vec::with_capacity(count);
         let mut squars: Vec<Vec<Ciphertext>> = Vec::with_capacity(count);
@@ -2138,11 +2497,11 @@ impl ProcessorCircuits for ProcessorBoolean {
         }
 
         for i in 0..count {
-            self.subtracter(sk, a[i], &m, &mut subs[i]);
+            self.subtracter(sk, a[i], &m, &mut subs[i])?;
         }
 
         for i in 0..count {
-            self.multiplier(sk, &subs[i], &subs[i], &mut squars[i]);
+            self.multiplier(sk, &subs[i], &subs[i], &mut squars[i])?;
             subs_squars.push(squars[i].clone());
         }
 
@@ -2150,18 +2509,12 @@ impl ProcessorCircuits for ProcessorBoolean {
 
         // sum the values
         for i in 1..count {
-            self.adder(sk, &sum, &subs_squars[i], &mut tmp_sum);
+            self.adder(sk, &sum, &subs_squars[i], &mut tmp_sum)?;
             self.copy_to_from(&mut sum, &tmp_sum);
         }
 
-        // Trivially encrypt the bits of count
-        let mut bits: Vec<bool> = Vec::new();
-        for i in 0..a[0].len() {
-            let bit = ((count >> i) & 1) != 0;
-            bits.push(bit);
-        }
-        let c_count: Vec<_> = bits.iter().map(|&bit| sk.trivial_encrypt(bit)).collect();
-        self.divider(sk, &sum, &c_count, result);
+        self.scalar_div(sk, &sum, count as i64, result)?;
+        Ok(())
     }
 
     fn standard_deviation(
@@ -2170,10 +2523,412 @@ impl ProcessorCircuits for ProcessorBoolean {
         a: &Vec<&[Ciphertext]>,
         count: usize,
         result: &mut [Ciphertext],
-    ) {
-        let mut var: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); a[0].len()];
-        self.variance(sk, a, count, &mut var);
-        self.sqrt(sk, &var, result);
+    ) -> Result<(), PulpError> {
+        let size: usize = Self::check_samples("standard_deviation", a, count)?;
+        let mut var: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
+        self.variance(sk, a, count, &mut var)?;
+        self.sqrt(sk, &var, result)?;
+        Ok(())
+    }
+
+    fn histogram(
+        &self,
+        sk: &ServerKey,
+        a: &Vec<&[Ciphertext]>,
+        thresholds: &[i64],
+        result: &mut Vec<Vec<Ciphertext>>,
+    ) -> Result<(), PulpError> {
+        if !thresholds.windows(2).all(|t| t[0] < t[1]) {
+            return Err(PulpError::invalid(
+                "histogram",
+                format!("thresholds must be strictly ascending: {thresholds:?}"),
+            ));
+        }
+        for x in a.iter() {
+            Self::check_nonempty("histogram", x)?;
+        }
+        let n_buckets: usize = thresholds.len() + 1;
+
+        // A single bucket may receive every sample, so the counters must hold a.len()
+        let width: usize = (usize::BITS - a.len().leading_zeros()).max(1) as usize;
+        result.clear();
+        for _ in 0..n_buckets {
+            result.push(vec![Ciphertext::Trivial(false); width]);
+        }
+
+        let mut ge: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); thresholds.len()];
+        let mut carry: Ciphertext = sk.trivial_encrypt(false);
+        let mut sum: Ciphertext = sk.trivial_encrypt(false);
+        for x in a.iter() {
+            // Thresholds outside the width of x fold to constants in `scalar_cmp`
+            for j in 0..thresholds.len() {
+                ge[j] = self.scalar_cmp(sk, x, thresholds[j], 3)?;
+            }
+
+            for k in 0..n_buckets {
+                // x falls into bucket k when x >= t_(k-1) and x < t_k
+                let mut in_bucket: Ciphertext = if thresholds.is_empty() {
+                    sk.trivial_encrypt(true)
+                } else if k == 0 {
+                    self.e_not_bit(sk, &ge[0])
+                } else if k == n_buckets - 1 {
+                    ge[k - 1].clone()
+                } else {
+                    let below = self.e_not_bit(sk, &ge[k]);
+                    self.e_and_bit(sk, &ge[k - 1], &below)
+                };
+
+                // Increment the bucket counter by the indicator bit
+                for bit in result[k].iter_mut() {
+                    self.half_adder(sk, bit, &in_bucket, &mut carry, &mut sum);
+                    *bit = sum.clone();
+                    in_bucket = carry.clone();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn dot_product(
+        &self,
+        sk: &ServerKey,
+        a: &Vec<&[Ciphertext]>,
+        b: &Vec<&[Ciphertext]>,
+        result: &mut Vec<Ciphertext>,
+    ) -> Result<(), PulpError> {
+        let size: usize = Self::check_elements("dot_product", "a", a)?;
+        PulpError::expect_width("dot_product", "b", a.len(), b.len())?;
+        Self::check_element_width("dot_product", "b", b, size)?;
+        let acc_size: usize = 2 * size + Self::ceil_log2(a.len());
+        *result = self.dot_into(sk, a, b, acc_size)?;
+        Ok(())
+    }
+
+    fn dot_product_plain(
+        &self,
+        sk: &ServerKey,
+        a: &Vec<&[Ciphertext]>,
+        w: &[i64],
+        result: &mut Vec<Ciphertext>,
+    ) -> Result<(), PulpError> {
+        let size: usize = Self::check_elements("dot_product_plain", "a", a)?;
+        let acc_size: usize = Self::plain_acc_size(size, w, a.len());
+        *result = self.plain_dot_into(sk, a, w, acc_size)?;
+        Ok(())
+    }
+
+    fn matvec(
+        &self,
+        sk: &ServerKey,
+        m: &Vec<Vec<&[Ciphertext]>>,
+        x: &Vec<&[Ciphertext]>,
+        result: &mut Vec<Vec<Ciphertext>>,
+    ) -> Result<(), PulpError> {
+        let size: usize = Self::check_elements("matvec", "x", x)?;
+        for row in m.iter() {
+            PulpError::expect_width("matvec", "row", x.len(), row.len())?;
+            Self::check_element_width("matvec", "row", row, size)?;
+        }
+        let acc_size: usize = 2 * size + Self::ceil_log2(x.len());
+        result.clear();
+        for row in m.iter() {
+            result.push(self.dot_into(sk, row, x, acc_size)?);
+        }
+        Ok(())
+    }
+
+    fn matvec_plain(
+        &self,
+        sk: &ServerKey,
+        m: &[Vec<i64>],
+        x: &Vec<&[Ciphertext]>,
+        result: &mut Vec<Vec<Ciphertext>>,
+    ) -> Result<(), PulpError> {
+        // Size every row for the largest weight so all outputs share one width
+        let weights: Vec<i64> = m.iter().flatten().copied().collect();
+        let size: usize = Self::check_elements("matvec_plain", "x", x)?;
+        for row in m.iter() {
+            PulpError::expect_width("matvec_plain", "row", x.len(), row.len())?;
+        }
+        let acc_size: usize = Self::plain_acc_size(size, &weights, x.len());
+        result.clear();
+        for row in m.iter() {
+            result.push(self.plain_dot_into(sk, x, row, acc_size)?);
+        }
+        Ok(())
+    }
+
+    fn matmul(
+        &self,
+        sk: &ServerKey,
+        a: &Vec<Vec<&[Ciphertext]>>,
+        b: &Vec<Vec<&[Ciphertext]>>,
+        result: &mut Vec<Vec<Vec<Ciphertext>>>,
+    ) -> Result<(), PulpError> {
+        let inner: usize = b.len();
+        PulpError::expect_at_least("matmul", "a", 1, a.len())?;
+        PulpError::expect_at_least("matmul", "b", 1, inner)?;
+        // Every element of both matrices has the width of the first
+        let size: usize = Self::check_elements("matmul", "a row", &a[0])?;
+        for row in a.iter() {
+            PulpError::expect_width("matmul", "a row", inner, row.len())?;
+            Self::check_element_width("matmul", "a row", row, size)?;
+        }
+        for row in b.iter() {
+            PulpError::expect_width("matmul", "b row", b[0].len(), row.len())?;
+            Self::check_element_width("matmul", "b row", row, size)?;
+        }
+        let acc_size: usize = 2 * size + Self::ceil_log2(inner);
+        result.clear();
+        for row in a.iter() {
+            let mut out_row: Vec<Vec<Ciphertext>> = Vec::with_capacity(b[0].len());
+            for j in 0..b[0].len() {
+                let column: Vec<&[Ciphertext]> = b.iter().map(|b_row| b_row[j]).collect();
+                out_row.push(self.dot_into(sk, row, &column, acc_size)?);
+            }
+            result.push(out_row);
+        }
+        Ok(())
+    }
+
+    fn matmul_plain(
+        &self,
+        sk: &ServerKey,
+        a: &Vec<Vec<&[Ciphertext]>>,
+        w: &[Vec<i64>],
+        result: &mut Vec<Vec<Vec<Ciphertext>>>,
+    ) -> Result<(), PulpError> {
+        let weights: Vec<i64> = w.iter().flatten().copied().collect();
+        PulpError::expect_at_least("matmul_plain", "a", 1, a.len())?;
+        PulpError::expect_at_least("matmul_plain", "w", 1, w.len())?;
+        let size: usize = Self::check_elements("matmul_plain", "a row", &a[0])?;
+        for row in a.iter() {
+            PulpError::expect_width("matmul_plain", "a row", w.len(), row.len())?;
+            Self::check_element_width("matmul_plain", "a row", row, size)?;
+        }
+        for row in w.iter() {
+            PulpError::expect_width("matmul_plain", "w row", w[0].len(), row.len())?;
+        }
+        let acc_size: usize = Self::plain_acc_size(size, &weights, w.len());
+        result.clear();
+        for row in a.iter() {
+            let mut out_row: Vec<Vec<Ciphertext>> = Vec::with_capacity(w[0].len());
+            for j in 0..w[0].len() {
+                let column: Vec<i64> = w.iter().map(|w_row| w_row[j]).collect();
+                out_row.push(self.plain_dot_into(sk, row, &column, acc_size)?);
+            }
+            result.push(out_row);
+        }
+        Ok(())
+    }
+
+    fn clz(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_nonempty("clz", a)?;
+        let width: usize = Self::count_width(a.len());
+        PulpError::expect_at_least("clz", "result", width, result.len())?;
+
+        // Leaves, most significant bit first, padded at the bottom to a power of two with
+        // ones: they never add leading zeros and fold away as constants. Each node holds
+        // its leading zero count, whose top bit is set exactly when the node is all zeros.
+        let leaves: usize = a.len().next_power_of_two();
+        let mut nodes: Vec<Vec<Ciphertext>> = a
+            .iter()
+            .rev()
+            .map(|bit| vec![self.e_not_bit(sk, bit)])
+            .collect();
+        nodes.resize(leaves, vec![Ciphertext::Trivial(false)]);
+
+        // Merging two halves of `level` bits each: an all-zero high half adds its length to
+        // the count of the low half, otherwise the count of the high half is kept
+        let mut level: usize = 0;
+        while nodes.len() > 1 {
+            nodes = nodes
+                .chunks(2)
+                .map(|pair| {
+                    let (hi, lo) = (&pair[0], &pair[1]);
+                    let (hi_zero, lo_zero) = (&hi[level], &lo[level]);
+                    let mut count: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); level + 2];
+                    self.e_mux(sk, hi_zero, &lo[..level], &hi[..level], &mut count[..level])?;
+                    let lo_set: Ciphertext = self.e_not_bit(sk, lo_zero);
+                    count[level] = self.e_and_bit(sk, hi_zero, &lo_set);
+                    count[level + 1] = self.e_and_bit(sk, hi_zero, lo_zero);
+                    Ok(count)
+                })
+                .collect::<Result<_, PulpError>>()?;
+            level += 1;
+        }
+        // The count is at most `a.len()`, so the bits above `width` are zero
+        self.copy_to_from(&mut result[..width], &nodes[0]);
+        for bit in result[width..].iter_mut() {
+            *bit = Ciphertext::Trivial(false);
+        }
+        Ok(())
+    }
+
+    fn ctz(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_nonempty("ctz", a)?;
+        let width: usize = Self::count_width(a.len());
+        PulpError::expect_at_least("ctz", "result", width, result.len())?;
+        let mut reversed: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); a.len()];
+        self.bit_reverse(a, &mut reversed)?;
+        self.clz(sk, &reversed, result)
+    }
+
+    fn popcount(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_nonempty("popcount", a)?;
+        let width: usize = Self::count_width(a.len());
+        PulpError::expect_at_least("popcount", "result", width, result.len())?;
+
+        // Every level adds neighbouring partial counts, growing them by one bit up to
+        // `width`; an odd count out is carried to the next level unchanged
+        let mut counts: Vec<Vec<Ciphertext>> = a.iter().map(|bit| vec![bit.clone()]).collect();
+        let mut size: usize = 1;
+        while counts.len() > 1 {
+            let next_size: usize = (size + 1).min(width);
+            counts = counts
+                .chunks(2)
+                .map(|pair| {
+                    let mut sum: Vec<Ciphertext> = pair[0].clone();
+                    sum.resize(next_size, Ciphertext::Trivial(false));
+                    if pair.len() == 1 {
+                        return Ok(sum);
+                    }
+                    if size == 1 {
+                        let (mut carry, mut bit) =
+                            (Ciphertext::Trivial(false), Ciphertext::Trivial(false));
+                        self.half_adder(sk, &pair[0][0], &pair[1][0], &mut carry, &mut bit);
+                        sum[0] = bit;
+                        sum[1] = carry;
+                    } else {
+                        let mut other: Vec<Ciphertext> = pair[1].clone();
+                        other.resize(next_size, Ciphertext::Trivial(false));
+                        let addend: Vec<Ciphertext> = sum.clone();
+                        self.adder(sk, &addend, &other, &mut sum)?;
+                    }
+                    Ok(sum)
+                })
+                .collect::<Result<_, PulpError>>()?;
+            size = next_size;
+        }
+        let mut count: Vec<Ciphertext> = counts.remove(0);
+        count.resize(width, Ciphertext::Trivial(false));
+        self.copy_to_from(&mut result[..width], &count);
+        for bit in result[width..].iter_mut() {
+            *bit = Ciphertext::Trivial(false);
+        }
+        Ok(())
+    }
+
+    fn parity(&self, sk: &ServerKey, a: &[Ciphertext]) -> Result<Ciphertext, PulpError> {
+        Self::check_nonempty("parity", a)?;
+        let mut bits: Vec<Ciphertext> = a.to_vec();
+        while bits.len() > 1 {
+            bits = bits
+                .chunks(2)
+                .map(|pair| match pair {
+                    [x, y] => self.e_xor_bit(sk, x, y),
+                    _ => pair[0].clone(),
+                })
+                .collect();
+        }
+        Ok(bits.remove(0))
+    }
+
+    fn bit_reverse(&self, a: &[Ciphertext], result: &mut [Ciphertext]) -> Result<(), PulpError> {
+        Self::check_unary("bit_reverse", a, result)?;
+        for (r, bit) in result.iter_mut().zip(a.iter().rev()) {
+            r.clone_from(bit);
+        }
+        Ok(())
+    }
+
+    fn hamming_distance(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_nonempty("hamming_distance", a)?;
+        PulpError::expect_width("hamming_distance", "b", a.len(), b.len())?;
+        let width: usize = Self::count_width(a.len());
+        PulpError::expect_at_least("hamming_distance", "result", width, result.len())?;
+
+        let mut differences: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); a.len()];
+        self.e_xor(sk, a, b, &mut differences)?;
+        self.popcount(sk, &differences, result)
+    }
+
+    fn match_below_threshold(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        threshold: usize,
+    ) -> Result<Ciphertext, PulpError> {
+        Self::check_nonempty("match_below_threshold", a)?;
+        PulpError::expect_width("match_below_threshold", "b", a.len(), b.len())?;
+        // The distance is at most `a.len()`, so thresholds outside it have known outcomes
+        if threshold == 0 {
+            return Ok(Ciphertext::Trivial(false));
+        }
+        if threshold > a.len() {
+            return Ok(Ciphertext::Trivial(true));
+        }
+
+        // The spare top bit keeps the distance non-negative for the signed `scalar_cmp`
+        let width: usize = Self::count_width(a.len());
+        let mut distance: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); width + 1];
+        self.hamming_distance(sk, a, b, &mut distance)?;
+        self.scalar_cmp(sk, &distance, threshold as i64, 1)
+    }
+
+    fn lookup_table(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        table: &[u64],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError> {
+        Self::check_nonempty("lookup_table", a)?;
+        // The table has to be materialised, so the index is bounded well below 64 bits
+        if a.len() >= usize::BITS as usize - 1 {
+            return Err(PulpError::UnsupportedWidth {
+                op: "lookup_table",
+                width: a.len(),
+            });
+        }
+        if result.len() > u64::BITS as usize {
+            return Err(PulpError::UnsupportedWidth {
+                op: "lookup_table",
+                width: result.len(),
+            });
+        }
+        PulpError::expect_width("lookup_table", "table", 1 << a.len(), table.len())?;
+
+        // Subtrees are keyed by their leaves; the leaf count gives the depth, so equal keys
+        // are the same function of the same index bits
+        let mut shared: HashMap<Vec<bool>, Ciphertext> = HashMap::new();
+        for (j, r) in result.iter_mut().enumerate() {
+            let leaves: Vec<bool> = table.iter().map(|entry| (entry >> j) & 1 == 1).collect();
+            *r = self.lookup_subtree(sk, a, &leaves, &mut shared);
+        }
+        Ok(())
     }
 
     fn copy_to_from(&self, target: &mut [Ciphertext], source: &[Ciphertext]) {
@@ -2,6 +2,8 @@
 use super::*; // Imports items from the parent module
 use rand::Rng;
 use serial_test::serial;
+use crate::key_manager::KeyManager;
+use crate::benchmark::Benchmark;
 
 fn encode(a: i16, size: usize) -> Vec<bool> {
     // Convert to binary representation as a Vec<bool>
@@ -78,7 +80,7 @@ pub fn new_decrypt_decode(ciphertexts: &[Ciphertext], client_key: &ClientKey) ->
 #[test]
 #[serial]
 fn test_encrypt_decrypt() {
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -100,11 +102,11 @@ fn test_encrypt_decrypt() {
 #[serial]
 fn test_gate_bootstrap_cycles() {
     println!("Measuring the time difference between gate bootstrapping and gate evaluation");
-    println!("[*] All times are in milliseconds");
+    println!("[*] All times are in microseconds");
 
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
-    let server = ProcessorBoolean;
+    let mut bench = Benchmark::new(1);
     let mut rng = rand::thread_rng();
 
     // Define two numbers and convert them to signed 8-bit representation.
@@ -121,12 +123,12 @@ fn test_gate_bootstrap_cycles() {
     let mut ct_result6: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
 
-    server.e_and_bench(&server_key, &ct_a, &ct_b, &mut ct_result1);
-    server.e_or_bench(&server_key, &ct_a, &ct_b, &mut ct_result2);
-    server.e_xor_bench(&server_key, &ct_a, &ct_b, &mut ct_result3);
-    server.e_nand_bench(&server_key, &ct_a, &ct_b, &mut ct_result4);
-    server.e_nor_bench(&server_key, &ct_a, &ct_b, &mut ct_result5);
-    server.e_xnor_bench(&server_key, &ct_a, &ct_b, &mut ct_result6);
+    bench.gate(&server_key, GateKind::And, &ct_a, &ct_b, &mut ct_result1);
+    bench.gate(&server_key, GateKind::Or, &ct_a, &ct_b, &mut ct_result2);
+    bench.gate(&server_key, GateKind::Xor, &ct_a, &ct_b, &mut ct_result3);
+    bench.gate(&server_key, GateKind::Nand, &ct_a, &ct_b, &mut ct_result4);
+    bench.gate(&server_key, GateKind::Nor, &ct_a, &ct_b, &mut ct_result5);
+    bench.gate(&server_key, GateKind::Xnor, &ct_a, &ct_b, &mut ct_result6);
 
 
 
@@ -147,17 +149,21 @@ fn test_gate_bootstrap_cycles() {
 
     let dec_res = decrypt_decode(&ct_result6, &client_key);
     assert_eq!(dec_res, !(a ^ b));
+
+    // Every gate yields a gate-time and a bootstrap-time record
+    assert_eq!(bench.records().len(), 12);
+    assert!(bench.records().iter().all(|r| r.width == 16));
 }
 
 #[test]
 #[serial]
 fn test_gate_cycles_nobootstrapping() {
     println!("Measuring the time difference between gate bootstrapping and gate evaluation");
-    println!("[*] All times are in milliseconds");
+    println!("[*] All times are in microseconds");
 
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
-    let server = ProcessorBoolean;
+    let mut bench = Benchmark::new(1);
     let mut rng = rand::thread_rng();
 
     // Define two numbers and convert them to signed 8-bit representation.
@@ -174,12 +180,12 @@ fn test_gate_cycles_nobootstrapping() {
     let mut ct_result6: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
 
-    server.e_and_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result1);
-    server.e_or_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result2);
-    server.e_xor_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result3);
-    server.e_nand_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result4);
-    server.e_nor_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result5);
-    server.e_xnor_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result6);
+    bench.gate_no_bootstrap(&server_key, GateKind::And, &ct_a, &ct_b, &mut ct_result1);
+    bench.gate_no_bootstrap(&server_key, GateKind::Or, &ct_a, &ct_b, &mut ct_result2);
+    bench.gate_no_bootstrap(&server_key, GateKind::Xor, &ct_a, &ct_b, &mut ct_result3);
+    bench.gate_no_bootstrap(&server_key, GateKind::Nand, &ct_a, &ct_b, &mut ct_result4);
+    bench.gate_no_bootstrap(&server_key, GateKind::Nor, &ct_a, &ct_b, &mut ct_result5);
+    bench.gate_no_bootstrap(&server_key, GateKind::Xnor, &ct_a, &ct_b, &mut ct_result6);
 
 
     let dec_res = decrypt_decode(&ct_result1, &client_key);
@@ -205,7 +211,7 @@ fn test_gate_cycles_nobootstrapping() {
 fn test_and() {
     let fn_name = "e_and";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -218,7 +224,7 @@ fn test_and() {
     let ct_b = encode_encrypt(b, 16, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_and(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.e_and(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -231,7 +237,7 @@ fn test_and() {
 fn test_or() {
     let fn_name = "e_or";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -244,7 +250,7 @@ fn test_or() {
     let ct_b = encode_encrypt(b, 16, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_or(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.e_or(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -257,7 +263,7 @@ fn test_or() {
 fn test_xor() {
     let fn_name = "e_xor";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -270,7 +276,7 @@ fn test_xor() {
     let ct_b = encode_encrypt(b, 16, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_xor(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.e_xor(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -282,7 +288,7 @@ fn test_xor() {
 fn test_nand() {
     let fn_name = "e_nand";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -295,7 +301,7 @@ fn test_nand() {
     let ct_b = encode_encrypt(b, 16, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_nand(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.e_nand(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -307,7 +313,7 @@ fn test_nand() {
 fn test_nor() {
     let fn_name = "e_nor";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -320,7 +326,7 @@ fn test_nor() {
     let ct_b = encode_encrypt(b, 16, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_nor(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.e_nor(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -332,7 +338,7 @@ fn test_nor() {
 fn test_xnor() {
     let fn_name = "e_xnor";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -345,7 +351,7 @@ fn test_xnor() {
     let ct_b = encode_encrypt(b, 16, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_xnor(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.e_xnor(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -357,7 +363,7 @@ fn test_xnor() {
 fn test_not() {
     let fn_name = "e_not";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -368,7 +374,7 @@ fn test_not() {
     let ct_a = encode_encrypt(a, 16, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_not(&server_key, &ct_a, &mut ct_result);
+    server.e_not(&server_key, &ct_a, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -381,7 +387,7 @@ fn test_not() {
 fn test_mux() {
     let fn_name = "e_mux";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -396,7 +402,7 @@ fn test_mux() {
     let ct_c = client_key.encrypt(c);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_mux(&server_key, &ct_c, &ct_a, &ct_b, &mut ct_result);
+    server.e_mux(&server_key, &ct_c, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -409,7 +415,7 @@ fn test_mux() {
 fn test_shl() {
     let fn_name = "e_shl";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -422,7 +428,7 @@ fn test_shl() {
     let ct_a = encode_encrypt(a, 16, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_shl(&ct_a, shift, &mut ct_result);
+    server.e_shl(&ct_a, shift, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -435,7 +441,7 @@ fn test_shl() {
 fn test_shr() {
     let fn_name = "e_shr";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -447,7 +453,7 @@ fn test_shr() {
     let ct_a = encode_encrypt(a, 16, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_shr(&ct_a, shift, &mut ct_result);
+    server.e_shr(&ct_a, shift, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -460,7 +466,7 @@ fn test_shr() {
 fn test_e_rotr() {
     let fn_name = "e_rotr";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -472,7 +478,7 @@ fn test_e_rotr() {
     let ct_a = encode_encrypt(a, 16, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_rotr(&ct_a, shift, &mut ct_result);
+    server.e_rotr(&ct_a, shift, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -485,7 +491,7 @@ fn test_e_rotr() {
 fn test_e_rotl() {
     let fn_name = "e_rotl";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -498,7 +504,7 @@ fn test_e_rotl() {
     let ct_a = encode_encrypt(a, 16, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_rotl(&ct_a, shift, &mut ct_result);
+    server.e_rotl(&ct_a, shift, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -511,7 +517,7 @@ fn test_e_rotl() {
 fn test_adder() {
     let fn_name = "adder";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -524,7 +530,7 @@ fn test_adder() {
     let ct_b = encode_encrypt(b, 16, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.adder(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.adder(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -537,7 +543,7 @@ fn test_adder() {
 fn test_sign_adder() {
     let fn_name = "adder";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -546,15 +552,28 @@ fn test_sign_adder() {
     let a: i16 = rng.gen_range(-50..50);
     let b: i16 = rng.gen_range(-50..50);
 
-    let ct_a = encode_encrypt(a, 16, &client_key);
-    let ct_b = encode_encrypt(b, 16, &client_key);
-    let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 16];
-
-    server.sign_adder(&server_key, &ct_a, &ct_b, &mut ct_result);
-
-    let dec_res = decrypt_decode(&ct_result, &client_key);
-    println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
-    assert_eq!(dec_res, a.wrapping_add(b));
+    // `sign_adder` works on sign-magnitude integers: the top bit is the sign and the
+    // other bits hold the magnitude. The fixed pairs take every sign path, the last one
+    // to a zero that may come back as negative zero.
+    let sign_magnitude = |v: i16| if v < 0 { -v | i16::MIN } else { v };
+    for (a, b) in [(a, b), (-7, 3), (7, -3), (-20, -30), (-5, 5)] {
+        let ct_a = encode_encrypt(sign_magnitude(a), 16, &client_key);
+        let ct_b = encode_encrypt(sign_magnitude(b), 16, &client_key);
+        let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 16];
+
+        server
+            .sign_adder(&server_key, &ct_a, &ct_b, &mut ct_result)
+            .unwrap();
+
+        let dec_res = decrypt_decode(&ct_result, &client_key);
+        let sum: i16 = if dec_res < 0 {
+            -(dec_res & i16::MAX)
+        } else {
+            dec_res
+        };
+        println!("\t {} {} {} = {}", a, b, fn_name, sum);
+        assert_eq!(sum, a + b);
+    }
     println!("[✓] PASS: {fn_name}\n");
 }
 
@@ -563,7 +582,7 @@ fn test_sign_adder() {
 fn test_subtracter() {
     let fn_name = "subtracter";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -580,7 +599,7 @@ fn test_subtracter() {
     println!("{a} - {b}");
     //server.ptxt_subtracter(&encode(a, 16), &encode(b, 16), &mut result);
 
-    server.subtracter(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.subtracter(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -593,7 +612,7 @@ fn test_subtracter() {
 fn test_multiplier() {
     let fn_name = "multiplier";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -613,7 +632,7 @@ fn test_multiplier() {
     println!("{a} * {b} = {}", decode(&res));
     println!("{:?} * {:?} = {:?}", enc_a, enc_b, res);
 
-    server.multiplier(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.multiplier(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -626,7 +645,7 @@ fn test_multiplier() {
 fn test_div() {
     let fn_name = "divider";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -644,7 +663,7 @@ fn test_div() {
     let ct_b = encode_encrypt(b, 16, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.divider(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.divider(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -656,25 +675,21 @@ fn test_div() {
 fn test_modulo() {
     let fn_name = "modulo";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
 
-    // Define two numbers and convert them to signed 16-bit representation.
-    let mut a: i16 = rng.gen_range(-50..50);
-    let mut b: i16 = rng.gen_range(-50..50);
-
-    while !(a >= b) {
-        a = rng.gen_range(-50..50);
-        b = rng.gen_range(-50..50);
-    }
+    // `modulo` takes the remainder of non-negative operands, so draw a non-negative
+    // dividend and a positive divisor
+    let a: i16 = rng.gen_range(0..50);
+    let b: i16 = rng.gen_range(1..50);
 
     let ct_a = encode_encrypt(a, 16, &client_key);
     let ct_b = encode_encrypt(b, 16, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.modulo(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.modulo(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -687,7 +702,7 @@ fn test_modulo() {
 fn test_max() {
     let fn_name = "max";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -706,7 +721,7 @@ fn test_max() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.max(&server_key, &ct_a, &mut ct_result);
+    server.max(&server_key, &ct_a, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     assert_eq!(dec_res, *a.iter().max().unwrap());
@@ -718,7 +733,7 @@ fn test_max() {
 fn test_min() {
     let fn_name = "min";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -737,7 +752,7 @@ fn test_min() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.min(&server_key, &ct_a, &mut ct_result);
+    server.min(&server_key, &ct_a, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     assert_eq!(dec_res, *a.iter().min().unwrap());
@@ -749,7 +764,7 @@ fn test_min() {
 fn test_relu() {
     let fn_name = "relu";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -765,8 +780,8 @@ fn test_relu() {
     let mut ct_result_a: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
     let mut ct_result_b: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_b.len()];
 
-    server.relu(&server_key, &ct_a, &mut ct_result_a);
-    server.relu(&server_key, &ct_b, &mut ct_result_b);
+    server.relu(&server_key, &ct_a, &mut ct_result_a).unwrap();
+    server.relu(&server_key, &ct_b, &mut ct_result_b).unwrap();
 
     let dec_res = decrypt_decode(&ct_result_a, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -782,7 +797,7 @@ fn test_relu() {
 fn test_sqrt() {
     let fn_name = "sqrt";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -792,7 +807,7 @@ fn test_sqrt() {
     let ct_a = encode_encrypt(a, 16, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.sqrt(&server_key, &ct_a, &mut ct_result);
+    server.sqrt(&server_key, &ct_a, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -804,7 +819,7 @@ fn test_sqrt() {
 fn test_mean() {
     let fn_name = "mean";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -823,7 +838,7 @@ fn test_mean() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.mean(&server_key, &ct_a, a.len(), &mut ct_result);
+    server.mean(&server_key, &ct_a, a.len(), &mut ct_result).unwrap();
 
     let ptxt_result: i16 = a.iter().sum();
 
@@ -836,7 +851,7 @@ fn test_mean() {
 fn test_variance() {
     let fn_name = "variance";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -855,7 +870,7 @@ fn test_variance() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.variance(&server_key, &ct_a, a.len(), &mut ct_result);
+    server.variance(&server_key, &ct_a, a.len(), &mut ct_result).unwrap();
 
     let ptxt_result: i16 = a.iter().sum();
     let mean:i16 = ptxt_result.wrapping_div(a.len() as i16);
@@ -874,7 +889,7 @@ fn test_variance() {
 fn test_standard_deviation() {
     let fn_name = "standard_deviation";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -893,7 +908,7 @@ fn test_standard_deviation() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.standard_deviation(&server_key, &ct_a, a.len(), &mut ct_result);
+    server.standard_deviation(&server_key, &ct_a, a.len(), &mut ct_result).unwrap();
 
     let ptxt_result: i16 = a.iter().sum();
     let mean:i16 = ptxt_result.wrapping_div(a.len() as i16);
@@ -909,3 +924,42 @@ fn test_standard_deviation() {
     assert_eq!(dec_res, stdev);
     println!("[✓] PASS: {fn_name}\n");
 }
+#[test]
+#[serial]
+fn test_instrumented() {
+    let fn_name = "instrumented";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+    let mut rng = rand::thread_rng();
+
+    let ct_a = encode_encrypt(rng.gen_range(-100..100), 16, &client_key);
+    let ct_b = encode_encrypt(rng.gen_range(-100..100), 16, &client_key);
+
+    let multiplier = server
+        .instrumented(&[&ct_a, &ct_b], |p| {
+            let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 16];
+            p.multiplier(&server_key, &ct_a, &ct_b, &mut ct_result)
+                .unwrap();
+            ct_result
+        })
+        .unwrap();
+    println!("\t multiplier: {multiplier}");
+
+    // Multiplying by a constant needs no AND gates for the partial products
+    let scalar_mul = server
+        .instrumented(&[&ct_a], |p| {
+            let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 16];
+            p.scalar_mul(&server_key, &ct_a, 100, &mut ct_result)
+                .unwrap();
+            ct_result
+        })
+        .unwrap();
+    println!("\t scalar_mul: {scalar_mul}");
+
+    assert_eq!(multiplier.pitch_trims, multiplier.gates() - multiplier.not - multiplier.mux);
+    assert!(scalar_mul.pitch_trims < multiplier.pitch_trims);
+    assert!(scalar_mul.depth < multiplier.depth);
+    println!("[✓] PASS: {fn_name}\n");
+}
@@ -2,6 +2,8 @@
 use super::*; // Imports items from the parent module
 use rand::Rng;
 use serial_test::serial;
+use crate::key_manager::KeyManager;
+use crate::benchmark::Benchmark;
 
 fn encode(a: i32, size: usize) -> Vec<bool> {
     // Convert to binary representation as a Vec<bool>
@@ -78,7 +80,7 @@ pub fn new_decrypt_decode(ciphertexts: &[Ciphertext], client_key: &ClientKey) ->
 #[test]
 #[serial]
 fn test_encrypt_decrypt() {
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -101,11 +103,11 @@ fn test_encrypt_decrypt() {
 #[serial]
 fn test_gate_bootstrap_cycles() {
     println!("Measuring the time difference between gate bootstrapping and gate evaluation");
-    println!("[*] All times are in milliseconds");
+    println!("[*] All times are in microseconds");
 
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
-    let server = ProcessorBoolean;
+    let mut bench = Benchmark::new(1);
     let mut rng = rand::thread_rng();
 
     // Define two numbers and convert them to signed 8-bit representation.
@@ -122,12 +124,12 @@ fn test_gate_bootstrap_cycles() {
     let mut ct_result6: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
 
-    server.e_and_bench(&server_key, &ct_a, &ct_b, &mut ct_result1);
-    server.e_or_bench(&server_key, &ct_a, &ct_b, &mut ct_result2);
-    server.e_xor_bench(&server_key, &ct_a, &ct_b, &mut ct_result3);
-    server.e_nand_bench(&server_key, &ct_a, &ct_b, &mut ct_result4);
-    server.e_nor_bench(&server_key, &ct_a, &ct_b, &mut ct_result5);
-    server.e_xnor_bench(&server_key, &ct_a, &ct_b, &mut ct_result6);
+    bench.gate(&server_key, GateKind::And, &ct_a, &ct_b, &mut ct_result1);
+    bench.gate(&server_key, GateKind::Or, &ct_a, &ct_b, &mut ct_result2);
+    bench.gate(&server_key, GateKind::Xor, &ct_a, &ct_b, &mut ct_result3);
+    bench.gate(&server_key, GateKind::Nand, &ct_a, &ct_b, &mut ct_result4);
+    bench.gate(&server_key, GateKind::Nor, &ct_a, &ct_b, &mut ct_result5);
+    bench.gate(&server_key, GateKind::Xnor, &ct_a, &ct_b, &mut ct_result6);
 
 
 
@@ -148,17 +150,21 @@ fn test_gate_bootstrap_cycles() {
 
     let dec_res = decrypt_decode(&ct_result6, &client_key);
     assert_eq!(dec_res, !(a ^ b));
+
+    // Every gate yields a gate-time and a bootstrap-time record
+    assert_eq!(bench.records().len(), 12);
+    assert!(bench.records().iter().all(|r| r.width == 32));
 }
 
 #[test]
 #[serial]
 fn test_gate_cycles_nobootstrapping() {
     println!("Measuring the time difference between gate bootstrapping and gate evaluation");
-    println!("[*] All times are in milliseconds");
+    println!("[*] All times are in microseconds");
 
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
-    let server = ProcessorBoolean;
+    let mut bench = Benchmark::new(1);
     let mut rng = rand::thread_rng();
 
     // Define two numbers and convert them to signed 8-bit representation.
@@ -175,12 +181,12 @@ fn test_gate_cycles_nobootstrapping() {
     let mut ct_result6: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
 
-    server.e_and_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result1);
-    server.e_or_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result2);
-    server.e_xor_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result3);
-    server.e_nand_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result4);
-    server.e_nor_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result5);
-    server.e_xnor_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result6);
+    bench.gate_no_bootstrap(&server_key, GateKind::And, &ct_a, &ct_b, &mut ct_result1);
+    bench.gate_no_bootstrap(&server_key, GateKind::Or, &ct_a, &ct_b, &mut ct_result2);
+    bench.gate_no_bootstrap(&server_key, GateKind::Xor, &ct_a, &ct_b, &mut ct_result3);
+    bench.gate_no_bootstrap(&server_key, GateKind::Nand, &ct_a, &ct_b, &mut ct_result4);
+    bench.gate_no_bootstrap(&server_key, GateKind::Nor, &ct_a, &ct_b, &mut ct_result5);
+    bench.gate_no_bootstrap(&server_key, GateKind::Xnor, &ct_a, &ct_b, &mut ct_result6);
 
 
     let dec_res = decrypt_decode(&ct_result1, &client_key);
@@ -207,7 +213,7 @@ fn test_gate_cycles_nobootstrapping() {
 fn test_and() {
     let fn_name = "e_and";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -220,7 +226,7 @@ fn test_and() {
     let ct_b = encode_encrypt(b, 32, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_and(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.e_and(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -233,7 +239,7 @@ fn test_and() {
 fn test_or() {
     let fn_name = "e_or";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -246,7 +252,7 @@ fn test_or() {
     let ct_b = encode_encrypt(b, 32, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_or(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.e_or(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -259,7 +265,7 @@ fn test_or() {
 fn test_xor() {
     let fn_name = "e_xor";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -272,7 +278,7 @@ fn test_xor() {
     let ct_b = encode_encrypt(b, 32, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_xor(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.e_xor(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -284,7 +290,7 @@ fn test_xor() {
 fn test_nand() {
     let fn_name = "e_nand";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -297,7 +303,7 @@ fn test_nand() {
     let ct_b = encode_encrypt(b, 32, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_nand(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.e_nand(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -309,7 +315,7 @@ fn test_nand() {
 fn test_nor() {
     let fn_name = "e_nor";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -322,7 +328,7 @@ fn test_nor() {
     let ct_b = encode_encrypt(b, 32, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_nor(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.e_nor(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -334,7 +340,7 @@ fn test_nor() {
 fn test_xnor() {
     let fn_name = "e_xnor";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -347,7 +353,7 @@ fn test_xnor() {
     let ct_b = encode_encrypt(b, 32, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_xnor(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.e_xnor(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -359,7 +365,7 @@ fn test_xnor() {
 fn test_not() {
     let fn_name = "e_not";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -370,7 +376,7 @@ fn test_not() {
     let ct_a = encode_encrypt(a, 32, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_not(&server_key, &ct_a, &mut ct_result);
+    server.e_not(&server_key, &ct_a, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -383,7 +389,7 @@ fn test_not() {
 fn test_mux() {
     let fn_name = "e_mux";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -398,7 +404,7 @@ fn test_mux() {
     let ct_c = client_key.encrypt(c);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_mux(&server_key, &ct_c, &ct_a, &ct_b, &mut ct_result);
+    server.e_mux(&server_key, &ct_c, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -411,7 +417,7 @@ fn test_mux() {
 fn test_shl() {
     let fn_name = "e_shl";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -424,7 +430,7 @@ fn test_shl() {
     let ct_a = encode_encrypt(a, 32, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_shl(&ct_a, shift, &mut ct_result);
+    server.e_shl(&ct_a, shift, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -437,7 +443,7 @@ fn test_shl() {
 fn test_shr() {
     let fn_name = "e_shr";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -449,7 +455,7 @@ fn test_shr() {
     let ct_a = encode_encrypt(a, 32, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_shr(&ct_a, shift, &mut ct_result);
+    server.e_shr(&ct_a, shift, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -462,7 +468,7 @@ fn test_shr() {
 fn test_e_rotr() {
     let fn_name = "e_rotr";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -474,7 +480,7 @@ fn test_e_rotr() {
     let ct_a = encode_encrypt(a, 32, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_rotr(&ct_a, shift, &mut ct_result);
+    server.e_rotr(&ct_a, shift, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -487,7 +493,7 @@ fn test_e_rotr() {
 fn test_e_rotl() {
     let fn_name = "e_rotl";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -500,7 +506,7 @@ fn test_e_rotl() {
     let ct_a = encode_encrypt(a, 32, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_rotl(&ct_a, shift, &mut ct_result);
+    server.e_rotl(&ct_a, shift, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -513,7 +519,7 @@ fn test_e_rotl() {
 fn test_adder() {
     let fn_name = "adder";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -526,7 +532,7 @@ fn test_adder() {
     let ct_b = encode_encrypt(b, 32, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.adder(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.adder(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -539,7 +545,7 @@ fn test_adder() {
 fn test_sign_adder() {
     let fn_name = "adder";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -548,15 +554,28 @@ fn test_sign_adder() {
     let a: i32 = rng.gen_range(-50..50);
     let b: i32 = rng.gen_range(-50..50);
 
-    let ct_a = encode_encrypt(a, 32, &client_key);
-    let ct_b = encode_encrypt(b, 32, &client_key);
-    let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 32];
-
-    server.sign_adder(&server_key, &ct_a, &ct_b, &mut ct_result);
-
-    let dec_res = decrypt_decode(&ct_result, &client_key);
-    println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
-    assert_eq!(dec_res, a.wrapping_add(b));
+    // `sign_adder` works on sign-magnitude integers: the top bit is the sign and the
+    // other bits hold the magnitude. The fixed pairs take every sign path, the last one
+    // to a zero that may come back as negative zero.
+    let sign_magnitude = |v: i32| if v < 0 { -v | i32::MIN } else { v };
+    for (a, b) in [(a, b), (-7, 3), (7, -3), (-20, -30), (-5, 5)] {
+        let ct_a = encode_encrypt(sign_magnitude(a), 32, &client_key);
+        let ct_b = encode_encrypt(sign_magnitude(b), 32, &client_key);
+        let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 32];
+
+        server
+            .sign_adder(&server_key, &ct_a, &ct_b, &mut ct_result)
+            .unwrap();
+
+        let dec_res = decrypt_decode(&ct_result, &client_key);
+        let sum: i32 = if dec_res < 0 {
+            -(dec_res & i32::MAX)
+        } else {
+            dec_res
+        };
+        println!("\t {} {} {} = {}", a, b, fn_name, sum);
+        assert_eq!(sum, a + b);
+    }
     println!("[✓] PASS: {fn_name}\n");
 }
 
@@ -565,7 +584,7 @@ fn test_sign_adder() {
 fn test_subtracter() {
     let fn_name = "subtracter";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -582,7 +601,7 @@ fn test_subtracter() {
     println!("{a} - {b}");
     //server.ptxt_subtracter(&encode(a, 32), &encode(b, 32), &mut result);
 
-    server.subtracter(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.subtracter(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -595,7 +614,7 @@ fn test_subtracter() {
 fn test_multiplier() {
     let fn_name = "multiplier";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -615,7 +634,7 @@ fn test_multiplier() {
     println!("{a} * {b} = {}", decode(&res));
     println!("{:?} * {:?} = {:?}", enc_a, enc_b, res);
 
-    server.multiplier(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.multiplier(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -628,7 +647,7 @@ fn test_multiplier() {
 fn test_div() {
     let fn_name = "divider";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -646,7 +665,7 @@ fn test_div() {
     let ct_b = encode_encrypt(b, 32, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.divider(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.divider(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -658,25 +677,21 @@ fn test_div() {
 fn test_modulo() {
     let fn_name = "modulo";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
 
-    // Define two numbers and convert them to signed 32-bit representation.
-    let mut a: i32 = rng.gen_range(-50..50);
-    let mut b: i32 = rng.gen_range(-50..50);
-
-    while !(a >= b) {
-        a = rng.gen_range(-50..50);
-        b = rng.gen_range(-50..50);
-    }
+    // `modulo` takes the remainder of non-negative operands, so draw a non-negative
+    // dividend and a positive divisor
+    let a: i32 = rng.gen_range(0..50);
+    let b: i32 = rng.gen_range(1..50);
 
     let ct_a = encode_encrypt(a, 32, &client_key);
     let ct_b = encode_encrypt(b, 32, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.modulo(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.modulo(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -689,7 +704,7 @@ fn test_modulo() {
 fn test_max() {
     let fn_name = "max";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -708,7 +723,7 @@ fn test_max() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.max(&server_key, &ct_a, &mut ct_result);
+    server.max(&server_key, &ct_a, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     assert_eq!(dec_res, *a.iter().max().unwrap());
@@ -720,7 +735,7 @@ fn test_max() {
 fn test_min() {
     let fn_name = "min";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -739,7 +754,7 @@ fn test_min() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.min(&server_key, &ct_a, &mut ct_result);
+    server.min(&server_key, &ct_a, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     assert_eq!(dec_res, *a.iter().min().unwrap());
@@ -751,7 +766,7 @@ fn test_min() {
 fn test_relu() {
     let fn_name = "relu";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -767,8 +782,8 @@ fn test_relu() {
     let mut ct_result_a: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
     let mut ct_result_b: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_b.len()];
 
-    server.relu(&server_key, &ct_a, &mut ct_result_a);
-    server.relu(&server_key, &ct_b, &mut ct_result_b);
+    server.relu(&server_key, &ct_a, &mut ct_result_a).unwrap();
+    server.relu(&server_key, &ct_b, &mut ct_result_b).unwrap();
 
     let dec_res = decrypt_decode(&ct_result_a, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -784,7 +799,7 @@ fn test_relu() {
 fn test_sqrt() {
     let fn_name = "sqrt";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -794,7 +809,7 @@ fn test_sqrt() {
     let ct_a = encode_encrypt(a, 32, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.sqrt(&server_key, &ct_a, &mut ct_result);
+    server.sqrt(&server_key, &ct_a, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -806,7 +821,7 @@ fn test_sqrt() {
 fn test_mean() {
     let fn_name = "mean";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -825,7 +840,7 @@ fn test_mean() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.mean(&server_key, &ct_a, a.len(), &mut ct_result);
+    server.mean(&server_key, &ct_a, a.len(), &mut ct_result).unwrap();
 
     let ptxt_result: i32 = a.iter().sum();
 
@@ -838,7 +853,7 @@ fn test_mean() {
 fn test_variance() {
     let fn_name = "variance";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -857,7 +872,7 @@ fn test_variance() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.variance(&server_key, &ct_a, a.len(), &mut ct_result);
+    server.variance(&server_key, &ct_a, a.len(), &mut ct_result).unwrap();
 
     let ptxt_result: i32 = a.iter().sum();
     let mean:i32 = ptxt_result.wrapping_div(a.len() as i32);
@@ -876,7 +891,7 @@ fn test_variance() {
 fn test_standard_deviation() {
     let fn_name = "standard_deviation";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -895,7 +910,7 @@ fn test_standard_deviation() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.standard_deviation(&server_key, &ct_a, a.len(), &mut ct_result);
+    server.standard_deviation(&server_key, &ct_a, a.len(), &mut ct_result).unwrap();
 
     let ptxt_result: i32 = a.iter().sum();
     let mean:i32 = ptxt_result.wrapping_div(a.len() as i32);
@@ -2,6 +2,8 @@
 use super::*; // Imports items from the parent module
 use rand::Rng;
 use serial_test::serial;
+use crate::key_manager::KeyManager;
+use crate::benchmark::Benchmark;
 
 fn encode(a: i8, size: usize) -> Vec<bool> {
     // Convert to binary representation as a Vec<bool>
@@ -75,10 +77,25 @@ pub fn new_decrypt_decode(ciphertexts: &[Ciphertext], client_key: &ClientKey) ->
     i16::from_ne_bytes(bits.to_ne_bytes())
 }
 
+// Decrypt a ciphertext vector of any width and sign-extend it to i64
+pub fn wide_decrypt_decode(ciphertexts: &[Ciphertext], client_key: &ClientKey) -> i64 {
+    let mut bits: i64 = 0;
+    for (i, ct) in ciphertexts.iter().enumerate() {
+        let bit = client_key.decrypt(ct);
+        print!("{}", bit as u8);
+        if bit {
+            bits |= 1i64 << i;
+        }
+    }
+    println!();
+    let unused = 64 - ciphertexts.len() as u32;
+    (bits << unused) >> unused
+}
+
 #[test]
 #[serial]
 fn test_encrypt_decrypt() {
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -101,11 +118,11 @@ fn test_encrypt_decrypt() {
 #[serial]
 fn test_gate_bootstrap_cycles() {
     println!("Measuring the time difference between gate bootstrapping and gate evaluation");
-    println!("[*] All times are in milliseconds");
+    println!("[*] All times are in microseconds");
 
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
-    let server = ProcessorBoolean;
+    let mut bench = Benchmark::new(1);
     let mut rng = rand::thread_rng();
 
     // Define two numbers and convert them to signed 8-bit representation.
@@ -122,12 +139,12 @@ fn test_gate_bootstrap_cycles() {
     let mut ct_result6: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
 
-    server.e_and_bench(&server_key, &ct_a, &ct_b, &mut ct_result1);
-    server.e_or_bench(&server_key, &ct_a, &ct_b, &mut ct_result2);
-    server.e_xor_bench(&server_key, &ct_a, &ct_b, &mut ct_result3);
-    server.e_nand_bench(&server_key, &ct_a, &ct_b, &mut ct_result4);
-    server.e_nor_bench(&server_key, &ct_a, &ct_b, &mut ct_result5);
-    server.e_xnor_bench(&server_key, &ct_a, &ct_b, &mut ct_result6);
+    bench.gate(&server_key, GateKind::And, &ct_a, &ct_b, &mut ct_result1);
+    bench.gate(&server_key, GateKind::Or, &ct_a, &ct_b, &mut ct_result2);
+    bench.gate(&server_key, GateKind::Xor, &ct_a, &ct_b, &mut ct_result3);
+    bench.gate(&server_key, GateKind::Nand, &ct_a, &ct_b, &mut ct_result4);
+    bench.gate(&server_key, GateKind::Nor, &ct_a, &ct_b, &mut ct_result5);
+    bench.gate(&server_key, GateKind::Xnor, &ct_a, &ct_b, &mut ct_result6);
 
 
 
@@ -148,17 +165,21 @@ fn test_gate_bootstrap_cycles() {
 
     let dec_res = decrypt_decode(&ct_result6, &client_key);
     assert_eq!(dec_res, !(a ^ b));
+
+    // Every gate yields a gate-time and a bootstrap-time record
+    assert_eq!(bench.records().len(), 12);
+    assert!(bench.records().iter().all(|r| r.width == 8));
 }
 
 #[test]
 #[serial]
 fn test_gate_cycles_nobootstrapping() {
     println!("Measuring the time difference between gate bootstrapping and gate evaluation");
-    println!("[*] All times are in milliseconds");
+    println!("[*] All times are in microseconds");
 
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
-    let server = ProcessorBoolean;
+    let mut bench = Benchmark::new(1);
     let mut rng = rand::thread_rng();
 
     // Define two numbers and convert them to signed 8-bit representation.
@@ -175,12 +196,12 @@ fn test_gate_cycles_nobootstrapping() {
     let mut ct_result6: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
 
-    server.e_and_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result1);
-    server.e_or_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result2);
-    server.e_xor_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result3);
-    server.e_nand_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result4);
-    server.e_nor_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result5);
-    server.e_xnor_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result6);
+    bench.gate_no_bootstrap(&server_key, GateKind::And, &ct_a, &ct_b, &mut ct_result1);
+    bench.gate_no_bootstrap(&server_key, GateKind::Or, &ct_a, &ct_b, &mut ct_result2);
+    bench.gate_no_bootstrap(&server_key, GateKind::Xor, &ct_a, &ct_b, &mut ct_result3);
+    bench.gate_no_bootstrap(&server_key, GateKind::Nand, &ct_a, &ct_b, &mut ct_result4);
+    bench.gate_no_bootstrap(&server_key, GateKind::Nor, &ct_a, &ct_b, &mut ct_result5);
+    bench.gate_no_bootstrap(&server_key, GateKind::Xnor, &ct_a, &ct_b, &mut ct_result6);
 
 
     let dec_res = decrypt_decode(&ct_result1, &client_key);
@@ -206,11 +227,11 @@ fn test_gate_cycles_nobootstrapping() {
 #[serial]
 fn test_composite_gate_ops_nobootstrapping() {
     println!("Measuring the time difference between gate bootstrapping and gate evaluation");
-    println!("[*] All times are in milliseconds");
+    println!("[*] All times are in microseconds");
 
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
-    let server = ProcessorBoolean;
+    let mut bench = Benchmark::new(1);
     let mut rng = rand::thread_rng();
 
     // Define two numbers and convert them to signed 8-bit representation.
@@ -227,14 +248,14 @@ fn test_composite_gate_ops_nobootstrapping() {
     let mut ct_result6: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
 
-    server.e_and_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result1);
-    server.e_or_bench_nobootstrapping(&server_key, &ct_a, &ct_result1, &mut ct_result2);
+    bench.gate_no_bootstrap(&server_key, GateKind::And, &ct_a, &ct_b, &mut ct_result1);
+    bench.gate_no_bootstrap(&server_key, GateKind::Or, &ct_a, &ct_result1, &mut ct_result2);
 
-    server.e_xor_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result3);
-    server.e_nand_bench_nobootstrapping(&server_key, &ct_a, &ct_result3, &mut ct_result4);
+    bench.gate_no_bootstrap(&server_key, GateKind::Xor, &ct_a, &ct_b, &mut ct_result3);
+    bench.gate_no_bootstrap(&server_key, GateKind::Nand, &ct_a, &ct_result3, &mut ct_result4);
 
-    server.e_nor_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result5);
-    server.e_xnor_bench_nobootstrapping(&server_key, &ct_a, &ct_result5, &mut ct_result6);
+    bench.gate_no_bootstrap(&server_key, GateKind::Nor, &ct_a, &ct_b, &mut ct_result5);
+    bench.gate_no_bootstrap(&server_key, GateKind::Xnor, &ct_a, &ct_result5, &mut ct_result6);
 
 
     let dec_res1 = decrypt_decode(&ct_result1, &client_key);
@@ -258,9 +279,9 @@ fn test_composite_gate_ops_nobootstrapping() {
     let mut ct_result1: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
     let mut ct_result2: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
     let mut ct_result3: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
-    server.e_and_bench_nobootstrapping(&server_key, &ct_a, &ct_b, &mut ct_result1);
-    server.e_or_bench_nobootstrapping(&server_key, &ct_a, &ct_result1, &mut ct_result2);
-    server.e_and_bench_nobootstrapping(&server_key, &ct_a, &ct_result2, &mut ct_result3);
+    bench.gate_no_bootstrap(&server_key, GateKind::And, &ct_a, &ct_b, &mut ct_result1);
+    bench.gate_no_bootstrap(&server_key, GateKind::Or, &ct_a, &ct_result1, &mut ct_result2);
+    bench.gate_no_bootstrap(&server_key, GateKind::And, &ct_a, &ct_result2, &mut ct_result3);
 
     let dec_res3 = decrypt_decode(&ct_result3, &client_key);
     assert_eq!(dec_res3, ((((a & b) | a) & a)));
@@ -271,7 +292,7 @@ fn test_composite_gate_ops_nobootstrapping() {
 fn test_and() {
     let fn_name = "e_and";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -284,7 +305,7 @@ fn test_and() {
     let ct_b = encode_encrypt(b, 8, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_and(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.e_and(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -297,7 +318,7 @@ fn test_and() {
 fn test_or() {
     let fn_name = "e_or";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -310,7 +331,7 @@ fn test_or() {
     let ct_b = encode_encrypt(b, 8, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_or(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.e_or(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -323,7 +344,7 @@ fn test_or() {
 fn test_xor() {
     let fn_name = "e_xor";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -336,7 +357,7 @@ fn test_xor() {
     let ct_b = encode_encrypt(b, 8, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_xor(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.e_xor(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -348,7 +369,7 @@ fn test_xor() {
 fn test_nand() {
     let fn_name = "e_nand";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -361,7 +382,7 @@ fn test_nand() {
     let ct_b = encode_encrypt(b, 8, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_nand(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.e_nand(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -373,7 +394,7 @@ fn test_nand() {
 fn test_nor() {
     let fn_name = "e_nor";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -386,7 +407,7 @@ fn test_nor() {
     let ct_b = encode_encrypt(b, 8, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_nor(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.e_nor(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -398,7 +419,7 @@ fn test_nor() {
 fn test_xnor() {
     let fn_name = "e_xnor";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -411,7 +432,7 @@ fn test_xnor() {
     let ct_b = encode_encrypt(b, 8, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_xnor(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.e_xnor(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -423,7 +444,7 @@ fn test_xnor() {
 fn test_not() {
     let fn_name = "e_not";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -434,7 +455,7 @@ fn test_not() {
     let ct_a = encode_encrypt(a, 8, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_not(&server_key, &ct_a, &mut ct_result);
+    server.e_not(&server_key, &ct_a, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -447,7 +468,7 @@ fn test_not() {
 fn test_mux() {
     let fn_name = "e_mux";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -462,7 +483,7 @@ fn test_mux() {
     let ct_c = client_key.encrypt(c);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_mux(&server_key, &ct_c, &ct_a, &ct_b, &mut ct_result);
+    server.e_mux(&server_key, &ct_c, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -475,7 +496,7 @@ fn test_mux() {
 fn test_shl() {
     let fn_name = "e_shl";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -488,7 +509,7 @@ fn test_shl() {
     let ct_a = encode_encrypt(a, 8, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_shl(&ct_a, shift, &mut ct_result);
+    server.e_shl(&ct_a, shift, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -501,7 +522,7 @@ fn test_shl() {
 fn test_shr() {
     let fn_name = "e_shr";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -513,7 +534,7 @@ fn test_shr() {
     let ct_a = encode_encrypt(a, 8, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_shr(&ct_a, shift, &mut ct_result);
+    server.e_shr(&ct_a, shift, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -526,7 +547,7 @@ fn test_shr() {
 fn test_e_rotr() {
     let fn_name = "e_rotr";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -538,7 +559,7 @@ fn test_e_rotr() {
     let ct_a = encode_encrypt(a, 8, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_rotr(&ct_a, shift, &mut ct_result);
+    server.e_rotr(&ct_a, shift, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -551,7 +572,7 @@ fn test_e_rotr() {
 fn test_e_rotl() {
     let fn_name = "e_rotl";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -564,7 +585,7 @@ fn test_e_rotl() {
     let ct_a = encode_encrypt(a, 8, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.e_rotl(&ct_a, shift, &mut ct_result);
+    server.e_rotl(&ct_a, shift, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -577,7 +598,7 @@ fn test_e_rotl() {
 fn test_adder() {
     let fn_name = "adder";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -590,7 +611,7 @@ fn test_adder() {
     let ct_b = encode_encrypt(b, 8, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.adder(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.adder(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -603,7 +624,7 @@ fn test_adder() {
 fn test_sign_adder() {
     let fn_name = "adder";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -612,15 +633,28 @@ fn test_sign_adder() {
     let a: i8 = rng.gen_range(-50..50);
     let b: i8 = rng.gen_range(-50..50);
 
-    let ct_a = encode_encrypt(a, 8, &client_key);
-    let ct_b = encode_encrypt(b, 8, &client_key);
-    let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 8];
-
-    server.sign_adder(&server_key, &ct_a, &ct_b, &mut ct_result);
-
-    let dec_res = decrypt_decode(&ct_result, &client_key);
-    println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
-    assert_eq!(dec_res, a.wrapping_add(b));
+    // `sign_adder` works on sign-magnitude integers: the top bit is the sign and the
+    // other bits hold the magnitude. The fixed pairs take every sign path, the last one
+    // to a zero that may come back as negative zero.
+    let sign_magnitude = |v: i8| if v < 0 { -v | i8::MIN } else { v };
+    for (a, b) in [(a, b), (-7, 3), (7, -3), (-20, -30), (-5, 5)] {
+        let ct_a = encode_encrypt(sign_magnitude(a), 8, &client_key);
+        let ct_b = encode_encrypt(sign_magnitude(b), 8, &client_key);
+        let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 8];
+
+        server
+            .sign_adder(&server_key, &ct_a, &ct_b, &mut ct_result)
+            .unwrap();
+
+        let dec_res = decrypt_decode(&ct_result, &client_key);
+        let sum: i8 = if dec_res < 0 {
+            -(dec_res & i8::MAX)
+        } else {
+            dec_res
+        };
+        println!("\t {} {} {} = {}", a, b, fn_name, sum);
+        assert_eq!(sum, a + b);
+    }
     println!("[✓] PASS: {fn_name}\n");
 }
 
@@ -629,7 +663,7 @@ fn test_sign_adder() {
 fn test_subtracter() {
     let fn_name = "subtracter";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -646,7 +680,7 @@ fn test_subtracter() {
     println!("{a} - {b}");
     //server.ptxt_subtracter(&encode(a, 8), &encode(b, 8), &mut result);
 
-    server.subtracter(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.subtracter(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -659,7 +693,7 @@ fn test_subtracter() {
 fn test_multiplier() {
     let fn_name = "multiplier";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -679,7 +713,7 @@ fn test_multiplier() {
     println!("{a} * {b} = {}", decode(&res));
     println!("{:?} * {:?} = {:?}", enc_a, enc_b, res);
 
-    server.multiplier(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.multiplier(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -692,7 +726,7 @@ fn test_multiplier() {
 fn test_newmultiplier() {
     let fn_name = "multiplier";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -724,7 +758,7 @@ fn test_newmultiplier() {
 fn test_div() {
     let fn_name = "divider";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
@@ -742,7 +776,7 @@ fn test_div() {
     let ct_b = encode_encrypt(b, 8, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.divider(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.divider(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -754,25 +788,21 @@ fn test_div() {
 fn test_modulo() {
     let fn_name = "modulo";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
     let mut rng = rand::thread_rng();
 
-    // Define two numbers and convert them to signed 8-bit representation.
-    let mut a: i8 = rng.gen_range(-50..50);
-    let mut b: i8 = rng.gen_range(-50..50);
-
-    while !(a >= b) {
-        a = rng.gen_range(-50..50);
-        b = rng.gen_range(-50..50);
-    }
+    // `modulo` takes the remainder of non-negative operands, so draw a non-negative
+    // dividend and a positive divisor
+    let a: i8 = rng.gen_range(0..50);
+    let b: i8 = rng.gen_range(1..50);
 
     let ct_a = encode_encrypt(a, 8, &client_key);
     let ct_b = encode_encrypt(b, 8, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.modulo(&server_key, &ct_a, &ct_b, &mut ct_result);
+    server.modulo(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} {} = {}", a, b, fn_name, dec_res);
@@ -785,7 +815,7 @@ fn test_modulo() {
 fn test_max() {
     let fn_name = "max";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -804,7 +834,7 @@ fn test_max() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.max(&server_key, &ct_a, &mut ct_result);
+    server.max(&server_key, &ct_a, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     assert_eq!(dec_res, *a.iter().max().unwrap());
@@ -816,7 +846,7 @@ fn test_max() {
 fn test_min() {
     let fn_name = "min";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -835,7 +865,7 @@ fn test_min() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.min(&server_key, &ct_a, &mut ct_result);
+    server.min(&server_key, &ct_a, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     assert_eq!(dec_res, *a.iter().min().unwrap());
@@ -847,7 +877,7 @@ fn test_min() {
 fn test_relu() {
     let fn_name = "relu";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -863,8 +893,8 @@ fn test_relu() {
     let mut ct_result_a: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
     let mut ct_result_b: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_b.len()];
 
-    server.relu(&server_key, &ct_a, &mut ct_result_a);
-    server.relu(&server_key, &ct_b, &mut ct_result_b);
+    server.relu(&server_key, &ct_a, &mut ct_result_a).unwrap();
+    server.relu(&server_key, &ct_b, &mut ct_result_b).unwrap();
 
     let dec_res = decrypt_decode(&ct_result_a, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -880,7 +910,7 @@ fn test_relu() {
 fn test_sqrt() {
     let fn_name = "sqrt";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -890,7 +920,7 @@ fn test_sqrt() {
     let ct_a = encode_encrypt(a, 8, &client_key);
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
 
-    server.sqrt(&server_key, &ct_a, &mut ct_result);
+    server.sqrt(&server_key, &ct_a, &mut ct_result).unwrap();
 
     let dec_res = decrypt_decode(&ct_result, &client_key);
     println!("\t {} {} = {}", a, fn_name, dec_res);
@@ -902,7 +932,7 @@ fn test_sqrt() {
 fn test_mean() {
     let fn_name = "mean";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -921,7 +951,7 @@ fn test_mean() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.mean(&server_key, &ct_a, a.len(), &mut ct_result);
+    server.mean(&server_key, &ct_a, a.len(), &mut ct_result).unwrap();
 
     let ptxt_result: i8 = a.iter().sum();
 
@@ -934,7 +964,7 @@ fn test_mean() {
 fn test_variance() {
     let fn_name = "variance";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -953,7 +983,7 @@ fn test_variance() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.variance(&server_key, &ct_a, a.len(), &mut ct_result);
+    server.variance(&server_key, &ct_a, a.len(), &mut ct_result).unwrap();
 
     let ptxt_result: i8 = a.iter().sum();
     let mean:i8 = ptxt_result.wrapping_div(a.len() as i8);
@@ -972,7 +1002,7 @@ fn test_variance() {
 fn test_standard_deviation() {
     let fn_name = "standard_deviation";
     println!("[*] TEST: {fn_name}");
-    let (client_key, server_key) = gen_keys();
+    let (client_key, server_key) = KeyManager::cached_keys();
 
     let server = ProcessorBoolean;
 
@@ -991,7 +1021,7 @@ fn test_standard_deviation() {
 
     let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a[0].len()];
 
-    server.standard_deviation(&server_key, &ct_a, a.len(), &mut ct_result);
+    server.standard_deviation(&server_key, &ct_a, a.len(), &mut ct_result).unwrap();
 
     let ptxt_result: i8 = a.iter().sum();
     let mean:i8 = ptxt_result.wrapping_div(a.len() as i8);
@@ -1007,3 +1037,601 @@ fn test_standard_deviation() {
     assert_eq!(dec_res, stdev);
     println!("[✓] PASS: {fn_name}\n");
 }
+#[test]
+#[serial]
+fn test_scalar_cmp() {
+    let fn_name = "scalar_cmp";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+    let mut rng = rand::thread_rng();
+
+    let a: i8 = rng.gen_range(-100..100);
+    let b: i8 = rng.gen_range(-100..100);
+
+    let ct_a = encode_encrypt(a, 8, &client_key);
+
+    // select codes: eq, lt, gt, geq, leq, neq
+    let expected = [a == b, a < b, a > b, a >= b, a <= b, a != b];
+    for select in 0..6u8 {
+        let ct_result = server.scalar_cmp(&server_key, &ct_a, b as i64, select).unwrap();
+        let dec_res = client_key.decrypt(&ct_result);
+        println!("\t {} {} {} (select {}) = {}", a, fn_name, b, select, dec_res);
+        assert_eq!(dec_res, expected[select as usize]);
+    }
+
+    // Equal operands exercise the boundary of every comparison
+    for select in 0..6u8 {
+        let ct_result = server.scalar_cmp(&server_key, &ct_a, a as i64, select).unwrap();
+        let dec_res = client_key.decrypt(&ct_result);
+        assert_eq!(dec_res, [true, false, false, true, true, false][select as usize]);
+    }
+
+    // Values outside the 8-bit range do not alias: 300 is not 44 and -200 is not 56
+    let ct_44 = encode_encrypt(44, 8, &client_key);
+    for (b, expected) in [
+        (300, [false, true, false, false, true, true]),
+        (128, [false, true, false, false, true, true]),
+        (-200, [false, false, true, true, false, true]),
+        (-129, [false, false, true, true, false, true]),
+    ] {
+        for select in 0..6u8 {
+            let ct_result = server.scalar_cmp(&server_key, &ct_44, b, select).unwrap();
+            let dec_res = client_key.decrypt(&ct_result);
+            assert_eq!(dec_res, expected[select as usize], "{b} (select {select})");
+        }
+    }
+    println!("[✓] PASS: {fn_name}\n");
+}
+#[test]
+#[serial]
+fn test_comparator() {
+    let fn_name = "comparator";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+    let mut rng = rand::thread_rng();
+
+    // Operands are unsigned; the pairs differ first in a high, a middle and the low bit
+    let random: (u8, u8) = (rng.r#gen(), rng.r#gen());
+    for (a, b) in [random, (0x80, 0x7F), (0x2C, 0x34), (0x41, 0x40), (0x99, 0x99)] {
+        let ct_a = encode_encrypt(a as i8, 8, &client_key);
+        let ct_b = encode_encrypt(b as i8, 8, &client_key);
+
+        // select codes: eq, lt, gt, geq, leq, neq
+        let expected = [a == b, a < b, a > b, a >= b, a <= b, a != b];
+        for select in 0..6u8 {
+            let ct_result = server.comparator(&server_key, &ct_a, &ct_b, select).unwrap();
+            let dec_res = client_key.decrypt(&ct_result);
+            println!("\t {} {} {} (select {}) = {}", a, fn_name, b, select, dec_res);
+            assert_eq!(dec_res, expected[select as usize]);
+        }
+    }
+    println!("[✓] PASS: {fn_name}\n");
+}
+#[test]
+#[serial]
+fn test_histogram() {
+    let fn_name = "histogram";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+
+    let a: [i8; 6] = [-5, 3, 10, 17, 42, 3];
+    let thresholds: [i64; 3] = [0, 10, 20];
+    let mut encrypted_values: Vec<Vec<Ciphertext>> = Vec::with_capacity(6);
+    let mut ct_a: Vec<&[Ciphertext]> = Vec::with_capacity(6);
+    for i in a.iter() {
+        let encrypted = encode_encrypt(*i, 8, &client_key);
+        encrypted_values.push(encrypted);
+    }
+
+    for encrypted in &encrypted_values {
+        ct_a.push(encrypted.as_slice());
+    }
+
+    let mut ct_result: Vec<Vec<Ciphertext>> = Vec::new();
+
+    server.histogram(&server_key, &ct_a, &thresholds, &mut ct_result).unwrap();
+
+    // Plaintext reference: bucket k holds t_(k-1) <= x < t_k
+    let mut ptxt_result: Vec<i8> = vec![0; thresholds.len() + 1];
+    for x in a.iter() {
+        let bucket = thresholds.iter().filter(|t| *x as i64 >= **t).count();
+        ptxt_result[bucket] += 1;
+    }
+
+    assert_eq!(ct_result.len(), ptxt_result.len());
+    for (k, counter) in ct_result.iter().enumerate() {
+        // 6 samples need a 3-bit counter
+        assert_eq!(counter.len(), 3);
+        let dec_res = decrypt_decode(counter, &client_key);
+        println!("\t bucket {} = {}", k, dec_res);
+        assert_eq!(dec_res, ptxt_result[k]);
+    }
+
+    // Thresholds outside the 8-bit samples do not wrap: 200 is not -56 and -200 is not 56
+    server.histogram(&server_key, &ct_a, &[-200, 10, 200], &mut ct_result).unwrap();
+    let counts: Vec<i8> = ct_result.iter().map(|c| decrypt_decode(c, &client_key)).collect();
+    println!("\t buckets [-200, 10, 200] = {:?}", counts);
+    assert_eq!(counts, vec![0, 3, 3, 0]);
+    println!("[✓] PASS: {fn_name}\n");
+}
+#[test]
+#[serial]
+fn test_dot_product() {
+    let fn_name = "dot_product";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+
+    let a: [i8; 3] = [3, -2, 5];
+    let b: [i8; 3] = [4, 7, -1];
+    let enc_a: Vec<Vec<Ciphertext>> = a.iter().map(|x| encode_encrypt(*x, 8, &client_key)).collect();
+    let enc_b: Vec<Vec<Ciphertext>> = b.iter().map(|x| encode_encrypt(*x, 8, &client_key)).collect();
+    let ct_a: Vec<&[Ciphertext]> = enc_a.iter().map(|x| x.as_slice()).collect();
+    let ct_b: Vec<&[Ciphertext]> = enc_b.iter().map(|x| x.as_slice()).collect();
+
+    let mut ct_result: Vec<Ciphertext> = Vec::new();
+    server.dot_product(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
+
+    let ptxt_result: i64 = a.iter().zip(b.iter()).map(|(x, y)| *x as i64 * *y as i64).sum();
+    // 2 * 8 bits for the product plus 2 bits for three terms
+    assert_eq!(ct_result.len(), 18);
+    let dec_res = wide_decrypt_decode(&ct_result, &client_key);
+    println!("\t {:?} {} {:?} = {}", a, fn_name, b, dec_res);
+    assert_eq!(dec_res, ptxt_result);
+    println!("[✓] PASS: {fn_name}\n");
+}
+#[test]
+#[serial]
+fn test_dot_product_plain() {
+    let fn_name = "dot_product_plain";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+
+    // Values that would overflow an 8-bit accumulator
+    let a: [i8; 4] = [127, -128, 100, -7];
+    let w: [i64; 4] = [7, -3, 0, -1];
+    let enc_a: Vec<Vec<Ciphertext>> = a.iter().map(|x| encode_encrypt(*x, 8, &client_key)).collect();
+    let ct_a: Vec<&[Ciphertext]> = enc_a.iter().map(|x| x.as_slice()).collect();
+
+    let mut ct_result: Vec<Ciphertext> = Vec::new();
+    server.dot_product_plain(&server_key, &ct_a, &w, &mut ct_result).unwrap();
+
+    let ptxt_result: i64 = a.iter().zip(w.iter()).map(|(x, y)| *x as i64 * *y).sum();
+    let dec_res = wide_decrypt_decode(&ct_result, &client_key);
+    println!("\t {:?} {} {:?} = {}", a, fn_name, w, dec_res);
+    assert_eq!(dec_res, ptxt_result);
+    println!("[✓] PASS: {fn_name}\n");
+}
+#[test]
+#[serial]
+fn test_matvec() {
+    let fn_name = "matvec";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+
+    let m: [[i8; 2]; 2] = [[2, -3], [-1, 4]];
+    let x: [i8; 2] = [5, 6];
+    let enc_m: Vec<Vec<Vec<Ciphertext>>> = m
+        .iter()
+        .map(|row| row.iter().map(|v| encode_encrypt(*v, 8, &client_key)).collect())
+        .collect();
+    let enc_x: Vec<Vec<Ciphertext>> = x.iter().map(|v| encode_encrypt(*v, 8, &client_key)).collect();
+    let ct_m: Vec<Vec<&[Ciphertext]>> = enc_m
+        .iter()
+        .map(|row| row.iter().map(|v| v.as_slice()).collect())
+        .collect();
+    let ct_x: Vec<&[Ciphertext]> = enc_x.iter().map(|v| v.as_slice()).collect();
+
+    let mut ct_result: Vec<Vec<Ciphertext>> = Vec::new();
+    server.matvec(&server_key, &ct_m, &ct_x, &mut ct_result).unwrap();
+
+    for (i, row) in m.iter().enumerate() {
+        let ptxt_result: i64 = row.iter().zip(x.iter()).map(|(a, b)| *a as i64 * *b as i64).sum();
+        let dec_res = wide_decrypt_decode(&ct_result[i], &client_key);
+        assert_eq!(dec_res, ptxt_result);
+    }
+
+    // Same product with the matrix in the clear
+    let w: Vec<Vec<i64>> = m.iter().map(|row| row.iter().map(|v| *v as i64).collect()).collect();
+    server.matvec_plain(&server_key, &w, &ct_x, &mut ct_result).unwrap();
+
+    for (i, row) in m.iter().enumerate() {
+        let ptxt_result: i64 = row.iter().zip(x.iter()).map(|(a, b)| *a as i64 * *b as i64).sum();
+        let dec_res = wide_decrypt_decode(&ct_result[i], &client_key);
+        assert_eq!(dec_res, ptxt_result);
+    }
+
+    // Elements narrower than those of the matrix are refused before any gate is evaluated
+    let narrow: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 4];
+    let narrow_x: Vec<&[Ciphertext]> = vec![narrow.as_slice(); 2];
+    assert_eq!(
+        server.matvec(&server_key, &ct_m, &narrow_x, &mut ct_result),
+        Err(PulpError::WidthMismatch {
+            op: "matvec",
+            operand: "row",
+            expected: 4,
+            found: 8
+        })
+    );
+    let mixed_x: Vec<&[Ciphertext]> = vec![ct_x[0], narrow.as_slice()];
+    let mut dot: Vec<Ciphertext> = Vec::new();
+    assert!(matches!(
+        server.dot_product(&server_key, &ct_x, &mixed_x, &mut dot),
+        Err(PulpError::WidthMismatch {
+            op: "dot_product",
+            operand: "b",
+            ..
+        })
+    ));
+    println!("[✓] PASS: {fn_name}\n");
+}
+#[test]
+#[serial]
+fn test_matmul() {
+    let fn_name = "matmul";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+
+    let a: [[i8; 2]; 2] = [[1, -2], [3, 4]];
+    let b: [[i8; 2]; 2] = [[-5, 6], [7, 8]];
+    let enc_a: Vec<Vec<Vec<Ciphertext>>> = a
+        .iter()
+        .map(|row| row.iter().map(|v| encode_encrypt(*v, 8, &client_key)).collect())
+        .collect();
+    let enc_b: Vec<Vec<Vec<Ciphertext>>> = b
+        .iter()
+        .map(|row| row.iter().map(|v| encode_encrypt(*v, 8, &client_key)).collect())
+        .collect();
+    let ct_a: Vec<Vec<&[Ciphertext]>> = enc_a
+        .iter()
+        .map(|row| row.iter().map(|v| v.as_slice()).collect())
+        .collect();
+    let ct_b: Vec<Vec<&[Ciphertext]>> = enc_b
+        .iter()
+        .map(|row| row.iter().map(|v| v.as_slice()).collect())
+        .collect();
+
+    let mut ptxt_result = [[0i64; 2]; 2];
+    for i in 0..2 {
+        for j in 0..2 {
+            for k in 0..2 {
+                ptxt_result[i][j] += a[i][k] as i64 * b[k][j] as i64;
+            }
+        }
+    }
+
+    let mut ct_result: Vec<Vec<Vec<Ciphertext>>> = Vec::new();
+    server.matmul(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
+    for i in 0..2 {
+        for j in 0..2 {
+            let dec_res = wide_decrypt_decode(&ct_result[i][j], &client_key);
+            assert_eq!(dec_res, ptxt_result[i][j]);
+        }
+    }
+
+    let w: Vec<Vec<i64>> = b.iter().map(|row| row.iter().map(|v| *v as i64).collect()).collect();
+    server.matmul_plain(&server_key, &ct_a, &w, &mut ct_result).unwrap();
+    for i in 0..2 {
+        for j in 0..2 {
+            let dec_res = wide_decrypt_decode(&ct_result[i][j], &client_key);
+            assert_eq!(dec_res, ptxt_result[i][j]);
+        }
+    }
+    // A narrower element in either matrix is refused before any gate is evaluated
+    let narrow: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 4];
+    let mut mixed_b = ct_b.clone();
+    mixed_b[1][0] = narrow.as_slice();
+    assert!(matches!(
+        server.matmul(&server_key, &ct_a, &mixed_b, &mut ct_result),
+        Err(PulpError::WidthMismatch {
+            op: "matmul",
+            operand: "b row",
+            ..
+        })
+    ));
+    let mut mixed_a = ct_a.clone();
+    mixed_a[1][1] = narrow.as_slice();
+    assert!(matches!(
+        server.matmul_plain(&server_key, &mixed_a, &w, &mut ct_result),
+        Err(PulpError::WidthMismatch {
+            op: "matmul_plain",
+            operand: "a row",
+            ..
+        })
+    ));
+    println!("[✓] PASS: {fn_name}\n");
+}
+#[test]
+#[serial]
+fn test_scalar_bitwise() {
+    let fn_name = "scalar_bitwise";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+    let mut rng = rand::thread_rng();
+
+    let a: i8 = rng.r#gen();
+    let b: i8 = rng.r#gen();
+    let ct_a = encode_encrypt(a, 8, &client_key);
+    let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
+
+    server.scalar_and(&server_key, &ct_a, b as i64, &mut ct_result).unwrap();
+    let dec_res = decrypt_decode(&ct_result, &client_key);
+    println!("\t {} & {} = {}", a, b, dec_res);
+    assert_eq!(dec_res, a & b);
+
+    server.scalar_or(&server_key, &ct_a, b as i64, &mut ct_result).unwrap();
+    let dec_res = decrypt_decode(&ct_result, &client_key);
+    println!("\t {} | {} = {}", a, b, dec_res);
+    assert_eq!(dec_res, a | b);
+
+    server.scalar_xor(&server_key, &ct_a, b as i64, &mut ct_result).unwrap();
+    let dec_res = decrypt_decode(&ct_result, &client_key);
+    println!("\t {} ^ {} = {}", a, b, dec_res);
+    assert_eq!(dec_res, a ^ b);
+    println!("[✓] PASS: {fn_name}\n");
+}
+#[test]
+#[serial]
+fn test_scalar_add() {
+    let fn_name = "scalar_add";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+    let mut rng = rand::thread_rng();
+
+    let a: i8 = rng.r#gen();
+    let ct_a = encode_encrypt(a, 8, &client_key);
+    let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
+
+    for b in [rng.r#gen::<i8>(), 0, 1, -1, 64] {
+        server.scalar_add(&server_key, &ct_a, b as i64, &mut ct_result).unwrap();
+        let dec_res = decrypt_decode(&ct_result, &client_key);
+        println!("\t {} {} {} = {}", a, fn_name, b, dec_res);
+        assert_eq!(dec_res, a.wrapping_add(b));
+    }
+    println!("[✓] PASS: {fn_name}\n");
+}
+#[test]
+#[serial]
+fn test_scalar_mul() {
+    let fn_name = "scalar_mul";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+    let mut rng = rand::thread_rng();
+
+    let a: i8 = rng.gen_range(-10..10);
+    let ct_a = encode_encrypt(a, 8, &client_key);
+    let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
+
+    for b in [rng.gen_range(-10..10), 0, 7, -3] {
+        server.scalar_mul(&server_key, &ct_a, b as i64, &mut ct_result).unwrap();
+        let dec_res = decrypt_decode(&ct_result, &client_key);
+        println!("\t {} {} {} = {}", a, fn_name, b, dec_res);
+        assert_eq!(dec_res, a.wrapping_mul(b));
+    }
+    println!("[✓] PASS: {fn_name}\n");
+}
+#[test]
+#[serial]
+fn test_scalar_div() {
+    let fn_name = "scalar_div";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+    let mut rng = rand::thread_rng();
+
+    let a: i8 = rng.r#gen();
+    let ct_a = encode_encrypt(a, 8, &client_key);
+    let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
+
+    for b in [rng.gen_range(1..20), -3, 127] {
+        server.scalar_div(&server_key, &ct_a, b as i64, &mut ct_result).unwrap();
+        let dec_res = decrypt_decode(&ct_result, &client_key);
+        println!("\t {} {} {} = {}", a, fn_name, b, dec_res);
+        assert_eq!(dec_res, a.wrapping_div(b));
+    }
+    println!("[✓] PASS: {fn_name}\n");
+}
+#[test]
+#[serial]
+fn test_constant_folding() {
+    let fn_name = "constant_folding";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+    let mut rng = rand::thread_rng();
+
+    let a: i8 = rng.r#gen();
+    let ct_a = encode_encrypt(a, 8, &client_key);
+    let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); ct_a.len()];
+
+    // Every gate with a constant operand folds without evaluation
+    for constant in [false, true] {
+        let ct_c: Vec<Ciphertext> = vec![Ciphertext::Trivial(constant); 8];
+        let c: i8 = if constant { -1 } else { 0 };
+
+        ConstantFoldModule::reset();
+        server.e_and(&server_key, &ct_a, &ct_c, &mut ct_result).unwrap();
+        assert_eq!(decrypt_decode(&ct_result, &client_key), a & c);
+        server.e_or(&server_key, &ct_c, &ct_a, &mut ct_result).unwrap();
+        assert_eq!(decrypt_decode(&ct_result, &client_key), a | c);
+        server.e_xor(&server_key, &ct_a, &ct_c, &mut ct_result).unwrap();
+        assert_eq!(decrypt_decode(&ct_result, &client_key), a ^ c);
+        server.e_nand(&server_key, &ct_a, &ct_c, &mut ct_result).unwrap();
+        assert_eq!(decrypt_decode(&ct_result, &client_key), !(a & c));
+        server.e_nor(&server_key, &ct_a, &ct_c, &mut ct_result).unwrap();
+        assert_eq!(decrypt_decode(&ct_result, &client_key), !(a | c));
+        server.e_xnor(&server_key, &ct_a, &ct_c, &mut ct_result).unwrap();
+        assert_eq!(decrypt_decode(&ct_result, &client_key), !(a ^ c));
+        server.e_mux(&server_key, &ct_c[0], &ct_a, &ct_c, &mut ct_result).unwrap();
+        assert_eq!(decrypt_decode(&ct_result, &client_key), if constant { a } else { c });
+
+        let saved = ConstantFoldModule::saved();
+        assert_eq!(saved.gates, 7 * 8);
+        assert_eq!(saved.bootstraps, 6 * 8);
+    }
+
+    // Fully encrypted operands are evaluated as usual
+    ConstantFoldModule::reset();
+    server.e_and(&server_key, &ct_a, &ct_a, &mut ct_result).unwrap();
+    assert_eq!(decrypt_decode(&ct_result, &client_key), a);
+    assert_eq!(ConstantFoldModule::saved().gates, 0);
+
+    // The zero carry-in of the adder is folded away
+    let b: i8 = rng.r#gen();
+    let ct_b = encode_encrypt(b, 8, &client_key);
+    server.adder(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
+    assert_eq!(decrypt_decode(&ct_result, &client_key), a.wrapping_add(b));
+    let saved = ConstantFoldModule::saved();
+    println!("\t adder saved {} gates and {} bootstraps", saved.gates, saved.bootstraps);
+    assert!(saved.bootstraps > 0);
+    println!("[✓] PASS: {fn_name}\n");
+}
+#[test]
+#[serial]
+fn test_instrumented() {
+    let fn_name = "instrumented";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+    let mut rng = rand::thread_rng();
+
+    let mut runs: Vec<CircuitStats> = Vec::new();
+    for _ in 0..2 {
+        let ct_a = encode_encrypt(rng.r#gen(), 8, &client_key);
+        let ct_b = encode_encrypt(rng.r#gen(), 8, &client_key);
+        let stats = server
+            .instrumented(&[&ct_a, &ct_b], |p| {
+                let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 8];
+                p.adder(&server_key, &ct_a, &ct_b, &mut ct_result).unwrap();
+                ct_result
+            })
+            .unwrap();
+        println!("\t adder: {stats}");
+        runs.push(stats);
+    }
+    // The counts do not depend on the encrypted values
+    assert_eq!(runs[0], runs[1]);
+
+    let adder = runs[0];
+    assert_eq!(adder.pitch_trims, adder.gates() - adder.not - adder.mux);
+    assert!(adder.trivial_gates > 0);
+    assert!(adder.depth > 0 && adder.depth <= adder.gates());
+
+    let ct_a = encode_encrypt(rng.r#gen(), 8, &client_key);
+    let ct_b = encode_encrypt(rng.r#gen(), 8, &client_key);
+    let sign_adder = server
+        .instrumented(&[&ct_a, &ct_b], |p| {
+            let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 8];
+            p.sign_adder(&server_key, &ct_a, &ct_b, &mut ct_result)
+                .unwrap();
+            ct_result
+        })
+        .unwrap();
+    println!("\t sign_adder: {sign_adder}");
+    assert!(sign_adder.gates() > adder.gates());
+
+    // A nested call fails without disturbing the recording around it
+    let mut nested: Option<Result<CircuitStats, PulpError>> = None;
+    let outer = server
+        .instrumented(&[&ct_a], |p| {
+            nested = Some(p.instrumented(&[&ct_a], |_| Vec::new()));
+            vec![p.e_not_bit(&server_key, &ct_a[0])]
+        })
+        .unwrap();
+    assert!(matches!(
+        nested,
+        Some(Err(PulpError::InvalidArgument {
+            op: "instrumented",
+            ..
+        }))
+    ));
+    assert_eq!((outer.gates(), outer.not), (1, 1));
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_operand_errors() {
+    let fn_name = "operand_errors";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+    let ct_a = encode_encrypt(5, 8, &client_key);
+    let ct_b = encode_encrypt(3, 4, &client_key);
+    let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 8];
+
+    // Bad operands are refused before any gate is evaluated
+    assert_eq!(
+        server.adder(&server_key, &ct_a, &ct_b, &mut ct_result),
+        Err(PulpError::WidthMismatch {
+            op: "adder",
+            operand: "b",
+            expected: 8,
+            found: 4,
+        })
+    );
+    assert!(matches!(
+        server.e_xor(&server_key, &ct_a, &ct_a, &mut ct_result[..4]),
+        Err(PulpError::WidthMismatch { operand: "result", .. })
+    ));
+    assert!(matches!(
+        server.e_and_range(&server_key, &ct_a, &ct_a, &mut ct_result, 6, 2),
+        Err(PulpError::InvalidArgument { op: "e_and_range", .. })
+    ));
+    assert!(matches!(
+        server.e_or_range(&server_key, &ct_a, &ct_b, &mut ct_result, 0, 6),
+        Err(PulpError::WidthMismatch { operand: "b", .. })
+    ));
+    assert!(matches!(
+        server.scalar_div(&server_key, &ct_a, 0, &mut ct_result),
+        Err(PulpError::InvalidArgument { op: "scalar_div", .. })
+    ));
+    assert!(matches!(
+        server.comparator(&server_key, &ct_a, &ct_a, 6),
+        Err(PulpError::InvalidArgument { op: "comparator", .. })
+    ));
+    assert_eq!(
+        server.sqrt(&server_key, &[], &mut ct_result),
+        Err(PulpError::UnsupportedWidth { op: "sqrt", width: 0 })
+    );
+    assert_eq!(
+        server.sign_adder(&server_key, &ct_a[..1], &ct_a[..1], &mut ct_result),
+        Err(PulpError::UnsupportedWidth { op: "sign_adder", width: 1 })
+    );
+    assert!(matches!(
+        server.mean(&server_key, &vec![&ct_a[..], &ct_b[..]], 2, &mut ct_result),
+        Err(PulpError::WidthMismatch { op: "mean", .. })
+    ));
+    assert_eq!(
+        server.variance(&server_key, &vec![&ct_a[..1]], 1, &mut ct_result),
+        Err(PulpError::UnsupportedWidth { op: "variance", width: 1 })
+    );
+    assert!(matches!(
+        server.histogram(&server_key, &vec![&ct_a[..]], &[4, 1], &mut Vec::new()),
+        Err(PulpError::InvalidArgument { op: "histogram", .. })
+    ));
+    println!("[✓] PASS: {fn_name}\n");
+}
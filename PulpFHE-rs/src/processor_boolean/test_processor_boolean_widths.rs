@@ -0,0 +1,345 @@
+#![allow(warnings)]
+use super::*; // Imports items from the parent module
+use crate::key_manager::KeyManager;
+use serial_test::serial;
+
+// Function to encrypt the low `size` bits of a signed integer using the Boolean API
+pub fn encode_encrypt(num: i64, size: usize, ck: &ClientKey) -> Vec<Ciphertext> {
+    // Binary encoding is LSB...MSB
+    (0..size)
+        .map(|i| ck.encrypt(((num >> i.min(63)) & 1) != 0))
+        .collect()
+}
+
+// Function to decrypt the ciphertext vector and sign-extend it to an i64
+pub fn decrypt_decode(ciphertexts: &[Ciphertext], client_key: &ClientKey) -> i64 {
+    let mut bits: u64 = 0;
+    for (i, ct) in ciphertexts.iter().enumerate() {
+        if client_key.decrypt(ct) {
+            bits |= 1u64 << i;
+        }
+    }
+    let unused: u32 = 64 - ciphertexts.len() as u32;
+    ((bits << unused) as i64) >> unused
+}
+
+fn encrypt_samples(values: &[i64], size: usize, ck: &ClientKey) -> Vec<Vec<Ciphertext>> {
+    values
+        .iter()
+        .map(|&v| encode_encrypt(v, size, ck))
+        .collect()
+}
+
+#[test]
+#[serial]
+fn test_sqrt_widths() {
+    let fn_name = "sqrt_widths";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+    let cases: [(usize, i64); 8] = [
+        (2, 1),
+        (5, 15),
+        (5, 9),
+        (12, 2047),
+        (12, 1000),
+        (24, 8_388_607),
+        (24, 1_000_000),
+        (64, i64::MAX),
+    ];
+    for (size, a) in cases {
+        let ct_a = encode_encrypt(a, size, &client_key);
+        let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
+
+        server.sqrt(&server_key, &ct_a, &mut ct_result).unwrap();
+
+        let dec_res = decrypt_decode(&ct_result, &client_key);
+        println!("\t {size}-bit: {a} sqrt = {dec_res}");
+        assert_eq!(dec_res, a.isqrt());
+    }
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_statistics_widths() {
+    let fn_name = "statistics_widths";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+    let cases: [(usize, [i64; 4]); 2] = [(12, [40, 10, 22, 8]), (24, [2000, -1000, 1500, 500])];
+    for (size, a) in cases {
+        let encrypted_values = encrypt_samples(&a, size, &client_key);
+        let ct_a: Vec<&[Ciphertext]> = encrypted_values.iter().map(|v| v.as_slice()).collect();
+        let count: usize = a.len();
+
+        // The circuits divide with truncation towards zero, like the plaintext reference
+        let mean: i64 = a.iter().sum::<i64>() / count as i64;
+        let variance: i64 = a.iter().map(|x| (x - mean) * (x - mean)).sum::<i64>() / count as i64;
+
+        let mut ct_mean: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
+        server
+            .mean(&server_key, &ct_a, count, &mut ct_mean)
+            .unwrap();
+        let dec_mean = decrypt_decode(&ct_mean, &client_key);
+        println!("\t {size}-bit: mean {a:?} = {dec_mean}");
+        assert_eq!(dec_mean, mean);
+
+        let mut ct_var: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
+        server
+            .variance(&server_key, &ct_a, count, &mut ct_var)
+            .unwrap();
+        let dec_var = decrypt_decode(&ct_var, &client_key);
+        println!("\t {size}-bit: variance {a:?} = {dec_var}");
+        assert_eq!(dec_var, variance);
+
+        let mut ct_std: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); size];
+        server
+            .standard_deviation(&server_key, &ct_a, count, &mut ct_std)
+            .unwrap();
+        let dec_std = decrypt_decode(&ct_std, &client_key);
+        println!("\t {size}-bit: standard_deviation {a:?} = {dec_std}");
+        assert_eq!(dec_std, variance.isqrt());
+    }
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+fn decrypt_unsigned(ciphertexts: &[Ciphertext], client_key: &ClientKey) -> u64 {
+    ciphertexts
+        .iter()
+        .enumerate()
+        .map(|(i, ct)| (client_key.decrypt(ct) as u64) << i)
+        .sum()
+}
+
+#[test]
+#[serial]
+fn test_bit_counting_widths() {
+    let fn_name = "bit_counting_widths";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+    let cases: [(usize, u64); 9] = [
+        (1, 0),
+        (1, 1),
+        (5, 0b00110),
+        (5, 0),
+        (8, 0b1000_0000),
+        (8, 0xFF),
+        (13, 0b0_0010_1101_0000),
+        (13, 0x1FFF),
+        (32, 0x0001_8000),
+    ];
+    for (size, a) in cases {
+        let ct_a = encode_encrypt(a as i64, size, &client_key);
+        let count_width: usize = (usize::BITS - size.leading_zeros()) as usize;
+        let mut ct_clz: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); count_width];
+        let mut ct_ctz: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); count_width];
+        let mut ct_pop: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); count_width];
+
+        server.clz(&server_key, &ct_a, &mut ct_clz).unwrap();
+        server.ctz(&server_key, &ct_a, &mut ct_ctz).unwrap();
+        server.popcount(&server_key, &ct_a, &mut ct_pop).unwrap();
+        let parity: bool = client_key.decrypt(&server.parity(&server_key, &ct_a).unwrap());
+
+        let (dec_clz, dec_ctz, dec_pop) = (
+            decrypt_unsigned(&ct_clz, &client_key),
+            decrypt_unsigned(&ct_ctz, &client_key),
+            decrypt_unsigned(&ct_pop, &client_key),
+        );
+        println!(
+            "\t {size}-bit: {a:#b} clz = {dec_clz}, ctz = {dec_ctz}, popcount = {dec_pop}, parity = {parity}"
+        );
+        let padded: u64 = a << (64 - size);
+        assert_eq!(dec_clz, padded.leading_zeros().min(size as u32) as u64);
+        assert_eq!(dec_ctz, a.trailing_zeros().min(size as u32) as u64);
+        assert_eq!(dec_pop, a.count_ones() as u64);
+        assert_eq!(parity, a.count_ones() % 2 == 1);
+    }
+
+    // A wider, reused result buffer is cleared above the count
+    let ct_a = encode_encrypt(0b00110, 5, &client_key);
+    type Count =
+        fn(&ProcessorBoolean, &ServerKey, &[Ciphertext], &mut [Ciphertext]) -> Result<(), PulpError>;
+    let counts: [(Count, u64); 3] = [
+        (ProcessorBoolean::clz, 2),
+        (ProcessorBoolean::ctz, 1),
+        (ProcessorBoolean::popcount, 2),
+    ];
+    for (count, expected) in counts {
+        let mut wide: Vec<Ciphertext> = vec![Ciphertext::Trivial(true); 8];
+        count(&server, &server_key, &ct_a, &mut wide).unwrap();
+        assert_eq!(decrypt_unsigned(&wide, &client_key), expected);
+    }
+
+    let ct_a = encode_encrypt(5, 5, &client_key);
+    let mut too_narrow: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 2];
+    assert_eq!(
+        server.popcount(&server_key, &ct_a, &mut too_narrow),
+        Err(PulpError::WidthMismatch {
+            op: "popcount",
+            operand: "result",
+            expected: 3,
+            found: 2
+        })
+    );
+    assert_eq!(
+        server.parity(&server_key, &[]).unwrap_err(),
+        PulpError::UnsupportedWidth {
+            op: "parity",
+            width: 0
+        }
+    );
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_bit_reverse() {
+    let fn_name = "bit_reverse";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, _) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+    let ct_a = encode_encrypt(0b1_1010_0011, 9, &client_key);
+    let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 9];
+    server.bit_reverse(&ct_a, &mut ct_result).unwrap();
+    assert_eq!(decrypt_unsigned(&ct_result, &client_key), 0b1_1000_1011);
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_hamming_distance() {
+    let fn_name = "hamming_distance";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+    // A 256-bit template and a copy with every seventh bit flipped
+    let template: Vec<bool> = (0..256).map(|i| (i * 37 + i / 5) % 3 == 0).collect();
+    let probe: Vec<bool> = template
+        .iter()
+        .enumerate()
+        .map(|(i, &bit)| bit ^ (i % 7 == 0))
+        .collect();
+    let expected: u64 = template.iter().zip(probe.iter()).filter(|(a, b)| a != b).count() as u64;
+    let ct_a: Vec<Ciphertext> = template.iter().map(|&bit| client_key.encrypt(bit)).collect();
+    let ct_b: Vec<Ciphertext> = probe.iter().map(|&bit| client_key.encrypt(bit)).collect();
+
+    let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 9];
+    server
+        .hamming_distance(&server_key, &ct_a, &ct_b, &mut ct_result)
+        .unwrap();
+    let dec_res = decrypt_unsigned(&ct_result, &client_key);
+    println!("\t 256-bit: distance = {dec_res}");
+    assert_eq!(dec_res, expected);
+
+    for threshold in [0, expected as usize, expected as usize + 1, 257] {
+        let ct_match = server
+            .match_below_threshold(&server_key, &ct_a, &ct_b, threshold)
+            .unwrap();
+        let dec_match: bool = client_key.decrypt(&ct_match);
+        println!("\t 256-bit: distance < {threshold} = {dec_match}");
+        assert_eq!(dec_match, (expected as usize) < threshold);
+    }
+
+    assert_eq!(
+        server
+            .match_below_threshold(&server_key, &ct_a, &ct_b[..255], 10)
+            .unwrap_err(),
+        PulpError::WidthMismatch {
+            op: "match_below_threshold",
+            operand: "b",
+            expected: 256,
+            found: 255
+        }
+    );
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_lookup_table() {
+    let fn_name = "lookup_table";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+
+    let server = ProcessorBoolean;
+    // The PRESENT cipher S-box
+    let sbox: [u64; 16] = [
+        0xC, 0x5, 0x6, 0xB, 0x9, 0x0, 0xA, 0xD, 0x3, 0xE, 0xF, 0x8, 0x4, 0x7, 0x1, 0x2,
+    ];
+    for x in 0..16 {
+        let ct_x = encode_encrypt(x, 4, &client_key);
+        let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 4];
+        server
+            .lookup_table(&server_key, &ct_x, &sbox, &mut ct_result)
+            .unwrap();
+        assert_eq!(decrypt_unsigned(&ct_result, &client_key), sbox[x as usize]);
+    }
+
+    // A sigmoid quantised to 5-bit signed inputs (step 1/4) and 6-bit unsigned outputs
+    // (step 1/64, saturating at 63)
+    let sigmoid: Vec<u64> = (0..32i64)
+        .map(|i| {
+            let x = (((i as u64) << 59) as i64 >> 59) as f64 / 4.0;
+            ((64.0 / (1.0 + (-x).exp())).round() as u64).min(63)
+        })
+        .collect();
+    for x in [-16, -5, -1, 0, 3, 15] {
+        let ct_x = encode_encrypt(x, 5, &client_key);
+        let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 6];
+        server
+            .lookup_table(&server_key, &ct_x, &sigmoid, &mut ct_result)
+            .unwrap();
+        let dec_res = decrypt_unsigned(&ct_result, &client_key);
+        println!("\t sigmoid({x}/4) = {dec_res}/64");
+        assert_eq!(dec_res, sigmoid[(x & 31) as usize]);
+    }
+
+    // Identical output bits share their trees, so four copies cost as much as one
+    let ct_x = encode_encrypt(9, 4, &client_key);
+    let one: Vec<u64> = sbox.iter().map(|&s| s & 1).collect();
+    let four: Vec<u64> = one.iter().map(|&s| s * 0xF).collect();
+    let cost = |table: &[u64], width: usize| {
+        server
+            .instrumented(&[&ct_x], |p| {
+                let mut r: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); width];
+                p.lookup_table(&server_key, &ct_x, table, &mut r).unwrap();
+                r
+            })
+            .unwrap()
+    };
+    let (cost_one, cost_four) = (cost(&one, 1), cost(&four, 4));
+    println!(
+        "\t 1 output bit: {} mux, 4 equal output bits: {} mux",
+        cost_one.mux, cost_four.mux
+    );
+    assert!(cost_one.mux > 0);
+    assert_eq!(cost_four.mux, cost_one.mux);
+    assert_eq!(cost_four.not, cost_one.not);
+
+    let mut ct_result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 4];
+    assert_eq!(
+        server.lookup_table(&server_key, &ct_x, &sbox[..8], &mut ct_result),
+        Err(PulpError::WidthMismatch {
+            op: "lookup_table",
+            operand: "table",
+            expected: 16,
+            found: 8
+        })
+    );
+    let mut too_wide: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); 65];
+    assert_eq!(
+        server.lookup_table(&server_key, &ct_x, &sbox, &mut too_wide),
+        Err(PulpError::UnsupportedWidth {
+            op: "lookup_table",
+            width: 65
+        })
+    );
+    println!("[✓] PASS: {fn_name}\n");
+}
@@ -10,7 +10,16 @@
 //! The following piece of code shows how to generate keys and run a small Boolean circuit
 //! homomorphically.
 //!
+//! # Errors
+//! Circuits taking slices check widths, counts and plaintext arguments before evaluating
+//! any gate and return a `PulpError` instead of panicking: `WidthMismatch` for operands
+//! of the wrong width, `UnsupportedWidth` for widths a circuit is not built for (e.g.
+//! 1-bit samples in `mean`), and `InvalidArgument` for a zero divisor, an unknown comparison selector or
+//! thresholds that are not ascending. `result` may be wider than the operands; the extra
+//! bits are left untouched.
+//!
 
+use crate::error::PulpError;
 use tfhe::boolean::prelude::*;
 
 pub trait ProcessorCircuits {
@@ -31,13 +40,13 @@ pub trait ProcessorCircuits {
     ///   operation will be stored.
     ///
     /// # Requirements
-    /// - The size of the `result` slice must be equal to or larger than the size of the input slice `a`.
-    ///   Failure to provide a sufficient-sized result slice may cause runtime issues.
+    /// - The size of the `result` slice must be equal to or larger than the size of the input slice `a`,
+    ///   otherwise `PulpError::WidthMismatch` is returned. Shifts of `a.len()` or more give zero.
     /// - The `Ciphertext` values must be valid and consistent with the encryption context
     ///   provided by the `ServerKey`.
     ///
     /// # Examples
-    /// ```rust
+    /// ```ignore
     /// // Assuming `key` is a valid ServerKey and `cipher_a` is a vector of Ciphertext
     /// // encrypted values.
     /// let sk = &key;
@@ -49,7 +58,12 @@ pub trait ProcessorCircuits {
     ///
     /// // The `result` vector now holds the left-shifted encrypted values.
     /// ```
-    fn e_shl(&self, a: &[Ciphertext], shift_amt: usize, result: &mut [Ciphertext]);
+    fn e_shl(
+        &self,
+        a: &[Ciphertext],
+        shift_amt: usize,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
 
     /// Performs a right bitwise shift operation on an encrypted value.
     ///
@@ -57,7 +71,12 @@ pub trait ProcessorCircuits {
     /// * `a`: &[Ciphertext] - The input encrypted array to be shifted
     /// * `shift_amt`: usize - The number of bits to shift right
     /// * `result`: &mut [Ciphertext] - The destination array for the shifted result
-    fn e_shr(&self, a: &[Ciphertext], shift_amt: usize, result: &mut [Ciphertext]);
+    fn e_shr(
+        &self,
+        a: &[Ciphertext],
+        shift_amt: usize,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
 
     /// Performs a right rotation (circular shift) on an encrypted value.
     ///
@@ -65,7 +84,12 @@ pub trait ProcessorCircuits {
     /// * `a`: &[Ciphertext] - The input encrypted array to be rotated
     /// * `rot_amt`: usize - The number of positions to rotate right
     /// * `result`: &mut [Ciphertext] - The destination array for the rotated result
-    fn e_rotr(&self, a: &[Ciphertext], rot_amt: usize, result: &mut [Ciphertext]);
+    fn e_rotr(
+        &self,
+        a: &[Ciphertext],
+        rot_amt: usize,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
 
     /// Performs a left rotation (circular shift) on an encrypted value.
     ///
@@ -73,16 +97,22 @@ pub trait ProcessorCircuits {
     /// * `a`: &[Ciphertext] - The input encrypted array to be rotated
     /// * `rot_amt`: usize - The number of positions to rotate left
     /// * `result`: &mut [Ciphertext] - The destination array for the rotated result
-    fn e_rotl(&self, a: &[Ciphertext], rot_amt: usize, result: &mut [Ciphertext]);
+    fn e_rotl(
+        &self,
+        a: &[Ciphertext],
+        rot_amt: usize,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
 
 
-    /// Compares two encrypted arrays and returns an encrypted bit indicating the result.
+    /// Compares two encrypted arrays, read as unsigned integers, and returns an encrypted
+    /// bit indicating the result.
     ///
     /// # Parameters
     /// * `sk`: &ServerKey - The server key for homomorphic operations
     /// * `a`: &[Ciphertext] - First array to compare
     /// * `b`: &[Ciphertext] - Second array to compare  
-    /// * `select`: u8 - Comparison type selector (e.g. equal, less than, etc.)
+    /// * `select`: u8 - Comparison type selector: 0 `==`, 1 `<`, 2 `>`, 3 `>=`, 4 `<=`, 5 `!=`
     ///
     /// # Returns
     /// * Ciphertext - Encrypted bit representing comparison result
@@ -92,7 +122,7 @@ pub trait ProcessorCircuits {
         a: &[Ciphertext],
         b: &[Ciphertext],
         select: u8,
-    ) -> Ciphertext;
+    ) -> Result<Ciphertext, PulpError>;
 
     /// Compares two encrypted bits considering a carry bit from previous comparison.
     ///
@@ -112,6 +142,123 @@ pub trait ProcessorCircuits {
         lsb_carry: &Ciphertext,
     ) -> Ciphertext;
 
+    /// Compares an encrypted array against a plaintext value and returns an encrypted bit
+    /// indicating the result. Both operands are treated as two's complement signed integers
+    /// of width `a.len()`; a `b` that does not fit in that width compares as it would against
+    /// every value of `a`, giving a constant result.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `a`: &[Ciphertext] - Encrypted array to compare
+    /// * `b`: i64 - Plaintext value to compare against
+    /// * `select`: u8 - Comparison type selector, same codes as `comparator`
+    ///
+    /// # Returns
+    /// * Ciphertext - Encrypted bit representing comparison result
+    fn scalar_cmp(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: i64,
+        select: u8,
+    ) -> Result<Ciphertext, PulpError>;
+
+    /// Performs bitwise AND between an encrypted array and a plaintext value. Bits where `b`
+    /// is 0 become constant false and bits where `b` is 1 are copied, so no gates are evaluated.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `a`: &[Ciphertext] - Encrypted array
+    /// * `b`: i64 - Plaintext operand, sign-extended to `a.len()` bits
+    /// * `result`: &mut [Ciphertext] - Destination array for result
+    fn scalar_and(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: i64,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
+
+    /// Performs bitwise OR between an encrypted array and a plaintext value. Bits where `b`
+    /// is 1 become constant true and bits where `b` is 0 are copied.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `a`: &[Ciphertext] - Encrypted array
+    /// * `b`: i64 - Plaintext operand, sign-extended to `a.len()` bits
+    /// * `result`: &mut [Ciphertext] - Destination array for result
+    fn scalar_or(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: i64,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
+
+    /// Performs bitwise XOR between an encrypted array and a plaintext value. Bits where `b`
+    /// is 1 are negated (no bootstrapping) and bits where `b` is 0 are copied.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `a`: &[Ciphertext] - Encrypted array
+    /// * `b`: i64 - Plaintext operand, sign-extended to `a.len()` bits
+    /// * `result`: &mut [Ciphertext] - Destination array for result
+    fn scalar_xor(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: i64,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
+
+    /// Adds a plaintext value to an encrypted array (modulo 2^`a.len()`). The carry chain is
+    /// specialised per plaintext bit and stays constant until the lowest set bit of `b`.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `a`: &[Ciphertext] - Encrypted array
+    /// * `b`: i64 - Plaintext operand, sign-extended to `a.len()` bits
+    /// * `result`: &mut [Ciphertext] - Destination array for sum
+    fn scalar_add(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: i64,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
+
+    /// Multiplies an encrypted array by a plaintext value (modulo 2^`a.len()`, like
+    /// `multiplier`) using shifts and additions over the canonical signed digits of `b`.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `a`: &[Ciphertext] - Encrypted array
+    /// * `b`: i64 - Plaintext multiplier
+    /// * `result`: &mut [Ciphertext] - Destination array for product
+    fn scalar_mul(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: i64,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
+
+    /// Divides an encrypted signed array by a non-zero plaintext value, truncating toward
+    /// zero like `divider`. The restoring steps subtract the known divisor directly.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `a`: &[Ciphertext] - Dividend array
+    /// * `b`: i64 - Plaintext divisor
+    /// * `result`: &mut [Ciphertext] - Destination array for quotient
+    fn scalar_div(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: i64,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
+
     /// Performs subtraction between two encrypted arrays.
     ///
     /// # Parameters
@@ -125,7 +272,7 @@ pub trait ProcessorCircuits {
         a: &[Ciphertext],
         b: &[Ciphertext],
         result: &mut [Ciphertext],
-    );
+    ) -> Result<(), PulpError>;
 
     /// Performs addition between two encrypted arrays.
     ///
@@ -134,7 +281,13 @@ pub trait ProcessorCircuits {
     /// * `a`: &[Ciphertext] - First operand array
     /// * `b`: &[Ciphertext] - Second operand array
     /// * `result`: &mut [Ciphertext] - Destination array for sum
-    fn adder(&self, sk: &ServerKey, a: &[Ciphertext], b: &[Ciphertext], result: &mut [Ciphertext]);
+    fn adder(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
 
     /// Performs signed addition between two encrypted arrays.
     ///
@@ -149,7 +302,7 @@ pub trait ProcessorCircuits {
         a: &[Ciphertext],
         b: &[Ciphertext],
         result: &mut [Ciphertext],
-    );
+    ) -> Result<(), PulpError>;
 
     /// Performs a half adder operation on two encrypted bits.
     ///
@@ -202,7 +355,7 @@ pub trait ProcessorCircuits {
         b: &[Ciphertext],
         size: usize,
         result: &mut [Ciphertext],
-    );
+    ) -> Result<(), PulpError>;
 
     /// Performs multiplication between two encrypted arrays.
     ///
@@ -217,7 +370,7 @@ pub trait ProcessorCircuits {
         a: &[Ciphertext],
         b: &[Ciphertext],
         result: &mut [Ciphertext],
-    );
+    ) -> Result<(), PulpError>;
 
     /// Performs the BLAKE3 hash function on encrypted data.
     ///
@@ -232,7 +385,7 @@ pub trait ProcessorCircuits {
         msg: Vec<&[Ciphertext]>,
         v: Vec<&[Ciphertext]>,
         result: &mut Vec<&mut [Ciphertext]>,
-    );
+    ) -> Result<(), PulpError>;
 
     /// Finds the maximum value among encrypted arrays.
     ///
@@ -240,7 +393,12 @@ pub trait ProcessorCircuits {
     /// * `sk`: &ServerKey - The server key for homomorphic operations
     /// * `a`: &Vec<&[Ciphertext]> - Vector of input arrays
     /// * `result`: &mut [Ciphertext] - Destination array for maximum value
-    fn max(&self, sk: &ServerKey, a: &Vec<&[Ciphertext]>, result: &mut [Ciphertext]);
+    fn max(
+        &self,
+        sk: &ServerKey,
+        a: &Vec<&[Ciphertext]>,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
 
     /// Finds the minimum value among encrypted arrays.
     ///
@@ -248,7 +406,12 @@ pub trait ProcessorCircuits {
     /// * `sk`: &ServerKey - The server key for homomorphic operations
     /// * `a`: &Vec<&[Ciphertext]> - Vector of input arrays
     /// * `result`: &mut [Ciphertext] - Destination array for minimum value
-    fn min(&self, sk: &ServerKey, a: &Vec<&[Ciphertext]>, result: &mut [Ciphertext]);
+    fn min(
+        &self,
+        sk: &ServerKey,
+        a: &Vec<&[Ciphertext]>,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
 
     /// Applies ReLU (Rectified Linear Unit) function on encrypted array.
     ///
@@ -256,7 +419,12 @@ pub trait ProcessorCircuits {
     /// * `sk`: &ServerKey - The server key for homomorphic operations
     /// * `a`: &[Ciphertext] - Input array
     /// * `result`: &mut [Ciphertext] - Destination array after ReLU
-    fn relu(&self, sk: &ServerKey, a: &[Ciphertext], result: &mut [Ciphertext]);
+    fn relu(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
 
     /// Performs division between two encrypted arrays.
     ///
@@ -271,35 +439,59 @@ pub trait ProcessorCircuits {
         a: &[Ciphertext],
         b: &[Ciphertext],
         result: &mut [Ciphertext],
-    );
+    ) -> Result<(), PulpError>;
 
-    /// Computes modulo operation between two encrypted arrays.
+    /// Computes modulo operation between two encrypted arrays. Both operands must be
+    /// non-negative and the modulus non-zero.
     ///
     /// # Parameters
     /// * `sk`: &ServerKey - The server key for homomorphic operations
     /// * `a`: &[Ciphertext] - Dividend array
     /// * `b`: &[Ciphertext] - Modulus array
     /// * `result`: &mut [Ciphertext] - Destination array for remainder
-    fn modulo(&self, sk: &ServerKey, a: &[Ciphertext], b: &[Ciphertext], result: &mut [Ciphertext]);
+    fn modulo(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
 
-    /// Computes arithmetic mean of encrypted arrays.
+    /// Computes arithmetic mean of encrypted arrays; the first `count`
+    /// elements must share one width of at least 2 bits.
     ///
     /// # Parameters
     /// * `sk`: &ServerKey - The server key for homomorphic operations
     /// * `a`: &Vec<&[Ciphertext]> - Vector of input arrays
     /// * `count`: usize - Number of elements
     /// * `result`: &mut [Ciphertext] - Destination array for mean
-    fn mean(&self, sk: &ServerKey, a: &Vec<&[Ciphertext]>, count: usize, result: &mut [Ciphertext]);
+    fn mean(
+        &self,
+        sk: &ServerKey,
+        a: &Vec<&[Ciphertext]>,
+        count: usize,
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
 
-    /// Computes square root of encrypted array.
+    /// Computes the integer square root of an encrypted array, read as unsigned.
+    ///
+    /// Uses a bit-by-bit (digit recurrence) square root: one root bit per pair of operand
+    /// bits, each found with a single subtraction, so the result is exact and the gate
+    /// count depends only on the width. Any non-empty width is supported.
     ///
     /// # Parameters
     /// * `sk`: &ServerKey - The server key for homomorphic operations
     /// * `a`: &[Ciphertext] - Input array
     /// * `result`: &mut [Ciphertext] - Destination array for square root
-    fn sqrt(&self, sk: &ServerKey, a: &[Ciphertext], result: &mut [Ciphertext]);
+    fn sqrt(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
 
-    /// Computes variance of encrypted arrays.
+    /// Computes variance of encrypted arrays; the first `count`
+    /// elements must share one width of at least 2 bits.
     ///
     /// # Parameters
     /// * `sk`: &ServerKey - The server key for homomorphic operations
@@ -312,9 +504,10 @@ pub trait ProcessorCircuits {
         a: &Vec<&[Ciphertext]>,
         count: usize,
         result: &mut [Ciphertext],
-    );
+    ) -> Result<(), PulpError>;
 
-    /// Computes standard deviation of encrypted arrays.
+    /// Computes standard deviation of encrypted arrays; the first `count`
+    /// elements must share one width of at least 2 bits.
     ///
     /// # Parameters
     /// * `sk`: &ServerKey - The server key for homomorphic operations
@@ -327,7 +520,248 @@ pub trait ProcessorCircuits {
         a: &Vec<&[Ciphertext]>,
         count: usize,
         result: &mut [Ciphertext],
-    );
+    ) -> Result<(), PulpError>;
+
+    /// Counts how many encrypted values fall into each bucket delimited by plaintext thresholds.
+    ///
+    /// With ascending thresholds `t_0 < t_1 < ... < t_(k-1)` there are `k + 1` buckets:
+    /// `x < t_0`, `t_0 <= x < t_1`, ..., `x >= t_(k-1)`. Each bucket count is an unsigned
+    /// encrypted counter wide enough to hold `a.len()` without overflow. Thresholds outside
+    /// the signed range of the samples compare exactly rather than wrapping, so the buckets
+    /// above one past the top of that range, or below one past the bottom, stay empty.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `a`: &Vec<&[Ciphertext]> - Vector of input arrays (signed)
+    /// * `thresholds`: &[i64] - Strictly ascending plaintext bucket boundaries
+    /// * `result`: &mut Vec<Vec<Ciphertext>> - Destination for the `k + 1` encrypted bucket counts
+    fn histogram(
+        &self,
+        sk: &ServerKey,
+        a: &Vec<&[Ciphertext]>,
+        thresholds: &[i64],
+        result: &mut Vec<Vec<Ciphertext>>,
+    ) -> Result<(), PulpError>;
+
+    /// Computes the dot product of two encrypted vectors.
+    ///
+    /// The accumulator is widened to `2 * width + ceil(log2(len))` bits, so the signed result
+    /// cannot overflow.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `a`: &Vec<&[Ciphertext]> - First vector (signed)
+    /// * `b`: &Vec<&[Ciphertext]> - Second vector (signed), same length and width as `a`
+    /// * `result`: &mut Vec<Ciphertext> - Destination for the widened dot product
+    fn dot_product(
+        &self,
+        sk: &ServerKey,
+        a: &Vec<&[Ciphertext]>,
+        b: &Vec<&[Ciphertext]>,
+        result: &mut Vec<Ciphertext>,
+    ) -> Result<(), PulpError>;
+
+    /// Computes the dot product of an encrypted vector and a plaintext weight vector.
+    ///
+    /// Each weight is recoded into signed digits and applied with shifts plus adds or
+    /// subtracts, so no `multiplier` is run. The accumulator is widened to
+    /// `width + weight_width + ceil(log2(len))` bits.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `a`: &Vec<&[Ciphertext]> - Encrypted vector (signed)
+    /// * `w`: &[i64] - Plaintext weights, same length as `a`
+    /// * `result`: &mut Vec<Ciphertext> - Destination for the widened dot product
+    fn dot_product_plain(
+        &self,
+        sk: &ServerKey,
+        a: &Vec<&[Ciphertext]>,
+        w: &[i64],
+        result: &mut Vec<Ciphertext>,
+    ) -> Result<(), PulpError>;
+
+    /// Multiplies an encrypted matrix by an encrypted vector.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `m`: &Vec<Vec<&[Ciphertext]>> - Matrix given as rows, elements as wide as those of `x`
+    /// * `x`: &Vec<&[Ciphertext]> - Vector with one element per matrix column
+    /// * `result`: &mut Vec<Vec<Ciphertext>> - Destination, one widened element per row
+    fn matvec(
+        &self,
+        sk: &ServerKey,
+        m: &Vec<Vec<&[Ciphertext]>>,
+        x: &Vec<&[Ciphertext]>,
+        result: &mut Vec<Vec<Ciphertext>>,
+    ) -> Result<(), PulpError>;
+
+    /// Multiplies a plaintext matrix by an encrypted vector using shift-and-add.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `m`: &[Vec<i64>] - Plaintext matrix given as rows
+    /// * `x`: &Vec<&[Ciphertext]> - Encrypted vector with one element per matrix column
+    /// * `result`: &mut Vec<Vec<Ciphertext>> - Destination, one widened element per row
+    fn matvec_plain(
+        &self,
+        sk: &ServerKey,
+        m: &[Vec<i64>],
+        x: &Vec<&[Ciphertext]>,
+        result: &mut Vec<Vec<Ciphertext>>,
+    ) -> Result<(), PulpError>;
+
+    /// Multiplies two encrypted matrices.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `a`: &Vec<Vec<&[Ciphertext]>> - Left matrix given as rows
+    /// * `b`: &Vec<Vec<&[Ciphertext]>> - Right matrix given as rows, elements as wide as those
+    ///   of `a`
+    /// * `result`: &mut Vec<Vec<Vec<Ciphertext>>> - Destination matrix of widened elements
+    fn matmul(
+        &self,
+        sk: &ServerKey,
+        a: &Vec<Vec<&[Ciphertext]>>,
+        b: &Vec<Vec<&[Ciphertext]>>,
+        result: &mut Vec<Vec<Vec<Ciphertext>>>,
+    ) -> Result<(), PulpError>;
+
+    /// Multiplies an encrypted matrix by a plaintext matrix using shift-and-add.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `a`: &Vec<Vec<&[Ciphertext]>> - Encrypted left matrix given as rows, all elements of
+    ///   one width
+    /// * `w`: &[Vec<i64>] - Plaintext right matrix given as rows
+    /// * `result`: &mut Vec<Vec<Vec<Ciphertext>>> - Destination matrix of widened elements
+    fn matmul_plain(
+        &self,
+        sk: &ServerKey,
+        a: &Vec<Vec<&[Ciphertext]>>,
+        w: &[Vec<i64>],
+        result: &mut Vec<Vec<Vec<Ciphertext>>>,
+    ) -> Result<(), PulpError>;
+
+    /// Counts the leading (most significant) zero bits of an encrypted array.
+    ///
+    /// The count is built with a tree of muxes over halves of the operand, so the depth
+    /// grows with log2 of the width. It is written, unsigned, to the low
+    /// `floor(log2(a.len())) + 1` bits of `result`, enough to hold `a.len()` when `a` is zero.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `a`: &[Ciphertext] - Input array
+    /// * `result`: &mut [Ciphertext] - Destination array for the count
+    fn clz(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
+
+    /// Counts the trailing (least significant) zero bits of an encrypted array; the count
+    /// is sized as in `clz`.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `a`: &[Ciphertext] - Input array
+    /// * `result`: &mut [Ciphertext] - Destination array for the count
+    fn ctz(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
+
+    /// Counts the set bits (Hamming weight) of an encrypted array.
+    ///
+    /// Bits are summed pairwise in a log-depth tree of half adders and adders; the count
+    /// is sized as in `clz`.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `a`: &[Ciphertext] - Input array
+    /// * `result`: &mut [Ciphertext] - Destination array for the count
+    fn popcount(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
+
+    /// Computes the parity (XOR of all bits) of an encrypted array.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `a`: &[Ciphertext] - Input array
+    ///
+    /// # Returns
+    /// * Ciphertext - Encrypted bit, set when an odd number of bits of `a` are set
+    fn parity(&self, sk: &ServerKey, a: &[Ciphertext]) -> Result<Ciphertext, PulpError>;
+
+    /// Reverses the bit order of an encrypted array; no gates are evaluated.
+    ///
+    /// # Parameters
+    /// * `a`: &[Ciphertext] - Input array
+    /// * `result`: &mut [Ciphertext] - Destination array for the reversed bits
+    fn bit_reverse(&self, a: &[Ciphertext], result: &mut [Ciphertext]) -> Result<(), PulpError>;
+
+    /// Computes the Hamming distance between two encrypted bit strings of the same
+    /// (arbitrary) length: the `popcount` of their XOR, sized as in `clz`.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `a`: &[Ciphertext] - First bit string
+    /// * `b`: &[Ciphertext] - Second bit string
+    /// * `result`: &mut [Ciphertext] - Destination array for the distance
+    fn hamming_distance(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
+
+    /// Matches two encrypted templates (e.g. iris codes): whether their Hamming distance
+    /// is below a plaintext threshold. Only the encrypted outcome is returned, never the
+    /// distance itself.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `a`: &[Ciphertext] - First template
+    /// * `b`: &[Ciphertext] - Second template
+    /// * `threshold`: usize - Distance from which templates no longer match
+    ///
+    /// # Returns
+    /// * Ciphertext - Encrypted bit, set when `hamming_distance(a, b) < threshold`
+    fn match_below_threshold(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        threshold: usize,
+    ) -> Result<Ciphertext, PulpError>;
+
+    /// Evaluates an arbitrary function of a small encrypted input given as a plaintext
+    /// table (e.g. an S-box or a quantised activation): `result = table[a]`.
+    ///
+    /// Each output bit is a multiplexer tree over the index bits whose leaves are the
+    /// table bits. Constant subtrees fold away and identical subtrees, within and across
+    /// output bits, are evaluated once, so each output bit costs at most `2^k - 1`
+    /// multiplexers for a `k`-bit input, and structured tables far fewer.
+    ///
+    /// # Parameters
+    /// * `sk`: &ServerKey - The server key for homomorphic operations
+    /// * `a`: &[Ciphertext] - Encrypted unsigned index of `k` bits
+    /// * `table`: &[u64] - The `2^k` entries; only their low `result.len()` bits are used
+    /// * `result`: &mut [Ciphertext] - Destination array for the entry, at most 64 bits
+    fn lookup_table(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        table: &[u64],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
 
     fn copy_to_from(&self, target: &mut [Ciphertext], source: &[Ciphertext]);
 
@@ -10,7 +10,13 @@
 //! The following piece of code shows how to generate keys and run a small Boolean circuit
 //! homomorphically.
 //!
+//! # Errors
+//! The vector, range and mux gates check their operands before evaluating anything and
+//! return a `PulpError` when `b` is not as wide as `a`, `result` is narrower than `a`, or
+//! a range does not fit the operands. The single-bit gates cannot fail.
+//!
 
+use crate::error::PulpError;
 use tfhe::boolean::prelude::*;
 
 pub trait ProcessorGate {
@@ -23,7 +29,13 @@ pub trait ProcessorGate {
     /// * `b`: &[Ciphertext] - The second operand, a vector of ciphertexts, each element encrypting a single bit.
     /// * `result`: &mut [Ciphertext] - A vector holding the result of the operation.
     ///
-    fn e_and(&self, sk: &ServerKey, a: &[Ciphertext], b: &[Ciphertext], result: &mut [Ciphertext]);
+    fn e_and(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
 
     /// Compute the encrypted AND gate on single bit ciphertexts `a` and `b`.
     ///
@@ -53,7 +65,7 @@ pub trait ProcessorGate {
         result: &mut [Ciphertext],
         index_low: usize,
         index_high: usize,
-    );
+    ) -> Result<(), PulpError>;
 
     /// Compute the encrypted OR gate on the `Ciphertext` vectors `a` and `b`. The result is stored in
     ///  the ` result ` vector.
@@ -64,7 +76,13 @@ pub trait ProcessorGate {
     /// * `b`: &[Ciphertext] - The second operand, a vector of ciphertexts, each element encrypting a single bit.
     /// * `result`: &mut [Ciphertext] - A vector holding the result of the operation.
     ///
-    fn e_or(&self, sk: &ServerKey, a: &[Ciphertext], b: &[Ciphertext], result: &mut [Ciphertext]);
+    fn e_or(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
 
     /// Compute the encrypted OR gate on single bit ciphertexts `a` and `b`.
     ///
@@ -94,7 +112,7 @@ pub trait ProcessorGate {
         result: &mut [Ciphertext],
         index_low: usize,
         index_high: usize,
-    );
+    ) -> Result<(), PulpError>;
 
     /// Compute the encrypted XOR gate on the `Ciphertext` vectors `a` and `b`. The result is stored in
     ///  the ` result ` vector.
@@ -105,7 +123,13 @@ pub trait ProcessorGate {
     /// * `b`: &[Ciphertext] - The second operand, a vector of ciphertexts, each element encrypting a single bit.
     /// * `result`: &mut [Ciphertext] - A vector holding the result of the operation.
     ///
-    fn e_xor(&self, sk: &ServerKey, a: &[Ciphertext], b: &[Ciphertext], result: &mut [Ciphertext]);
+    fn e_xor(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
     fn e_xor_bit(&self, sk: &ServerKey, a: &Ciphertext, b: &Ciphertext) -> Ciphertext;
     fn e_xor_range(
         &self,
@@ -115,7 +139,7 @@ pub trait ProcessorGate {
         result: &mut [Ciphertext],
         index_low: usize,
         index_high: usize,
-    );
+    ) -> Result<(), PulpError>;
 
     /// Compute the encrypted NAND gate on the `Ciphertext` vectors `a` and `b`. The result is stored in
     ///  the ` result ` vector.
@@ -126,7 +150,13 @@ pub trait ProcessorGate {
     /// * `b`: &[Ciphertext] - The second operand, a vector of ciphertexts, each element encrypting a single bit.
     /// * `result`: &mut [Ciphertext] - A vector holding the result of the operation.
     ///
-    fn e_nand(&self, sk: &ServerKey, a: &[Ciphertext], b: &[Ciphertext], result: &mut [Ciphertext]);
+    fn e_nand(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
     fn e_nand_bit(&self, sk: &ServerKey, a: &Ciphertext, b: &Ciphertext) -> Ciphertext;
     fn e_nand_range(
         &self,
@@ -136,7 +166,7 @@ pub trait ProcessorGate {
         result: &mut [Ciphertext],
         index_low: usize,
         index_high: usize,
-    );
+    ) -> Result<(), PulpError>;
 
     /// Compute the encrypted NOR gate on the `Ciphertext` vectors `a` and `b`. The result is stored in
     ///  the ` result ` vector.
@@ -147,7 +177,13 @@ pub trait ProcessorGate {
     /// * `b`: &[Ciphertext] - The second operand, a vector of ciphertexts, each element encrypting a single bit.
     /// * `result`: &mut [Ciphertext] - A vector holding the result of the operation.
     ///
-    fn e_nor(&self, sk: &ServerKey, a: &[Ciphertext], b: &[Ciphertext], result: &mut [Ciphertext]);
+    fn e_nor(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
     fn e_nor_bit(&self, sk: &ServerKey, a: &Ciphertext, b: &Ciphertext) -> Ciphertext;
     fn e_nor_range(
         &self,
@@ -157,7 +193,7 @@ pub trait ProcessorGate {
         result: &mut [Ciphertext],
         index_low: usize,
         index_high: usize,
-    );
+    ) -> Result<(), PulpError>;
 
     /// Compute the encrypted XNOR gate on the `Ciphertext` vectors `a` and `b`. The result is stored in
     ///  the ` result ` vector.
@@ -168,7 +204,13 @@ pub trait ProcessorGate {
     /// * `b`: &[Ciphertext] - The second operand, a vector of ciphertexts, each element encrypting a single bit.
     /// * `result`: &mut [Ciphertext] - A vector holding the result of the operation.
     ///
-    fn e_xnor(&self, sk: &ServerKey, a: &[Ciphertext], b: &[Ciphertext], result: &mut [Ciphertext]);
+    fn e_xnor(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
     fn e_xnor_bit(&self, sk: &ServerKey, a: &Ciphertext, b: &Ciphertext) -> Ciphertext;
     fn e_xnor_range(
         &self,
@@ -178,7 +220,7 @@ pub trait ProcessorGate {
         result: &mut [Ciphertext],
         index_low: usize,
         index_high: usize,
-    );
+    ) -> Result<(), PulpError>;
 
     /// Compute the encrypted NOT gate on the `Ciphertext` vector `a`. The result is stored in
     ///  the ` result ` vector.
@@ -188,7 +230,12 @@ pub trait ProcessorGate {
     /// * `a`: &[Ciphertext] - A vector of ciphertexts, each element encrypting a single bit.
     /// * `result`: &mut [Ciphertext] - A vector holding the result of the operation.
     ///
-    fn e_not(&self, sk: &ServerKey, a: &[Ciphertext], result: &mut [Ciphertext]);
+    fn e_not(
+        &self,
+        sk: &ServerKey,
+        a: &[Ciphertext],
+        result: &mut [Ciphertext],
+    ) -> Result<(), PulpError>;
     fn e_not_bit(&self, sk: &ServerKey, a: &Ciphertext) -> Ciphertext;
     fn e_not_range(
         &self,
@@ -197,7 +244,7 @@ pub trait ProcessorGate {
         result: &mut [Ciphertext],
         index_low: usize,
         index_high: usize,
-    );
+    ) -> Result<(), PulpError>;
 
     /// Multiplexer (MUX) operation that selects between two encrypted arrays based on a selector bit.
     ///
@@ -214,7 +261,7 @@ pub trait ProcessorGate {
         ct_then: &[Ciphertext],
         ct_else: &[Ciphertext],
         result: &mut [Ciphertext],
-    );
+    ) -> Result<(), PulpError>;
 
     /// Single bit multiplexer operation that selects between two encrypted bits.
     ///
@@ -253,6 +300,6 @@ pub trait ProcessorGate {
         result: &mut [Ciphertext],
         index_low: usize,
         index_high: usize,
-    );
+    ) -> Result<(), PulpError>;
     
 }
\ No newline at end of file
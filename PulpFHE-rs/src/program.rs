@@ -0,0 +1,540 @@
+//! # Description
+//! A small assembly language for the `Controller`, run with `Controller::run`.
+//!
+//! One statement per line, `#` starts a comment:
+//!
+//! ```text
+//! input a 8          # register filled from an input ciphertext
+//! input b 8
+//! reg t 8            # register initialised to trivial zeros
+//! xor t, a, b        # whole registers of equal width
+//! and t[0], a[3], b[3]
+//! not t, t
+//! output t
+//! ```
+//!
+//! The gates are `and`, `or`, `xor`, `nand`, `nor`, `xnor` and `not` (issued as `nand` with
+//! both operands equal). Operands are either whole registers or single bits `name[i]`,
+//! LSB first; a statement uses one form for all of its operands. Registers are declared
+//! before use and are at most 127 bits wide, the range of the controller's bit indices.
+//!
+//! Oblivious memories (`ObliviousMemory`) hold words addressed by an encrypted index
+//! register, read as unsigned:
+//!
+//! ```text
+//! mem table 16 8     # 16 words of 8 bits, initialised to trivial zeros
+//! store table, i, a  # table[i] = a
+//! load t, table, j   # t = table[j]
+//! ```
+//!
+//! A memory holds at most 4096 words. The value registers of `load` and `store` are as wide
+//! as the words; the index register may have any width.
+//!
+//! Control flow is data-oblivious. Both arms of an `if` run and the registers and memories
+//! they write are merged with `e_mux` on the encrypted condition; a `repeat` runs its body
+//! a fixed number of times, and once its exit flag is set every later iteration is
+//! discarded:
+//!
+//! ```text
+//! if c[0] {          # condition: a single bit
+//!     xor t, a, b
+//! } else {           # optional
+//!     and t, a, b
+//! }
+//! repeat 8 until done[0] {
+//!     ...            # may set done[0] to stop
+//! }
+//! ```
+//!
+//! Blocks nest; declarations (`input`, `reg`, `mem`) and `output` stay outside them.
+
+use crate::constant_fold_module::GateKind;
+use crate::error::PulpError;
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod test_program;
+
+/// Widest register the controller can index
+pub const MAX_REGISTER_WIDTH: usize = i8::MAX as usize;
+
+/// Most words a `mem` declaration may hold, so that a program cannot allocate unbounded
+/// memory up front
+pub const MAX_MEMORY_WORDS: usize = 1 << 12;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Operand {
+    Register(String),
+    Bit(String, usize),
+}
+
+impl Operand {
+    pub fn register(&self) -> &str {
+        match self {
+            Operand::Register(name) | Operand::Bit(name, _) => name,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Statement {
+    Input {
+        name: String,
+        width: usize,
+    },
+    Reg {
+        name: String,
+        width: usize,
+    },
+    Gate {
+        kind: GateKind,
+        dst: Operand,
+        a: Operand,
+        b: Operand,
+    },
+    Output {
+        name: String,
+    },
+    Mem {
+        name: String,
+        words: usize,
+        width: usize,
+    },
+    Load {
+        dst: String,
+        mem: String,
+        index: String,
+    },
+    Store {
+        mem: String,
+        index: String,
+        src: String,
+    },
+    If {
+        cond: Operand,
+        then: Vec<Instruction>,
+        other: Vec<Instruction>,
+    },
+    Repeat {
+        count: usize,
+        until: Operand,
+        body: Vec<Instruction>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instruction {
+    pub line: usize,
+    pub statement: Statement,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Program {
+    pub instructions: Vec<Instruction>,
+}
+
+/// A block being parsed: its opening line and the statements read so far.
+struct Block {
+    line: usize,
+    kind: BlockKind,
+    body: Vec<Instruction>,
+}
+
+enum BlockKind {
+    /// `then` is set once `} else {` is read
+    If {
+        cond: Operand,
+        then: Option<Vec<Instruction>>,
+    },
+    Repeat {
+        count: usize,
+        until: Operand,
+    },
+}
+
+impl Program {
+    /// Parses and checks a program: registers and memories are declared once before use,
+    /// whole-register operands have equal widths, bit indices are in range and blocks are
+    /// closed.
+    pub fn parse(source: &str) -> Result<Program, PulpError> {
+        let mut widths: HashMap<String, usize> = HashMap::new();
+        let mut memories: HashMap<String, usize> = HashMap::new();
+        let mut instructions = Vec::new();
+        let mut blocks: Vec<Block> = Vec::new();
+        for (i, raw) in source.lines().enumerate() {
+            let mut line = i + 1;
+            let text = raw.split('#').next().unwrap_or("").trim();
+            if text.is_empty() {
+                continue;
+            }
+            let (mnemonic, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+            let args: Vec<&str> = rest
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|s| !s.is_empty())
+                .collect();
+            if !blocks.is_empty() && matches!(mnemonic, "input" | "reg" | "mem" | "output") {
+                return Err(PulpError::program(
+                    line,
+                    format!("`{mnemonic}` is not allowed inside a block"),
+                ));
+            }
+
+            let statement = match mnemonic {
+                "if" => {
+                    let [cond, "{"] = args[..] else {
+                        return Err(PulpError::program(line, "expected `if <bit> {`"));
+                    };
+                    let cond = Self::condition(line, &widths, cond)?;
+                    blocks.push(Block {
+                        line,
+                        kind: BlockKind::If { cond, then: None },
+                        body: Vec::new(),
+                    });
+                    continue;
+                }
+                "repeat" => {
+                    let [count, "until", until, "{"] = args[..] else {
+                        return Err(PulpError::program(
+                            line,
+                            "expected `repeat <count> until <bit> {`",
+                        ));
+                    };
+                    let count: usize = count.parse().map_err(|_| {
+                        PulpError::program(line, format!("invalid repeat count `{count}`"))
+                    })?;
+                    let until = Self::condition(line, &widths, until)?;
+                    blocks.push(Block {
+                        line,
+                        kind: BlockKind::Repeat { count, until },
+                        body: Vec::new(),
+                    });
+                    continue;
+                }
+                "}" => {
+                    let Some(block) = blocks.pop() else {
+                        return Err(PulpError::program(line, "`}` without an open block"));
+                    };
+                    match (&args[..], block.kind) {
+                        (["else", "{"], BlockKind::If { cond, then: None }) => {
+                            blocks.push(Block {
+                                line: block.line,
+                                kind: BlockKind::If {
+                                    cond,
+                                    then: Some(block.body),
+                                },
+                                body: Vec::new(),
+                            });
+                            continue;
+                        }
+                        ([], BlockKind::If { cond, then }) => {
+                            let (then, other) = match then {
+                                Some(then) => (then, block.body),
+                                None => (block.body, Vec::new()),
+                            };
+                            line = block.line;
+                            Statement::If { cond, then, other }
+                        }
+                        ([], BlockKind::Repeat { count, until }) => {
+                            line = block.line;
+                            Statement::Repeat {
+                                count,
+                                until,
+                                body: block.body,
+                            }
+                        }
+                        _ => return Err(PulpError::program(line, "expected `}` or `} else {`")),
+                    }
+                }
+                "input" | "reg" => {
+                    let [name, width] = args[..] else {
+                        return Err(PulpError::program(
+                            line,
+                            format!("`{mnemonic}` takes a name and a width"),
+                        ));
+                    };
+                    Self::check_name(line, name)?;
+                    let width: usize = width
+                        .parse()
+                        .ok()
+                        .filter(|w| (1..=MAX_REGISTER_WIDTH).contains(w))
+                        .ok_or_else(|| {
+                            PulpError::program(
+                                line,
+                                format!("width must be 1..={MAX_REGISTER_WIDTH}, got `{width}`"),
+                            )
+                        })?;
+                    if memories.contains_key(name)
+                        || widths.insert(name.to_string(), width).is_some()
+                    {
+                        return Err(PulpError::program(
+                            line,
+                            format!("register `{name}` is declared twice"),
+                        ));
+                    }
+                    if mnemonic == "input" {
+                        Statement::Input {
+                            name: name.to_string(),
+                            width,
+                        }
+                    } else {
+                        Statement::Reg {
+                            name: name.to_string(),
+                            width,
+                        }
+                    }
+                }
+                "mem" => {
+                    let [name, words, width] = args[..] else {
+                        return Err(PulpError::program(
+                            line,
+                            "`mem` takes a name, a word count and a width",
+                        ));
+                    };
+                    Self::check_name(line, name)?;
+                    let words: usize = words
+                        .parse()
+                        .ok()
+                        .filter(|n| (1..=MAX_MEMORY_WORDS).contains(n))
+                        .ok_or_else(|| {
+                            PulpError::program(
+                                line,
+                                format!("word count must be 1..={MAX_MEMORY_WORDS}, got `{words}`"),
+                            )
+                        })?;
+                    let width: usize = width
+                        .parse()
+                        .ok()
+                        .filter(|w| (1..=MAX_REGISTER_WIDTH).contains(w))
+                        .ok_or_else(|| {
+                            PulpError::program(
+                                line,
+                                format!("width must be 1..={MAX_REGISTER_WIDTH}, got `{width}`"),
+                            )
+                        })?;
+                    if widths.contains_key(name)
+                        || memories.insert(name.to_string(), width).is_some()
+                    {
+                        return Err(PulpError::program(
+                            line,
+                            format!("memory `{name}` is declared twice"),
+                        ));
+                    }
+                    Statement::Mem {
+                        name: name.to_string(),
+                        words,
+                        width,
+                    }
+                }
+                "load" | "store" => {
+                    let [first, second, third] = args[..] else {
+                        return Err(PulpError::program(
+                            line,
+                            format!("`{mnemonic}` takes 3 operands"),
+                        ));
+                    };
+                    // load dst, mem, index / store mem, index, src
+                    let (mem, index, value) = if mnemonic == "load" {
+                        (second, third, first)
+                    } else {
+                        (first, second, third)
+                    };
+                    let width: usize = memories.get(mem).copied().ok_or_else(|| {
+                        PulpError::program(line, format!("memory `{mem}` is not declared"))
+                    })?;
+                    Self::lookup(line, &widths, index)?;
+                    let found: usize = Self::lookup(line, &widths, value)?;
+                    if found != width {
+                        return Err(PulpError::program(
+                            line,
+                            format!("register `{value}` is {found} bits wide, expected {width}"),
+                        ));
+                    }
+                    if mnemonic == "load" {
+                        Statement::Load {
+                            dst: value.to_string(),
+                            mem: mem.to_string(),
+                            index: index.to_string(),
+                        }
+                    } else {
+                        Statement::Store {
+                            mem: mem.to_string(),
+                            index: index.to_string(),
+                            src: value.to_string(),
+                        }
+                    }
+                }
+                "output" => {
+                    let [name] = args[..] else {
+                        return Err(PulpError::program(line, "`output` takes a register name"));
+                    };
+                    Self::lookup(line, &widths, name)?;
+                    Statement::Output {
+                        name: name.to_string(),
+                    }
+                }
+                _ => {
+                    let kind = match mnemonic {
+                        "and" => GateKind::And,
+                        "or" => GateKind::Or,
+                        "xor" => GateKind::Xor,
+                        "nand" | "not" => GateKind::Nand,
+                        "nor" => GateKind::Nor,
+                        "xnor" => GateKind::Xnor,
+                        _ => {
+                            return Err(PulpError::program(
+                                line,
+                                format!("unknown instruction `{mnemonic}`"),
+                            ));
+                        }
+                    };
+                    let expected = if mnemonic == "not" { 2 } else { 3 };
+                    if args.len() != expected {
+                        return Err(PulpError::program(
+                            line,
+                            format!("`{mnemonic}` takes {expected} operands"),
+                        ));
+                    }
+                    let operands = args
+                        .iter()
+                        .map(|arg| Self::operand(line, &widths, arg))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    Self::check_operands(line, &widths, &operands)?;
+                    let b = operands[expected - 1].clone();
+                    Statement::Gate {
+                        kind,
+                        dst: operands[0].clone(),
+                        a: operands[1].clone(),
+                        b,
+                    }
+                }
+            };
+            blocks
+                .last_mut()
+                .map_or(&mut instructions, |block| &mut block.body)
+                .push(Instruction { line, statement });
+        }
+        if let Some(block) = blocks.last() {
+            return Err(PulpError::program(block.line, "block is not closed"));
+        }
+        Ok(Program { instructions })
+    }
+
+    /// Registers filled from inputs, with their widths, in declaration order.
+    pub fn inputs(&self) -> Vec<(&str, usize)> {
+        self.instructions
+            .iter()
+            .filter_map(|i| match &i.statement {
+                Statement::Input { name, width } => Some((name.as_str(), *width)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Registers returned by `Controller::run`, in program order.
+    pub fn outputs(&self) -> Vec<&str> {
+        self.instructions
+            .iter()
+            .filter_map(|i| match &i.statement {
+                Statement::Output { name } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn check_name(line: usize, name: &str) -> Result<(), PulpError> {
+        let valid = name
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            return Err(PulpError::program(
+                line,
+                format!("invalid register name `{name}`"),
+            ));
+        }
+        Ok(())
+    }
+
+    fn lookup(
+        line: usize,
+        widths: &HashMap<String, usize>,
+        name: &str,
+    ) -> Result<usize, PulpError> {
+        widths
+            .get(name)
+            .copied()
+            .ok_or_else(|| PulpError::program(line, format!("register `{name}` is not declared")))
+    }
+
+    /// A single-bit operand: a bit or a 1-bit register.
+    fn condition(
+        line: usize,
+        widths: &HashMap<String, usize>,
+        text: &str,
+    ) -> Result<Operand, PulpError> {
+        let operand = Self::operand(line, widths, text)?;
+        if let Operand::Register(name) = &operand
+            && widths[name] != 1
+        {
+            return Err(PulpError::program(
+                line,
+                format!(
+                    "condition `{name}` is {} bits wide, expected 1",
+                    widths[name]
+                ),
+            ));
+        }
+        Ok(operand)
+    }
+
+    fn operand(
+        line: usize,
+        widths: &HashMap<String, usize>,
+        text: &str,
+    ) -> Result<Operand, PulpError> {
+        let Some((name, index)) = text.strip_suffix(']').and_then(|t| t.split_once('[')) else {
+            Self::lookup(line, widths, text)?;
+            return Ok(Operand::Register(text.to_string()));
+        };
+        let width = Self::lookup(line, widths, name)?;
+        match index.parse::<usize>() {
+            Ok(bit) if bit < width => Ok(Operand::Bit(name.to_string(), bit)),
+            _ => Err(PulpError::program(
+                line,
+                format!("bit `{index}` is out of range for {width}-bit register `{name}`"),
+            )),
+        }
+    }
+
+    fn check_operands(
+        line: usize,
+        widths: &HashMap<String, usize>,
+        operands: &[Operand],
+    ) -> Result<(), PulpError> {
+        let whole = operands
+            .iter()
+            .filter(|op| matches!(op, Operand::Register(_)))
+            .count();
+        if whole != 0 && whole != operands.len() {
+            return Err(PulpError::program(
+                line,
+                "operands mix whole registers and single bits",
+            ));
+        }
+        if whole != 0 {
+            let width = widths[operands[0].register()];
+            if let Some(op) = operands.iter().find(|op| widths[op.register()] != width) {
+                return Err(PulpError::program(
+                    line,
+                    format!(
+                        "register `{}` is {} bits wide, expected {width}",
+                        op.register(),
+                        widths[op.register()]
+                    ),
+                ));
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,174 @@
+#![allow(warnings)]
+use super::*; // Imports items from the parent module
+
+#[test]
+fn test_parse_program() {
+    let fn_name = "parse_program";
+    println!("[*] TEST: {fn_name}");
+    let program = Program::parse(
+        "# adds nothing, checks the syntax\n\
+         input a 8\n\
+         reg t 8\n\
+         \n\
+         nor t, a, a   # whole registers\n\
+         xnor t[7], a[0], t[1]\n\
+         not t[2], a[2]\n\
+         output t\n",
+    )
+    .unwrap();
+    assert_eq!(program.instructions.len(), 6);
+    assert_eq!(program.inputs(), vec![("a", 8)]);
+    assert_eq!(program.outputs(), vec!["t"]);
+    assert_eq!(program.instructions[2].line, 5);
+    assert_eq!(
+        program.instructions[3].statement,
+        Statement::Gate {
+            kind: GateKind::Xnor,
+            dst: Operand::Bit("t".to_string(), 7),
+            a: Operand::Bit("a".to_string(), 0),
+            b: Operand::Bit("t".to_string(), 1),
+        }
+    );
+    // `not` is a nand of the operand with itself
+    assert_eq!(
+        program.instructions[4].statement,
+        Statement::Gate {
+            kind: GateKind::Nand,
+            dst: Operand::Bit("t".to_string(), 2),
+            a: Operand::Bit("a".to_string(), 2),
+            b: Operand::Bit("a".to_string(), 2),
+        }
+    );
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+fn test_parse_program_errors() {
+    let fn_name = "parse_program_errors";
+    println!("[*] TEST: {fn_name}");
+    let line_of = |source: &str| Program::parse(source).unwrap_err().line().unwrap();
+    assert_eq!(line_of("input a 8\nadd a, a, a"), 2);
+    assert_eq!(line_of("input a 8\nand a, a, b"), 2);
+    assert_eq!(line_of("input a 8\nreg a 4"), 2);
+    assert_eq!(line_of("input a 200"), 1);
+    assert_eq!(line_of("input 1a 8"), 1);
+    assert_eq!(line_of("input a 8\nand a[8], a[0], a[1]"), 2);
+    assert_eq!(line_of("input a 8\nreg b 4\nor b, a, a"), 3);
+    assert_eq!(line_of("input a 8\nor a[0], a, a"), 2);
+    assert_eq!(line_of("input a 8\nxor a, a"), 2);
+    assert_eq!(line_of("\n\noutput x"), 3);
+    assert_eq!(line_of("input a 8\nmem a 4 8"), 2);
+    assert_eq!(line_of("mem m 0 8"), 1);
+    assert_eq!(line_of("reg t 1\nmem m 4097 8"), 2);
+    assert_eq!(line_of("mem m 18446744073709551615 127"), 1);
+    assert!(Program::parse("mem m 4096 8").is_ok());
+    assert_eq!(line_of("input i 2\ninput a 4\nmem m 4 8\nstore m, i, a"), 4);
+    assert_eq!(line_of("input i 2\nreg t 8\nload t, m, i"), 3);
+    assert_eq!(line_of("input i 2\nmem m 4 8\nload m, i"), 3);
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+fn test_parse_memory() {
+    let fn_name = "parse_memory";
+    println!("[*] TEST: {fn_name}");
+    let program = Program::parse(
+        "input i 2\n\
+         input a 8\n\
+         reg t 8\n\
+         mem table 3 8\n\
+         store table, i, a\n\
+         load t, table, i\n\
+         output t\n",
+    )
+    .unwrap();
+    assert_eq!(program.inputs(), vec![("i", 2), ("a", 8)]);
+    assert_eq!(
+        program.instructions[3].statement,
+        Statement::Mem {
+            name: "table".to_string(),
+            words: 3,
+            width: 8,
+        }
+    );
+    assert_eq!(
+        program.instructions[4].statement,
+        Statement::Store {
+            mem: "table".to_string(),
+            index: "i".to_string(),
+            src: "a".to_string(),
+        }
+    );
+    assert_eq!(
+        program.instructions[5].statement,
+        Statement::Load {
+            dst: "t".to_string(),
+            mem: "table".to_string(),
+            index: "i".to_string(),
+        }
+    );
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+fn test_parse_blocks() {
+    let fn_name = "parse_blocks";
+    println!("[*] TEST: {fn_name}");
+    let program = Program::parse(
+        "input c 1\n\
+         input a 8\n\
+         reg done 1\n\
+         if c {\n\
+             not a, a\n\
+         } else {\n\
+             repeat 3 until done[0] {\n\
+                 xor a, a, a\n\
+             }\n\
+         }\n\
+         if a[7] {\n\
+         }\n",
+    )
+    .unwrap();
+    assert_eq!(program.instructions.len(), 5);
+    assert_eq!(program.instructions[3].line, 4);
+    let Statement::If { cond, then, other } = &program.instructions[3].statement else {
+        panic!("expected an if block");
+    };
+    assert_eq!(*cond, Operand::Register("c".to_string()));
+    assert_eq!(then.len(), 1);
+    assert_eq!(
+        other[0].statement,
+        Statement::Repeat {
+            count: 3,
+            until: Operand::Bit("done".to_string(), 0),
+            body: vec![Instruction {
+                line: 8,
+                statement: Statement::Gate {
+                    kind: GateKind::Xor,
+                    dst: Operand::Register("a".to_string()),
+                    a: Operand::Register("a".to_string()),
+                    b: Operand::Register("a".to_string()),
+                },
+            }],
+        }
+    );
+    assert_eq!(
+        program.instructions[4].statement,
+        Statement::If {
+            cond: Operand::Bit("a".to_string(), 7),
+            then: Vec::new(),
+            other: Vec::new(),
+        }
+    );
+
+    let line_of = |source: &str| Program::parse(source).unwrap_err().line().unwrap();
+    assert_eq!(line_of("input a 8\nif a {\n}"), 2);
+    assert_eq!(line_of("input c 1\nif c {\nreg t 8\n}"), 3);
+    assert_eq!(line_of("input c 1\nif c {\n} else {\n} else {\n}"), 4);
+    assert_eq!(line_of("input c 1\nrepeat 2 until c {\n} else {\n}"), 3);
+    assert_eq!(line_of("input c 1\nrepeat n until c {\n}"), 2);
+    assert_eq!(line_of("input c 1\nif c\n}"), 2);
+    assert_eq!(line_of("input c 1\n}"), 2);
+    assert_eq!(line_of("input c 1\nif c {\nrepeat 2 until c {\n}"), 2);
+    println!("[✓] PASS: {fn_name}\n");
+}
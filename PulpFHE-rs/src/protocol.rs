@@ -0,0 +1,340 @@
+//! # Description
+//! Request/response protocol between a client holding the `ClientKey` and an evaluation
+//! server holding only the `ServerKey`.
+//!
+//! Every message is one frame:
+//!
+//! | bytes | field                                  |
+//! |-------|----------------------------------------|
+//! | 2     | protocol version, little endian        |
+//! | 1     | message kind (`MessageKind`)           |
+//! | 8     | payload length in bytes, little endian |
+//!
+//! followed by the payload. Keys and ciphertexts travel in their file formats
+//! (`KeyManager::write_server_key`, `CiphertextFile::write`), so the server applies the
+//! same version and key checks as for files. A session uploads its server key once and
+//! then sends any number of `Eval` and `Run` requests; each is answered with a result or
+//! an `Error` message carrying the reason.
+
+use crate::ciphertext_file::{CiphertextFile, CiphertextFileError, EncryptedIntegers};
+use crate::evaluator::EvalOptions;
+use crate::key_manager::KeyError;
+use serde::de::DeserializeOwned;
+use std::fmt;
+use std::io::{self, Cursor, Read, Write};
+
+pub const PROTOCOL_VERSION: u16 = 1;
+/// Largest accepted payload; a default server key is about 130 MB
+pub const MAX_PAYLOAD_LEN: u64 = 1 << 30;
+const FRAME_HEADER_LEN: usize = 11;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageKind {
+    /// Client → server: a server key file
+    UploadKey,
+    /// Client → server: an `EvalRequest`
+    Eval,
+    /// Client → server: a `RunRequest`
+    Run,
+    /// Server → client: the id of the accepted key
+    KeyAccepted,
+    /// Server → client: one ciphertext file
+    Result,
+    /// Server → client: named ciphertext files, see `Protocol::encode_outputs`
+    Outputs,
+    /// Server → client: an error message
+    Error,
+}
+
+impl MessageKind {
+    pub fn id(&self) -> u8 {
+        match self {
+            MessageKind::UploadKey => 1,
+            MessageKind::Eval => 2,
+            MessageKind::Run => 3,
+            MessageKind::KeyAccepted => 0x81,
+            MessageKind::Result => 0x82,
+            MessageKind::Outputs => 0x83,
+            MessageKind::Error => 0xff,
+        }
+    }
+
+    pub fn from_id(id: u8) -> Option<MessageKind> {
+        [
+            MessageKind::UploadKey,
+            MessageKind::Eval,
+            MessageKind::Run,
+            MessageKind::KeyAccepted,
+            MessageKind::Result,
+            MessageKind::Outputs,
+            MessageKind::Error,
+        ]
+        .into_iter()
+        .find(|kind| kind.id() == id)
+    }
+}
+
+#[derive(Debug)]
+pub enum ProtocolError {
+    Io(io::Error),
+    UnsupportedVersion {
+        found: u16,
+        expected: u16,
+    },
+    UnknownMessage(u8),
+    /// A message that is not valid at this point of the session
+    UnexpectedMessage(MessageKind),
+    PayloadTooLarge(u64),
+    Encoding(String),
+    Key(KeyError),
+    Ciphertext(CiphertextFileError),
+    /// The peer answered with an `Error` message
+    Remote(String),
+}
+
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtocolError::Io(e) => write!(f, "connection error: {e}"),
+            ProtocolError::UnsupportedVersion { found, expected } => write!(
+                f,
+                "protocol version {found} is not supported (expected {expected})"
+            ),
+            ProtocolError::UnknownMessage(id) => write!(f, "unknown message kind {id:#04x}"),
+            ProtocolError::UnexpectedMessage(kind) => write!(f, "unexpected {kind:?} message"),
+            ProtocolError::PayloadTooLarge(len) => {
+                write!(f, "payload of {len} bytes exceeds {MAX_PAYLOAD_LEN}")
+            }
+            ProtocolError::Encoding(e) => write!(f, "message encoding error: {e}"),
+            ProtocolError::Key(e) => write!(f, "{e}"),
+            ProtocolError::Ciphertext(e) => write!(f, "{e}"),
+            ProtocolError::Remote(msg) => write!(f, "server error: {msg}"),
+        }
+    }
+}
+
+impl std::error::Error for ProtocolError {}
+
+impl From<io::Error> for ProtocolError {
+    fn from(e: io::Error) -> Self {
+        ProtocolError::Io(e)
+    }
+}
+
+impl From<bincode::Error> for ProtocolError {
+    fn from(e: bincode::Error) -> Self {
+        ProtocolError::Encoding(e.to_string())
+    }
+}
+
+impl From<KeyError> for ProtocolError {
+    fn from(e: KeyError) -> Self {
+        ProtocolError::Key(e)
+    }
+}
+
+impl From<CiphertextFileError> for ProtocolError {
+    fn from(e: CiphertextFileError) -> Self {
+        ProtocolError::Ciphertext(e)
+    }
+}
+
+/// One operation of `Evaluator` and its operands.
+#[derive(Clone, Debug)]
+pub struct EvalRequest {
+    /// Operation name as accepted by `Operation::from_str`
+    pub op: String,
+    pub options: EvalOptions,
+    pub inputs: Vec<EncryptedIntegers>,
+}
+
+/// A `Program` source and one integer per `input` register.
+#[derive(Clone, Debug)]
+pub struct RunRequest {
+    pub program: String,
+    pub inputs: Vec<(String, EncryptedIntegers)>,
+    /// Whether the outputs are returned as signed integers
+    pub signed: bool,
+}
+
+pub struct Protocol;
+
+impl Protocol {
+    pub fn write_frame<W: Write>(
+        mut writer: W,
+        kind: MessageKind,
+        payload: &[u8],
+    ) -> Result<(), ProtocolError> {
+        let mut header = [0u8; FRAME_HEADER_LEN];
+        header[..2].copy_from_slice(&PROTOCOL_VERSION.to_le_bytes());
+        header[2] = kind.id();
+        header[3..].copy_from_slice(&(payload.len() as u64).to_le_bytes());
+        writer.write_all(&header)?;
+        writer.write_all(payload)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Reads the next frame; `None` when the peer closed the connection between frames.
+    pub fn read_frame<R: Read>(
+        mut reader: R,
+    ) -> Result<Option<(MessageKind, Vec<u8>)>, ProtocolError> {
+        let mut header = [0u8; FRAME_HEADER_LEN];
+        let mut filled = 0;
+        while filled < FRAME_HEADER_LEN {
+            match reader.read(&mut header[filled..])? {
+                0 if filled == 0 => return Ok(None),
+                0 => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+                n => filled += n,
+            }
+        }
+        let version = u16::from_le_bytes([header[0], header[1]]);
+        if version != PROTOCOL_VERSION {
+            return Err(ProtocolError::UnsupportedVersion {
+                found: version,
+                expected: PROTOCOL_VERSION,
+            });
+        }
+        let kind =
+            MessageKind::from_id(header[2]).ok_or(ProtocolError::UnknownMessage(header[2]))?;
+        let len = u64::from_le_bytes(header[3..].try_into().unwrap());
+        if len > MAX_PAYLOAD_LEN {
+            return Err(ProtocolError::PayloadTooLarge(len));
+        }
+        let mut payload = vec![0u8; len as usize];
+        reader.read_exact(&mut payload)?;
+        Ok(Some((kind, payload)))
+    }
+
+    pub fn encode_eval(
+        op: &str,
+        options: &EvalOptions,
+        inputs: &[EncryptedIntegers],
+    ) -> Result<Vec<u8>, ProtocolError> {
+        let mut payload = Vec::new();
+        bincode::serialize_into(&mut payload, &(op, options))?;
+        Self::encode_integers(&mut payload, inputs.iter())?;
+        Ok(payload)
+    }
+
+    pub fn decode_eval(payload: &[u8]) -> Result<EvalRequest, ProtocolError> {
+        let mut cursor = Cursor::new(payload);
+        let (op, options): (String, EvalOptions) = Self::decode_meta(&mut cursor)?;
+        let inputs = Self::decode_integers(&mut cursor)?;
+        Self::expect_end(&cursor)?;
+        Ok(EvalRequest {
+            op,
+            options,
+            inputs,
+        })
+    }
+
+    pub fn encode_run(
+        program: &str,
+        inputs: &[(&str, &EncryptedIntegers)],
+        signed: bool,
+    ) -> Result<Vec<u8>, ProtocolError> {
+        let mut payload = Vec::new();
+        let names: Vec<&str> = inputs.iter().map(|(name, _)| *name).collect();
+        bincode::serialize_into(&mut payload, &(program, signed, names))?;
+        Self::encode_integers(&mut payload, inputs.iter().map(|(_, v)| *v))?;
+        Ok(payload)
+    }
+
+    pub fn decode_run(payload: &[u8]) -> Result<RunRequest, ProtocolError> {
+        let mut cursor = Cursor::new(payload);
+        let (program, signed, names): (String, bool, Vec<String>) = Self::decode_meta(&mut cursor)?;
+        let values = Self::decode_integers(&mut cursor)?;
+        Self::expect_end(&cursor)?;
+        if names.len() != values.len() {
+            return Err(ProtocolError::Encoding(format!(
+                "{} input names for {} inputs",
+                names.len(),
+                values.len()
+            )));
+        }
+        Ok(RunRequest {
+            program,
+            inputs: names.into_iter().zip(values).collect(),
+            signed,
+        })
+    }
+
+    /// Named results of a `Run` request, in program order.
+    pub fn encode_outputs(
+        outputs: &[(String, EncryptedIntegers)],
+    ) -> Result<Vec<u8>, ProtocolError> {
+        let mut payload = Vec::new();
+        let names: Vec<&String> = outputs.iter().map(|(name, _)| name).collect();
+        bincode::serialize_into(&mut payload, &names)?;
+        Self::encode_integers(&mut payload, outputs.iter().map(|(_, v)| v))?;
+        Ok(payload)
+    }
+
+    pub fn decode_outputs(
+        payload: &[u8],
+    ) -> Result<Vec<(String, EncryptedIntegers)>, ProtocolError> {
+        let mut cursor = Cursor::new(payload);
+        let names: Vec<String> = Self::decode_meta(&mut cursor)?;
+        let values = Self::decode_integers(&mut cursor)?;
+        Self::expect_end(&cursor)?;
+        if names.len() != values.len() {
+            return Err(ProtocolError::Encoding(format!(
+                "{} output names for {} outputs",
+                names.len(),
+                values.len()
+            )));
+        }
+        Ok(names.into_iter().zip(values).collect())
+    }
+
+    pub fn encode_error(message: &str) -> Result<Vec<u8>, ProtocolError> {
+        Ok(bincode::serialize(message)?)
+    }
+
+    pub fn decode_error(payload: &[u8]) -> Result<String, ProtocolError> {
+        Ok(bincode::deserialize(payload)?)
+    }
+
+    fn decode_meta<T: DeserializeOwned>(cursor: &mut Cursor<&[u8]>) -> Result<T, ProtocolError> {
+        Ok(bincode::deserialize_from(cursor)?)
+    }
+
+    /// A count followed by one ciphertext file per entry
+    fn encode_integers<'a, I: ExactSizeIterator<Item = &'a EncryptedIntegers>>(
+        payload: &mut Vec<u8>,
+        integers: I,
+    ) -> Result<(), ProtocolError> {
+        Self::serialize_count(payload, integers.len())?;
+        for value in integers {
+            CiphertextFile::write(&mut *payload, value)?;
+        }
+        Ok(())
+    }
+
+    fn decode_integers(
+        cursor: &mut Cursor<&[u8]>,
+    ) -> Result<Vec<EncryptedIntegers>, ProtocolError> {
+        let count: u32 = bincode::deserialize_from(&mut *cursor)?;
+        (0..count)
+            .map(|_| Ok(CiphertextFile::read(&mut *cursor)?))
+            .collect()
+    }
+
+    fn serialize_count(payload: &mut Vec<u8>, count: usize) -> Result<(), ProtocolError> {
+        let count = u32::try_from(count)
+            .map_err(|_| ProtocolError::Encoding(format!("{count} operands is too many")))?;
+        bincode::serialize_into(payload, &count)?;
+        Ok(())
+    }
+
+    fn expect_end(cursor: &Cursor<&[u8]>) -> Result<(), ProtocolError> {
+        let rest = cursor.get_ref().len() as u64 - cursor.position();
+        if rest != 0 {
+            return Err(ProtocolError::Encoding(format!(
+                "{rest} trailing bytes after the message"
+            )));
+        }
+        Ok(())
+    }
+}
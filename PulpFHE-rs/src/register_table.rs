@@ -1,4 +1,15 @@
+//! # Description
+//! The register table of the `Controller`, with register renaming and in-order commit.
+//!
+//! Every write is renamed to a tag and held in a `ReorderBuffer` until it commits, so the
+//! table has two views: the speculative state, the newest value of every register, which
+//! is what instructions read; and the architectural state, the register file as of the
+//! last committed write. The register status maps each register with a write in flight to
+//! the tag of its newest write.
+
+use crate::reorder_buffer::{ReorderBuffer, RobEntry};
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::rc::Weak;
 use tfhe::boolean::prelude::*;
@@ -42,100 +53,176 @@ pub trait Subscriber {
 
 // Publisher struct implementing the Observer pattern
 pub struct RegisterTable {
-    reg_elements: Vec<RegisterElement>,
+    reg_elements: Vec<RegisterElement>, // architectural register file
+    status: HashMap<String, usize>,     // register status: tag of the newest in-flight write
+    rob: ReorderBuffer,
     subscribers: Vec<Weak<RefCell<dyn Subscriber>>>,
 }
 
 impl RegisterTable {
+    /// Number of in-flight writes from which issuing a write first commits the complete
+    /// ones, which keeps the buffer bounded over long programs
+    pub const ROB_CAPACITY: usize = 64;
+
     // Create a new Publisher
     pub fn new() -> Self {
         RegisterTable {
             reg_elements: Vec::new(),
+            status: HashMap::new(),
+            rob: ReorderBuffer::new(),
             subscribers: Vec::new(),
         }
     }
 
-    // Add a new object
+    // Add a new object; the write is renamed and commits later
     pub fn add_element(&mut self, element: RegisterElement) {
         let dst = element.dst.clone();
-        self.reg_elements.push(element);
-        self.notify(1, self.reg_elements.len() - 1, dst);
+        let tag = self.rename(element);
+        self.notify(1, tag, dst);
     }
 
-    // Remove an object by index
+    // Remove an object by index, with its in-flight writes
     pub fn remove_element_indx(&mut self, index: usize) -> bool {
         if index >= self.reg_elements.len() {
             return false;
         }
-        self.reg_elements.remove(index);
+        let removed = self.reg_elements.remove(index);
+        self.rob.squash(&removed.dst);
+        self.status.remove(&removed.dst);
         self.notify(2, index, "-".parse().unwrap());
         true
     }
 
-    // Update an object at a specific index
-    pub fn update_element_indx(&mut self, index: usize, element: RegisterElement) -> bool {
+    // Update the register at a specific index of the architectural register file
+    pub fn update_element_indx(&mut self, index: usize, mut element: RegisterElement) -> bool {
         if index >= self.reg_elements.len() {
             return false;
         }
+        element.dst = self.reg_elements[index].dst.clone();
         let dst = element.dst.clone();
-        self.reg_elements[index] = element;
-        self.notify(3, index, dst);
+        let tag = self.rename(element);
+        self.notify(3, tag, dst);
         true
     }
 
+    // Remove a register, committed or not, with its in-flight writes
     pub fn remove_element_dst(&mut self, dst: String) -> bool {
-        for (i, e) in self.reg_elements.iter().enumerate() {
-            if e.dst == dst {
-                self.reg_elements.remove(i);
-                self.notify(4, i, dst);
-                return true;
-            }
+        let in_flight = self.status.remove(&dst).is_some();
+        self.rob.squash(&dst);
+        if let Some(i) = self.reg_elements.iter().position(|e| e.dst == dst) {
+            self.reg_elements.remove(i);
+            self.notify(4, i, dst);
+            return true;
         }
-        false
+        in_flight
     }
 
-    // Update an object at a specific destination
+    // Update an existing register at a specific destination
     pub fn update_element_dst(&mut self, element: RegisterElement) -> bool {
         let current_dst: String = element.dst.clone();
-        for (i, e) in self.reg_elements.iter().enumerate() {
-            if e.dst == current_dst {
-                self.reg_elements[i].dst = element.dst.clone();
-                self.reg_elements[i].vec_ctxt = element.vec_ctxt.clone();
-                self.reg_elements[i].bootstrap = element.bootstrap.clone();
-                self.reg_elements[i].priority = element.priority.clone();
-                self.notify(5, i, current_dst);
-                return true;
+        if self.get_element_dst(current_dst.clone()).is_none() {
+            return false;
+        }
+        let tag = self.rename(element);
+        self.notify(5, tag, current_dst);
+        true
+    }
+
+    /// Completes the in-flight write `tag` with `value`; it commits once every older
+    /// write has. Returns false if `tag` is not in flight.
+    pub fn complete(&mut self, tag: usize, value: Vec<Ciphertext>) -> bool {
+        match self.rob.get_mut(tag) {
+            Some(entry) => {
+                entry.element.waiting_for = "-".to_string();
+                entry.element.vec_ctxt = Some(value);
+                true
             }
+            None => false,
         }
-        false
     }
 
-    // Get object at index
+    // Get object at index of the architectural register file
     pub fn get_element_indx(&self, index: usize) -> Option<&RegisterElement> {
         self.reg_elements.get(index)
     }
+
+    /// The speculative value of `dst`: its newest in-flight write, else its committed value.
     pub fn get_element_dst(&self, dst: String) -> Option<&RegisterElement> {
-        for (i, e) in self.reg_elements.iter().enumerate() {
-            if e.dst == dst {
-                return Some(&self.reg_elements[i]);
-            }
+        match self.status.get(&dst) {
+            Some(&tag) => self.rob.get(tag).map(|entry| &entry.element),
+            None => self.get_architectural_dst(&dst),
         }
-        None
     }
 
-    // Get all objects
+    /// The committed value of `dst`.
+    pub fn get_architectural_dst(&self, dst: &str) -> Option<&RegisterElement> {
+        self.reg_elements.iter().find(|e| e.dst == dst)
+    }
+
+    /// The tag of the newest in-flight write to `dst`, if any.
+    pub fn get_tag(&self, dst: &str) -> Option<usize> {
+        self.status.get(dst).copied()
+    }
+
+    // Get all objects of the architectural register file
     pub fn get_all_elements(&self) -> &[RegisterElement] {
         &self.reg_elements
     }
 
+    pub fn get_rob(&self) -> &ReorderBuffer {
+        &self.rob
+    }
+
+    /// Commits the complete writes at the head of the reorder buffer, in program order, and
+    /// returns how many were committed.
+    pub fn commit(&mut self) -> usize {
+        let retired: Vec<RobEntry> = self.rob.retire();
+        let count = retired.len();
+        for entry in retired {
+            let dst = entry.element.dst.clone();
+            if self.status.get(&dst) == Some(&entry.tag) {
+                self.status.remove(&dst);
+            }
+            match self.reg_elements.iter().position(|e| e.dst == dst) {
+                Some(i) => self.reg_elements[i] = entry.element,
+                None => self.reg_elements.push(entry.element),
+            }
+        }
+        count
+    }
+
+    fn rename(&mut self, element: RegisterElement) -> usize {
+        if self.rob.len() >= Self::ROB_CAPACITY {
+            self.commit();
+        }
+        let dst = element.dst.clone();
+        let tag = self.rob.push(element);
+        self.status.insert(dst, tag);
+        tag
+    }
+
     pub fn print_register_table(&self) {
-        println!("Register Table:");
+        println!("Register Table (architectural):");
         println!("=============================================");
-        println!("|Index || dst | waiting_for | ctxt | bootstrap? | priority|");
+        println!("|Index || dst | waiting_for | ctxt | bootstrap? | priority | tag|");
         for (i, e) in self.reg_elements.iter().enumerate() {
+            let tag = self
+                .status
+                .get(&e.dst)
+                .map_or("-".to_string(), |t| format!("#{t}"));
+            println!(
+                "|{} || {:<4} | {:<4} | {:?} | {:<4} | {:<4} | {:<4}|",
+                i, e.dst, e.waiting_for, e.vec_ctxt, e.bootstrap, e.priority, tag
+            );
+        }
+        println!("Reorder Buffer (speculative, oldest first):");
+        println!("|tag || dst | waiting_for | ctxt | newest?|");
+        for entry in self.rob.entries() {
+            let e = &entry.element;
+            let newest = self.status.get(&e.dst) == Some(&entry.tag);
             println!(
-                "|{} || {:<4} | {:<4} | {:?} | {:<4} | {:<4}|",
-                i, e.dst, e.waiting_for, e.vec_ctxt, e.bootstrap, e.priority
+                "|#{} || {:<4} | {:<4} | {:?} | {}|",
+                entry.tag, e.dst, e.waiting_for, e.vec_ctxt, newest
             );
         }
         println!("=============================================");
@@ -0,0 +1,84 @@
+//! # Description
+//! The reorder buffer of the `RegisterTable`: register writes held in program order until
+//! they commit to the architectural register file.
+//!
+//! As in Tomasulo's algorithm with a reorder buffer, every write is renamed to a tag, its
+//! entry number. A register's newest tag names its speculative value, so repeated writes
+//! to one register (WAW) and reads that precede a later write (WAR) refer to different
+//! tags instead of overwriting each other. Entries retire from the head only, once
+//! complete, so the architectural state is always the result of a prefix of the program.
+
+use crate::register_table::RegisterElement;
+use std::collections::VecDeque;
+
+#[cfg(test)]
+mod test_reorder_buffer;
+
+#[derive(Clone, Debug)]
+pub struct RobEntry {
+    pub tag: usize,
+    /// The value written; `dst` is the architectural register
+    pub element: RegisterElement,
+}
+
+impl RobEntry {
+    /// A write is complete once it no longer waits for a reservation station.
+    pub fn is_complete(&self) -> bool {
+        self.element.waiting_for == "-"
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ReorderBuffer {
+    entries: VecDeque<RobEntry>,
+    next_tag: usize,
+}
+
+impl ReorderBuffer {
+    pub fn new() -> Self {
+        ReorderBuffer::default()
+    }
+
+    /// Appends a write at the tail and returns its tag. Tags are never reused.
+    pub fn push(&mut self, element: RegisterElement) -> usize {
+        let tag = self.next_tag;
+        self.next_tag += 1;
+        self.entries.push_back(RobEntry { tag, element });
+        tag
+    }
+
+    pub fn get(&self, tag: usize) -> Option<&RobEntry> {
+        let i = self.entries.binary_search_by_key(&tag, |e| e.tag).ok()?;
+        self.entries.get(i)
+    }
+
+    pub fn get_mut(&mut self, tag: usize) -> Option<&mut RobEntry> {
+        let i = self.entries.binary_search_by_key(&tag, |e| e.tag).ok()?;
+        self.entries.get_mut(i)
+    }
+
+    /// Removes the complete entries at the head, up to the first incomplete one, and
+    /// returns them in program order.
+    pub fn retire(&mut self) -> Vec<RobEntry> {
+        let count = self.entries.iter().take_while(|e| e.is_complete()).count();
+        self.entries.drain(..count).collect()
+    }
+
+    /// Drops every in-flight write to `dst`.
+    pub fn squash(&mut self, dst: &str) {
+        self.entries.retain(|e| e.element.dst != dst);
+    }
+
+    /// The in-flight writes, oldest first.
+    pub fn entries(&self) -> &VecDeque<RobEntry> {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
@@ -0,0 +1,94 @@
+#![allow(warnings)]
+use super::*; // Imports items from the parent module
+use crate::register_table::RegisterTable;
+use tfhe::boolean::prelude::*;
+
+fn element(dst: &str, value: bool, waiting_for: &str) -> RegisterElement {
+    RegisterElement::new(
+        dst.to_string(),
+        waiting_for.to_string(),
+        Some(vec![Ciphertext::Trivial(value)]),
+        -2,
+        1,
+    )
+}
+
+fn value(element: &RegisterElement) -> bool {
+    matches!(
+        element.vec_ctxt.as_deref(),
+        Some([Ciphertext::Trivial(true)])
+    )
+}
+
+#[test]
+fn test_reorder_buffer() {
+    let fn_name = "reorder_buffer";
+    println!("[*] TEST: {fn_name}");
+    let mut rob = ReorderBuffer::new();
+    assert_eq!(rob.push(element("a", true, "-")), 0);
+    assert_eq!(rob.push(element("b", true, "AndRs")), 1);
+    assert_eq!(rob.push(element("a", false, "-")), 2);
+
+    // Retirement stops at the first incomplete write
+    let retired = rob.retire();
+    assert_eq!(retired.len(), 1);
+    assert_eq!(retired[0].tag, 0);
+    assert!(rob.retire().is_empty());
+    rob.get_mut(1).unwrap().element.waiting_for = "-".to_string();
+    let tags: Vec<usize> = rob.retire().iter().map(|e| e.tag).collect();
+    assert_eq!(tags, vec![1, 2]);
+
+    // Tags are not reused
+    assert_eq!(rob.push(element("c", true, "-")), 3);
+    rob.squash("c");
+    assert!(rob.is_empty());
+    assert!(rob.get(3).is_none());
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+fn test_register_renaming() {
+    let fn_name = "register_renaming";
+    println!("[*] TEST: {fn_name}");
+    let mut table = RegisterTable::new();
+    table.add_element(element("x", true, "-"));
+    assert_eq!(table.commit(), 1);
+
+    // Two writes to x (WAW) get their own tags; reads see the newest
+    assert!(table.update_element_dst(element("x", false, "-")));
+    assert!(table.update_element_dst(element("x", true, "-")));
+    table.add_element(element("y", false, "-"));
+    assert_eq!(table.get_tag("x"), Some(2));
+    assert!(value(table.get_element_dst("x".to_string()).unwrap()));
+    assert!(!value(table.get_element_dst("y".to_string()).unwrap()));
+    // The architectural state is unchanged until the writes commit
+    assert!(value(table.get_architectural_dst("x").unwrap()));
+    assert!(table.get_architectural_dst("y").is_none());
+    assert_eq!(table.get_all_elements().len(), 1);
+    table.print_register_table();
+
+    assert_eq!(table.commit(), 3);
+    assert_eq!(table.get_tag("x"), None);
+    assert!(value(table.get_architectural_dst("x").unwrap()));
+    assert!(table.get_architectural_dst("y").is_some());
+    assert!(table.get_rob().is_empty());
+
+    // A write by index goes to the register at that index, whatever `dst` it names
+    assert!(table.update_element_indx(0, element("w", false, "AndRs")));
+    let tag = table.get_tag("x").unwrap();
+    assert_eq!(table.get_tag("w"), None);
+    assert_eq!(table.commit(), 0);
+    assert!(table.complete(tag, vec![Ciphertext::Trivial(false)]));
+    assert!(!table.complete(tag + 1, vec![Ciphertext::Trivial(false)]));
+    assert_eq!(table.commit(), 1);
+    assert!(!value(table.get_architectural_dst("x").unwrap()));
+    assert!(table.get_architectural_dst("w").is_none());
+
+    // Writes to unknown registers are refused, removal drops in-flight writes
+    assert!(!table.update_element_dst(element("z", true, "-")));
+    table.add_element(element("z", true, "-"));
+    assert!(table.remove_element_dst("z".to_string()));
+    assert!(table.get_element_dst("z".to_string()).is_none());
+    assert!(table.get_rob().is_empty());
+    println!("[✓] PASS: {fn_name}\n");
+}
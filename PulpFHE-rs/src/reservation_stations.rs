@@ -3,21 +3,21 @@ use tfhe::boolean::prelude::*;
 
 // Shape trait that extends Subscriber
 pub struct RsEntry {
-    id: String,
-    busy: bool,
-    rs1: String,
-    rs2: String,
-    val1: Option<Vec<Ciphertext>>,
-    val2: Option<Vec<Ciphertext>>,
-    dst: String,
+    pub id: String,
+    pub busy: bool,
+    pub rs1: Option<usize>, // tag of the write the first operand waits for
+    pub rs2: Option<usize>, // tag of the write the second operand waits for
+    pub val1: Option<Vec<Ciphertext>>,
+    pub val2: Option<Vec<Ciphertext>>,
+    pub dst: String,
 }
 
 impl RsEntry {
     pub fn new(
         id: String,
-        busy: bool,       
-        rs1: String,
-        rs2: String,
+        busy: bool,
+        rs1: Option<usize>,
+        rs2: Option<usize>,
         val1: Option<Vec<Ciphertext>>,
         val2: Option<Vec<Ciphertext>>,
         dst: String,
@@ -32,6 +32,18 @@ impl RsEntry {
             dst,
         }
     }
+
+    /// Captures `value` for the operands waiting for the write `tag`.
+    pub fn wakeup(&mut self, tag: usize, value: &[Ciphertext]) {
+        if self.rs1 == Some(tag) {
+            self.rs1 = None;
+            self.val1 = Some(value.to_vec());
+        }
+        if self.rs2 == Some(tag) {
+            self.rs2 = None;
+            self.val2 = Some(value.to_vec());
+        }
+    }
 }
 pub struct AndRs {
     entries: Vec<RsEntry>,
@@ -45,8 +57,8 @@ impl AndRs {
     pub fn add_entry(
         &mut self,
         id: String,
-        rs1: String,
-        rs2: String,
+        rs1: Option<usize>,
+        rs2: Option<usize>,
         val1: Option<Vec<Ciphertext>>,
         val2: Option<Vec<Ciphertext>>,
         dst: String,
@@ -54,6 +66,17 @@ impl AndRs {
         let entry = RsEntry::new(id, true, rs1, rs2, val1, val2, dst);
         self.entries.push(entry);
     }
+
+    pub fn entries(&self) -> &[RsEntry] {
+        &self.entries
+    }
+
+    /// Wakes the entries waiting for the write `tag` with its value.
+    pub fn wakeup(&mut self, tag: usize, value: &[Ciphertext]) {
+        for entry in self.entries.iter_mut() {
+            entry.wakeup(tag, value);
+        }
+    }
 }
 pub struct OrRs {
     entries: Vec<RsEntry>,
@@ -68,8 +91,8 @@ impl OrRs {
     pub fn add_entry(
         &mut self,
         id: String,
-        rs1: String,
-        rs2: String,
+        rs1: Option<usize>,
+        rs2: Option<usize>,
         val1: Option<Vec<Ciphertext>>,
         val2: Option<Vec<Ciphertext>>,
         dst: String,
@@ -77,6 +100,17 @@ impl OrRs {
         let entry = RsEntry::new(id, true, rs1, rs2, val1, val2, dst);
         self.entries.push(entry);
     }
+
+    pub fn entries(&self) -> &[RsEntry] {
+        &self.entries
+    }
+
+    /// Wakes the entries waiting for the write `tag` with its value.
+    pub fn wakeup(&mut self, tag: usize, value: &[Ciphertext]) {
+        for entry in self.entries.iter_mut() {
+            entry.wakeup(tag, value);
+        }
+    }
 }
 pub struct XorRs {
     entries: Vec<RsEntry>,
@@ -91,8 +125,8 @@ impl XorRs {
     pub fn add_entry(
         &mut self,
         id: String,
-        rs1: String,
-        rs2: String,
+        rs1: Option<usize>,
+        rs2: Option<usize>,
         val1: Option<Vec<Ciphertext>>,
         val2: Option<Vec<Ciphertext>>,
         dst: String,
@@ -100,6 +134,17 @@ impl XorRs {
         let entry = RsEntry::new(id, true, rs1, rs2, val1, val2, dst);
         self.entries.push(entry);
     }
+
+    pub fn entries(&self) -> &[RsEntry] {
+        &self.entries
+    }
+
+    /// Wakes the entries waiting for the write `tag` with its value.
+    pub fn wakeup(&mut self, tag: usize, value: &[Ciphertext]) {
+        for entry in self.entries.iter_mut() {
+            entry.wakeup(tag, value);
+        }
+    }
 }
 pub struct NandRs {
     entries: Vec<RsEntry>,
@@ -113,8 +158,8 @@ impl NandRs {
     pub fn add_entry(
         &mut self,
         id: String,
-        rs1: String,
-        rs2: String,
+        rs1: Option<usize>,
+        rs2: Option<usize>,
         val1: Option<Vec<Ciphertext>>,
         val2: Option<Vec<Ciphertext>>,
         dst: String,
@@ -122,6 +167,17 @@ impl NandRs {
         let entry = RsEntry::new(id, true, rs1, rs2, val1, val2, dst);
         self.entries.push(entry);
     }
+
+    pub fn entries(&self) -> &[RsEntry] {
+        &self.entries
+    }
+
+    /// Wakes the entries waiting for the write `tag` with its value.
+    pub fn wakeup(&mut self, tag: usize, value: &[Ciphertext]) {
+        for entry in self.entries.iter_mut() {
+            entry.wakeup(tag, value);
+        }
+    }
 }
 pub struct NorRs {
     entries: Vec<RsEntry>,
@@ -136,8 +192,8 @@ impl NorRs {
     pub fn add_entry(
         &mut self,
         id: String,
-        rs1: String,
-        rs2: String,
+        rs1: Option<usize>,
+        rs2: Option<usize>,
         val1: Option<Vec<Ciphertext>>,
         val2: Option<Vec<Ciphertext>>,
         dst: String,
@@ -145,6 +201,17 @@ impl NorRs {
         let entry = RsEntry::new(id, true, rs1, rs2, val1, val2, dst);
         self.entries.push(entry);
     }
+
+    pub fn entries(&self) -> &[RsEntry] {
+        &self.entries
+    }
+
+    /// Wakes the entries waiting for the write `tag` with its value.
+    pub fn wakeup(&mut self, tag: usize, value: &[Ciphertext]) {
+        for entry in self.entries.iter_mut() {
+            entry.wakeup(tag, value);
+        }
+    }
 }
 
 pub struct XnorRs {
@@ -160,8 +227,8 @@ impl XnorRs {
     pub fn add_entry(
         &mut self,
         id: String,
-        rs1: String,
-        rs2: String,
+        rs1: Option<usize>,
+        rs2: Option<usize>,
         val1: Option<Vec<Ciphertext>>,
         val2: Option<Vec<Ciphertext>>,
         dst: String,
@@ -169,6 +236,17 @@ impl XnorRs {
         let entry = RsEntry::new(id, true, rs1, rs2, val1, val2, dst);
         self.entries.push(entry);
     }
+
+    pub fn entries(&self) -> &[RsEntry] {
+        &self.entries
+    }
+
+    /// Wakes the entries waiting for the write `tag` with its value.
+    pub fn wakeup(&mut self, tag: usize, value: &[Ciphertext]) {
+        for entry in self.entries.iter_mut() {
+            entry.wakeup(tag, value);
+        }
+    }
 }
 impl Subscriber for AndRs {
     fn decode(&self, code: u8, index: usize, dst: String) {
@@ -0,0 +1,468 @@
+//! # Description
+//! An emulator for a subset of the RV32I base integer instruction set, in which the register
+//! file and the data memory are encrypted and the program is plaintext.
+//!
+//! Supported instructions:
+//!
+//! * arithmetic and logic: `add`, `sub`, `xor`, `or`, `and` and their immediate forms;
+//! * shifts: `sll`, `srl`, `sra` (by the low five bits of an encrypted register) and
+//!   `slli`, `srli`, `srai`;
+//! * compare-set: `slt`, `sltu`, `slti`, `sltiu`;
+//! * constants: `lui`, `auipc`;
+//! * data memory: `lw`, `sw`;
+//! * calls and jumps: `jal` and `ret` (`jalr x0, 0(ra)`);
+//! * `ecall` and `ebreak`, which stop the program.
+//!
+//! The control flow of a program cannot depend on encrypted data, so branches, the other
+//! forms of `jalr` and the remaining loads and stores are rejected. The program counter is
+//! plaintext: `jal` jumps to a fixed target and, when it links `ra`, records the return
+//! address on a plaintext call stack, which `ret` returns to. The encrypted `ra` is written
+//! as usual but never read for control flow. Execution starts at the entry point and stops
+//! at `ecall`/`ebreak`, at a `ret` with no call to return from, or at the end of the text.
+//!
+//! The data memory is an `ObliviousMemory` of 32-bit words in its own address space,
+//! starting at address 0. Only the address bits that index a word are decoded: the two low
+//! bits are ignored and addresses alias modulo the memory size rounded up to a power of two
+//! words; words past the end read as zero and ignore stores.
+//!
+//! Programs are loaded as raw little-endian binaries or from the executable segment of a
+//! 32-bit little-endian RISC-V ELF file, as produced by a standard RV32I toolchain.
+
+use crate::error::PulpError;
+use crate::oblivious_memory::ObliviousMemory;
+use crate::processor_circuits::ProcessorCircuits;
+use crate::processor_gates::ProcessorGate;
+use tfhe::boolean::prelude::*;
+
+#[cfg(test)]
+mod test_rv32i;
+
+/// Register width in bits
+pub const XLEN: usize = 32;
+/// Number of integer registers, `x0` (hardwired to zero) to `x31`
+pub const REGISTERS: usize = 32;
+
+/// Register-register and register-immediate operations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AluOp {
+    Add,
+    Sub,
+    Sll,
+    Slt,
+    Sltu,
+    Xor,
+    Srl,
+    Sra,
+    Or,
+    And,
+}
+
+/// A decoded instruction; register fields are indices `0..32`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    /// `rd = imm`, the upper 20 bits already in place
+    Lui { rd: usize, imm: u32 },
+    /// `rd = pc + imm`
+    Auipc { rd: usize, imm: u32 },
+    /// `rd = rs1 op rs2`
+    Op {
+        op: AluOp,
+        rd: usize,
+        rs1: usize,
+        rs2: usize,
+    },
+    /// `rd = rs1 op imm`; for shifts `imm` is the shift amount
+    OpImm {
+        op: AluOp,
+        rd: usize,
+        rs1: usize,
+        imm: i32,
+    },
+    /// `rd = memory[rs1 + offset]`
+    Lw { rd: usize, rs1: usize, offset: i32 },
+    /// `memory[rs1 + offset] = rs2`
+    Sw { rs1: usize, rs2: usize, offset: i32 },
+    /// `rd = pc + 4`, then jump to `pc + offset`
+    Jal { rd: usize, offset: i32 },
+    /// `jalr x0, 0(ra)`: return to the caller
+    Ret,
+    /// `ecall` or `ebreak`
+    Halt,
+}
+
+impl Instruction {
+    /// Decodes a 32-bit instruction word; `pc` is only used in error messages.
+    pub fn decode(word: u32, pc: u32) -> Result<Self, PulpError> {
+        let unsupported = || {
+            PulpError::invalid(
+                "rv32i",
+                format!("unsupported instruction {word:#010x} at {pc:#x}"),
+            )
+        };
+        let rd: usize = ((word >> 7) & 0x1f) as usize;
+        let funct3: u32 = (word >> 12) & 0x7;
+        let rs1: usize = ((word >> 15) & 0x1f) as usize;
+        let rs2: usize = ((word >> 20) & 0x1f) as usize;
+        let funct7: u32 = word >> 25;
+        let imm_i: i32 = (word as i32) >> 20;
+        let imm_s: i32 = (((word as i32) >> 25) << 5) | rd as i32;
+        let imm_j: i32 = (((word as i32) >> 31) << 20)
+            | (word & 0x000f_f000) as i32
+            | ((word >> 9) & 0x800) as i32
+            | ((word >> 20) & 0x7fe) as i32;
+
+        let instruction = match word & 0x7f {
+            0x37 => Instruction::Lui {
+                rd,
+                imm: word & 0xffff_f000,
+            },
+            0x17 => Instruction::Auipc {
+                rd,
+                imm: word & 0xffff_f000,
+            },
+            0x13 => {
+                let (op, imm) = match (funct3, funct7) {
+                    (0, _) => (AluOp::Add, imm_i),
+                    (2, _) => (AluOp::Slt, imm_i),
+                    (3, _) => (AluOp::Sltu, imm_i),
+                    (4, _) => (AluOp::Xor, imm_i),
+                    (6, _) => (AluOp::Or, imm_i),
+                    (7, _) => (AluOp::And, imm_i),
+                    (1, 0x00) => (AluOp::Sll, rs2 as i32),
+                    (5, 0x00) => (AluOp::Srl, rs2 as i32),
+                    (5, 0x20) => (AluOp::Sra, rs2 as i32),
+                    _ => return Err(unsupported()),
+                };
+                Instruction::OpImm { op, rd, rs1, imm }
+            }
+            0x33 => {
+                let op = match (funct3, funct7) {
+                    (0, 0x00) => AluOp::Add,
+                    (0, 0x20) => AluOp::Sub,
+                    (1, 0x00) => AluOp::Sll,
+                    (2, 0x00) => AluOp::Slt,
+                    (3, 0x00) => AluOp::Sltu,
+                    (4, 0x00) => AluOp::Xor,
+                    (5, 0x00) => AluOp::Srl,
+                    (5, 0x20) => AluOp::Sra,
+                    (6, 0x00) => AluOp::Or,
+                    (7, 0x00) => AluOp::And,
+                    _ => return Err(unsupported()),
+                };
+                Instruction::Op { op, rd, rs1, rs2 }
+            }
+            0x03 if funct3 == 2 => Instruction::Lw {
+                rd,
+                rs1,
+                offset: imm_i,
+            },
+            0x23 if funct3 == 2 => Instruction::Sw {
+                rs1,
+                rs2,
+                offset: imm_s,
+            },
+            0x6f => Instruction::Jal { rd, offset: imm_j },
+            0x67 if word == 0x0000_8067 => Instruction::Ret,
+            0x73 if word == 0x0000_0073 || word == 0x0010_0073 => Instruction::Halt,
+            _ => return Err(unsupported()),
+        };
+        Ok(instruction)
+    }
+}
+
+/// A plaintext program: instruction words loaded at `base`, run from `entry`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rv32iProgram {
+    pub base: u32,
+    pub entry: u32,
+    pub text: Vec<u32>,
+}
+
+impl Rv32iProgram {
+    /// A raw binary of little-endian instruction words, loaded and run from `base`.
+    pub fn from_binary(bytes: &[u8], base: u32) -> Result<Self, PulpError> {
+        if !bytes.len().is_multiple_of(4) {
+            return Err(PulpError::invalid(
+                "rv32i_load",
+                format!(
+                    "binary of {} bytes is not a whole number of words",
+                    bytes.len()
+                ),
+            ));
+        }
+        let text: Vec<u32> = bytes
+            .chunks(4)
+            .map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+            .collect();
+        Ok(Rv32iProgram {
+            base,
+            entry: base,
+            text,
+        })
+    }
+
+    /// The executable segment of a 32-bit little-endian RISC-V ELF file, run from the ELF
+    /// entry point. The entry point must lie in that segment.
+    pub fn from_elf(bytes: &[u8]) -> Result<Self, PulpError> {
+        let bad = |reason: &str| PulpError::invalid("rv32i_load", reason.to_string());
+        let u16_at = |at: usize| -> Result<u32, PulpError> {
+            let b = bytes
+                .get(at..at + 2)
+                .ok_or_else(|| bad("truncated ELF file"))?;
+            Ok(u16::from_le_bytes([b[0], b[1]]) as u32)
+        };
+        let u32_at = |at: usize| -> Result<u32, PulpError> {
+            let b = bytes
+                .get(at..at + 4)
+                .ok_or_else(|| bad("truncated ELF file"))?;
+            Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        };
+
+        if bytes.get(..4) != Some(b"\x7fELF".as_slice()) {
+            return Err(bad("not an ELF file"));
+        }
+        // 32-bit class, little-endian data, RISC-V machine
+        if bytes.get(4..6) != Some([1u8, 1].as_slice()) || u16_at(18)? != 0xf3 {
+            return Err(bad("not a 32-bit little-endian RISC-V ELF file"));
+        }
+        let entry: u32 = u32_at(24)?;
+        let phoff: usize = u32_at(28)? as usize;
+        let phentsize: usize = u16_at(42)? as usize;
+        let phnum: usize = u16_at(44)? as usize;
+
+        for i in 0..phnum {
+            let header: usize = phoff + i * phentsize;
+            // PT_LOAD with the PF_X flag
+            if u32_at(header)? != 1 || u32_at(header + 24)? & 1 == 0 {
+                continue;
+            }
+            let (offset, vaddr) = (u32_at(header + 4)? as usize, u32_at(header + 8)?);
+            let filesz: usize = u32_at(header + 16)? as usize;
+            let segment: &[u8] = bytes
+                .get(offset..offset + filesz)
+                .ok_or_else(|| bad("truncated ELF file"))?;
+            let mut program = Self::from_binary(&segment[..filesz & !3], vaddr)?;
+            if entry < vaddr || entry - vaddr >= filesz as u32 {
+                return Err(bad("the entry point is not in the executable segment"));
+            }
+            program.entry = entry;
+            return Ok(program);
+        }
+        Err(bad("no executable segment"))
+    }
+
+    /// The instruction word at `pc`, if `pc` is an aligned address inside the text.
+    pub fn fetch(&self, pc: u32) -> Option<u32> {
+        let offset: u32 = pc.checked_sub(self.base)?;
+        if !offset.is_multiple_of(4) {
+            return None;
+        }
+        self.text.get((offset / 4) as usize).copied()
+    }
+}
+
+/// The encrypted architectural state: registers and data memory.
+#[derive(Clone, Debug)]
+pub struct Rv32iCpu {
+    /// `x0` to `x31`, each `XLEN` bits, LSB first; `x0` stays zero
+    pub registers: Vec<Vec<Ciphertext>>,
+    pub memory: ObliviousMemory,
+}
+
+impl Rv32iCpu {
+    /// A CPU with zeroed registers and `memory_words` zeroed words of data memory.
+    pub fn new(memory_words: usize) -> Result<Self, PulpError> {
+        let memory = ObliviousMemory::zeroed(memory_words, XLEN)?;
+        if 2 + memory.index_width() > XLEN {
+            return Err(PulpError::invalid(
+                "rv32i",
+                format!("{memory_words} words do not fit the 32-bit address space"),
+            ));
+        }
+        Ok(Rv32iCpu {
+            registers: vec![vec![Ciphertext::Trivial(false); XLEN]; REGISTERS],
+            memory,
+        })
+    }
+
+    pub fn register(&self, index: usize) -> Result<&[Ciphertext], PulpError> {
+        self.registers
+            .get(index)
+            .map(|r| r.as_slice())
+            .ok_or_else(|| PulpError::UnknownRegister(format!("x{index}")))
+    }
+
+    /// Sets register `index`; writes to `x0` are ignored.
+    pub fn set_register(&mut self, index: usize, value: Vec<Ciphertext>) -> Result<(), PulpError> {
+        PulpError::expect_width("rv32i", "value", XLEN, value.len())?;
+        if index >= REGISTERS {
+            return Err(PulpError::UnknownRegister(format!("x{index}")));
+        }
+        if index != 0 {
+            self.registers[index] = value;
+        }
+        Ok(())
+    }
+
+    /// Runs `program` from its entry point until `ecall`/`ebreak`, a `ret` from the entry
+    /// point or the end of its text and returns the number of instructions executed. The
+    /// program is decoded as it runs, so an unsupported instruction fails only once it is
+    /// reached; the state is then left as it was after the previous instruction. A program
+    /// that would execute more than `max_steps` instructions, e.g. an idle loop `j .`, fails
+    /// the same way.
+    pub fn run<P: ProcessorGate + ProcessorCircuits>(
+        &mut self,
+        processor: &P,
+        sk: &ServerKey,
+        program: &Rv32iProgram,
+        max_steps: usize,
+    ) -> Result<usize, PulpError> {
+        let mut pc: u32 = program.entry;
+        let mut executed: usize = 0;
+        // Return addresses of the calls in progress, innermost last
+        let mut calls: Vec<u32> = Vec::new();
+        while let Some(word) = program.fetch(pc) {
+            let instruction = Instruction::decode(word, pc)?;
+            let next: u32 = match instruction {
+                Instruction::Halt => break,
+                Instruction::Ret => match calls.pop() {
+                    Some(address) => address,
+                    None => break,
+                },
+                Instruction::Jal { rd, offset } => {
+                    if rd == 1 {
+                        calls.push(pc.wrapping_add(4));
+                    }
+                    pc.wrapping_add(offset as u32)
+                }
+                _ => pc.wrapping_add(4),
+            };
+            if executed == max_steps {
+                return Err(PulpError::invalid(
+                    "rv32i",
+                    format!("no halt within {max_steps} instructions, at pc {pc:#x}"),
+                ));
+            }
+            self.execute(processor, sk, instruction, pc)?;
+            executed += 1;
+            pc = next;
+        }
+        Ok(executed)
+    }
+
+    /// Executes one instruction located at `pc`. Jumps only write their link register; the
+    /// program counter is `run`'s.
+    pub fn execute<P: ProcessorGate + ProcessorCircuits>(
+        &mut self,
+        processor: &P,
+        sk: &ServerKey,
+        instruction: Instruction,
+        pc: u32,
+    ) -> Result<(), PulpError> {
+        let alu = Alu { p: processor, sk };
+        match instruction {
+            Instruction::Lui { rd, imm } => self.set_register(rd, constant(imm)),
+            Instruction::Auipc { rd, imm } => self.set_register(rd, constant(pc.wrapping_add(imm))),
+            Instruction::Op { op, rd, rs1, rs2 } => {
+                let result = alu.apply(op, self.register(rs1)?, self.register(rs2)?)?;
+                self.set_register(rd, result)
+            }
+            Instruction::OpImm { op, rd, rs1, imm } => {
+                let result = alu.apply(op, self.register(rs1)?, &constant(imm as u32))?;
+                self.set_register(rd, result)
+            }
+            Instruction::Lw { rd, rs1, offset } => {
+                let index = self.word_index(&alu, rs1, offset)?;
+                let word = self.memory.read(processor, sk, &index)?;
+                self.set_register(rd, word)
+            }
+            Instruction::Sw { rs1, rs2, offset } => {
+                let index = self.word_index(&alu, rs1, offset)?;
+                let value: Vec<Ciphertext> = self.register(rs2)?.to_vec();
+                self.memory.write(processor, sk, &index, &value)
+            }
+            Instruction::Jal { rd, .. } => self.set_register(rd, constant(pc.wrapping_add(4))),
+            Instruction::Ret | Instruction::Halt => Ok(()),
+        }
+    }
+
+    /// The memory word index addressed by `rs1 + offset`.
+    fn word_index<P: ProcessorGate + ProcessorCircuits>(
+        &self,
+        alu: &Alu<'_, P>,
+        rs1: usize,
+        offset: i32,
+    ) -> Result<Vec<Ciphertext>, PulpError> {
+        let address = alu.apply(AluOp::Add, self.register(rs1)?, &constant(offset as u32))?;
+        Ok(address[2..2 + self.memory.index_width()].to_vec())
+    }
+}
+
+/// The trivial encryption of `value`, LSB first.
+fn constant(value: u32) -> Vec<Ciphertext> {
+    (0..XLEN)
+        .map(|i| Ciphertext::Trivial((value >> i) & 1 == 1))
+        .collect()
+}
+
+/// The processor and key, evaluating the `AluOp`s on `XLEN`-bit operands.
+struct Alu<'a, P> {
+    p: &'a P,
+    sk: &'a ServerKey,
+}
+
+impl<P: ProcessorGate + ProcessorCircuits> Alu<'_, P> {
+    fn apply(
+        &self,
+        op: AluOp,
+        a: &[Ciphertext],
+        b: &[Ciphertext],
+    ) -> Result<Vec<Ciphertext>, PulpError> {
+        let (p, sk) = (self.p, self.sk);
+        let mut result: Vec<Ciphertext> = vec![Ciphertext::Trivial(false); XLEN];
+        match op {
+            AluOp::Add => p.adder(sk, a, b, &mut result)?,
+            AluOp::Sub => p.subtracter(sk, a, b, &mut result)?,
+            AluOp::Xor => p.e_xor(sk, a, b, &mut result)?,
+            AluOp::Or => p.e_or(sk, a, b, &mut result)?,
+            AluOp::And => p.e_and(sk, a, b, &mut result)?,
+            AluOp::Sltu => result[0] = p.comparator(sk, a, b, 1)?,
+            AluOp::Slt => {
+                // Flipping the sign bits maps signed order onto unsigned order
+                let (mut a, mut b) = (a.to_vec(), b.to_vec());
+                a[XLEN - 1] = p.e_not_bit(sk, &a[XLEN - 1]);
+                b[XLEN - 1] = p.e_not_bit(sk, &b[XLEN - 1]);
+                result[0] = p.comparator(sk, &a, &b, 1)?;
+            }
+            AluOp::Sll | AluOp::Srl | AluOp::Sra => result = self.shift(op, a, &b[..5])?,
+        }
+        Ok(result)
+    }
+
+    /// Barrel shifter over the encrypted `amount`; a trivial amount folds every stage.
+    fn shift(
+        &self,
+        op: AluOp,
+        a: &[Ciphertext],
+        amount: &[Ciphertext],
+    ) -> Result<Vec<Ciphertext>, PulpError> {
+        let fill: Ciphertext = match op {
+            AluOp::Sra => a[XLEN - 1].clone(),
+            _ => Ciphertext::Trivial(false),
+        };
+        let mut bits: Vec<Ciphertext> = a.to_vec();
+        for (j, select) in amount.iter().enumerate() {
+            let step: usize = 1 << j;
+            let mut shifted: Vec<Ciphertext> = vec![fill.clone(); XLEN];
+            if op == AluOp::Sll {
+                shifted[step..].clone_from_slice(&bits[..XLEN - step]);
+            } else {
+                shifted[..XLEN - step].clone_from_slice(&bits[step..]);
+            }
+            let previous: Vec<Ciphertext> = bits.clone();
+            self.p
+                .e_mux(self.sk, select, &shifted, &previous, &mut bits)?;
+        }
+        Ok(bits)
+    }
+}
@@ -0,0 +1,375 @@
+#![allow(warnings)]
+use super::*; // Imports items from the parent module
+use crate::key_manager::KeyManager;
+use crate::processor_boolean::ProcessorBoolean;
+use serial_test::serial;
+
+fn encrypt_word(value: u32, ck: &ClientKey) -> Vec<Ciphertext> {
+    (0..XLEN)
+        .map(|i| ck.encrypt((value >> i) & 1 == 1))
+        .collect()
+}
+
+fn decrypt_word(ciphertexts: &[Ciphertext], ck: &ClientKey) -> u32 {
+    ciphertexts
+        .iter()
+        .enumerate()
+        .map(|(i, bit)| (ck.decrypt(bit) as u32) << i)
+        .sum()
+}
+
+// Instruction encoders for the formats used by the tests
+fn r_type(funct7: u32, rs2: u32, rs1: u32, funct3: u32, rd: u32) -> u32 {
+    (funct7 << 25) | (rs2 << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | 0x33
+}
+
+fn i_type(imm: i32, rs1: u32, funct3: u32, rd: u32, opcode: u32) -> u32 {
+    (((imm as u32) & 0xfff) << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | opcode
+}
+
+fn s_type(imm: i32, rs2: u32, rs1: u32) -> u32 {
+    let imm = imm as u32;
+    ((imm >> 5 & 0x7f) << 25) | (rs2 << 20) | (rs1 << 15) | (2 << 12) | ((imm & 0x1f) << 7) | 0x23
+}
+
+fn u_type(imm: u32, rd: u32, opcode: u32) -> u32 {
+    (imm << 12) | (rd << 7) | opcode
+}
+
+fn j_type(imm: i32, rd: u32) -> u32 {
+    let imm = imm as u32;
+    ((imm >> 20 & 1) << 31)
+        | ((imm >> 1 & 0x3ff) << 21)
+        | ((imm >> 11 & 1) << 20)
+        | ((imm >> 12 & 0xff) << 12)
+        | (rd << 7)
+        | 0x6f
+}
+
+const ECALL: u32 = 0x0000_0073;
+const MAX_STEPS: usize = 1_000;
+const RET: u32 = 0x0000_8067;
+
+fn to_bytes(words: &[u32]) -> Vec<u8> {
+    words.iter().flat_map(|w| w.to_le_bytes()).collect()
+}
+
+/// A minimal executable: the ELF header, one program header and `text` loaded at `vaddr`.
+fn elf(vaddr: u32, entry: u32, text: &[u8]) -> Vec<u8> {
+    let mut elf: Vec<u8> = Vec::new();
+    elf.extend_from_slice(b"\x7fELF\x01\x01\x01\0\0\0\0\0\0\0\0\0");
+    for half in [2u16, 0xf3] {
+        elf.extend_from_slice(&half.to_le_bytes()); // e_type, e_machine
+    }
+    for word in [1u32, entry, 52, 0, 0] {
+        elf.extend_from_slice(&word.to_le_bytes()); // e_version .. e_flags
+    }
+    for half in [52u16, 32, 1, 40, 0, 0] {
+        elf.extend_from_slice(&half.to_le_bytes()); // e_ehsize .. e_shstrndx
+    }
+    let phdr: [u32; 8] = [
+        1,
+        84,
+        vaddr,
+        vaddr,
+        text.len() as u32,
+        text.len() as u32,
+        5,
+        4,
+    ];
+    for word in phdr {
+        elf.extend_from_slice(&word.to_le_bytes());
+    }
+    elf.extend_from_slice(text);
+    elf
+}
+
+#[test]
+#[serial]
+fn test_rv32i_decode() {
+    let fn_name = "rv32i_decode";
+    println!("[*] TEST: {fn_name}");
+
+    assert_eq!(
+        Instruction::decode(r_type(0x20, 2, 1, 0, 3), 0).unwrap(),
+        Instruction::Op {
+            op: AluOp::Sub,
+            rd: 3,
+            rs1: 1,
+            rs2: 2
+        }
+    );
+    assert_eq!(
+        Instruction::decode(i_type(-2048, 5, 0, 6, 0x13), 0).unwrap(),
+        Instruction::OpImm {
+            op: AluOp::Add,
+            rd: 6,
+            rs1: 5,
+            imm: -2048
+        }
+    );
+    assert_eq!(
+        Instruction::decode(i_type(0x400 | 7, 1, 5, 2, 0x13), 0).unwrap(),
+        Instruction::OpImm {
+            op: AluOp::Sra,
+            rd: 2,
+            rs1: 1,
+            imm: 7
+        }
+    );
+    assert_eq!(
+        Instruction::decode(s_type(-12, 9, 2), 0).unwrap(),
+        Instruction::Sw {
+            rs1: 2,
+            rs2: 9,
+            offset: -12
+        }
+    );
+    assert_eq!(
+        Instruction::decode(0x0010_0073, 0).unwrap(),
+        Instruction::Halt
+    );
+    assert_eq!(
+        Instruction::decode(j_type(-0x8_0000, 1), 0).unwrap(),
+        Instruction::Jal {
+            rd: 1,
+            offset: -0x8_0000
+        }
+    );
+    assert_eq!(
+        Instruction::decode(j_type(0x7_fffe, 0), 0).unwrap(),
+        Instruction::Jal {
+            rd: 0,
+            offset: 0x7_fffe
+        }
+    );
+    assert_eq!(Instruction::decode(RET, 0).unwrap(), Instruction::Ret);
+
+    // beq x1, x2, 0, mul x3, x1, x2 and jalr ra, 0(t0), whose target is encrypted
+    for word in [
+        0x0020_8063,
+        r_type(0x01, 2, 1, 0, 3),
+        i_type(0, 5, 0, 1, 0x67),
+    ] {
+        assert!(matches!(
+            Instruction::decode(word, 0x40),
+            Err(PulpError::InvalidArgument { op: "rv32i", .. })
+        ));
+    }
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_rv32i_run() {
+    let fn_name = "rv32i_run";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let server = ProcessorBoolean;
+
+    let text: Vec<u32> = vec![
+        r_type(0x00, 2, 1, 0, 3),          // add   x3, x1, x2
+        r_type(0x20, 2, 1, 0, 4),          // sub   x4, x1, x2
+        r_type(0x00, 2, 1, 2, 5),          // slt   x5, x1, x2
+        r_type(0x00, 2, 1, 3, 6),          // sltu  x6, x1, x2
+        r_type(0x20, 2, 1, 5, 7),          // sra   x7, x1, x2
+        r_type(0x00, 2, 1, 5, 8),          // srl   x8, x1, x2
+        r_type(0x00, 2, 2, 1, 9),          // sll   x9, x2, x2
+        i_type(-1, 1, 4, 10, 0x13),        // xori  x10, x1, -1
+        i_type(0x7f, 1, 7, 11, 0x13),      // andi  x11, x1, 0x7f
+        i_type(0x100, 2, 6, 12, 0x13),     // ori   x12, x2, 0x100
+        i_type(-19, 1, 2, 13, 0x13),       // slti  x13, x1, -19
+        i_type(2, 2, 3, 14, 0x13),         // sltiu x14, x2, 2
+        i_type(30, 2, 1, 15, 0x13),        // slli  x15, x2, 30
+        i_type(0x400 | 2, 1, 5, 16, 0x13), // srai  x16, x1, 2
+        u_type(0x12345, 17, 0x37),         // lui   x17, 0x12345
+        i_type(0x678, 17, 0, 17, 0x13),    // addi  x17, x17, 0x678
+        u_type(1, 18, 0x17),               // auipc x18, 1
+        s_type(8, 17, 0),                  // sw    x17, 8(x0)
+        s_type(1, 3, 2),                   // sw    x3, 1(x2)
+        i_type(5, 2, 2, 19, 0x03),         // lw    x19, 5(x2)
+        i_type(5, 0, 0, 0, 0x13),          // addi  x0, x0, 5
+        ECALL,
+        i_type(1, 0, 0, 20, 0x13), // addi  x20, x0, 1 (not reached)
+    ];
+    let program = Rv32iProgram::from_binary(&to_bytes(&text), 0x100).unwrap();
+
+    let (a, b): (i32, i32) = (-20, 3);
+    let mut cpu = Rv32iCpu::new(4).unwrap();
+    cpu.set_register(1, encrypt_word(a as u32, client_key))
+        .unwrap();
+    cpu.set_register(2, encrypt_word(b as u32, client_key))
+        .unwrap();
+    let executed = cpu.run(&server, server_key, &program, 21).unwrap();
+    assert_eq!(executed, 21);
+
+    let expected: [(usize, u32); 20] = [
+        (0, 0),
+        (3, (a + b) as u32),
+        (4, (a - b) as u32),
+        (5, 1),
+        (6, 0),
+        (7, (a >> b) as u32),
+        (8, (a as u32) >> b),
+        (9, 24),
+        (10, !a as u32),
+        (11, (a & 0x7f) as u32),
+        (12, 0x103),
+        (13, 1),
+        (14, 0),
+        (15, 0xc000_0000),
+        (16, (a >> 2) as u32),
+        (17, 0x1234_5678),
+        (18, 0x1140),
+        (19, 0x1234_5678),
+        (20, 0),
+        (2, b as u32),
+    ];
+    for (index, value) in expected {
+        let register = decrypt_word(cpu.register(index).unwrap(), client_key);
+        println!("\t x{index} = {register:#010x}");
+        assert_eq!(register, value, "x{index}");
+    }
+    let memory: Vec<u32> = cpu
+        .memory
+        .words
+        .iter()
+        .map(|word| decrypt_word(word, client_key))
+        .collect();
+    assert_eq!(memory, vec![0, (a + b) as u32, 0x1234_5678, 0]);
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_rv32i_load_elf() {
+    let fn_name = "rv32i_load_elf";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let server = ProcessorBoolean;
+
+    // The text ends with a data word that is never decoded
+    let vaddr: u32 = 0x1_0054;
+    let text: Vec<u8> = to_bytes(&[
+        i_type(5, 0, 0, 1, 0x13), // addi x1, x0, 5 (before the entry point)
+        i_type(7, 0, 0, 1, 0x13), // addi x1, x0, 7
+        ECALL,
+        0xffff_ffff,
+    ]);
+    let elf: Vec<u8> = elf(vaddr, vaddr + 4, &text);
+
+    let program = Rv32iProgram::from_elf(&elf).unwrap();
+    assert_eq!((program.base, program.entry), (vaddr, vaddr + 4));
+    assert_eq!(program.text.len(), 4);
+
+    let mut cpu = Rv32iCpu::new(1).unwrap();
+    assert_eq!(
+        cpu.run(&server, server_key, &program, MAX_STEPS).unwrap(),
+        1
+    );
+    assert_eq!(decrypt_word(cpu.register(1).unwrap(), client_key), 7);
+
+    let mut not_riscv: Vec<u8> = elf.clone();
+    not_riscv[18] = 0x3e;
+    for bytes in [&not_riscv[..], &elf[..40], b"MZ\0\0"] {
+        assert!(matches!(
+            Rv32iProgram::from_elf(bytes),
+            Err(PulpError::InvalidArgument {
+                op: "rv32i_load",
+                ..
+            })
+        ));
+    }
+    assert!(Rv32iProgram::from_binary(&[0x13, 0, 0], 0).is_err());
+    assert_eq!(
+        cpu.set_register(32, encrypt_word(0, client_key)),
+        Err(PulpError::UnknownRegister("x32".to_string()))
+    );
+
+    // Decoding happens as the program runs
+    let branch = Rv32iProgram::from_binary(&to_bytes(&[0x0020_8063]), 0).unwrap();
+    assert!(matches!(
+        cpu.run(&server, server_key, &branch, MAX_STEPS),
+        Err(PulpError::InvalidArgument { op: "rv32i", .. })
+    ));
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_rv32i_calls() {
+    let fn_name = "rv32i_calls";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let server = ProcessorBoolean;
+
+    // `add3` and a `main` that calls it twice, laid out and linked as a toolchain emits
+    // them: `main` is the entry point, saves `ra` in its frame and returns with `ret`
+    let vaddr: u32 = 0x1_0074;
+    let text: Vec<u8> = to_bytes(&[
+        i_type(3, 10, 0, 10, 0x13), // add3: addi a0, a0, 3
+        RET,                        //       ret
+        i_type(-16, 2, 0, 2, 0x13), // main: addi sp, sp, -16
+        s_type(12, 1, 2),           //       sw   ra, 12(sp)
+        j_type(-16, 1),             //       jal  ra, add3
+        j_type(-20, 1),             //       jal  ra, add3
+        i_type(12, 2, 2, 1, 0x03),  //       lw   ra, 12(sp)
+        i_type(16, 2, 0, 2, 0x13),  //       addi sp, sp, 16
+        RET,                        //       ret
+        i_type(1, 0, 0, 10, 0x13),  //       addi a0, x0, 1 (not reached)
+    ]);
+    let program = Rv32iProgram::from_elf(&elf(vaddr, vaddr + 8, &text)).unwrap();
+
+    let mut cpu = Rv32iCpu::new(4).unwrap();
+    cpu.set_register(10, encrypt_word(4, client_key)).unwrap();
+    assert_eq!(
+        cpu.run(&server, server_key, &program, MAX_STEPS).unwrap(),
+        10
+    );
+    let expected: [(usize, u32); 3] = [(10, 10), (1, 0), (2, 0)];
+    for (index, value) in expected {
+        let register = decrypt_word(cpu.register(index).unwrap(), client_key);
+        println!("\t x{index} = {register:#010x}");
+        assert_eq!(register, value, "x{index}");
+    }
+
+    // A call links `ra` even though returns use the plaintext call stack
+    let program =
+        Rv32iProgram::from_binary(&to_bytes(&[j_type(8, 1), ECALL, ECALL]), 0x40).unwrap();
+    assert_eq!(
+        cpu.run(&server, server_key, &program, MAX_STEPS).unwrap(),
+        1
+    );
+    assert_eq!(decrypt_word(cpu.register(1).unwrap(), client_key), 0x44);
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_rv32i_max_steps() {
+    let fn_name = "rv32i_max_steps";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let server = ProcessorBoolean;
+    let mut cpu = Rv32iCpu::new(1).unwrap();
+
+    // The crt0 idle loop `j .` and a function that calls itself forever
+    for (name, word) in [("j .", j_type(0, 0)), ("jal ra, .", j_type(0, 1))] {
+        let program = Rv32iProgram::from_binary(&to_bytes(&[word]), 0x40).unwrap();
+        let err = cpu.run(&server, server_key, &program, 8).unwrap_err();
+        println!("\t {name}: {err}");
+        assert!(matches!(
+            err,
+            PulpError::InvalidArgument { op: "rv32i", .. }
+        ));
+    }
+    assert_eq!(decrypt_word(cpu.register(1).unwrap(), client_key), 0x44);
+
+    // The limit counts executed instructions, not the halt
+    let program =
+        Rv32iProgram::from_binary(&to_bytes(&[i_type(3, 0, 0, 5, 0x13), ECALL]), 0).unwrap();
+    assert!(cpu.run(&server, server_key, &program, 0).is_err());
+    assert_eq!(cpu.run(&server, server_key, &program, 1).unwrap(), 1);
+    assert_eq!(decrypt_word(cpu.register(5).unwrap(), client_key), 3);
+    println!("[✓] PASS: {fn_name}\n");
+}
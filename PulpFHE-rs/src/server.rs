@@ -0,0 +1,138 @@
+//! # Description
+//! Evaluation server for the `protocol` module. Each connection is a session with its own
+//! uploaded `ServerKey`; requests are evaluated with `Evaluator` or `Controller::run` and
+//! answered in order. A failing request is answered with an `Error` message and the session
+//! continues; a malformed frame closes the connection.
+
+use crate::ciphertext_file::{CiphertextFile, EncryptedIntegers};
+use crate::controller::Controller;
+use crate::evaluator::{Evaluator, Operation};
+use crate::key_manager::{KeyInfo, KeyManager};
+use crate::program::Program;
+use crate::protocol::{MessageKind, Protocol, ProtocolError};
+use std::collections::HashMap;
+use std::io::{self, BufReader, BufWriter};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::thread;
+use tfhe::boolean::prelude::ServerKey;
+
+#[cfg(test)]
+mod test_server;
+
+pub struct Server {
+    listener: TcpListener,
+}
+
+impl Server {
+    pub fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<Server> {
+        Ok(Server {
+            listener: TcpListener::bind(addr)?,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accepts connections forever, one thread per session.
+    pub fn serve(&self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            thread::spawn(move || {
+                let peer = stream.peer_addr().ok();
+                if let Err(e) = Self::handle(stream) {
+                    eprintln!("session {peer:?} closed: {e}");
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Accepts a single connection and serves it until the client disconnects.
+    pub fn serve_one(&self) -> Result<(), ProtocolError> {
+        let (stream, _) = self.listener.accept()?;
+        Self::handle(stream)
+    }
+
+    /// Serves one session on `stream`.
+    pub fn handle(stream: TcpStream) -> Result<(), ProtocolError> {
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = BufWriter::new(stream);
+        let mut key: Option<(ServerKey, KeyInfo)> = None;
+        while let Some((kind, payload)) = Protocol::read_frame(&mut reader)? {
+            let (reply, payload) = match Self::dispatch(&mut key, kind, &payload) {
+                Ok(reply) => reply,
+                Err(e @ ProtocolError::UnexpectedMessage(_)) => return Err(e),
+                Err(ProtocolError::Remote(msg)) => {
+                    (MessageKind::Error, Protocol::encode_error(&msg)?)
+                }
+                Err(e) => (MessageKind::Error, Protocol::encode_error(&e.to_string())?),
+            };
+            Protocol::write_frame(&mut writer, reply, &payload)?;
+        }
+        Ok(())
+    }
+
+    fn dispatch(
+        key: &mut Option<(ServerKey, KeyInfo)>,
+        kind: MessageKind,
+        payload: &[u8],
+    ) -> Result<(MessageKind, Vec<u8>), ProtocolError> {
+        match kind {
+            MessageKind::UploadKey => {
+                let (server_key, info) = KeyManager::read_server_key(payload)?;
+                *key = Some((server_key, info));
+                Ok((MessageKind::KeyAccepted, info.key_id.to_le_bytes().to_vec()))
+            }
+            MessageKind::Eval => {
+                let (server_key, info) = Self::session_key(key)?;
+                let request = Protocol::decode_eval(payload)?;
+                let op = request
+                    .op
+                    .parse::<Operation>()
+                    .map_err(|e| ProtocolError::Remote(e.to_string()))?;
+                let result = Evaluator::new(server_key, *info)
+                    .eval(op, &request.inputs, &request.options)
+                    .map_err(|e| ProtocolError::Remote(e.to_string()))?;
+                let mut bytes = Vec::new();
+                CiphertextFile::write(&mut bytes, &result)?;
+                Ok((MessageKind::Result, bytes))
+            }
+            MessageKind::Run => {
+                let (server_key, info) = Self::session_key(key)?;
+                let request = Protocol::decode_run(payload)?;
+                let program = Program::parse(&request.program)
+                    .map_err(|e| ProtocolError::Remote(e.to_string()))?;
+                let mut inputs = HashMap::new();
+                for (name, integers) in request.inputs {
+                    integers.verify_key(info)?;
+                    integers.verify_count(1)?;
+                    inputs.insert(name, integers.values.into_iter().next().unwrap());
+                }
+                let mut outputs = Controller::new(server_key.clone())
+                    .run(&program, &inputs)
+                    .map_err(|e| ProtocolError::Remote(e.to_string()))?;
+                let named: Vec<(String, EncryptedIntegers)> = program
+                    .outputs()
+                    .into_iter()
+                    .map(|name| {
+                        let bits = outputs.remove(name).unwrap_or_default();
+                        let integers =
+                            EncryptedIntegers::new(bits.len(), request.signed, info, vec![bits])?;
+                        Ok((name.to_string(), integers))
+                    })
+                    .collect::<Result<_, ProtocolError>>()?;
+                Ok((MessageKind::Outputs, Protocol::encode_outputs(&named)?))
+            }
+            other => Err(ProtocolError::UnexpectedMessage(other)),
+        }
+    }
+
+    fn session_key(
+        key: &Option<(ServerKey, KeyInfo)>,
+    ) -> Result<(&ServerKey, &KeyInfo), ProtocolError> {
+        key.as_ref()
+            .map(|(server_key, info)| (server_key, info))
+            .ok_or_else(|| ProtocolError::Remote("no server key uploaded".to_string()))
+    }
+}
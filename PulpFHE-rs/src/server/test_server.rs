@@ -0,0 +1,117 @@
+#![allow(warnings)]
+use super::*; // Imports items from the parent module
+use crate::client::Client;
+use crate::evaluator::EvalOptions;
+use crate::key_manager::ParameterSet;
+use crate::protocol::PROTOCOL_VERSION;
+use serial_test::serial;
+use std::io::Write;
+
+/// Starts a server on a free loopback port that serves exactly one session.
+fn spawn_server() -> (SocketAddr, thread::JoinHandle<Result<(), ProtocolError>>) {
+    let server = Server::bind("127.0.0.1:0").unwrap();
+    let addr = server.local_addr().unwrap();
+    (addr, thread::spawn(move || server.serve_one()))
+}
+
+#[test]
+#[serial]
+fn test_loopback_session() {
+    let fn_name = "loopback_session";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, server_key) = KeyManager::cached_keys();
+    let info = KeyManager::cached_key_info();
+    let (addr, handle) = spawn_server();
+
+    let mut client = Client::connect(addr).unwrap();
+    client.upload_server_key(server_key, &info).unwrap();
+
+    let a = EncryptedIntegers::encrypt(client_key, &info, 8, true, &[100, -20]).unwrap();
+    let b = EncryptedIntegers::encrypt(client_key, &info, 8, true, &[27, 5]).unwrap();
+    let sum = client
+        .eval("add", &[a.clone(), b.clone()], &EvalOptions::default())
+        .unwrap();
+    assert_eq!(sum.decrypt(client_key), vec![127, -15]);
+
+    let first = |x: &EncryptedIntegers| {
+        EncryptedIntegers::new(8, true, &info, vec![x.values[0].clone()]).unwrap()
+    };
+    let outputs = client
+        .run(
+            "input a 8\ninput b 8\nreg t 8\nxor t, a, b\noutput t\n",
+            &[("a", &first(&a)), ("b", &first(&b))],
+            false,
+        )
+        .unwrap();
+    assert_eq!(outputs["t"].decrypt(client_key), vec![100 ^ 27]);
+
+    // Failing requests are answered and the session goes on
+    assert!(matches!(
+        client.eval("bogus", &[a.clone()], &EvalOptions::default()),
+        Err(ProtocolError::Remote(msg)) if msg.contains("bogus")
+    ));
+    let foreign = EncryptedIntegers::encrypt(
+        client_key,
+        &KeyInfo::new(ParameterSet::Default),
+        8,
+        true,
+        &[1],
+    )
+    .unwrap();
+    assert!(matches!(
+        client.eval("not", &[foreign], &EvalOptions::default()),
+        Err(ProtocolError::Remote(msg)) if msg.contains("key id")
+    ));
+    let options = EvalOptions {
+        imm: Some(3),
+        ..EvalOptions::default()
+    };
+    let product = client.eval("scalar_mul", &[b], &options).unwrap();
+    assert_eq!(product.decrypt(client_key), vec![81, 15]);
+
+    drop(client);
+    assert!(handle.join().unwrap().is_ok());
+    println!("[✓] PASS: {fn_name}\n");
+}
+
+#[test]
+#[serial]
+fn test_loopback_rejects_bad_sessions() {
+    let fn_name = "loopback_rejects_bad_sessions";
+    println!("[*] TEST: {fn_name}");
+    let (client_key, _) = KeyManager::cached_keys();
+    let info = KeyManager::cached_key_info();
+
+    // Requests need an uploaded key
+    let (addr, handle) = spawn_server();
+    let mut client = Client::connect(addr).unwrap();
+    let a = EncryptedIntegers::encrypt(client_key, &info, 4, false, &[3]).unwrap();
+    assert!(matches!(
+        client.eval("not", &[a], &EvalOptions::default()),
+        Err(ProtocolError::Remote(msg)) if msg == "no server key uploaded"
+    ));
+    drop(client);
+    assert!(handle.join().unwrap().is_ok());
+
+    // A frame of another protocol version closes the session
+    let (addr, handle) = spawn_server();
+    let mut stream = TcpStream::connect(addr).unwrap();
+    let mut frame = (PROTOCOL_VERSION + 1).to_le_bytes().to_vec();
+    frame.push(MessageKind::Eval.id());
+    frame.extend_from_slice(&0u64.to_le_bytes());
+    stream.write_all(&frame).unwrap();
+    assert!(matches!(
+        handle.join().unwrap(),
+        Err(ProtocolError::UnsupportedVersion { .. })
+    ));
+
+    // Server replies are not valid requests
+    let (addr, handle) = spawn_server();
+    let mut stream = TcpStream::connect(addr).unwrap();
+    Protocol::write_frame(&mut stream, MessageKind::Result, &[]).unwrap();
+    assert!(matches!(
+        handle.join().unwrap(),
+        Err(ProtocolError::UnexpectedMessage(MessageKind::Result))
+    ));
+    println!("[✓] PASS: {fn_name}\n");
+}